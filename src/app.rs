@@ -1,20 +1,28 @@
+#[cfg(test)]
+mod tests;
+
 use std::{
     env::{args, current_dir},
+    io::{stdin, IsTerminal, Read},
     path::{Path, PathBuf},
 };
 
 use crate::{
     config::{Config, ConfigError},
     ctx::Ctx,
+    geometry::position::Position,
     lsp::Lsp,
     platform::{file_watcher::FileWatcher, gfx::Gfx, process::Process, window::Window},
     pool::Pooled,
+    text::doc::{Doc, DocFlags},
     ui::{
         command_palette::{file_explorer_mode::FileExplorerMode, CommandPalette},
         controller::Controller,
-        core::{Ui, WidgetId},
+        core::{Ui, WidgetId, WidgetLayout, WidgetSettings},
         editor::Editor,
+        file_tree::FileTree,
         msg::Msg,
+        problems_panel::ProblemsPanel,
         status_bar::StatusBar,
         terminal::Terminal,
     },
@@ -38,7 +46,9 @@ pub struct App {
     ui: Ui,
     controller: Controller,
     command_palette: CommandPalette,
+    file_tree: FileTree,
     editor: Editor,
+    problems_panel: ProblemsPanel,
     terminal: Terminal,
     status_bar: StatusBar,
 
@@ -47,6 +57,9 @@ pub struct App {
     lsp: Lsp,
 
     config_dir: Pooled<PathBuf>,
+    config_path: Pooled<PathBuf>,
+    project_config_dir: Pooled<PathBuf>,
+    project_config_path: Pooled<PathBuf>,
     config: Config,
     config_error: Option<ConfigError>,
 }
@@ -56,14 +69,20 @@ impl App {
         let mut current_dir: Pooled<PathBuf> = current_dir().unwrap().as_path().into();
 
         let config_dir = Config::dir(&current_dir);
+        let config_path = Config::path(&config_dir);
+        let project_config_dir = current_dir.clone();
+        let project_config_path = Config::project_path(&project_config_dir);
 
-        let (config, config_error) = match Config::load(&config_dir) {
+        let (mut config, config_error) = match Config::load(&config_dir, &project_config_path) {
             Ok(config) => (config, None),
             Err(err) => (Config::default(), Some(err)),
         };
 
+        config.update_theme(window.is_dark_mode());
+
         window.set_theme(&config.theme);
         gfx.set_font(&config.font, config.font_size);
+        gfx.set_tab_width(config.tab_display_width);
 
         let mut ui = Ui::new();
         let mut lsp = Lsp::new();
@@ -81,21 +100,40 @@ impl App {
         let controller = Controller::new(WidgetId::ROOT, ctx.ui);
         let controller_id = controller.widget_id();
 
+        let workspace_id = ctx.ui.new_widget(
+            controller_id,
+            WidgetSettings {
+                wants_msgs: false,
+                layout: WidgetLayout::Horizontal,
+                ..Default::default()
+            },
+        );
+
         let mut command_palette = CommandPalette::new(controller_id, ctx.ui);
-        let mut editor = Editor::new(controller_id, &mut ctx);
-        let terminal = Terminal::new(controller_id, &mut ctx);
+        let file_tree = FileTree::new(workspace_id, &mut ctx);
+        let mut editor = Editor::new(workspace_id, &mut ctx);
+        let problems_panel = ProblemsPanel::new(controller_id, &mut ctx);
+        let mut terminal = Terminal::new(controller_id, &mut ctx);
         let status_bar = StatusBar::new(controller_id, &mut ctx);
 
         let (pane, _) = editor.last_focused_pane_and_doc_list(ctx.ui);
         ctx.ui.focus(pane.widget_id());
 
-        handle_args(&mut editor, &mut command_palette, &mut ctx);
+        let has_file = handle_args(&mut editor, &mut command_palette, &mut ctx);
+
+        if !has_file && ctx.config.restore_session {
+            if let Ok(Some(terminal_dir)) = editor.restore_session(&mut ctx) {
+                terminal.set_last_current_dir(&terminal_dir, &ctx);
+            }
+        }
 
         Self {
             ui,
             controller,
+            file_tree,
             editor,
             command_palette,
+            problems_panel,
             terminal,
             status_bar,
 
@@ -104,13 +142,16 @@ impl App {
             lsp,
 
             config_dir,
+            config_path,
+            project_config_dir,
+            project_config_path,
             config,
             config_error,
         }
     }
 
     fn receive_msgs(&mut self, window: &mut Window, gfx: &mut Gfx, time: f64) {
-        self.ui.send_window_msgs(&self.config, window);
+        self.ui.send_window_msgs(&self.config, window, time);
 
         let ctx = ctx_for_app!(self, window, gfx, time);
 
@@ -119,35 +160,60 @@ impl App {
 
             self.controller.receive_msgs(
                 &mut self.editor,
+                &mut self.file_tree,
+                &mut self.problems_panel,
                 &self.terminal,
                 &mut self.command_palette,
                 ctx,
             );
 
             self.command_palette.receive_msgs(&mut self.editor, ctx);
+            self.file_tree.receive_msgs(&mut self.editor, ctx);
+            self.problems_panel.receive_msgs(&mut self.editor, ctx);
             self.editor.receive_msgs(ctx);
             self.terminal.receive_msgs(ctx);
-            self.status_bar.receive_msgs(ctx);
+            self.status_bar.receive_msgs(&mut self.problems_panel, ctx);
         }
     }
 
     pub fn update(&mut self, window: &mut Window, gfx: &mut Gfx, time: f64, dt: f32) {
-        let config_changed = self
-            .file_watcher
-            .changed_files(&self.current_dir)
-            .any(|changed_file| changed_file.starts_with(&self.config_dir));
-
-        if config_changed {
-            match Config::load(&self.config_dir) {
-                Ok(config) => self.config = config,
+        let config_changed =
+            self.file_watcher
+                .changed_files(&self.current_dir)
+                .any(|changed_file| {
+                    changed_file.starts_with(&self.config_dir)
+                        || Config::is_project_path(&changed_file)
+                });
+
+        // Opening a different folder can change which project config applies.
+        let project_changed = self.project_config_dir != self.current_dir;
+
+        if project_changed {
+            self.project_config_dir = self.current_dir.clone();
+        }
+
+        if config_changed || project_changed {
+            self.project_config_path = Config::project_path(&self.current_dir);
+
+            // Invalid configs are reported and the previous config stays in use.
+            match Config::load(&self.config_dir, &self.project_config_path) {
+                Ok(config) => {
+                    self.config = config;
+                    self.config.update_theme(window.is_dark_mode());
+
+                    window.set_theme(&self.config.theme);
+                    gfx.set_font(&self.config.font, self.controller.font_size(&self.config));
+                    gfx.set_tab_width(self.config.tab_display_width);
+
+                    self.editor.clear_doc_highlights();
+                    self.ui.broadcast(WidgetId::ROOT, Msg::FontChanged);
+                }
                 Err(err) => self.config_error = Some(err),
             }
+        }
 
+        if self.config.update_theme(window.is_dark_mode()) {
             window.set_theme(&self.config.theme);
-            gfx.set_font(&self.config.font, self.config.font_size);
-
-            self.editor.clear_doc_highlights();
-            self.ui.broadcast(WidgetId::ROOT, Msg::FontChanged);
         }
 
         if let Some(err) = window
@@ -165,6 +231,8 @@ impl App {
         Lsp::update(&mut self.editor, &mut self.command_palette, ctx);
 
         self.command_palette.update(&mut self.editor, ctx, dt);
+        self.file_tree.update(ctx, dt);
+        self.problems_panel.update(ctx, dt);
         self.editor.update(&mut self.file_watcher, ctx, dt);
         self.terminal.update(ctx, dt);
     }
@@ -178,6 +246,8 @@ impl App {
 
         self.status_bar.draw(&self.editor, ctx);
         self.terminal.draw(ctx);
+        self.problems_panel.draw(ctx);
+        self.file_tree.draw(ctx);
         self.editor.draw(ctx);
         self.command_palette.draw(ctx);
 
@@ -189,7 +259,8 @@ impl App {
     pub fn close(&mut self, window: &mut Window, gfx: &mut Gfx, time: f64) {
         let ctx = ctx_for_app!(self, window, gfx, time);
 
-        self.editor.on_close(ctx);
+        let terminal_dir = self.terminal.last_current_dir(ctx);
+        self.editor.on_close(terminal_dir, ctx);
     }
 
     pub fn config(&self) -> &Config {
@@ -200,8 +271,12 @@ impl App {
         let ctx = ctx_for_app!(self, window, gfx, time);
 
         self.editor.is_animating(ctx)
+            || self.file_tree.is_animating()
+            || self.problems_panel.is_animating()
             || self.terminal.is_animating(ctx)
             || self.command_palette.is_animating(ctx)
+            // Keeps updating so that a chord can time out without more input.
+            || self.ui.is_chord_pending(time)
     }
 
     pub fn files_and_processes(
@@ -213,18 +288,37 @@ impl App {
     ) {
         (
             &mut self.file_watcher,
-            self.editor.files(),
+            // The config file is always watched so that edits made outside of the editor are applied.
+            [
+                self.config_path.as_path(),
+                self.project_config_path.as_path(),
+            ]
+            .into_iter()
+            .chain(self.editor.files()),
             self.terminal.ptys().chain(self.lsp.processes()),
         )
     }
 }
 
-fn handle_args(editor: &mut Editor, command_palette: &mut CommandPalette, ctx: &mut Ctx) {
+// Returns whether any files were opened.
+fn handle_args(editor: &mut Editor, command_palette: &mut CommandPalette, ctx: &mut Ctx) -> bool {
     let args: Vec<String> = args().skip(1).collect();
     let mut has_file = false;
+    let mut next_line = None;
 
     for arg in &args {
-        let path = Path::new(arg);
+        if arg == "-" {
+            has_file |= open_stdin(editor, ctx);
+            continue;
+        }
+
+        if let Some(line) = parse_line_arg(arg) {
+            next_line = Some(line);
+            continue;
+        }
+
+        let (path, line, column) = parse_file_arg(arg);
+        let line = line.or(next_line.take());
 
         if path.is_dir() {
             continue;
@@ -232,13 +326,26 @@ fn handle_args(editor: &mut Editor, command_palette: &mut CommandPalette, ctx: &
 
         let (pane, doc_list) = editor.last_focused_pane_and_doc_list_mut(ctx.ui);
 
-        if pane.open_file(path, doc_list, ctx).is_ok() {
-            has_file = true;
+        if pane.open_file(path, doc_list, ctx).is_err() {
+            continue;
+        }
+
+        has_file = true;
+
+        let Some(line) = line else {
+            continue;
+        };
+
+        if let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) {
+            let position = doc.line_column_position(line, column.unwrap_or(1));
+
+            doc.jump_cursors(position, false, ctx.gfx);
+            tab.camera.recenter();
         }
     }
 
     for arg in args.iter().rev() {
-        let path = Path::new(arg);
+        let (path, _, _) = parse_file_arg(arg);
 
         let dir = if path.is_dir() {
             Some(path)
@@ -258,4 +365,68 @@ fn handle_args(editor: &mut Editor, command_palette: &mut CommandPalette, ctx: &
 
         break;
     }
+
+    has_file
+}
+
+// Returns whether stdin had any contents to open.
+fn open_stdin(editor: &mut Editor, ctx: &mut Ctx) -> bool {
+    let Some(text) = read_piped_input(stdin()) else {
+        return false;
+    };
+
+    let mut doc = Doc::new(None, None, DocFlags::MULTI_LINE);
+    doc.insert(Position::ZERO, &text, ctx);
+    doc.jump_cursors(Position::ZERO, false, ctx.gfx);
+
+    let (pane, doc_list) = editor.last_focused_pane_and_doc_list_mut(ctx.ui);
+    let doc_id = doc_list.add(doc);
+
+    pane.add_tab(doc_id, doc_list, ctx);
+
+    true
+}
+
+// A TTY would block startup waiting for input until EOF, so only piped input is read.
+fn read_piped_input(mut input: impl Read + IsTerminal) -> Option<String> {
+    if input.is_terminal() {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+
+    if input.read_to_end(&mut bytes).is_err() || bytes.is_empty() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// A +N argument sets the line for the file that follows it.
+fn parse_line_arg(arg: &str) -> Option<usize> {
+    arg.strip_prefix('+').and_then(|line| line.parse().ok())
+}
+
+// Splits a path:line:column argument into its parts, the line and column are one-based.
+fn parse_file_arg(arg: &str) -> (&Path, Option<usize>, Option<usize>) {
+    let path = Path::new(arg);
+
+    if path.exists() {
+        return (path, None, None);
+    }
+
+    let Some((rest, last)) = arg
+        .rsplit_once(':')
+        .and_then(|(rest, last)| Some((rest, last.parse::<usize>().ok()?)))
+    else {
+        return (path, None, None);
+    };
+
+    match rest
+        .rsplit_once(':')
+        .and_then(|(rest, line)| Some((rest, line.parse::<usize>().ok()?)))
+    {
+        Some((rest, line)) => (Path::new(rest), Some(line), Some(last)),
+        _ => (Path::new(rest), Some(last), None),
+    }
 }
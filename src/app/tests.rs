@@ -0,0 +1,98 @@
+use std::{
+    env::temp_dir,
+    fs::{remove_file, File},
+    io::Write,
+    path::Path,
+};
+
+use super::{parse_file_arg, parse_line_arg, read_piped_input};
+
+#[test]
+fn parse_path_with_line() {
+    assert_eq!(
+        parse_file_arg("src/missing.rs:12"),
+        (Path::new("src/missing.rs"), Some(12), None)
+    );
+}
+
+#[test]
+fn parse_path_with_line_and_column() {
+    assert_eq!(
+        parse_file_arg("src/missing.rs:12:5"),
+        (Path::new("src/missing.rs"), Some(12), Some(5))
+    );
+}
+
+#[test]
+fn parse_path_without_line() {
+    assert_eq!(
+        parse_file_arg("src/missing.rs"),
+        (Path::new("src/missing.rs"), None, None)
+    );
+    assert_eq!(
+        parse_file_arg("src/missing.rs:abc"),
+        (Path::new("src/missing.rs:abc"), None, None)
+    );
+}
+
+#[test]
+fn parse_line_before_path() {
+    assert_eq!(parse_line_arg("+12"), Some(12));
+    assert_eq!(parse_line_arg("+abc"), None);
+    assert_eq!(parse_line_arg("12"), None);
+}
+
+#[test]
+fn parse_path_with_drive_letter() {
+    assert_eq!(
+        parse_file_arg("C:\\missing.rs"),
+        (Path::new("C:\\missing.rs"), None, None)
+    );
+    assert_eq!(
+        parse_file_arg("C:\\missing.rs:12"),
+        (Path::new("C:\\missing.rs"), Some(12), None)
+    );
+    assert_eq!(
+        parse_file_arg("C:\\missing.rs:12:5"),
+        (Path::new("C:\\missing.rs"), Some(12), Some(5))
+    );
+}
+
+// Windows doesn't allow colons in file names.
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn parse_existing_path_with_colon() {
+    let path = temp_dir().join("keylime_parse_existing_path_with_colon:12");
+    File::create(&path).unwrap();
+
+    let arg = path.to_str().unwrap().to_owned();
+    let parsed = parse_file_arg(&arg);
+    let _ = remove_file(&path);
+
+    assert_eq!(parsed, (path.as_path(), None, None));
+}
+
+#[test]
+fn read_piped_input_from_file() {
+    let path = temp_dir().join("keylime_read_piped_input_from_file.txt");
+    File::create(&path)
+        .unwrap()
+        .write_all(b"piped\xff")
+        .unwrap();
+
+    let text = read_piped_input(File::open(&path).unwrap());
+    let _ = remove_file(&path);
+
+    assert_eq!(text.as_deref(), Some("piped\u{FFFD}"));
+}
+
+#[test]
+fn read_empty_piped_input() {
+    let path = temp_dir().join("keylime_read_empty_piped_input.txt");
+    File::create(&path).unwrap();
+
+    let text = read_piped_input(File::open(&path).unwrap());
+    let _ = remove_file(&path);
+
+    assert!(text.is_none());
+}
@@ -1,21 +1,28 @@
+pub mod ignore;
 pub mod language;
 pub mod theme;
 
+#[cfg(test)]
+mod tests;
+
 use std::{
     collections::{HashMap, HashSet},
     env::current_exe,
+    fmt::Write,
     fs::{read_dir, read_to_string},
     path::{Path, PathBuf},
 };
 
-use language::{IndentWidth, Language, LanguageLsp};
+use language::{IndentWidth, Language, LanguageLsp, LongLineHighlight};
 use serde::Deserialize;
+use serde_json::Value;
 use theme::Theme;
 
 use crate::{
-    config::language::LanguageBlocks,
+    config::language::{LanguageAutoPairs, LanguageBlocks},
     input::{
         action::ActionName,
+        chord::Chord,
         key::Key,
         keybind::Keybind,
         mods::{Mod, Mods},
@@ -23,11 +30,13 @@ use crate::{
     normalizable::Normalizable,
     platform::{
         dialog::{message, MessageKind},
+        gfx::Gfx,
         window::Window,
     },
     pool::{format_pooled, Pooled, PATH_POOL, STRING_POOL},
     text::{
         doc::Doc,
+        emmet::EmmetSyntax,
         syntax::{Syntax, SyntaxRange, SyntaxToken},
     },
 };
@@ -63,11 +72,20 @@ impl SyntaxDesc<'_> {
     }
 }
 
+#[derive(Deserialize, Debug)]
+struct KeybindDesc {
+    key: Key,
+    #[serde(default)]
+    mods: Vec<Mod>,
+}
+
 #[derive(Deserialize, Debug)]
 struct KeymapDesc {
     key: Key,
     mods: Vec<Mod>,
     action: ActionName,
+    #[serde(default)]
+    chord: Vec<KeybindDesc>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -76,8 +94,23 @@ struct LanguageDesc<'a> {
     extensions: Vec<Pooled<String>>,
     #[serde(default)]
     indent_width: IndentWidth,
+    // Prefer the indentation already used by a file, the configured width is kept when false.
+    #[serde(default = "LanguageDesc::DEFAULT_DETECT_INDENT_WIDTH")]
+    detect_indent_width: bool,
+    #[serde(default)]
+    elastic_tabstops: bool,
+    #[serde(default)]
+    max_line_length: Option<usize>,
+    #[serde(default)]
+    long_line_highlight: LongLineHighlight,
+    #[serde(default)]
+    emmet: Option<EmmetSyntax>,
+    #[serde(default)]
+    word_chars: Option<Pooled<String>>,
     #[serde(default)]
     blocks: LanguageBlocks,
+    #[serde(default)]
+    auto_pairs: LanguageAutoPairs,
     #[serde(default = "LanguageDesc::DEFAULT_COMMENT")]
     comment: Pooled<String>,
     #[serde(default)]
@@ -88,6 +121,48 @@ struct LanguageDesc<'a> {
 
 impl LanguageDesc<'_> {
     const DEFAULT_COMMENT: fn() -> Pooled<String> = || "//".into();
+    const DEFAULT_DETECT_INDENT_WIDTH: fn() -> bool = || true;
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BellKind {
+    None,
+    #[default]
+    Visual,
+    Audible,
+    Both,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineNumberKind {
+    #[default]
+    Absolute,
+    // Distance from the main cursor's line.
+    Relative,
+    // Absolute on the main cursor's line, relative elsewhere.
+    Hybrid,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TerminalConfig {
+    // Replaces the platform's default shells when set.
+    #[serde(default)]
+    pub shell: Option<Pooled<String>>,
+    #[serde(default)]
+    pub shell_args: Vec<Pooled<String>>,
+    // Sent to the shell once it starts, as if it had been typed.
+    #[serde(default)]
+    pub startup_command: Option<Pooled<String>>,
+    #[serde(default)]
+    pub env: HashMap<Pooled<String>, Pooled<String>>,
+    #[serde(default)]
+    pub copy_on_select: bool,
+    #[serde(default)]
+    pub middle_click_paste: bool,
+    #[serde(default)]
+    pub bell: BellKind,
 }
 
 #[derive(Deserialize, Debug)]
@@ -96,18 +171,71 @@ struct ConfigDesc<'a> {
     font_size: f32,
     #[serde(default = "ConfigDesc::DEFAULT_TRIM_TRAILING_WHITESPACE")]
     trim_trailing_whitespace: bool,
+    // Follows trim_trailing_whitespace unless it's set separately.
+    #[serde(default)]
+    trim_blank_lines: Option<bool>,
     #[serde(default = "ConfigDesc::DEFAULT_FORMAT_ON_SAVE")]
     format_on_save: bool,
+    #[serde(default)]
+    backup_on_save: bool,
+    // Backups are saved next to the file as "file~" unless a directory is set.
+    #[serde(default)]
+    backup_dir: Option<Pooled<String>>,
+    // Ask before creating missing folders when saving, they're created without asking when false.
+    #[serde(default = "ConfigDesc::DEFAULT_CONFIRM_CREATE_DIRS_ON_SAVE")]
+    confirm_create_dirs_on_save: bool,
+    #[serde(default = "ConfigDesc::DEFAULT_SMOOTH_SCROLLING")]
+    smooth_scrolling: bool,
+    #[serde(default = "ConfigDesc::DEFAULT_SCROLL_SPEED")]
+    scroll_speed: f32,
+    #[serde(default = "ConfigDesc::DEFAULT_ALLOW_SCROLLING_PAST_RIGHT")]
+    allow_scrolling_past_right: bool,
+    // The number of lines to keep visible above and below the cursor. The camera always keeps
+    // a small margin, so 0 (or anything smaller than that margin) leaves scrolling unchanged.
+    #[serde(default)]
+    scroll_off: usize,
+    #[serde(default = "ConfigDesc::DEFAULT_TAB_DISPLAY_WIDTH")]
+    tab_display_width: usize,
+    #[serde(default)]
+    line_numbers: LineNumberKind,
+    #[serde(default = "ConfigDesc::DEFAULT_SHOW_INVISIBLE_CHARS")]
+    show_invisible_chars: bool,
+    #[serde(default = "ConfigDesc::DEFAULT_PRESERVE_CASE_ON_REPLACE")]
+    preserve_case_on_replace: bool,
+    #[serde(default = "ConfigDesc::DEFAULT_RESTORE_SESSION")]
+    restore_session: bool,
     theme: &'a str,
+    #[serde(default)]
+    light_theme: Option<&'a str>,
+    #[serde(default)]
+    dark_theme: Option<&'a str>,
+    #[serde(default = "ConfigDesc::DEFAULT_FOLLOW_SYSTEM_THEME")]
+    follow_system_theme: bool,
     #[serde(default = "ConfigDesc::DEFAULT_IGNORED_FILES")]
     ignored_files: Vec<Pooled<String>>,
     #[serde(default = "ConfigDesc::DEFAULT_IGNORED_EXTENSIONS")]
     ignored_extensions: Vec<Pooled<String>>,
+    #[serde(default = "ConfigDesc::DEFAULT_SHOW_HIDDEN_FILES")]
+    show_hidden_files: bool,
+    #[serde(default)]
+    keymaps: Vec<KeymapDesc>,
+    #[serde(default = "ConfigDesc::DEFAULT_VIM_MODE")]
+    vim_mode: bool,
+    #[serde(default)]
+    terminal: TerminalConfig,
 }
 
 impl ConfigDesc<'_> {
     const DEFAULT_TRIM_TRAILING_WHITESPACE: fn() -> bool = || true;
     const DEFAULT_FORMAT_ON_SAVE: fn() -> bool = || true;
+    const DEFAULT_CONFIRM_CREATE_DIRS_ON_SAVE: fn() -> bool = || true;
+    const DEFAULT_SMOOTH_SCROLLING: fn() -> bool = || true;
+    const DEFAULT_SCROLL_SPEED: fn() -> f32 = || 1.0;
+    const DEFAULT_ALLOW_SCROLLING_PAST_RIGHT: fn() -> bool = || false;
+    const DEFAULT_TAB_DISPLAY_WIDTH: fn() -> usize = || Gfx::TAB_WIDTH;
+    const DEFAULT_SHOW_INVISIBLE_CHARS: fn() -> bool = || true;
+    const DEFAULT_PRESERVE_CASE_ON_REPLACE: fn() -> bool = || false;
+    const DEFAULT_RESTORE_SESSION: fn() -> bool = || true;
 
     const DEFAULT_IGNORED_FILES: fn() -> Vec<Pooled<String>> = || {
         ["target", "build", "out", ".git", "node_modules"]
@@ -119,6 +247,10 @@ impl ConfigDesc<'_> {
 
     const DEFAULT_IGNORED_EXTENSIONS: fn() -> Vec<Pooled<String>> =
         || ["exe", "app"].iter().copied().map(Into::into).collect();
+
+    const DEFAULT_SHOW_HIDDEN_FILES: fn() -> bool = || false;
+    const DEFAULT_VIM_MODE: fn() -> bool = || false;
+    const DEFAULT_FOLLOW_SYSTEM_THEME: fn() -> bool = || true;
 }
 
 pub struct ConfigError {
@@ -136,22 +268,57 @@ impl ConfigError {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThemeKind {
+    Default,
+    Light,
+    Dark,
+}
+
 pub struct Config {
     pub font: Pooled<String>,
     pub font_size: f32,
     pub trim_trailing_whitespace: bool,
+    pub trim_blank_lines: bool,
     pub format_on_save: bool,
+    pub backup_on_save: bool,
+    pub backup_dir: Option<Pooled<String>>,
+    pub confirm_create_dirs_on_save: bool,
+    pub smooth_scrolling: bool,
+    pub scroll_speed: f32,
+    pub allow_scrolling_past_right: bool,
+    pub scroll_off: usize,
+    pub tab_display_width: usize,
+    pub line_numbers: LineNumberKind,
+    pub show_invisible_chars: bool,
+    pub preserve_case_on_replace: bool,
+    pub restore_session: bool,
     pub theme: Theme,
+    theme_kind: ThemeKind,
+    default_theme: Theme,
+    light_theme: Option<Theme>,
+    dark_theme: Option<Theme>,
+    follow_system_theme: bool,
     pub keymaps: HashMap<Keybind, ActionName>,
+    pub chords: Vec<Chord>,
     pub languages: Vec<Language>,
     pub extension_languages: HashMap<Pooled<String>, usize>,
     pub ignored_files: HashSet<Pooled<String>>,
     pub ignored_extensions: HashSet<Pooled<String>>,
+    pub show_hidden_files: bool,
+    pub vim_mode: bool,
+    pub terminal: TerminalConfig,
 }
 
 impl Config {
     const CONFIG_FILE: &str = "config.json";
     const CONFIG_DIR: &str = "config";
+    const PROJECT_CONFIG_FILE: &str = ".keylime.json";
+    const PROJECT_UNTRUSTED_KEYS: &[&str] = &["backup_dir"];
+    const PROJECT_LANGUAGES_KEY: &str = "languages";
+    const PROJECT_LANGUAGE_COMMAND_KEYS: &[&str] = &["lsp"];
+    const PROJECT_TERMINAL_COMMAND_KEYS: &[&str] =
+        &["shell", "shell_args", "startup_command", "env"];
 
     const DEFAULT_KEYMAPS: fn() -> HashMap<Keybind, ActionName> = || {
         [
@@ -171,12 +338,34 @@ impl Config {
     #[cfg(target_os = "windows")]
     const KEYMAPS_FILE: &str = "windows.json";
 
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     const KEYMAPS_FILE: &str = "macos.json";
 
     const KEYMAPS_DIR: &str = "keymaps";
 
-    pub fn load(dir: &Path) -> Result<Self, ConfigError> {
+    // Settings are layered as defaults, then the user's config, then the project's config.
+    // Objects in a later layer are merged key by key, while other values (including lists)
+    // replace the earlier value entirely. Per-language settings in the project config go under
+    // "languages", keyed by the language file's name, eg. "rust" for "languages/rust.json".
+    // Settings that run commands are ignored in the project config, see remove_project_commands.
+    pub fn load(dir: &Path, project_path: &Path) -> Result<Self, ConfigError> {
+        let mut project_desc = project_path
+            .exists()
+            .then(|| {
+                let project_desc_string = Self::load_file_string(project_path)?;
+                Self::load_file_data::<Value>(project_path, &project_desc_string)
+            })
+            .transpose()?;
+
+        if let Some(project_desc) = &mut project_desc {
+            remove_project_commands(project_desc);
+        }
+
+        let project_languages = project_desc
+            .as_mut()
+            .and_then(Value::as_object_mut)
+            .and_then(|project_desc| project_desc.remove(Self::PROJECT_LANGUAGES_KEY));
+
         let mut path = PATH_POOL.new_item();
 
         path.clear();
@@ -184,14 +373,13 @@ impl Config {
         path.push(Self::KEYMAPS_DIR);
         path.push(Self::KEYMAPS_FILE);
 
-        let mut keymaps = HashMap::new();
+        let mut keymaps = Self::DEFAULT_KEYMAPS();
+        let mut chords = Vec::new();
 
         let keymaps_desc_string = Self::load_file_string(&path)?;
         let keymaps_desc = Self::load_file_data::<Vec<KeymapDesc>>(&path, &keymaps_desc_string)?;
 
-        for KeymapDesc { key, mods, action } in keymaps_desc {
-            keymaps.insert(Keybind::new(key, mods.into()), action);
-        }
+        Self::insert_keymaps(&path, keymaps_desc, &mut keymaps, &mut chords)?;
 
         path.clear();
         path.push(dir);
@@ -207,7 +395,15 @@ impl Config {
                 };
 
                 let path = entry.path();
+
+                let language_overrides = path
+                    .file_stem()
+                    .and_then(|file_stem| file_stem.to_str())
+                    .and_then(|file_stem| project_languages.as_ref()?.get(file_stem));
+
                 let language_desc_string = Self::load_file_string(&path)?;
+                let language_desc_string =
+                    Self::apply_overrides(&path, language_desc_string, language_overrides)?;
                 let mut language_desc =
                     Self::load_file_data::<LanguageDesc>(&path, &language_desc_string)?;
 
@@ -226,16 +422,29 @@ impl Config {
         path.push(Self::CONFIG_FILE);
 
         let config_desc_string = Self::load_file_string(&path)?;
-        let config_desc = Self::load_file_data::<ConfigDesc>(&path, &config_desc_string)?;
-
-        path.clear();
-        path.push(dir);
-        path.push("themes");
-        path.push(config_desc.theme);
-        path.set_extension("json");
-
-        let theme_string = Self::load_file_string(&path)?;
-        let theme = Self::load_file_data(&path, &theme_string)?;
+        let config_desc_string =
+            Self::apply_overrides(&path, config_desc_string, project_desc.as_ref())?;
+        let mut config_desc = Self::load_file_data::<ConfigDesc>(&path, &config_desc_string)?;
+
+        // Keymaps in the main config file take precedence over the platform's keymaps.
+        Self::insert_keymaps(
+            &path,
+            config_desc.keymaps.drain(..),
+            &mut keymaps,
+            &mut chords,
+        )?;
+
+        let theme = Self::load_theme(dir, config_desc.theme)?;
+
+        let light_theme = config_desc
+            .light_theme
+            .map(|name| Self::load_theme(dir, name))
+            .transpose()?;
+
+        let dark_theme = config_desc
+            .dark_theme
+            .map(|name| Self::load_theme(dir, name))
+            .transpose()?;
 
         let ignored_files = HashSet::from_iter(config_desc.ignored_files);
         let ignored_extensions = HashSet::from_iter(config_desc.ignored_extensions);
@@ -244,16 +453,183 @@ impl Config {
             font: config_desc.font,
             font_size: config_desc.font_size,
             trim_trailing_whitespace: config_desc.trim_trailing_whitespace,
+            trim_blank_lines: config_desc
+                .trim_blank_lines
+                .unwrap_or(config_desc.trim_trailing_whitespace),
             format_on_save: config_desc.format_on_save,
+            backup_on_save: config_desc.backup_on_save,
+            backup_dir: config_desc.backup_dir,
+            confirm_create_dirs_on_save: config_desc.confirm_create_dirs_on_save,
+            smooth_scrolling: config_desc.smooth_scrolling,
+            scroll_speed: config_desc.scroll_speed.max(0.0),
+            allow_scrolling_past_right: config_desc.allow_scrolling_past_right,
+            scroll_off: config_desc.scroll_off,
+            tab_display_width: config_desc.tab_display_width,
+            line_numbers: config_desc.line_numbers,
+            show_invisible_chars: config_desc.show_invisible_chars,
+            preserve_case_on_replace: config_desc.preserve_case_on_replace,
+            restore_session: config_desc.restore_session,
             ignored_files,
             ignored_extensions,
-            theme,
+            show_hidden_files: config_desc.show_hidden_files,
+            vim_mode: config_desc.vim_mode,
+            terminal: config_desc.terminal,
+            theme: theme.clone(),
+            theme_kind: ThemeKind::Default,
+            default_theme: theme,
+            light_theme,
+            dark_theme,
+            follow_system_theme: config_desc.follow_system_theme,
             keymaps,
+            chords,
             languages,
             extension_languages,
         })
     }
 
+    fn load_theme(dir: &Path, name: &str) -> Result<Theme, ConfigError> {
+        let mut path = PATH_POOL.new_item();
+
+        path.push(dir);
+        path.push("themes");
+        path.push(name);
+        path.set_extension("json");
+
+        let theme_string = Self::load_file_string(&path)?;
+
+        Self::load_file_data(&path, &theme_string)
+    }
+
+    // Picks the light or dark theme to match the OS, falling back to the default theme when
+    // the OS preference is unknown. Returns true if the active theme changed.
+    pub fn update_theme(&mut self, is_dark_mode: Option<bool>) -> bool {
+        let (theme_kind, theme) = match is_dark_mode.filter(|_| self.follow_system_theme) {
+            Some(true) if self.dark_theme.is_some() => (ThemeKind::Dark, &self.dark_theme),
+            Some(false) if self.light_theme.is_some() => (ThemeKind::Light, &self.light_theme),
+            _ => (ThemeKind::Default, &None),
+        };
+
+        if theme_kind == self.theme_kind {
+            return false;
+        }
+
+        self.theme = theme.as_ref().unwrap_or(&self.default_theme).clone();
+        self.theme_kind = theme_kind;
+
+        true
+    }
+
+    fn insert_keymaps(
+        path: &Path,
+        keymaps_desc: impl IntoIterator<Item = KeymapDesc>,
+        keymaps: &mut HashMap<Keybind, ActionName>,
+        chords: &mut Vec<Chord>,
+    ) -> Result<(), ConfigError> {
+        let file_name = path
+            .file_stem()
+            .and_then(|file_name| file_name.to_str())
+            .unwrap_or_default();
+
+        let conflict_error = |keybinds: &[Keybind], action: ActionName, other_action| {
+            let mut keybinds_text = STRING_POOL.new_item();
+
+            for keybind in keybinds {
+                if !keybinds_text.is_empty() {
+                    keybinds_text.push(' ');
+                }
+
+                let _ = write!(keybinds_text, "{}", keybind);
+            }
+
+            ConfigError::new(
+                "Error Loading Config",
+                format_pooled!(
+                    "Unable to load \"{}\": {} is bound to both {:?} and {:?}",
+                    file_name,
+                    keybinds_text,
+                    other_action,
+                    action
+                ),
+            )
+        };
+
+        let mut file_keymaps = HashMap::new();
+        let mut file_chords: Vec<Chord> = Vec::new();
+
+        for KeymapDesc {
+            key,
+            mods,
+            action,
+            chord,
+        } in keymaps_desc
+        {
+            let keybind = Keybind::new(key, mods.into());
+
+            if chord.is_empty() {
+                if let Some(other_action) = file_keymaps
+                    .insert(keybind, action)
+                    .filter(|other_action| *other_action != action)
+                {
+                    return Err(conflict_error(&[keybind], action, other_action));
+                }
+
+                continue;
+            }
+
+            let keybinds: Vec<Keybind> = [keybind]
+                .into_iter()
+                .chain(
+                    chord
+                        .into_iter()
+                        .map(|KeybindDesc { key, mods }| Keybind::new(key, mods.into())),
+                )
+                .collect();
+
+            if let Some(other_chord) = file_chords
+                .iter()
+                .find(|other_chord| other_chord.keybinds == keybinds)
+            {
+                if other_chord.action_name != action {
+                    return Err(conflict_error(&keybinds, action, other_chord.action_name));
+                }
+
+                continue;
+            }
+
+            file_chords.push(Chord {
+                keybinds,
+                action_name: action,
+            });
+        }
+
+        keymaps.extend(file_keymaps);
+
+        chords.retain(|chord| {
+            !file_chords
+                .iter()
+                .any(|file_chord| file_chord.keybinds == chord.keybinds)
+        });
+
+        chords.append(&mut file_chords);
+
+        Ok(())
+    }
+
+    fn apply_overrides(
+        path: &Path,
+        string: String,
+        overrides: Option<&Value>,
+    ) -> Result<String, ConfigError> {
+        let Some(overrides) = overrides else {
+            return Ok(string);
+        };
+
+        let mut value = Self::load_file_data::<Value>(path, &string)?;
+        merge_values(&mut value, overrides.clone());
+
+        Ok(value.to_string())
+    }
+
     fn load_file_string(path: &Path) -> Result<String, ConfigError> {
         let file_name = path
             .file_stem()
@@ -302,31 +678,39 @@ impl Config {
     }
 
     pub fn indent_width_for_doc(&self, doc: &Doc) -> IndentWidth {
-        self.get_language_for_doc(doc)
+        let language = self.get_language_for_doc(doc);
+
+        if let Some(indent_width) = doc
+            .detected_indent_width()
+            .filter(|_| language.is_none_or(|language| language.detect_indent_width))
+        {
+            return indent_width;
+        }
+
+        language
             .map(|language| language.indent_width)
             .unwrap_or_default()
     }
 
-    pub fn is_path_ignored(&self, path: &Path) -> bool {
-        let is_file_ignored = path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .is_some_and(|name| self.ignored_files.contains(name));
-
-        if is_file_ignored {
-            return true;
-        }
-
-        let is_extension_ignored = path
-            .extension()
-            .and_then(|extension| extension.to_str())
-            .is_some_and(|extension| self.ignored_extensions.contains(extension));
+    pub fn path(dir: &Path) -> Pooled<PathBuf> {
+        dir.join(Self::CONFIG_FILE).as_path().into()
+    }
 
-        if is_extension_ignored {
-            return true;
-        }
+    // The closest project config in the current directory or its ancestors, or where one would
+    // be created in the current directory if there are none.
+    pub fn project_path(current_dir: &Path) -> Pooled<PathBuf> {
+        current_dir
+            .ancestors()
+            .map(|dir| dir.join(Self::PROJECT_CONFIG_FILE))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| current_dir.join(Self::PROJECT_CONFIG_FILE))
+            .as_path()
+            .into()
+    }
 
-        false
+    pub fn is_project_path(path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|file_name| file_name == Self::PROJECT_CONFIG_FILE)
     }
 
     pub fn dir(current_dir: &Path) -> Pooled<PathBuf> {
@@ -360,13 +744,83 @@ impl Default for Config {
             font: STRING_POOL.new_item(),
             font_size: 13.0,
             trim_trailing_whitespace: ConfigDesc::DEFAULT_TRIM_TRAILING_WHITESPACE(),
+            trim_blank_lines: ConfigDesc::DEFAULT_TRIM_TRAILING_WHITESPACE(),
             format_on_save: ConfigDesc::DEFAULT_FORMAT_ON_SAVE(),
+            backup_on_save: false,
+            backup_dir: None,
+            confirm_create_dirs_on_save: ConfigDesc::DEFAULT_CONFIRM_CREATE_DIRS_ON_SAVE(),
+            smooth_scrolling: ConfigDesc::DEFAULT_SMOOTH_SCROLLING(),
+            scroll_speed: ConfigDesc::DEFAULT_SCROLL_SPEED(),
+            allow_scrolling_past_right: ConfigDesc::DEFAULT_ALLOW_SCROLLING_PAST_RIGHT(),
+            scroll_off: 0,
+            tab_display_width: ConfigDesc::DEFAULT_TAB_DISPLAY_WIDTH(),
+            line_numbers: LineNumberKind::default(),
+            show_invisible_chars: ConfigDesc::DEFAULT_SHOW_INVISIBLE_CHARS(),
+            preserve_case_on_replace: ConfigDesc::DEFAULT_PRESERVE_CASE_ON_REPLACE(),
+            restore_session: ConfigDesc::DEFAULT_RESTORE_SESSION(),
             theme: Theme::default(),
+            theme_kind: ThemeKind::Default,
+            default_theme: Theme::default(),
+            light_theme: None,
+            dark_theme: None,
+            follow_system_theme: ConfigDesc::DEFAULT_FOLLOW_SYSTEM_THEME(),
             keymaps: Self::DEFAULT_KEYMAPS(),
+            chords: Vec::new(),
             languages: Vec::new(),
             extension_languages: HashMap::new(),
             ignored_files: HashSet::from_iter(ConfigDesc::DEFAULT_IGNORED_FILES()),
             ignored_extensions: HashSet::from_iter(ConfigDesc::DEFAULT_IGNORED_EXTENSIONS()),
+            show_hidden_files: ConfigDesc::DEFAULT_SHOW_HIDDEN_FILES(),
+            vim_mode: ConfigDesc::DEFAULT_VIM_MODE(),
+            terminal: TerminalConfig::default(),
+        }
+    }
+}
+
+fn merge_values(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_values(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+// Project configs come from whichever folder is opened, so they aren't trusted to choose
+// commands to run. Language servers and terminal shells can only be set in the user's config.
+fn remove_project_commands(project_desc: &mut Value) {
+    let Some(project_desc) = project_desc.as_object_mut() else {
+        return;
+    };
+
+    for key in Config::PROJECT_UNTRUSTED_KEYS {
+        project_desc.remove(*key);
+    }
+
+    if let Some(languages) = project_desc
+        .get_mut(Config::PROJECT_LANGUAGES_KEY)
+        .and_then(Value::as_object_mut)
+    {
+        for language in languages.values_mut().filter_map(Value::as_object_mut) {
+            for key in Config::PROJECT_LANGUAGE_COMMAND_KEYS {
+                language.remove(*key);
+            }
+        }
+    }
+
+    if let Some(terminal) = project_desc
+        .get_mut("terminal")
+        .and_then(Value::as_object_mut)
+    {
+        for key in Config::PROJECT_TERMINAL_COMMAND_KEYS {
+            terminal.remove(*key);
         }
     }
 }
@@ -0,0 +1,210 @@
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use crate::pool::{format_pooled, Pooled, STRING_POOL};
+
+use super::Config;
+
+struct IgnoreRule {
+    // The folder containing the ignore file this rule came from, relative to the root.
+    dir: Pooled<String>,
+    pattern: Pooled<String>,
+    is_negated: bool,
+    is_dir_only: bool,
+    is_anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(dir: &str, line: &str) -> Option<Self> {
+        let line = line.trim_end();
+
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (is_negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        let (is_dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        // Patterns containing a separator are relative to the root rather than matching at any depth.
+        let is_anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            dir: dir.into(),
+            pattern: line.into(),
+            is_negated,
+            is_dir_only,
+            is_anchored,
+        })
+    }
+
+    fn matches(&self, relative_path: &str, file_name: &str, is_dir: bool) -> bool {
+        if self.is_dir_only && !is_dir {
+            return false;
+        }
+
+        let relative_path = if self.dir.is_empty() {
+            relative_path
+        } else {
+            let Some(relative_path) = relative_path
+                .strip_prefix(self.dir.as_str())
+                .and_then(|relative_path| relative_path.strip_prefix('/'))
+            else {
+                return false;
+            };
+
+            relative_path
+        };
+
+        let text = if self.is_anchored {
+            relative_path
+        } else {
+            file_name
+        };
+
+        does_glob_match(self.pattern.as_bytes(), text.as_bytes())
+    }
+}
+
+// Decides which paths to skip when walking the workspace. Rules are checked in order and
+// the last matching rule wins, so project ignore files can override the configured defaults.
+// Ignore files in nested folders only apply within that folder, and are read with load_dir
+// as the walk reaches them.
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn load(root: &Path, config: &Config) -> Self {
+        let mut ignore_rules = Self::new(root);
+
+        if !config.show_hidden_files {
+            ignore_rules.push_rules(".*");
+        }
+
+        for extension in &config.ignored_extensions {
+            ignore_rules.push_rules(&format_pooled!("*.{}", extension));
+        }
+
+        for file in &config.ignored_files {
+            ignore_rules.push_rules(file);
+        }
+
+        ignore_rules.load_dir(root);
+
+        ignore_rules
+    }
+
+    pub fn load_dir(&mut self, dir: &Path) {
+        let Some(relative_dir) = self.relative_path(dir) else {
+            return;
+        };
+
+        for file in Self::IGNORE_FILES {
+            if let Ok(rules) = read_to_string(dir.join(file)) {
+                self.push_rules_in(&relative_dir, &rules);
+            }
+        }
+    }
+
+    pub fn push_rules(&mut self, rules: &str) {
+        self.push_rules_in("", rules);
+    }
+
+    fn push_rules_in(&mut self, relative_dir: &str, rules: &str) {
+        self.rules.extend(
+            rules
+                .lines()
+                .filter_map(|line| IgnoreRule::parse(relative_dir, line)),
+        );
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+
+        let Some(relative_path) = self.relative_path(path) else {
+            return false;
+        };
+
+        let mut is_ignored = false;
+
+        for rule in &self.rules {
+            if rule.matches(&relative_path, file_name, is_dir) {
+                is_ignored = !rule.is_negated;
+            }
+        }
+
+        is_ignored
+    }
+
+    fn relative_path(&self, path: &Path) -> Option<Pooled<String>> {
+        let mut relative_path = STRING_POOL.new_item();
+
+        for component in path.strip_prefix(&self.root).unwrap_or(path).components() {
+            let component = component.as_os_str().to_str()?;
+
+            if !relative_path.is_empty() {
+                relative_path.push('/');
+            }
+
+            relative_path.push_str(component);
+        }
+
+        Some(relative_path)
+    }
+}
+
+fn does_glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', pattern @ ..] => {
+            let pattern = pattern.strip_prefix(b"/").unwrap_or(pattern);
+
+            (0..=text.len()).any(|i| does_glob_match(pattern, &text[i..]))
+        }
+        [b'*', pattern @ ..] => {
+            for i in 0..=text.len() {
+                if does_glob_match(pattern, &text[i..]) {
+                    return true;
+                }
+
+                if text.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+
+            false
+        }
+        [b'?', pattern @ ..] => {
+            matches!(text, [c, text @ ..] if *c != b'/' && does_glob_match(pattern, text))
+        }
+        [p, pattern @ ..] => {
+            matches!(text, [c, text @ ..] if c == p && does_glob_match(pattern, text))
+        }
+    }
+}
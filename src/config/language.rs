@@ -1,10 +1,18 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::{platform::gfx::Gfx, pool::Pooled, text::syntax::Syntax};
+use crate::{
+    platform::gfx::Gfx,
+    pool::Pooled,
+    text::{emmet::EmmetSyntax, syntax::Syntax},
+};
 
 use super::{LanguageDesc, SyntaxDesc};
 
+type AutoPairs = Vec<(Pooled<String>, Pooled<String>)>;
+
 const DEFAULT_BLOCK_START_DELIMITERS: fn() -> Vec<Pooled<String>> = || {
     ["{", "[", "(", ":"]
         .iter()
@@ -14,6 +22,12 @@ const DEFAULT_BLOCK_START_DELIMITERS: fn() -> Vec<Pooled<String>> = || {
 };
 const DEFAULT_BLOCK_END_DELIMITERS: fn() -> Vec<Pooled<String>> =
     || ["}", "]", ")"].iter().copied().map(Into::into).collect();
+const DEFAULT_AUTO_PAIRS: fn() -> AutoPairs = || {
+    [("(", ")"), ("[", "]"), ("{", "}"), ("\"", "\""), ("'", "'")]
+        .iter()
+        .map(|(start, end)| ((*start).into(), (*end).into()))
+        .collect()
+};
 
 #[derive(Deserialize, Debug, Default, Clone, Copy)]
 #[serde(untagged)]
@@ -41,13 +55,122 @@ impl IndentWidth {
             Self::Spaces(_) => " ",
         }
     }
+
+    // Guesses the indent width from the leading whitespace of the first indented lines.
+    pub fn detect<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        const MAX_SAMPLED_LINES: usize = 100;
+        const MAX_SPACES: usize = 8;
+
+        let mut tab_lines = 0;
+        let mut space_lines = 0;
+        let mut space_deltas = [0; MAX_SPACES + 1];
+        let mut previous_spaces = 0;
+
+        let lines = lines
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .take(MAX_SAMPLED_LINES);
+
+        for line in lines {
+            if line.starts_with('\t') {
+                tab_lines += 1;
+                continue;
+            }
+
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+
+            if line[spaces..].starts_with('\t') {
+                // Mixed indentation doesn't say much about either style.
+                continue;
+            }
+
+            if spaces > 0 {
+                space_lines += 1;
+            }
+
+            let delta = spaces.abs_diff(previous_spaces);
+            previous_spaces = spaces;
+
+            if delta > 1 && delta <= MAX_SPACES {
+                space_deltas[delta] += 1;
+            }
+        }
+
+        if tab_lines == 0 && space_lines == 0 {
+            return None;
+        }
+
+        if tab_lines >= space_lines {
+            return Some(Self::Tab);
+        }
+
+        let (spaces, count) = space_deltas
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| **count)?;
+
+        (*count > 0).then_some(Self::Spaces(spaces))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LanguageLspServer {
+    pub command: Pooled<String>,
+    #[serde(default)]
+    pub args: Vec<Pooled<String>>,
+    #[serde(default)]
+    pub env: HashMap<Pooled<String>, Pooled<String>>,
+    pub options: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LanguageLspDesc {
+    language_id: Option<Pooled<String>>,
+    command: Option<Pooled<String>>,
+    #[serde(default)]
+    args: Vec<Pooled<String>>,
+    #[serde(default)]
+    env: HashMap<Pooled<String>, Pooled<String>>,
+    options: Option<Value>,
+    #[serde(default)]
+    additional_servers: Vec<LanguageLspServer>,
 }
 
 #[derive(Debug, Default, Deserialize)]
+#[serde(from = "LanguageLspDesc")]
 pub struct LanguageLsp {
     pub language_id: Option<Pooled<String>>,
-    pub command: Option<Pooled<String>>,
-    pub options: Option<Value>,
+    // The main server comes first, followed by additional servers such as linters.
+    pub servers: Vec<LanguageLspServer>,
+}
+
+impl From<LanguageLspDesc> for LanguageLsp {
+    fn from(desc: LanguageLspDesc) -> Self {
+        let main_server = desc.command.map(|command| LanguageLspServer {
+            command,
+            args: desc.args,
+            env: desc.env,
+            options: desc.options,
+        });
+
+        Self {
+            language_id: desc.language_id,
+            servers: main_server
+                .into_iter()
+                .chain(desc.additional_servers)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LongLineHighlight {
+    // Only the part of the line past the max length is highlighted.
+    #[default]
+    Overflow,
+    Line,
 }
 
 pub enum DelimiterKind {
@@ -78,11 +201,51 @@ impl Default for LanguageBlocks {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LanguageAutoPairs {
+    #[serde(default = "DEFAULT_AUTO_PAIRS")]
+    pub pairs: AutoPairs,
+    // Pairs aren't closed automatically in strings or comments unless this is set.
+    #[serde(default)]
+    pub close_in_strings: bool,
+}
+
+impl LanguageAutoPairs {
+    pub fn get_end(&self, start: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(pair_start, _)| pair_start.as_str() == start)
+            .map(|(_, pair_end)| pair_end.as_str())
+    }
+
+    pub fn is_end(&self, end: &str) -> bool {
+        self.pairs
+            .iter()
+            .any(|(_, pair_end)| pair_end.as_str() == end)
+    }
+}
+
+impl Default for LanguageAutoPairs {
+    fn default() -> Self {
+        Self {
+            pairs: DEFAULT_AUTO_PAIRS(),
+            close_in_strings: false,
+        }
+    }
+}
+
 pub struct Language {
     pub index: usize,
     pub name: Pooled<String>,
     pub indent_width: IndentWidth,
+    pub detect_indent_width: bool,
+    pub elastic_tabstops: bool,
+    pub max_line_length: Option<usize>,
+    pub long_line_highlight: LongLineHighlight,
+    pub emmet: Option<EmmetSyntax>,
+    pub word_chars: Option<Pooled<String>>,
     pub blocks: LanguageBlocks,
+    pub auto_pairs: LanguageAutoPairs,
     pub syntax: Option<Syntax>,
     pub comment: Pooled<String>,
     pub lsp: LanguageLsp,
@@ -94,7 +257,14 @@ impl Language {
             index,
             name: desc.name,
             indent_width: desc.indent_width,
+            detect_indent_width: desc.detect_indent_width,
+            elastic_tabstops: desc.elastic_tabstops,
+            max_line_length: desc.max_line_length,
+            long_line_highlight: desc.long_line_highlight,
+            emmet: desc.emmet,
+            word_chars: desc.word_chars,
             blocks: desc.blocks,
+            auto_pairs: desc.auto_pairs,
             comment: desc.comment,
             lsp: desc.lsp,
             syntax: desc.syntax.map(SyntaxDesc::syntax),
@@ -0,0 +1,226 @@
+use std::{
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir_all, write},
+    path::Path,
+};
+
+use serde_json::json;
+
+use super::{
+    ignore::IgnoreRules,
+    language::{IndentWidth, LanguageAutoPairs, LanguageLsp},
+    merge_values, remove_project_commands,
+};
+
+#[test]
+fn ignore_file_name_at_any_depth() {
+    let mut ignore_rules = IgnoreRules::new(Path::new("/project"));
+    ignore_rules.push_rules("target\n*.log");
+
+    assert!(ignore_rules.is_ignored(Path::new("/project/target"), true));
+    assert!(ignore_rules.is_ignored(Path::new("/project/sub/target"), true));
+    assert!(ignore_rules.is_ignored(Path::new("/project/sub/debug.log"), false));
+    assert!(!ignore_rules.is_ignored(Path::new("/project/src/main.rs"), false));
+}
+
+#[test]
+fn ignore_anchored_and_dir_only() {
+    let mut ignore_rules = IgnoreRules::new(Path::new("/project"));
+    ignore_rules.push_rules("# Comment\n/docs/*.md\nbuild/\nassets/**/*.png");
+
+    assert!(ignore_rules.is_ignored(Path::new("/project/docs/readme.md"), false));
+    assert!(!ignore_rules.is_ignored(Path::new("/project/sub/docs/readme.md"), false));
+    assert!(!ignore_rules.is_ignored(Path::new("/project/docs/nested/readme.md"), false));
+
+    assert!(ignore_rules.is_ignored(Path::new("/project/build"), true));
+    assert!(!ignore_rules.is_ignored(Path::new("/project/build"), false));
+
+    assert!(ignore_rules.is_ignored(Path::new("/project/assets/icon.png"), false));
+    assert!(ignore_rules.is_ignored(Path::new("/project/assets/a/b/icon.png"), false));
+}
+
+#[test]
+fn ignore_negated_rule_overrides_earlier_rules() {
+    let mut ignore_rules = IgnoreRules::new(Path::new("/project"));
+    ignore_rules.push_rules(".*\n*.log");
+    ignore_rules.push_rules("!.github\n!keep.log");
+
+    assert!(ignore_rules.is_ignored(Path::new("/project/.git"), true));
+    assert!(!ignore_rules.is_ignored(Path::new("/project/.github"), true));
+    assert!(ignore_rules.is_ignored(Path::new("/project/debug.log"), false));
+    assert!(!ignore_rules.is_ignored(Path::new("/project/keep.log"), false));
+}
+
+#[test]
+fn ignore_nested_files_only_apply_within_their_dir() {
+    let root = temp_dir().join("keylime_ignore_nested_files_only_apply_within_their_dir");
+    let sub = root.join("sub");
+
+    create_dir_all(&sub).unwrap();
+    write(root.join(".gitignore"), "*.log").unwrap();
+    write(sub.join(".gitignore"), "!keep.log\n/generated\n*.tmp").unwrap();
+
+    let mut ignore_rules = IgnoreRules::new(&root);
+    ignore_rules.load_dir(&root);
+    ignore_rules.load_dir(&sub);
+
+    assert!(ignore_rules.is_ignored(&root.join("debug.log"), false));
+    assert!(ignore_rules.is_ignored(&root.join("keep.log"), false));
+    assert!(!ignore_rules.is_ignored(&sub.join("keep.log"), false));
+    assert!(ignore_rules.is_ignored(&sub.join("debug.log"), false));
+
+    assert!(ignore_rules.is_ignored(&sub.join("generated"), true));
+    assert!(!ignore_rules.is_ignored(&root.join("generated"), true));
+    assert!(!ignore_rules.is_ignored(&sub.join("nested/generated"), true));
+
+    assert!(ignore_rules.is_ignored(&sub.join("nested/a.tmp"), false));
+    assert!(!ignore_rules.is_ignored(&root.join("a.tmp"), false));
+    assert!(!ignore_rules.is_ignored(&root.join("other/a.tmp"), false));
+
+    remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn lsp_bare_command() {
+    let lsp: LanguageLsp = serde_json::from_str(r#"{ "command": "clangd" }"#).unwrap();
+
+    assert_eq!(lsp.servers.len(), 1);
+    assert_eq!(lsp.servers[0].command.as_str(), "clangd");
+    assert!(lsp.servers[0].args.is_empty());
+    assert!(lsp.servers[0].env.is_empty());
+}
+
+#[test]
+fn lsp_command_args_and_env() {
+    let lsp: LanguageLsp = serde_json::from_str(
+        r#"{
+            "command": "rust-analyzer",
+            "args": ["--log-file", "ra.log"],
+            "env": { "RA_LOG": "info" },
+            "additional_servers": [{ "command": "typos-lsp", "args": ["--verbose"] }]
+        }"#,
+    )
+    .unwrap();
+
+    let main_server = &lsp.servers[0];
+    let args: Vec<&str> = main_server.args.iter().map(|arg| arg.as_str()).collect();
+
+    assert_eq!(args, ["--log-file", "ra.log"]);
+    assert_eq!(
+        main_server.env.get("RA_LOG").map(|value| value.as_str()),
+        Some("info")
+    );
+
+    assert_eq!(lsp.servers[1].command.as_str(), "typos-lsp");
+    assert_eq!(lsp.servers[1].args.len(), 1);
+}
+
+#[test]
+fn auto_pairs_default_and_custom() {
+    let default_auto_pairs: LanguageAutoPairs = serde_json::from_str("{}").unwrap();
+
+    assert_eq!(default_auto_pairs.get_end("("), Some(")"));
+    assert!(default_auto_pairs.is_end("'"));
+    assert!(!default_auto_pairs.close_in_strings);
+
+    let auto_pairs: LanguageAutoPairs =
+        serde_json::from_str(r#"{ "pairs": [["(", ")"], ["`", "`"]], "close_in_strings": true }"#)
+            .unwrap();
+
+    assert_eq!(auto_pairs.get_end("`"), Some("`"));
+    assert_eq!(auto_pairs.get_end("{"), None);
+    assert!(!auto_pairs.is_end("'"));
+    assert!(auto_pairs.close_in_strings);
+}
+
+fn detect_indent_width(text: &str) -> Option<IndentWidth> {
+    IndentWidth::detect(text.lines())
+}
+
+#[test]
+fn detect_indent_width_spaces() {
+    let four_spaces = "fn main() {\n    if a {\n        b();\n    }\n}";
+    let two_spaces = "{\n  \"a\": {\n    \"b\": 1\n  },\n\n  \"c\": 2\n}";
+
+    assert!(matches!(
+        detect_indent_width(four_spaces),
+        Some(IndentWidth::Spaces(4))
+    ));
+    assert!(matches!(
+        detect_indent_width(two_spaces),
+        Some(IndentWidth::Spaces(2))
+    ));
+}
+
+#[test]
+fn detect_indent_width_tabs() {
+    let text = "int main() {\n\tif (a) {\n\t\tb();\n\t}\n    // Stray alignment.\n}";
+
+    assert!(matches!(detect_indent_width(text), Some(IndentWidth::Tab)));
+}
+
+#[test]
+fn detect_indent_width_without_indentation() {
+    assert!(detect_indent_width("a\nb\n\nc").is_none());
+    assert!(detect_indent_width("").is_none());
+}
+
+#[test]
+fn project_config_overrides_user_config() {
+    let mut config = json!({
+        "format_on_save": true,
+        "ignored_files": ["target", "build"],
+        "terminal": { "shell": "bash", "copy_on_select": true },
+    });
+
+    merge_values(
+        &mut config,
+        json!({
+            "format_on_save": false,
+            "ignored_files": ["dist"],
+            "terminal": { "shell": "zsh" },
+        }),
+    );
+
+    assert_eq!(
+        config,
+        json!({
+            "format_on_save": false,
+            "ignored_files": ["dist"],
+            "terminal": { "shell": "zsh", "copy_on_select": true },
+        })
+    );
+}
+
+#[test]
+fn project_config_cannot_set_commands() {
+    let mut project_config = json!({
+        "format_on_save": false,
+        "backup_on_save": true,
+        "backup_dir": "/evil",
+        "languages": {
+            "rust": { "indent_width": 2, "lsp": { "command": "evil" } },
+        },
+        "terminal": {
+            "shell": "evil",
+            "shell_args": ["-c", "evil"],
+            "startup_command": "evil",
+            "env": { "PATH": "evil" },
+            "copy_on_select": true,
+        },
+    });
+
+    remove_project_commands(&mut project_config);
+
+    assert_eq!(
+        project_config,
+        json!({
+            "format_on_save": false,
+            "backup_on_save": true,
+            "languages": {
+                "rust": { "indent_width": 2 },
+            },
+            "terminal": { "copy_on_select": true },
+        })
+    );
+}
@@ -5,11 +5,14 @@ use crate::{
     ui::color::Color,
 };
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct TerminalTheme {
     pub background: Color,
     pub foreground: Color,
+    // Black and white fall back to the background and foreground when unset.
+    pub black: Option<Color>,
+    pub white: Option<Color>,
     pub red: Color,
     pub green: Color,
     pub yellow: Color,
@@ -19,6 +22,8 @@ pub struct TerminalTheme {
 
     pub bright_background: Color,
     pub bright_foreground: Color,
+    pub bright_black: Option<Color>,
+    pub bright_white: Option<Color>,
     pub bright_red: Color,
     pub bright_green: Color,
     pub bright_yellow: Color,
@@ -32,6 +37,8 @@ impl Default for TerminalTheme {
         Self {
             background: Color::from_hex(0x0C0C0CFF),
             foreground: Color::from_hex(0xCCCCCCFF),
+            black: None,
+            white: None,
             red: Color::from_hex(0xC50F1FFF),
             green: Color::from_hex(0x13A10EFF),
             yellow: Color::from_hex(0xC19C00FF),
@@ -41,6 +48,8 @@ impl Default for TerminalTheme {
 
             bright_background: Color::from_hex(0x767676FF),
             bright_foreground: Color::from_hex(0xF2F2F2FF),
+            bright_black: None,
+            bright_white: None,
             bright_red: Color::from_hex(0xE74856FF),
             bright_green: Color::from_hex(0x16C60CFF),
             bright_yellow: Color::from_hex(0xF9F1A5FF),
@@ -51,7 +60,7 @@ impl Default for TerminalTheme {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct Theme {
     pub normal: Color,
@@ -71,6 +80,7 @@ pub struct Theme {
     pub info: Color,
     pub warning: Color,
     pub error: Color,
+    pub long_line: Color,
 
     pub terminal: TerminalTheme,
 }
@@ -90,6 +100,12 @@ impl Theme {
             HighlightKind::Terminal(terminal_highlight_kind) => match terminal_highlight_kind {
                 TerminalHighlightKind::Foreground => self.terminal.foreground,
                 TerminalHighlightKind::Background => self.terminal.background,
+                TerminalHighlightKind::Black => {
+                    self.terminal.black.unwrap_or(self.terminal.background)
+                }
+                TerminalHighlightKind::White => {
+                    self.terminal.white.unwrap_or(self.terminal.foreground)
+                }
                 TerminalHighlightKind::Red => self.terminal.red,
                 TerminalHighlightKind::Green => self.terminal.green,
                 TerminalHighlightKind::Yellow => self.terminal.yellow,
@@ -98,6 +114,14 @@ impl Theme {
                 TerminalHighlightKind::Cyan => self.terminal.cyan,
                 TerminalHighlightKind::BrightForeground => self.terminal.bright_foreground,
                 TerminalHighlightKind::BrightBackground => self.terminal.bright_background,
+                TerminalHighlightKind::BrightBlack => self
+                    .terminal
+                    .bright_black
+                    .unwrap_or(self.terminal.bright_background),
+                TerminalHighlightKind::BrightWhite => self
+                    .terminal
+                    .bright_white
+                    .unwrap_or(self.terminal.bright_foreground),
                 TerminalHighlightKind::BrightRed => self.terminal.bright_red,
                 TerminalHighlightKind::BrightGreen => self.terminal.bright_green,
                 TerminalHighlightKind::BrightYellow => self.terminal.bright_yellow,
@@ -138,6 +162,7 @@ impl Default for Theme {
             info: Color::from_hex(0x6E7681FF),
             warning: Color::from_hex(0xC5A82DFF),
             error: Color::from_hex(0xAB311FFF),
+            long_line: Color::from_hex(0xAB311F33),
 
             terminal: TerminalTheme::default(),
         }
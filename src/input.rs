@@ -1,4 +1,5 @@
 pub mod action;
+pub mod chord;
 pub mod editing_actions;
 pub mod key;
 pub mod keybind;
@@ -6,6 +7,7 @@ pub mod mods;
 pub mod mouse_button;
 pub mod mouse_scroll;
 pub mod mousebind;
+pub mod vim;
 
 #[cfg(test)]
 mod tests;
@@ -33,8 +33,16 @@ enum_variants!(
     OpenAllActions,
     OpenAllFiles,
     OpenAllDiagnostics,
+    OpenAllBookmarks,
+    OpenSwitchBuffer,
+    OpenCompareWithBuffer,
+    OpenLanguageServerLogs,
+    ReopenWithEncoding,
+    RestoreSession,
     OpenSearch,
     OpenSearchAndReplace,
+    ToggleSearchInSelection,
+    ReplaceAll,
     OpenFindInFiles,
     OpenGoToLine,
     OpenFile,
@@ -43,16 +51,29 @@ enum_variants!(
     SaveFile,
     NewTab,
     CloseTab,
+    CloseAllTabs,
+    CloseOtherTabs,
+    ReopenClosedTab,
+    TogglePinTab,
     NewPane,
     ClosePane,
     NextTab,
     PreviousTab,
+    CycleRecentTabs,
     NextPane,
     PreviousPane,
     ReloadFile,
     FocusTerminal,
+    ToggleFileTree,
+    ToggleProblems,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
     PageUp,
     PageDown,
+    CenterCursor,
+    ScrollCursorToTop,
+    ScrollCursorToBottom,
     Undo,
     Redo,
     Copy,
@@ -62,29 +83,54 @@ enum_variants!(
     AddCursorUp,
     AddCursorDown,
     ToggleComments,
+    Surround,
+    ChangeSurrounding,
+    DeleteSurrounding,
+    ExpandAbbreviation,
     Indent,
     Unindent,
     MoveLeft,
     MoveRight,
     MoveLeftWord,
     MoveRightWord,
+    MoveLeftSubWord,
+    MoveRightSubWord,
     MoveUp,
     MoveDown,
     MoveUpParagraph,
     MoveDownParagraph,
     ShiftLinesUp,
     ShiftLinesDown,
+    ReverseLines,
+    ShuffleLines,
+    UniqueLines,
+    UniqueLinesIgnoreCase,
+    UniqueAdjacentLines,
+    UniqueAdjacentLinesIgnoreCase,
     UndoCursorPosition,
     RedoCursorPosition,
+    ToggleBookmark,
+    ToggleReadOnly,
+    NextBookmark,
+    PreviousBookmark,
+    NextChange,
+    PreviousChange,
+    RevertChange,
     DeleteBackward,
     DeleteBackwardWord,
     DeleteBackwardLine,
     DeleteForward,
     DeleteForwardWord,
+    DeleteBackwardSubWord,
+    DeleteForwardSubWord,
     RequestCodeAction,
     Rename,
     FindReferences,
     Examine,
+    GoToRelatedInformation,
+    ShowSupertypes,
+    ShowSubtypes,
+    ShowSignatureHelp,
 );
 
 macro_rules! action_name {
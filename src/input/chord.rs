@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+use crate::{config::Config, pool::Pooled, ui::msg::Msg};
+
+use super::{
+    action::{Action, ActionName},
+    key::Key,
+    keybind::Keybind,
+};
+
+#[derive(Debug)]
+pub struct Chord {
+    pub keybinds: Vec<Keybind>,
+    pub action_name: ActionName,
+}
+
+// Tracks a partially entered chord (eg. a leader key followed by more keys).
+pub struct ChordState {
+    keybinds: Vec<Keybind>,
+    // Input received while the chord was pending, replayed if the chord isn't completed.
+    pending_msgs: Vec<Msg>,
+    last_time: f64,
+    is_completing_grapheme: bool,
+    msgs: VecDeque<Msg>,
+}
+
+impl ChordState {
+    const TIMEOUT: f64 = 1.0;
+
+    pub fn new() -> Self {
+        Self {
+            keybinds: Vec::new(),
+            pending_msgs: Vec::new(),
+            last_time: 0.0,
+            is_completing_grapheme: false,
+            msgs: VecDeque::new(),
+        }
+    }
+
+    // Returns true if the keybind was consumed as part of a chord.
+    pub fn push(&mut self, action: Action, config: &Config, time: f64) -> bool {
+        self.update(time);
+        self.is_completing_grapheme = false;
+
+        if action.name.is_some() {
+            self.msgs.push_back(Msg::Action(action));
+            return false;
+        }
+
+        let action = action.translate(&config.keymaps);
+
+        if Self::is_modifier_key(action.keybind.key) {
+            if self.is_pending(time) {
+                self.pending_msgs.push(Msg::Action(action));
+            } else {
+                self.msgs.push_back(Msg::Action(action));
+            }
+
+            return false;
+        }
+
+        self.keybinds.push(action.keybind);
+        self.pending_msgs.push(Msg::Action(action));
+        self.last_time = time;
+
+        let mut is_prefix = false;
+
+        for chord in &config.chords {
+            if chord.keybinds == self.keybinds {
+                self.msgs.push_back(Msg::Action(Action {
+                    keybind: action.keybind,
+                    name: Some(chord.action_name),
+                }));
+
+                self.keybinds.clear();
+                self.pending_msgs.clear();
+                // Keys that are part of a chord shouldn't also be typed.
+                self.is_completing_grapheme = true;
+
+                return true;
+            }
+
+            is_prefix |= chord.keybinds.starts_with(&self.keybinds);
+        }
+
+        if is_prefix {
+            return true;
+        }
+
+        // The keys didn't form a chord, so they're handled like normal input instead.
+        self.replay_pending_msgs();
+
+        false
+    }
+
+    pub fn push_grapheme(&mut self, grapheme: Pooled<String>, time: f64) {
+        self.update(time);
+
+        if self.is_completing_grapheme {
+            return;
+        }
+
+        let msg = Msg::Grapheme(grapheme);
+
+        if self.is_pending(time) {
+            self.pending_msgs.push(msg);
+        } else {
+            self.msgs.push_back(msg);
+        }
+    }
+
+    // Keys from a chord that timed out are handled like normal input.
+    pub fn update(&mut self, time: f64) {
+        if !self.keybinds.is_empty() && !self.is_pending(time) {
+            self.replay_pending_msgs();
+        }
+    }
+
+    fn replay_pending_msgs(&mut self) {
+        self.keybinds.clear();
+        self.msgs.extend(self.pending_msgs.drain(..));
+    }
+
+    pub fn pop(&mut self) -> Option<Msg> {
+        self.msgs.pop_front()
+    }
+
+    pub fn is_pending(&self, time: f64) -> bool {
+        !self.keybinds.is_empty() && time - self.last_time < Self::TIMEOUT
+    }
+
+    fn is_modifier_key(key: Key) -> bool {
+        matches!(
+            key,
+            Key::Shift
+                | Key::Ctrl
+                | Key::Cmd
+                | Key::Alt
+                | Key::LShift
+                | Key::RShift
+                | Key::LCtrl
+                | Key::RCtrl
+                | Key::LAlt
+                | Key::RAlt
+        )
+    }
+}
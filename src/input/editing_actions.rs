@@ -1,9 +1,13 @@
 use std::cmp::Ordering;
 
 use crate::{
-    config::language::DelimiterKind,
+    config::{
+        language::{DelimiterKind, LanguageAutoPairs},
+        Config,
+    },
     ctx::Ctx,
     geometry::position::Position,
+    lsp::types::TypeHierarchyDirection,
     platform::gfx::Gfx,
     pool::STRING_POOL,
     text::{
@@ -14,7 +18,7 @@ use crate::{
         grapheme,
         selection::Selection,
     },
-    ui::tab::Tab,
+    ui::{camera::CameraRecenterKind, tab::Tab},
 };
 
 use super::{
@@ -26,6 +30,7 @@ use super::{
 pub enum DeleteKind {
     Char,
     Word,
+    SubWord,
     Line,
 }
 
@@ -38,6 +43,9 @@ pub fn handle_grapheme(grapheme: &str, doc: &mut Doc, ctx: &mut Ctx) {
         return;
     }
 
+    let mut default_auto_pairs = None;
+    let auto_pairs = get_auto_pairs(doc, ctx.config, &mut default_auto_pairs);
+
     for index in doc.cursor_indices() {
         let cursor = doc.cursor(index);
 
@@ -46,7 +54,7 @@ pub fn handle_grapheme(grapheme: &str, doc: &mut Doc, ctx: &mut Ctx) {
         let previous_position = doc.move_position(cursor.position, -1, 0, ctx.gfx);
         let previous_grapheme = doc.grapheme(previous_position);
 
-        let matching_grapheme = get_matching_grapheme(grapheme);
+        let matching_grapheme = auto_pairs.get_end(grapheme);
 
         if let Some((matching_grapheme, selection)) = matching_grapheme.zip(cursor.get_selection())
         {
@@ -57,15 +65,17 @@ pub fn handle_grapheme(grapheme: &str, doc: &mut Doc, ctx: &mut Ctx) {
             continue;
         }
 
-        if is_matching_grapheme(grapheme) && next_grapheme == grapheme {
+        if auto_pairs.is_end(grapheme) && next_grapheme == grapheme {
             doc.move_cursor(index, 1, 0, false, ctx.gfx);
 
             continue;
         }
 
-        if let Some(matching_grapheme) = matching_grapheme
-            .filter(|_| should_insert_matching_grapheme(grapheme, next_grapheme, previous_grapheme))
-        {
+        if let Some(matching_grapheme) = matching_grapheme.filter(|_| {
+            should_insert_matching_grapheme(grapheme, next_grapheme, previous_grapheme)
+                && (auto_pairs.close_in_strings
+                    || !doc.is_position_in_string_or_comment(cursor.position))
+        }) {
             doc.insert_at_cursor(index, grapheme, ctx);
             doc.insert_at_cursor(index, matching_grapheme, ctx);
             doc.move_cursor(index, -1, 0, false, ctx.gfx);
@@ -93,7 +103,7 @@ fn should_insert_matching_grapheme(
     is_next_clear && is_previous_clear
 }
 
-pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) -> bool {
+pub fn handle_action(action: Action, tab: &mut Tab, doc: &mut Doc, ctx: &mut Ctx) -> bool {
     match action {
         action_name!(MoveLeft, mods) => handle_move(-1, 0, mods.contains(Mod::Shift), doc, ctx.gfx),
         action_name!(MoveRight, mods) => handle_move(1, 0, mods.contains(Mod::Shift), doc, ctx.gfx),
@@ -105,6 +115,12 @@ pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) ->
         action_name!(MoveRightWord, mods) => {
             doc.move_cursors_to_next_word(1, mods.contains(Mod::Shift), ctx.gfx)
         }
+        action_name!(MoveLeftSubWord, mods) => {
+            doc.move_cursors_to_next_sub_word(-1, mods.contains(Mod::Shift), ctx.gfx)
+        }
+        action_name!(MoveRightSubWord, mods) => {
+            doc.move_cursors_to_next_sub_word(1, mods.contains(Mod::Shift), ctx.gfx)
+        }
         action_name!(MoveUpParagraph, mods) => {
             doc.move_cursors_to_next_paragraph(-1, mods.contains(Mod::Shift), ctx.gfx)
         }
@@ -113,6 +129,15 @@ pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) ->
         }
         action_name!(ShiftLinesUp) => handle_shift_lines(-1, doc, ctx),
         action_name!(ShiftLinesDown) => handle_shift_lines(1, doc, ctx),
+        action_name!(ReverseLines) => doc.reverse_lines_at_cursors(ctx),
+        action_name!(ShuffleLines) => doc.shuffle_lines_at_cursors(ctx.time.to_bits(), ctx),
+        action_name!(UniqueLines) => doc.unique_lines_at_cursors(false, ctx),
+        action_name!(UniqueLinesIgnoreCase) => doc.unique_lines_at_cursors(true, ctx),
+        action_name!(UniqueAdjacentLines) => doc.unique_adjacent_lines_at_cursors(false, ctx),
+        action_name!(UniqueAdjacentLinesIgnoreCase) => {
+            doc.unique_adjacent_lines_at_cursors(true, ctx)
+        }
+        action_name!(ExpandAbbreviation) => handle_expand_abbreviation(doc, ctx),
         action_name!(AddCursorUp) => handle_add_cursor(-1, doc, ctx.gfx),
         action_name!(AddCursorDown) => handle_add_cursor(1, doc, ctx.gfx),
         action_name!(DeleteBackward) => handle_delete_backward(DeleteKind::Char, doc, ctx),
@@ -120,6 +145,10 @@ pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) ->
         action_name!(DeleteBackwardLine) => handle_delete_backward(DeleteKind::Line, doc, ctx),
         action_name!(DeleteForward) => handle_delete_forward(DeleteKind::Char, doc, ctx),
         action_name!(DeleteForwardWord) => handle_delete_forward(DeleteKind::Word, doc, ctx),
+        action_name!(DeleteBackwardSubWord) => {
+            handle_delete_backward(DeleteKind::SubWord, doc, ctx)
+        }
+        action_name!(DeleteForwardSubWord) => handle_delete_forward(DeleteKind::SubWord, doc, ctx),
         action_keybind!(key: Enter, mods: Mods::NONE) => handle_enter(doc, ctx),
         action_keybind!(key: Tab, mods) => handle_tab(mods, doc, ctx),
         action_name!(PageUp, mods) => {
@@ -132,6 +161,21 @@ pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) ->
 
             doc.move_cursors(0, height_lines, mods.contains(Mod::Shift), ctx.gfx);
         }
+        action_name!(CenterCursor) => {
+            tab.camera
+                .vertical
+                .recenter(CameraRecenterKind::CursorAtCenter);
+        }
+        action_name!(ScrollCursorToTop) => {
+            tab.camera
+                .vertical
+                .recenter(CameraRecenterKind::CursorAtTop);
+        }
+        action_name!(ScrollCursorToBottom) => {
+            tab.camera
+                .vertical
+                .recenter(CameraRecenterKind::CursorAtBottom);
+        }
         action_name!(Home, mods) => handle_home(mods.contains(Mod::Shift), doc, ctx.gfx),
         action_name!(End, mods) => handle_end(mods.contains(Mod::Shift), doc, ctx.gfx),
         action_name!(GoToStart, mods) => {
@@ -156,6 +200,17 @@ pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) ->
         action_name!(Paste) => handle_paste(doc, ctx),
         action_name!(AddCursorAtNextOccurance) => doc.add_cursor_at_next_occurance(ctx.gfx),
         action_name!(ToggleComments) => doc.toggle_comments_at_cursors(ctx),
+        action_name!(ToggleBookmark) => {
+            let y = doc.cursor(CursorIndex::Main).position.y;
+            doc.toggle_bookmark(y);
+        }
+        action_name!(ToggleReadOnly) => doc.toggle_read_only(),
+        action_name!(NextChange) => handle_jump_to_change(false, doc, ctx.gfx),
+        action_name!(PreviousChange) => handle_jump_to_change(true, doc, ctx.gfx),
+        action_name!(RevertChange) => {
+            let y = doc.cursor(CursorIndex::Main).position.y;
+            doc.revert_diff_hunk(y, ctx);
+        }
         action_name!(Indent) => doc.indent_lines_at_cursors(false, ctx),
         action_name!(Unindent) => doc.indent_lines_at_cursors(true, ctx),
         action_name!(RequestCodeAction) => {
@@ -167,6 +222,12 @@ pub fn handle_action(action: Action, tab: &Tab, doc: &mut Doc, ctx: &mut Ctx) ->
         action_name!(FindReferences) => {
             doc.lsp_references(ctx);
         }
+        action_name!(ShowSupertypes) => {
+            doc.lsp_prepare_type_hierarchy(TypeHierarchyDirection::Supertypes, ctx);
+        }
+        action_name!(ShowSubtypes) => {
+            doc.lsp_prepare_type_hierarchy(TypeHierarchyDirection::Subtypes, ctx);
+        }
         _ => return false,
     }
 
@@ -276,6 +337,24 @@ pub fn handle_left_click(
     doc.jump_cursors(end, true, gfx);
 }
 
+// Clicking in the gutter works like triple clicking, selecting whole lines.
+pub fn handle_gutter_click(doc: &mut Doc, position: Position, mods: Mods, gfx: &mut Gfx) {
+    if !mods.contains(Mod::Shift) {
+        doc.jump_cursors(position, false, gfx);
+    }
+
+    handle_left_click(doc, position, mods, MouseClickCount::Triple, false, gfx);
+}
+
+fn handle_jump_to_change(is_reverse: bool, doc: &mut Doc, gfx: &mut Gfx) {
+    let y = doc.cursor(CursorIndex::Main).position.y;
+
+    if let Some(y) = doc.diff_hunk_y(y, is_reverse) {
+        let x = doc.line_start(y);
+        doc.jump_cursors(Position::new(x, y), false, gfx);
+    }
+}
+
 pub fn handle_delete_backward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
     for index in doc.cursor_indices() {
         let cursor = doc.cursor(index);
@@ -298,8 +377,18 @@ pub fn handle_delete_backward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
                         let start = doc.move_position(end, -1, 0, ctx.gfx);
                         let start_grapheme = doc.grapheme(start);
 
-                        if get_matching_grapheme(start_grapheme)
-                            == Some(doc.grapheme(cursor.position))
+                        let mut default_auto_pairs = None;
+                        let auto_pairs = get_auto_pairs(doc, ctx.config, &mut default_auto_pairs);
+                        let end_grapheme = auto_pairs.get_end(start_grapheme);
+
+                        // Pairs that aren't closed in strings or comments shouldn't be deleted together there.
+                        let is_bracket_in_string_or_comment = end_grapheme
+                            .is_some_and(|end_grapheme| end_grapheme != start_grapheme)
+                            && !auto_pairs.close_in_strings
+                            && doc.is_position_in_string_or_comment(start);
+
+                        if end_grapheme == Some(doc.grapheme(cursor.position))
+                            && !is_bracket_in_string_or_comment
                         {
                             end = doc.move_position(end, 1, 0, ctx.gfx);
                         }
@@ -308,6 +397,7 @@ pub fn handle_delete_backward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
                     }
                 }
                 DeleteKind::Word => doc.move_position_to_next_word(end, -1, ctx.gfx),
+                DeleteKind::SubWord => doc.move_position_to_next_sub_word(end, -1, ctx.gfx),
                 DeleteKind::Line => Position::new(0, end.y),
             };
 
@@ -319,7 +409,7 @@ pub fn handle_delete_backward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
     }
 }
 
-fn handle_delete_forward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
+pub fn handle_delete_forward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
     for index in doc.cursor_indices() {
         let cursor = doc.cursor(index);
 
@@ -331,6 +421,7 @@ fn handle_delete_forward(kind: DeleteKind, doc: &mut Doc, ctx: &mut Ctx) {
             let end = match kind {
                 DeleteKind::Char => doc.move_position(start, 1, 0, ctx.gfx),
                 DeleteKind::Word => doc.move_position_to_next_word(start, 1, ctx.gfx),
+                DeleteKind::SubWord => doc.move_position_to_next_sub_word(start, 1, ctx.gfx),
                 DeleteKind::Line => doc.line_end(start.y),
             };
 
@@ -405,14 +496,30 @@ pub fn handle_enter(doc: &mut Doc, ctx: &mut Ctx) {
     }
 }
 
-fn handle_tab(mods: Mods, doc: &mut Doc, ctx: &mut Ctx) {
+pub fn handle_tab(mods: Mods, doc: &mut Doc, ctx: &mut Ctx) {
     let do_unindent = mods.contains(Mod::Shift);
 
     for index in doc.cursor_indices() {
-        doc.indent_lines_at_cursor(index, do_unindent, ctx);
+        if do_unindent || doc.cursor(index).get_selection().is_some() {
+            doc.indent_lines_at_cursor(index, do_unindent, ctx);
+        } else {
+            doc.indent_at_cursor(index, ctx);
+        }
     }
 }
 
+fn handle_expand_abbreviation(doc: &mut Doc, ctx: &mut Ctx) {
+    let Some(syntax) = ctx
+        .config
+        .get_language_for_doc(doc)
+        .and_then(|language| language.emmet)
+    else {
+        return;
+    };
+
+    doc.expand_abbreviation_at_cursors(syntax, ctx);
+}
+
 fn handle_home(should_select: bool, doc: &mut Doc, gfx: &mut Gfx) {
     for index in doc.cursor_indices() {
         let cursor = doc.cursor(index);
@@ -572,17 +679,14 @@ fn handle_shift_lines(direction: isize, doc: &mut Doc, ctx: &mut Ctx) {
     }
 }
 
-fn get_matching_grapheme(grapheme: &str) -> Option<&str> {
-    match grapheme {
-        "\"" => Some("\""),
-        "'" => Some("'"),
-        "(" => Some(")"),
-        "[" => Some("]"),
-        "{" => Some("}"),
-        _ => None,
+// Docs without a language use the default pairs.
+fn get_auto_pairs<'a>(
+    doc: &Doc,
+    config: &'a Config,
+    default_auto_pairs: &'a mut Option<LanguageAutoPairs>,
+) -> &'a LanguageAutoPairs {
+    match config.get_language_for_doc(doc) {
+        Some(language) => &language.auto_pairs,
+        None => default_auto_pairs.insert(LanguageAutoPairs::default()),
     }
 }
-
-fn is_matching_grapheme(grapheme: &str) -> bool {
-    matches!(grapheme, "\"" | "'" | ")" | "]" | "}")
-}
@@ -100,5 +100,6 @@ pub enum Key {
     LBracket,
     RBracket,
     Minus,
+    Equals,
     Period,
 }
@@ -1,4 +1,9 @@
-use super::{key::Key, mods::Mods};
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    key::Key,
+    mods::{Mod, Mods},
+};
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct Keybind {
@@ -11,3 +16,15 @@ impl Keybind {
         Self { key, mods }
     }
 }
+
+impl Display for Keybind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for mod_ in [Mod::Ctrl, Mod::Cmd, Mod::Alt, Mod::Shift] {
+            if self.mods.contains(mod_) {
+                write!(f, "{:?}+", mod_)?;
+            }
+        }
+
+        write!(f, "{:?}", self.key)
+    }
+}
@@ -15,4 +15,5 @@ define_bit_field!(Mods, Mod, u8);
 impl Mods {
     pub const SHIFT: Self = Self::from(Mod::Shift);
     pub const CTRL: Self = Self::from(Mod::Ctrl);
+    pub const ALT: Self = Self::from(Mod::Alt);
 }
@@ -1,11 +1,26 @@
 use crate::{
+    config::Config,
+    ctx::ctx_with_time,
     geometry::position::Position,
-    input::editing_actions::handle_add_cursor,
+    input::editing_actions::{handle_add_cursor, handle_gutter_click},
+    pool::Pooled,
     tests::{test_with_doc, HELLO_GOODBYE_TEXT, HELLO_WORLD_CODE_TEXT},
-    text::{cursor_index::CursorIndex, doc::DocFlags},
+    text::{action_history::ActionKind, cursor_index::CursorIndex, doc::DocFlags},
+    ui::{core::WidgetId, msg::Msg, slot_list::SlotId, tab::Tab},
 };
 
-use super::editing_actions::{handle_delete_backward, handle_enter, handle_grapheme, DeleteKind};
+use super::{
+    action::{Action, ActionName},
+    chord::{Chord, ChordState},
+    editing_actions::{
+        handle_delete_backward, handle_delete_forward, handle_enter, handle_grapheme, handle_tab,
+        DeleteKind,
+    },
+    key::Key,
+    keybind::Keybind,
+    mods::Mods,
+    vim::{Vim, VimMode},
+};
 
 test_with_doc!(
     delete_backward_wrap_to_previous_line,
@@ -20,6 +35,74 @@ test_with_doc!(
     }
 );
 
+test_with_doc!(delete_sub_words, "getFooBar", |ctx, doc| {
+    let ctx = ctx_with_time!(ctx, 1.0);
+
+    doc.jump_cursor(CursorIndex::Main, Position::new(6, 0), false, ctx.gfx);
+    doc.add_cursor_at(Position::new(9, 0), ctx.gfx);
+    handle_delete_backward(DeleteKind::SubWord, doc, ctx);
+
+    assert_eq!(doc.to_string(), "get");
+
+    doc.undo(ActionKind::Done, ctx);
+    assert_eq!(doc.to_string(), "getFooBar");
+
+    doc.clear_extra_cursors(CursorIndex::Main);
+    doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+    handle_delete_forward(DeleteKind::SubWord, doc, ctx);
+
+    assert_eq!(doc.to_string(), "FooBar");
+});
+
+test_with_doc!(
+    tab_indents_selected_lines,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        doc.jump_cursor(CursorIndex::Main, Position::new(2, 0), false, ctx.gfx);
+        doc.jump_cursor(CursorIndex::Main, Position::new(4, 1), true, ctx.gfx);
+
+        handle_tab(Mods::NONE, doc, ctx);
+        handle_tab(Mods::NONE, doc, ctx);
+
+        assert_eq!(doc.get_line(0), Some("\t\thello world"));
+        assert_eq!(doc.get_line(1), Some("\t\tgoodbye world"));
+
+        let selection = doc.cursor(CursorIndex::Main).get_selection().unwrap();
+        assert_eq!(selection.start, Position::new(4, 0));
+        assert_eq!(selection.end, Position::new(6, 1));
+
+        handle_tab(Mods::SHIFT, doc, ctx);
+
+        assert_eq!(doc.get_line(0), Some("\thello world"));
+        assert_eq!(doc.get_line(1), Some("\tgoodbye world"));
+    }
+);
+
+test_with_doc!(tab_indents_at_cursors, HELLO_GOODBYE_TEXT, |ctx, doc| {
+    doc.jump_cursor(CursorIndex::Main, Position::new(5, 0), false, ctx.gfx);
+    doc.add_cursor_at(Position::new(7, 1), ctx.gfx);
+
+    handle_tab(Mods::NONE, doc, ctx);
+
+    assert_eq!(doc.get_line(0), Some("hello\t world"));
+    assert_eq!(doc.get_line(1), Some("goodbye\t world"));
+    assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(8, 1));
+});
+
+test_with_doc!(gutter_click_selects_lines, "a\nb\nc\nd", |ctx, doc| {
+    handle_gutter_click(doc, Position::new(0, 1), Mods::NONE, ctx.gfx);
+
+    let selection = doc.cursor(CursorIndex::Main).get_selection().unwrap();
+    assert_eq!(selection.start, Position::new(0, 1));
+    assert_eq!(selection.end, Position::new(0, 2));
+
+    handle_gutter_click(doc, Position::new(0, 2), Mods::SHIFT, ctx.gfx);
+
+    let selection = doc.cursor(CursorIndex::Main).get_selection().unwrap();
+    assert_eq!(selection.start, Position::new(0, 1));
+    assert_eq!(selection.end, Position::new(0, 3));
+});
+
 test_with_doc!(match_pairs_in_multi_line_doc, "run_app", |ctx, doc| {
     handle_grapheme("(", doc, ctx);
 
@@ -158,3 +241,178 @@ test_with_doc!(multi_cursor_preserve_x, "hello\n\nworld", |ctx, doc| {
     assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(1, 2));
     assert_eq!(doc.cursor(CursorIndex::Main).desired_visual_x, 1);
 });
+
+fn pop_chord_action(chord_state: &mut ChordState) -> Option<Action> {
+    match chord_state.pop()? {
+        Msg::Action(action) => Some(action),
+        _ => None,
+    }
+}
+
+fn pop_chord_grapheme(chord_state: &mut ChordState) -> Option<Pooled<String>> {
+    match chord_state.pop()? {
+        Msg::Grapheme(grapheme) => Some(grapheme),
+        _ => None,
+    }
+}
+
+#[test]
+fn chord_completes_after_leader() {
+    let leader = Keybind::new(Key::Space, Mods::CTRL);
+    let f = Keybind::new(Key::F, Mods::NONE);
+
+    let mut config = Config::default();
+    config.chords.push(Chord {
+        keybinds: vec![leader, f, f],
+        action_name: ActionName::OpenAllFiles,
+    });
+
+    let mut chord_state = ChordState::new();
+
+    assert!(chord_state.push(Action::from_keybind(leader), &config, 0.0));
+    assert!(chord_state.push(Action::from_keybind(f), &config, 0.1));
+    chord_state.push_grapheme("f".into(), 0.1);
+    assert!(chord_state.is_pending(0.1));
+    assert!(chord_state.pop().is_none());
+
+    assert!(chord_state.push(Action::from_keybind(f), &config, 0.2));
+    chord_state.push_grapheme("f".into(), 0.2);
+    assert_eq!(
+        pop_chord_action(&mut chord_state).and_then(|action| action.name),
+        Some(ActionName::OpenAllFiles)
+    );
+    assert!(chord_state.pop().is_none());
+    assert!(!chord_state.is_pending(0.2));
+}
+
+#[test]
+fn chord_falls_through_when_unmatched() {
+    let leader = Keybind::new(Key::Space, Mods::CTRL);
+    let f = Keybind::new(Key::F, Mods::NONE);
+    let up = Keybind::new(Key::Up, Mods::NONE);
+
+    let mut config = Config::default();
+    config.chords.push(Chord {
+        keybinds: vec![leader, f],
+        action_name: ActionName::OpenAllFiles,
+    });
+
+    let mut chord_state = ChordState::new();
+
+    assert!(chord_state.push(Action::from_keybind(leader), &config, 0.0));
+    assert!(!chord_state.push(Action::from_keybind(up), &config, 0.1));
+
+    assert_eq!(
+        pop_chord_action(&mut chord_state).map(|action| action.keybind),
+        Some(leader)
+    );
+    assert_eq!(
+        pop_chord_action(&mut chord_state).and_then(|action| action.name),
+        Some(ActionName::MoveUp)
+    );
+    assert!(chord_state.pop().is_none());
+}
+
+#[test]
+fn chord_replays_keys_after_timeout() {
+    let leader = Keybind::new(Key::Space, Mods::CTRL);
+    let f = Keybind::new(Key::F, Mods::NONE);
+
+    let mut config = Config::default();
+    config.chords.push(Chord {
+        keybinds: vec![leader, f, f],
+        action_name: ActionName::OpenAllFiles,
+    });
+
+    let mut chord_state = ChordState::new();
+
+    assert!(chord_state.push(Action::from_keybind(leader), &config, 0.0));
+    assert!(chord_state.push(Action::from_keybind(f), &config, 0.1));
+    chord_state.push_grapheme("f".into(), 0.1);
+
+    chord_state.update(0.5);
+    assert!(chord_state.pop().is_none());
+
+    chord_state.update(1.5);
+    assert!(!chord_state.is_pending(1.5));
+    assert_eq!(
+        pop_chord_action(&mut chord_state).map(|action| action.keybind),
+        Some(leader)
+    );
+    assert_eq!(
+        pop_chord_action(&mut chord_state).map(|action| action.keybind),
+        Some(f)
+    );
+    assert_eq!(
+        pop_chord_grapheme(&mut chord_state)
+            .as_deref()
+            .map(String::as_str),
+        Some("f")
+    );
+    assert!(chord_state.pop().is_none());
+}
+
+test_with_doc!(
+    vim_normal_mode_edits_and_inserts,
+    "hello world",
+    |ctx, doc| {
+        let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+        let mut vim = Vim::new();
+
+        doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+
+        assert!(vim.handle_grapheme("w", &mut tab, doc, ctx));
+        assert!(vim.handle_grapheme("x", &mut tab, doc, ctx));
+        assert_eq!(doc.to_string(), "helloworld");
+
+        assert!(vim.handle_grapheme("i", &mut tab, doc, ctx));
+        assert_eq!(vim.mode(), VimMode::Insert);
+        assert!(!vim.handle_grapheme("w", &mut tab, doc, ctx));
+
+        let escape = Action::from_keybind(Keybind::new(Key::Escape, Mods::NONE));
+        assert!(vim.handle_action(escape, &mut tab, doc, ctx));
+        assert_eq!(vim.mode(), VimMode::Normal);
+    }
+);
+
+test_with_doc!(
+    vim_operators_apply_to_motions,
+    "hello world again",
+    |ctx, doc| {
+        let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+        let mut vim = Vim::new();
+
+        doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+
+        assert!(vim.handle_grapheme("d", &mut tab, doc, ctx));
+        assert!(vim.handle_grapheme("w", &mut tab, doc, ctx));
+        assert_eq!(doc.to_string(), " world again");
+        assert_eq!(vim.mode(), VimMode::Normal);
+
+        assert!(vim.handle_grapheme("l", &mut tab, doc, ctx));
+        assert!(vim.handle_grapheme("c", &mut tab, doc, ctx));
+        assert!(vim.handle_grapheme("w", &mut tab, doc, ctx));
+        assert_eq!(doc.to_string(), "  again");
+        assert_eq!(vim.mode(), VimMode::Insert);
+    }
+);
+
+test_with_doc!(
+    vim_unsupported_operator_motion_passes_through,
+    "hello world\nfoo",
+    |ctx, doc| {
+        let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+        let mut vim = Vim::new();
+
+        doc.jump_cursor(CursorIndex::Main, Position::new(0, 1), false, ctx.gfx);
+
+        assert!(vim.handle_grapheme("d", &mut tab, doc, ctx));
+        assert!(vim.handle_grapheme("k", &mut tab, doc, ctx));
+        assert_eq!(doc.to_string(), "hello world\nfoo");
+        assert_eq!(doc.cursor(CursorIndex::Main).position.y, 0);
+
+        assert!(vim.handle_grapheme("x", &mut tab, doc, ctx));
+        assert_eq!(doc.to_string().lines().count(), 2);
+        assert_eq!(doc.to_string().len(), "hello world\nfoo".len() - 1);
+    }
+);
@@ -0,0 +1,258 @@
+use crate::{
+    config::Config,
+    ctx::Ctx,
+    text::doc::{Doc, DocFlag},
+    ui::tab::Tab,
+};
+
+use super::{
+    action::{action_keybind, action_name, Action, ActionName},
+    editing_actions::handle_action,
+    key::Key,
+    keybind::Keybind,
+    mods::Mods,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimMode {
+    Normal,
+    Insert,
+    Visual,
+}
+
+impl VimMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Visual => "VISUAL",
+        }
+    }
+}
+
+// An optional modal input layer that translates keys into the usual editing actions.
+#[derive(Debug, Clone, Copy)]
+pub struct Vim {
+    mode: VimMode,
+    pending_operator: Option<char>,
+}
+
+impl Vim {
+    pub fn new() -> Self {
+        Self {
+            mode: VimMode::Normal,
+            pending_operator: None,
+        }
+    }
+
+    pub fn is_enabled(doc: &Doc, config: &Config) -> bool {
+        config.vim_mode
+            && doc.flags().contains(DocFlag::TrackHistory)
+            && doc.flags().contains(DocFlag::AllowMultipleLines)
+    }
+
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    // Returns true if the grapheme was handled and shouldn't be inserted.
+    pub fn handle_grapheme(
+        &mut self,
+        grapheme: &str,
+        tab: &mut Tab,
+        doc: &mut Doc,
+        ctx: &mut Ctx,
+    ) -> bool {
+        if self.mode == VimMode::Insert {
+            return false;
+        }
+
+        let is_visual = self.mode == VimMode::Visual;
+        let pending_operator = self.pending_operator.take();
+
+        if let Some(operator @ ('d' | 'c' | 'y')) = pending_operator {
+            if let Some(motion) = Self::operator_motion(grapheme) {
+                self.apply_operator(operator, motion, tab, doc, ctx);
+                return true;
+            }
+        }
+
+        match (pending_operator, grapheme) {
+            (None, "h") => self.motion(ActionName::MoveLeft, tab, doc, ctx),
+            (None, "j") => self.motion(ActionName::MoveDown, tab, doc, ctx),
+            (None, "k") => self.motion(ActionName::MoveUp, tab, doc, ctx),
+            (None, "l") => self.motion(ActionName::MoveRight, tab, doc, ctx),
+            (None, "w") => self.motion(ActionName::MoveRightWord, tab, doc, ctx),
+            (None, "b") => self.motion(ActionName::MoveLeftWord, tab, doc, ctx),
+            (None, "0") => self.motion(ActionName::Home, tab, doc, ctx),
+            (None, "$") => self.motion(ActionName::End, tab, doc, ctx),
+            (None, "G") => self.motion(ActionName::GoToEnd, tab, doc, ctx),
+            (Some('g'), "g") => self.motion(ActionName::GoToStart, tab, doc, ctx),
+            (Some('z'), "z") => Self::act(ActionName::CenterCursor, tab, doc, ctx),
+            (Some('z'), "t") => Self::act(ActionName::ScrollCursorToTop, tab, doc, ctx),
+            (Some('z'), "b") => Self::act(ActionName::ScrollCursorToBottom, tab, doc, ctx),
+            (None, "i") => self.mode = VimMode::Insert,
+            (None, "a") => {
+                Self::act(ActionName::MoveRight, tab, doc, ctx);
+                self.mode = VimMode::Insert;
+            }
+            (None, "A") => {
+                Self::act(ActionName::End, tab, doc, ctx);
+                self.mode = VimMode::Insert;
+            }
+            (None, "I") => {
+                Self::act(ActionName::Home, tab, doc, ctx);
+                self.mode = VimMode::Insert;
+            }
+            (None, "o") => {
+                Self::act(ActionName::End, tab, doc, ctx);
+                handle_action(
+                    Action::from_keybind(Keybind::new(Key::Enter, Mods::NONE)),
+                    tab,
+                    doc,
+                    ctx,
+                );
+                self.mode = VimMode::Insert;
+            }
+            (None, "v") if is_visual => self.enter_normal_mode(tab, doc, ctx),
+            (None, "v") => self.mode = VimMode::Visual,
+            (None, "d" | "x") if is_visual => {
+                Self::act(ActionName::Cut, tab, doc, ctx);
+                self.mode = VimMode::Normal;
+            }
+            (None, "c") if is_visual => {
+                Self::act(ActionName::Cut, tab, doc, ctx);
+                self.mode = VimMode::Insert;
+            }
+            (None, "y") if is_visual => {
+                Self::act(ActionName::Copy, tab, doc, ctx);
+                self.enter_normal_mode(tab, doc, ctx);
+            }
+            (Some('d'), "d") => Self::act(ActionName::Cut, tab, doc, ctx),
+            (Some('y'), "y") => Self::act(ActionName::Copy, tab, doc, ctx),
+            (Some('c'), "c") => {
+                Self::act(ActionName::Home, tab, doc, ctx);
+                self.apply_operator('c', ActionName::End, tab, doc, ctx);
+            }
+            (None, "x") => Self::act(ActionName::DeleteForward, tab, doc, ctx),
+            (None, "p") => Self::act(ActionName::Paste, tab, doc, ctx),
+            (None, "u") => Self::act(ActionName::Undo, tab, doc, ctx),
+            (None, "d" | "c" | "y" | "g" | "z") => self.pending_operator = grapheme.chars().next(),
+            // Keys that don't complete the pending operator are handled as if it wasn't there.
+            (Some(_), _) => return self.handle_grapheme(grapheme, tab, doc, ctx),
+            _ => {}
+        }
+
+        true
+    }
+
+    fn operator_motion(grapheme: &str) -> Option<ActionName> {
+        match grapheme {
+            "h" => Some(ActionName::MoveLeft),
+            "l" => Some(ActionName::MoveRight),
+            "w" | "e" => Some(ActionName::MoveRightWord),
+            "b" => Some(ActionName::MoveLeftWord),
+            "0" => Some(ActionName::Home),
+            "$" => Some(ActionName::End),
+            "G" => Some(ActionName::GoToEnd),
+            _ => None,
+        }
+    }
+
+    fn apply_operator(
+        &mut self,
+        operator: char,
+        motion: ActionName,
+        tab: &mut Tab,
+        doc: &mut Doc,
+        ctx: &mut Ctx,
+    ) {
+        let action = Action {
+            keybind: Keybind::new(Key::Null, Mods::SHIFT),
+            name: Some(motion),
+        };
+
+        handle_action(action, tab, doc, ctx);
+
+        // Cutting or copying without a selection would use the whole line instead.
+        let has_selection = doc
+            .cursor_indices()
+            .any(|index| doc.cursor(index).get_selection().is_some());
+
+        match operator {
+            'd' if has_selection => Self::act(ActionName::Cut, tab, doc, ctx),
+            'c' => {
+                if has_selection {
+                    Self::act(ActionName::Cut, tab, doc, ctx);
+                }
+
+                self.mode = VimMode::Insert;
+            }
+            'y' if has_selection => {
+                Self::act(ActionName::Copy, tab, doc, ctx);
+                self.enter_normal_mode(tab, doc, ctx);
+            }
+            _ => {}
+        }
+    }
+
+    // Returns true if the action was handled and shouldn't be processed normally.
+    pub fn handle_action(
+        &mut self,
+        action: Action,
+        tab: &mut Tab,
+        doc: &mut Doc,
+        ctx: &mut Ctx,
+    ) -> bool {
+        match (self.mode, action) {
+            (VimMode::Insert, action_keybind!(key: Escape, mods: Mods::NONE)) => {
+                self.mode = VimMode::Normal;
+            }
+            (VimMode::Visual, action_keybind!(key: Escape, mods: Mods::NONE)) => {
+                self.enter_normal_mode(tab, doc, ctx);
+            }
+            (VimMode::Normal | VimMode::Visual, action_keybind!(key: Enter, mods: Mods::NONE)) => {
+                self.motion(ActionName::MoveDown, tab, doc, ctx);
+            }
+            (VimMode::Normal | VimMode::Visual, action_name!(DeleteBackward)) => {
+                self.motion(ActionName::MoveLeft, tab, doc, ctx);
+            }
+            (VimMode::Normal | VimMode::Visual, action_keybind!(key: Tab, mods: Mods::NONE)) => {}
+            _ => return false,
+        }
+
+        self.pending_operator = None;
+
+        true
+    }
+
+    fn enter_normal_mode(&mut self, tab: &mut Tab, doc: &mut Doc, ctx: &mut Ctx) {
+        self.mode = VimMode::Normal;
+
+        handle_action(
+            Action::from_keybind(Keybind::new(Key::Escape, Mods::NONE)),
+            tab,
+            doc,
+            ctx,
+        );
+    }
+
+    fn motion(&self, action_name: ActionName, tab: &mut Tab, doc: &mut Doc, ctx: &mut Ctx) {
+        let mods = if self.mode == VimMode::Visual {
+            Mods::SHIFT
+        } else {
+            Mods::NONE
+        };
+
+        let action = Action {
+            keybind: Keybind::new(Key::Null, mods),
+            name: Some(action_name),
+        };
+
+        handle_action(action, tab, doc, ctx);
+    }
+
+    fn act(action_name: ActionName, tab: &mut Tab, doc: &mut Doc, ctx: &mut Ctx) {
+        handle_action(Action::from_name(action_name), tab, doc, ctx);
+    }
+}
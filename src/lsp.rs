@@ -1,15 +1,21 @@
 pub mod language_server;
+mod message_parser;
 pub mod position_encoding;
+mod type_hierarchy;
 pub mod types;
 pub mod uri;
 
+#[cfg(test)]
+mod tests;
+
 use std::{
+    borrow::Borrow,
     collections::{hash_map::Entry, HashMap},
     path::Path,
 };
 
 use language_server::{LanguageServer, MessageResult};
-use types::{DecodedDiagnostic, DecodedRange, DecodedTextEdit, Message};
+use types::{DecodedCompletionItem, DecodedDiagnostic, DecodedRange, Message};
 use uri::uri_to_path;
 
 use crate::{
@@ -17,12 +23,13 @@ use crate::{
     ctx::Ctx,
     geometry::position::Position,
     platform::process::Process,
-    pool::STRING_POOL,
+    pool::{format_pooled, STRING_POOL},
     text::doc::Doc,
     ui::{
         command_palette::{
             find_in_files_mode::FindInFilesMode, references_mode::ReferencesMode,
-            rename_mode::RenameMode, CommandPalette,
+            type_hierarchy_mode::TypeHierarchyMode, CommandPalette, CommandPaletteMetaData,
+            CommandPaletteResult,
         },
         editor::Editor,
     },
@@ -40,8 +47,29 @@ pub struct LspExpectedResponse {
     pub version: usize,
 }
 
+// Completions from every server attached to a doc are shown together,
+// skipping items that an earlier server already suggested.
+pub fn merge_completion_items<T: Borrow<DecodedCompletionItem>>(
+    items: &mut Vec<T>,
+    new_items: impl IntoIterator<Item = T>,
+) {
+    for new_item in new_items {
+        let is_duplicate = items.iter().any(|item| {
+            let item: &DecodedCompletionItem = item.borrow();
+            let new_item: &DecodedCompletionItem = new_item.borrow();
+
+            item.label == new_item.label && item.insert_text() == new_item.insert_text()
+        });
+
+        if !is_duplicate {
+            items.push(new_item);
+        }
+    }
+}
+
 pub struct Lsp {
-    servers: HashMap<usize, Option<LanguageServer>>,
+    // Keyed by the language index and the server's index within that language.
+    servers: HashMap<(usize, usize), Option<LanguageServer>>,
 }
 
 impl Lsp {
@@ -62,12 +90,13 @@ impl Lsp {
     }
 
     fn handle_message(
-        (language_index, message): (usize, Message),
+        ((language_index, server_index), message): ((usize, usize), Message),
         editor: &mut Editor,
         command_palette: &mut CommandPalette,
         ctx: &mut Ctx,
     ) -> Option<()> {
-        let server = ctx.lsp.servers.get_mut(&language_index)?.as_mut()?;
+        let server_key = (language_index, server_index);
+        let server = ctx.lsp.servers.get_mut(&server_key)?.as_mut()?;
         let encoding = server.position_encoding();
 
         let (path, method) = server.get_message_path_and_method(&message);
@@ -79,12 +108,12 @@ impl Lsp {
             .unzip();
 
         if let Some(ref mut doc) = doc {
-            if !doc.lsp_is_response_expected(method, message.id, ctx) {
+            if !doc.lsp_is_response_expected(method, message.id, server_index, ctx) {
                 return None;
             }
         }
 
-        let server = ctx.lsp.servers.get_mut(&language_index)?.as_mut()?;
+        let server = ctx.lsp.servers.get_mut(&server_key)?.as_mut()?;
         let result = server.handle_message(method, &message)?;
 
         match result {
@@ -101,6 +130,7 @@ impl Lsp {
                 editor.lsp_update_completion_results(
                     items,
                     server.needs_completion_resolve(),
+                    server_index,
                     doc_id?,
                     ctx,
                 );
@@ -109,9 +139,7 @@ impl Lsp {
                 let doc = doc?;
                 let item = item.decode(encoding, doc);
 
-                editor
-                    .completion_list
-                    .lsp_resolve_completion_item(message.id, item, ctx);
+                editor.lsp_resolve_completion_item(message.id, item, server_index, doc_id?, ctx);
             }
             MessageResult::CodeAction(results) => {
                 let results = results
@@ -119,9 +147,7 @@ impl Lsp {
                     .map(|result| result.decode(encoding))
                     .collect();
 
-                editor
-                    .completion_list
-                    .lsp_update_code_action_results(results, ctx);
+                editor.lsp_update_code_action_results(results, server_index, doc_id?, ctx);
             }
             MessageResult::PrepareRename { range, placeholder } => {
                 let doc = doc?;
@@ -131,12 +157,12 @@ impl Lsp {
                     STRING_POOL.init_item(|placeholder| doc.collect_string(start, end, placeholder))
                 });
 
-                command_palette.open(Box::new(RenameMode::new(placeholder)), editor, ctx);
+                editor.lsp_show_rename(DecodedRange { start, end }, &placeholder, doc_id?, ctx);
             }
             MessageResult::Rename(workspace_edit) => {
-                let edit_lists = workspace_edit.decode(encoding);
+                let document_changes = workspace_edit.decode(encoding);
 
-                editor.lsp_apply_edit_lists(edit_lists, ctx);
+                editor.lsp_apply_document_changes(document_changes, ctx);
             }
             MessageResult::References(mut results) => {
                 results.sort_by(|a, b| a.uri.cmp(b.uri));
@@ -192,6 +218,42 @@ impl Lsp {
                 doc.jump_cursors(position, false, ctx.gfx);
                 tab.camera.recenter();
             }
+            MessageResult::TypeHierarchy { direction, items } => {
+                let results = items
+                    .into_iter()
+                    .map(|item| {
+                        let relative_path = item
+                            .path
+                            .strip_prefix(&*ctx.current_dir)
+                            .unwrap_or(&item.path);
+
+                        // Each level of the hierarchy is indented below the type it came from.
+                        let text = format_pooled!(
+                            "{:indent$}{} {}:{}",
+                            "",
+                            item.name,
+                            relative_path.display(),
+                            item.position.line + 1,
+                            indent = item.depth * 2,
+                        );
+
+                        CommandPaletteResult {
+                            text,
+                            meta_data: CommandPaletteMetaData::PathWithEncodedPosition {
+                                path: item.path,
+                                encoding,
+                                position: item.position,
+                            },
+                        }
+                    })
+                    .collect();
+
+                command_palette.open(
+                    Box::new(TypeHierarchyMode::new(direction, results)),
+                    editor,
+                    ctx,
+                );
+            }
             MessageResult::SignatureHelp(signature_help) => {
                 editor
                     .signature_help_popup
@@ -206,12 +268,7 @@ impl Lsp {
             MessageResult::Formatting(edits) => {
                 let doc = doc?;
 
-                let mut edits: Vec<DecodedTextEdit> = edits
-                    .into_iter()
-                    .map(|edit| edit.decode(encoding, doc))
-                    .collect();
-
-                doc.lsp_apply_edit_list(&mut edits, ctx);
+                doc.apply_text_edits(edits, encoding, ctx);
                 let _ = doc.save(None, ctx);
             }
             MessageResult::Diagnostic(diagnostics) => {
@@ -225,14 +282,14 @@ impl Lsp {
         Some(())
     }
 
-    fn poll(&mut self) -> Option<(usize, Message)> {
-        for (index, server) in self.servers.iter_mut() {
+    fn poll(&mut self) -> Option<((usize, usize), Message)> {
+        for (key, server) in self.servers.iter_mut() {
             let Some(server) = server else {
                 continue;
             };
 
             if let Some(result) = server.poll() {
-                return Some((*index, result));
+                return Some((*key, result));
             }
         }
 
@@ -243,31 +300,60 @@ impl Lsp {
         self.servers.values_mut().flatten()
     }
 
-    pub fn get_language_server_mut(
+    pub fn iter_servers(&self) -> impl Iterator<Item = ((usize, usize), &LanguageServer)> {
+        self.servers
+            .iter()
+            .filter_map(|(key, server)| Some((*key, server.as_ref()?)))
+    }
+
+    pub fn get_server(&self, key: (usize, usize)) -> Option<&LanguageServer> {
+        self.servers.get(&key)?.as_ref()
+    }
+
+    pub fn get_language_servers_mut(
         &mut self,
         doc: &Doc,
         config: &Config,
         current_dir: &Path,
-    ) -> Option<&mut LanguageServer> {
-        if doc
+    ) -> impl Iterator<Item = (usize, &mut LanguageServer)> {
+        let language = doc
             .path()
             .some()
-            .is_none_or(|path| !path.starts_with(current_dir))
-        {
-            return None;
+            .filter(|path| path.starts_with(current_dir))
+            .and_then(|_| config.get_language_for_doc(doc));
+
+        if let Some(language) = language {
+            for (server_index, server) in language.lsp.servers.iter().enumerate() {
+                if let Entry::Vacant(entry) = self.servers.entry((language.index, server_index)) {
+                    entry.insert(LanguageServer::new(
+                        &server.command,
+                        &server.args,
+                        &server.env,
+                        current_dir,
+                        &server.options,
+                    ));
+                }
+            }
         }
 
-        let language = config.get_language_for_doc(doc)?;
+        let language_index = language.map(|language| language.index);
 
-        if let Entry::Vacant(entry) = self.servers.entry(language.index) {
-            let command = language.lsp.command.as_ref()?;
-            let language_server = LanguageServer::new(command, current_dir, &language.lsp.options);
-
-            entry.insert(language_server);
-        }
+        self.servers
+            .iter_mut()
+            .filter(move |((index, _), _)| Some(*index) == language_index)
+            .filter_map(|((_, server_index), server)| Some((*server_index, server.as_mut()?)))
+    }
 
-        let server = self.servers.get_mut(&language.index)?;
-        server.as_mut()
+    pub fn get_language_server_at_mut(
+        &mut self,
+        server_index: usize,
+        doc: &Doc,
+        config: &Config,
+        current_dir: &Path,
+    ) -> Option<&mut LanguageServer> {
+        self.get_language_servers_mut(doc, config, current_dir)
+            .find(|(index, _)| *index == server_index)
+            .map(|(_, server)| server)
     }
 
     pub fn get_diagnostic_at<'a>(
@@ -1,4 +1,3 @@
-use core::str;
 use std::{
     collections::{HashMap, HashSet},
     path::{Path, PathBuf},
@@ -12,8 +11,9 @@ use crate::{
     lsp::{
         types::{
             EncodedDefinitionResult, EncodedFullDocumentDiagnosticParams, EncodedLocation,
-            EncodedPosition, EncodedPublishDiagnosticsParams, InitializeResult,
-            LspCodeActionResult, LspCompletionResult, Message, RegistrationParams,
+            EncodedPosition, EncodedPublishDiagnosticsParams, EncodedTypeHierarchyItem,
+            InitializeResult, LspCodeActionResult, LspCompletionResult, Message,
+            RegistrationParams, TypeHierarchyDirection,
         },
         uri::uri_to_path,
     },
@@ -26,13 +26,15 @@ use crate::{
 };
 
 use super::{
+    message_parser::MessageParser,
     position_encoding::PositionEncoding,
+    type_hierarchy::{TypeHierarchy, TypeHierarchyItem},
     types::{
         DecodedCompletionItem, DecodedDiagnostic, EncodedCompletionItem, EncodedDiagnostic,
         EncodedHover, EncodedRange, EncodedTextEdit, EncodedWorkspaceEdit, LspPrepareRenameResult,
         SignatureHelp,
     },
-    uri::path_to_uri,
+    uri::{path_to_untitled_uri, path_to_uri},
     LspSentRequest,
 };
 
@@ -70,16 +72,10 @@ impl Diagnostics {
     }
 }
 
-enum MessageParseState {
-    Idle,
-    HasContentLen(usize),
-}
-
 #[derive(Debug)]
-#[allow(clippy::large_enum_variant)]
 pub(super) enum MessageResult<'a> {
     Completion(Vec<EncodedCompletionItem>),
-    CompletionItemResolve(EncodedCompletionItem),
+    CompletionItemResolve(Box<EncodedCompletionItem>),
     CodeAction(Vec<LspCodeActionResult>),
     PrepareRename {
         range: EncodedRange,
@@ -91,6 +87,10 @@ pub(super) enum MessageResult<'a> {
         path: Pooled<PathBuf>,
         range: EncodedRange,
     },
+    TypeHierarchy {
+        direction: TypeHierarchyDirection,
+        items: Vec<TypeHierarchyItem>,
+    },
     SignatureHelp(Option<SignatureHelp>),
     Hover(Option<EncodedHover>),
     Formatting(Vec<EncodedTextEdit>),
@@ -99,42 +99,72 @@ pub(super) enum MessageResult<'a> {
 
 pub struct LanguageServer {
     pub(super) process: Process,
+    name: Pooled<String>,
+    log: String,
+    log_generation: usize,
+    diagnostics_generation: usize,
     next_request_id: usize,
     pending_requests: HashMap<usize, (Option<Pooled<PathBuf>>, &'static str)>,
-    parse_state: MessageParseState,
-    incoming_messages: Vec<u8>,
+    message_parser: MessageParser,
     outgoing_messages: Vec<u8>,
     has_initialized: bool,
 
     diagnostics: HashMap<Pooled<PathBuf>, Diagnostics>,
+    untitled_paths: HashSet<Pooled<PathBuf>>,
     needs_completion_resolve: bool,
     do_pull_diagnostics: bool,
 
     position_encoding: PositionEncoding,
     trigger_chars: HashSet<char>,
     retrigger_chars: HashSet<char>,
+    completion_triggers: Vec<Pooled<String>>,
+    type_hierarchy: Option<TypeHierarchy>,
 }
 
 impl LanguageServer {
-    pub fn new(command: &str, current_dir: &Path, options: &Option<Value>) -> Option<Self> {
-        let process = Process::new(&[command], ProcessKind::Normal).ok()?;
+    const MAX_LOG_LEN: usize = 1 << 20;
+
+    pub fn new(
+        command: &str,
+        args: &[Pooled<String>],
+        env: &HashMap<Pooled<String>, Pooled<String>>,
+        current_dir: &Path,
+        options: &Option<Value>,
+    ) -> Option<Self> {
+        let command: Vec<&str> = [command]
+            .into_iter()
+            .chain(args.iter().map(|arg| arg.as_str()))
+            .collect();
+
+        let env: Vec<(&str, &str)> = env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+
+        let process = Process::new(&[&command], &env, None, ProcessKind::Normal).ok()?;
 
         let mut language_server = Self {
             process,
+            name: command[0].into(),
+            log: String::new(),
+            log_generation: 0,
+            diagnostics_generation: 0,
             next_request_id: 0,
             pending_requests: HashMap::new(),
-            parse_state: MessageParseState::Idle,
-            incoming_messages: Vec::new(),
+            message_parser: MessageParser::new(),
             outgoing_messages: Vec::new(),
             has_initialized: false,
 
             diagnostics: HashMap::new(),
+            untitled_paths: HashSet::new(),
             needs_completion_resolve: false,
             do_pull_diagnostics: false,
 
             position_encoding: PositionEncoding::Utf16,
             trigger_chars: HashSet::new(),
             retrigger_chars: HashSet::new(),
+            completion_triggers: Vec::new(),
+            type_hierarchy: None,
         };
 
         let workspace_name = current_dir
@@ -160,6 +190,11 @@ impl LanguageServer {
                 "capabilities": {
                     "workspace": {
                         "workspaceFolders": true,
+                        "workspaceEdit": {
+                            "documentChanges": true,
+                            "resourceOperations": ["create", "rename", "delete"],
+                            "failureHandling": "abort",
+                        },
                     },
                     "general": {
                         "positionEncodings": ["utf-8", "utf-16"],
@@ -198,9 +233,15 @@ impl LanguageServer {
                         "definition": {
                             "linkSupport": true,
                         },
+                        "typeHierarchy": {
+                            "dynamicRegistration": false,
+                        },
                         "diagnostic": {
                             "dynamicRegistration": true,
                         },
+                        "publishDiagnostics": {
+                            "relatedInformation": true,
+                        },
                     },
                 },
             }),
@@ -226,62 +267,79 @@ impl LanguageServer {
         self.diagnostics.iter_mut()
     }
 
-    pub(super) fn poll(&mut self) -> Option<Message> {
-        loop {
-            let (_, mut output) = self.process.input_output();
-            self.incoming_messages.extend_from_slice(output.data());
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 
-            match self.parse_state {
-                MessageParseState::Idle => {
-                    let mut header_len = None;
+    pub fn log(&self) -> &str {
+        &self.log
+    }
 
-                    for i in 3..self.incoming_messages.len() {
-                        if &self.incoming_messages[i - 3..=i] == b"\r\n\r\n" {
-                            header_len = Some(i + 1);
-                            break;
-                        }
-                    }
+    // Incremented whenever the start of the log is discarded.
+    pub fn log_generation(&self) -> usize {
+        self.log_generation
+    }
 
-                    let header_len = header_len?;
-                    let header = str::from_utf8(&self.incoming_messages[..header_len]);
+    // Incremented whenever any diagnostics are replaced.
+    pub fn diagnostics_generation(&self) -> usize {
+        self.diagnostics_generation
+    }
 
-                    let Ok(header) = header else {
-                        self.incoming_messages.drain(..header_len);
-                        return None;
-                    };
+    fn append_log(&mut self, text: &str) {
+        self.log.push_str(text);
 
-                    let prefix_len = "Content-Length: ".len();
-                    let suffix_len = "\r\n\r\n".len();
+        if self.log.len() <= Self::MAX_LOG_LEN {
+            return;
+        }
 
-                    if header_len < prefix_len + suffix_len {
-                        self.incoming_messages.drain(..header_len);
-                        return None;
-                    }
+        // Discard the older half of the log, starting from a line boundary.
+        let mut start = self.log.len() - Self::MAX_LOG_LEN / 2;
 
-                    let Ok(content_len) =
-                        header[prefix_len..header_len - suffix_len].parse::<usize>()
-                    else {
-                        self.incoming_messages.drain(..header_len);
-                        return None;
-                    };
+        while !self.log.is_char_boundary(start) {
+            start += 1;
+        }
 
-                    self.parse_state = MessageParseState::HasContentLen(content_len);
-                    self.incoming_messages.drain(..header_len);
-                }
-                MessageParseState::HasContentLen(content_len) => {
-                    if self.incoming_messages.len() < content_len {
-                        return None;
-                    }
+        if let Some(newline_index) = self.log[start..].find('\n') {
+            start += newline_index + 1;
+        }
 
-                    self.parse_state = MessageParseState::Idle;
+        self.log.drain(..start);
+        self.log_generation += 1;
+    }
 
-                    let message =
-                        serde_json::from_slice::<Message>(&self.incoming_messages[..content_len]);
+    fn read_error_output(&mut self) {
+        let mut error_output = self.process.error_output();
 
-                    self.incoming_messages.drain(..content_len);
+        if error_output.data().is_empty() {
+            return;
+        }
 
-                    return message.ok();
-                }
+        let text = String::from_utf8_lossy(error_output.data()).into_owned();
+        drop(error_output);
+
+        self.append_log(&text);
+    }
+
+    pub(super) fn poll(&mut self) -> Option<Message> {
+        self.read_error_output();
+
+        let (_, mut output) = self.process.input_output();
+        self.message_parser.push(output.data());
+        drop(output);
+
+        loop {
+            let content = self.message_parser.next_content()?;
+            let message = serde_json::from_slice::<Message>(content);
+
+            #[cfg(feature = "lsp_debug")]
+            {
+                let text = format!("<-- {}\n", String::from_utf8_lossy(content));
+                self.append_log(&text);
+            }
+
+            // Skip messages that can't be parsed rather than stopping at them.
+            if let Ok(message) = message {
+                return Some(message);
             }
         }
     }
@@ -314,10 +372,16 @@ impl LanguageServer {
                         self.position_encoding = PositionEncoding::Utf8;
                     }
 
-                    self.needs_completion_resolve = result
-                        .capabilities
-                        .completion_provider
-                        .is_some_and(|provider| provider.resolve_provider);
+                    if let Some(provider) = result.capabilities.completion_provider {
+                        self.needs_completion_resolve = provider.resolve_provider;
+
+                        self.completion_triggers.extend(
+                            provider
+                                .trigger_characters
+                                .into_iter()
+                                .filter(|trigger| !trigger.is_empty()),
+                        );
+                    }
 
                     if let Some(provider) = result.capabilities.signature_help_provider {
                         self.trigger_chars.extend(
@@ -390,7 +454,7 @@ impl LanguageServer {
                 let result = message.result.as_ref()?;
                 let result = serde_json::from_str::<EncodedCompletionItem>(result.get()).ok()?;
 
-                Some(MessageResult::CompletionItemResolve(result))
+                Some(MessageResult::CompletionItemResolve(Box::new(result)))
             }
             "textDocument/codeAction" => {
                 let result = message
@@ -462,6 +526,74 @@ impl LanguageServer {
                     range: result.range,
                 })
             }
+            "textDocument/prepareTypeHierarchy" => {
+                // Responses to requests that were replaced by a newer one are ignored.
+                self.type_hierarchy.as_mut()?.take_request(message.id?)?;
+
+                let item = message
+                    .result
+                    .as_ref()
+                    .and_then(|result| {
+                        serde_json::from_str::<Option<Vec<&RawValue>>>(result.get()).ok()
+                    })
+                    .flatten()
+                    .and_then(|items| items.into_iter().nth(0));
+
+                match item {
+                    Some(item) => self.request_type_hierarchy(item, None),
+                    None => self.type_hierarchy = None,
+                }
+
+                None
+            }
+            "typeHierarchy/supertypes" | "typeHierarchy/subtypes" => {
+                let type_hierarchy = self.type_hierarchy.as_mut()?;
+                let parent = type_hierarchy.take_request(message.id?)?;
+
+                let items = message
+                    .result
+                    .as_ref()
+                    .and_then(|result| {
+                        serde_json::from_str::<Option<Vec<&RawValue>>>(result.get()).ok()
+                    })
+                    .flatten()
+                    .unwrap_or_default();
+
+                let mut next_requests = Vec::new();
+
+                for raw_item in items {
+                    let Ok(item) = serde_json::from_str::<EncodedTypeHierarchyItem>(raw_item.get())
+                    else {
+                        continue;
+                    };
+
+                    let Some(path) = uri_to_path(item.uri) else {
+                        continue;
+                    };
+
+                    let position = item.selection_range.start;
+
+                    if let Some(index) = type_hierarchy.push_item(parent, item.name, path, position)
+                    {
+                        next_requests.push((raw_item, index));
+                    }
+                }
+
+                for (raw_item, index) in next_requests {
+                    self.request_type_hierarchy(raw_item, Some(index));
+                }
+
+                if !self.type_hierarchy.as_ref()?.is_done() {
+                    return None;
+                }
+
+                let type_hierarchy = self.type_hierarchy.take()?;
+
+                Some(MessageResult::TypeHierarchy {
+                    direction: type_hierarchy.direction(),
+                    items: type_hierarchy.into_items(),
+                })
+            }
             "textDocument/signatureHelp" => {
                 let result = message
                     .result
@@ -509,14 +641,28 @@ impl LanguageServer {
     ) {
         let path_diagnostics = self.diagnostics.entry(path).or_default();
         path_diagnostics.replace(&mut diagnostics);
+        self.diagnostics_generation = self.diagnostics_generation.wrapping_add(1);
     }
 
-    pub fn did_open(&mut self, path: &Path, language_id: &str, version: usize, text: &str) {
+    pub fn did_open(
+        &mut self,
+        path: &Path,
+        is_untitled: bool,
+        language_id: &str,
+        version: usize,
+        text: &str,
+    ) {
+        if is_untitled {
+            self.untitled_paths.insert(path.into());
+        } else {
+            self.untitled_paths.remove(path);
+        }
+
         self.send_notification(
             "textDocument/didOpen",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                     "languageId": language_id,
                     "version": version,
                     "text": text,
@@ -538,7 +684,7 @@ impl LanguageServer {
             "textDocument/didChange",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                     "version": version,
                 },
                 "contentChanges": [{
@@ -552,15 +698,29 @@ impl LanguageServer {
         );
     }
 
-    pub fn completion(&mut self, path: &Path, position: Position, doc: &Doc) -> LspSentRequest {
+    pub fn completion(
+        &mut self,
+        path: &Path,
+        position: Position,
+        trigger: Option<&str>,
+        doc: &Doc,
+    ) -> LspSentRequest {
         self.send_request(
             Some(path),
             "textDocument/completion",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
+                "context": {
+                    "triggerKind": if trigger.is_some() {
+                        2
+                    } else {
+                        1
+                    },
+                    "triggerCharacter": trigger,
+                },
             }),
         )
     }
@@ -597,7 +757,7 @@ impl LanguageServer {
             "textDocument/codeAction",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "range": {
                     "start": EncodedPosition::encode(start, encoding, doc),
@@ -616,7 +776,7 @@ impl LanguageServer {
             "textDocument/prepareRename",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
             }),
@@ -635,7 +795,7 @@ impl LanguageServer {
             "textDocument/rename",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
                 "newName": new_name,
@@ -649,7 +809,7 @@ impl LanguageServer {
             "textDocument/references",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
                 "context": {
@@ -676,13 +836,60 @@ impl LanguageServer {
             "textDocument/definition",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
             }),
         )
     }
 
+    pub fn prepare_type_hierarchy(
+        &mut self,
+        direction: TypeHierarchyDirection,
+        path: &Path,
+        position: Position,
+        doc: &Doc,
+    ) -> LspSentRequest {
+        let sent_request = self.send_request(
+            Some(path),
+            "textDocument/prepareTypeHierarchy",
+            json!({
+                "textDocument": {
+                    "uri": self.uri(path),
+                },
+                "position": EncodedPosition::encode(position, self.position_encoding, doc),
+            }),
+        );
+
+        let mut type_hierarchy = TypeHierarchy::new(direction, path.into());
+        type_hierarchy.add_request(sent_request.id, None);
+
+        self.type_hierarchy = Some(type_hierarchy);
+
+        sent_request
+    }
+
+    fn request_type_hierarchy(&mut self, item: &RawValue, parent: Option<usize>) {
+        let Some(type_hierarchy) = &self.type_hierarchy else {
+            return;
+        };
+
+        let method = type_hierarchy.direction().method();
+        let path = type_hierarchy.path().clone();
+
+        let sent_request = self.send_request(
+            Some(&path),
+            method,
+            json!({
+                "item": item,
+            }),
+        );
+
+        if let Some(type_hierarchy) = &mut self.type_hierarchy {
+            type_hierarchy.add_request(sent_request.id, parent);
+        }
+    }
+
     pub fn signature_help(
         &mut self,
         path: &Path,
@@ -696,14 +903,16 @@ impl LanguageServer {
             "textDocument/signatureHelp",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
                 "context": {
                     "triggerKind": if trigger_char.is_some() {
                         2
-                    } else {
+                    } else if is_retrigger {
                         3
+                    } else {
+                        1
                     },
                     "triggerCharacter": trigger_char,
                     "isRetrigger": is_retrigger,
@@ -718,7 +927,7 @@ impl LanguageServer {
             "textDocument/hover",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "position": EncodedPosition::encode(position, self.position_encoding, doc),
             }),
@@ -731,7 +940,7 @@ impl LanguageServer {
             "textDocument/formatting",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
                 "options": {
                     "tabSize": Gfx::TAB_WIDTH,
@@ -751,7 +960,7 @@ impl LanguageServer {
             "textDocument/diagnostic",
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
             }),
         ))
@@ -762,10 +971,22 @@ impl LanguageServer {
             method,
             json!({
                 "textDocument": {
-                    "uri": path_to_uri(path),
+                    "uri": self.uri(path),
                 },
             }),
         );
+
+        if method == "textDocument/didClose" {
+            self.untitled_paths.remove(path);
+        }
+    }
+
+    fn uri(&self, path: &Path) -> Pooled<String> {
+        if self.untitled_paths.contains(path) {
+            path_to_untitled_uri(path)
+        } else {
+            path_to_uri(path)
+        }
     }
 
     fn send_request(
@@ -817,6 +1038,9 @@ impl LanguageServer {
         let content = format_pooled!("{}", content);
         let header = format_pooled!("Content-Length: {}\r\n\r\n", content.len());
 
+        #[cfg(feature = "lsp_debug")]
+        self.append_log(&format_pooled!("--> {}\n", content));
+
         let destination = if do_enqueue {
             self.process.input()
         } else {
@@ -846,6 +1070,15 @@ impl LanguageServer {
     pub fn is_retrigger_char(&self, c: char) -> bool {
         self.retrigger_chars.contains(&c)
     }
+
+    // Multi-character triggers like "::" are preferred over shorter ones that they end with.
+    pub fn completion_trigger_at_end(&self, text: &str) -> Option<&str> {
+        self.completion_triggers
+            .iter()
+            .filter(|trigger| text.ends_with(trigger.as_str()))
+            .max_by_key(|trigger| trigger.len())
+            .map(|trigger| trigger.as_str())
+    }
 }
 
 impl Drop for LanguageServer {
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests;
+
+use std::str;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageParseState {
+    Idle,
+    HasContentLen(usize),
+}
+
+// Splits the bytes sent by a language server into the contents of each message.
+pub(super) struct MessageParser {
+    state: MessageParseState,
+    buffer: Vec<u8>,
+    consumed_len: usize,
+}
+
+impl MessageParser {
+    const CONTENT_LEN_PREFIX: &[u8] = b"Content-Length:";
+    const HEADER_SUFFIX: &[u8] = b"\r\n\r\n";
+
+    pub fn new() -> Self {
+        Self {
+            state: MessageParseState::Idle,
+            buffer: Vec::new(),
+            consumed_len: 0,
+        }
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.drain_consumed();
+        self.buffer.extend_from_slice(data);
+    }
+
+    // The returned content stays in the buffer until the parser is used again.
+    pub fn next_content(&mut self) -> Option<&[u8]> {
+        self.drain_consumed();
+
+        loop {
+            match self.state {
+                MessageParseState::Idle => {
+                    // Anything before the start of a header can't be part of a message.
+                    let Some(header_start) = find(&self.buffer, Self::CONTENT_LEN_PREFIX) else {
+                        // Keep enough bytes to finish a prefix that was split between reads.
+                        let kept_len = self.buffer.len().min(Self::CONTENT_LEN_PREFIX.len() - 1);
+                        self.buffer.drain(..self.buffer.len() - kept_len);
+
+                        return None;
+                    };
+
+                    self.buffer.drain(..header_start);
+
+                    let header_len =
+                        find(&self.buffer, Self::HEADER_SUFFIX)? + Self::HEADER_SUFFIX.len();
+
+                    let Some(content_len) = Self::parse_content_len(&self.buffer[..header_len])
+                    else {
+                        // Skip past this header's prefix and look for the next one.
+                        self.buffer.drain(..Self::CONTENT_LEN_PREFIX.len());
+                        continue;
+                    };
+
+                    self.buffer.drain(..header_len);
+                    self.state = MessageParseState::HasContentLen(content_len);
+                }
+                MessageParseState::HasContentLen(content_len) => {
+                    if self.buffer.len() < content_len {
+                        return None;
+                    }
+
+                    self.state = MessageParseState::Idle;
+                    self.consumed_len = content_len;
+
+                    return Some(&self.buffer[..content_len]);
+                }
+            }
+        }
+    }
+
+    fn parse_content_len(header: &[u8]) -> Option<usize> {
+        let header = str::from_utf8(header).ok()?;
+        let content_len_line = header.split("\r\n").next()?;
+
+        content_len_line
+            .strip_prefix("Content-Length:")?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    fn drain_consumed(&mut self) {
+        self.buffer.drain(..self.consumed_len);
+        self.consumed_len = 0;
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,122 @@
+use super::MessageParser;
+
+fn parse_contents(parser: &mut MessageParser) -> Vec<String> {
+    let mut contents = Vec::new();
+
+    while let Some(content) = parser.next_content() {
+        contents.push(String::from_utf8_lossy(content).into_owned());
+    }
+
+    contents
+}
+
+#[test]
+fn parse_message_with_extra_headers() {
+    let mut parser = MessageParser::new();
+    parser.push(
+        b"Content-Length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
+    );
+
+    assert_eq!(parse_contents(&mut parser), ["{}"]);
+}
+
+#[test]
+fn parse_message_after_garbled_header() {
+    let mut parser = MessageParser::new();
+    parser.push(b"Content-Length: abc\r\n\r\n{}Content-Length: 4\r\n\r\ntrue");
+
+    assert_eq!(parse_contents(&mut parser), ["true"]);
+}
+
+#[test]
+fn parse_message_after_stray_output() {
+    let mut parser = MessageParser::new();
+    parser.push(b"server started\nContent-Length: 4\r\n\r\nnull");
+
+    assert_eq!(parse_contents(&mut parser), ["null"]);
+}
+
+#[test]
+fn parse_message_with_partial_header() {
+    let mut parser = MessageParser::new();
+    parser.push(b"log line\r\nContent-Len");
+
+    assert!(parser.next_content().is_none());
+
+    parser.push(b"gth: 2\r\n");
+
+    assert!(parser.next_content().is_none());
+
+    parser.push(b"\r\n{}");
+
+    assert_eq!(parse_contents(&mut parser), ["{}"]);
+}
+
+#[test]
+fn parse_message_split_between_header_and_body() {
+    let mut parser = MessageParser::new();
+    parser.push(b"Content-Length: 4\r\n\r\n");
+
+    assert!(parser.next_content().is_none());
+
+    parser.push(b"tr");
+
+    assert!(parser.next_content().is_none());
+
+    parser.push(b"ue");
+
+    assert_eq!(parse_contents(&mut parser), ["true"]);
+}
+
+#[test]
+fn parse_multiple_messages_in_one_read() {
+    let mut parser = MessageParser::new();
+    parser.push(
+        b"Content-Length: 2\r\n\r\n{}Content-Length: 4\r\n\r\nnullContent-Length: 1\r\n\r\n1",
+    );
+
+    assert_eq!(parse_contents(&mut parser), ["{}", "null", "1"]);
+}
+
+#[test]
+fn parse_message_pushed_before_previous_is_read() {
+    let mut parser = MessageParser::new();
+    parser.push(b"Content-Length: 2\r\n\r\n{}");
+
+    assert_eq!(parser.next_content(), Some(&b"{}"[..]));
+
+    parser.push(b"Content-Length: 4\r\n\r\nnull");
+
+    assert_eq!(parse_contents(&mut parser), ["null"]);
+}
+
+#[test]
+fn parse_messages_split_at_every_offset() {
+    let data = b"Content-Length: 2\r\n\r\n{}Content-Length: 4\r\n\r\nnull";
+
+    for i in 0..=data.len() {
+        let mut parser = MessageParser::new();
+
+        parser.push(&data[..i]);
+        let mut contents = parse_contents(&mut parser);
+
+        parser.push(&data[i..]);
+        contents.extend(parse_contents(&mut parser));
+
+        assert_eq!(contents, ["{}", "null"], "split at {i}");
+    }
+}
+
+#[test]
+fn parse_messages_one_byte_at_a_time() {
+    let data = b"Content-Length: 3\r\n\r\n\"\xc3\xa9Content-Length: 2\r\n\r\n{}";
+    let mut parser = MessageParser::new();
+    let mut contents = Vec::new();
+
+    for byte in data {
+        parser.push(&[*byte]);
+        contents.extend(parse_contents(&mut parser));
+    }
+
+    assert_eq!(contents, ["\"\u{e9}", "{}"]);
+}
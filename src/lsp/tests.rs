@@ -0,0 +1,392 @@
+use std::{collections::HashMap, env::current_dir, path::PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::{
+    geometry::position::Position,
+    pool::Pooled,
+    tests::test_with_doc,
+    text::{action_history::ActionKind, doc::Doc},
+};
+
+use super::{
+    language_server::{LanguageServer, MessageResult},
+    merge_completion_items,
+    position_encoding::PositionEncoding,
+    type_hierarchy::TypeHierarchy,
+    types::{
+        DecodedCompletionItem, DecodedDocumentChange, EncodedCompletionItem, EncodedPosition,
+        EncodedTextEdit, EncodedWorkspaceEdit, TypeHierarchyDirection,
+    },
+    uri::{path_to_untitled_uri, path_to_uri, uri_to_path},
+    Lsp,
+};
+
+fn decode_completion_items(json: &str, doc: &Doc) -> Vec<DecodedCompletionItem> {
+    serde_json::from_str::<Vec<EncodedCompletionItem>>(json)
+        .unwrap()
+        .into_iter()
+        .map(|item| item.decode(PositionEncoding::Utf16, doc))
+        .collect()
+}
+
+test_with_doc!(merge_completions_from_two_servers, "", |_, doc| {
+    let main_server_items =
+        decode_completion_items(r#"[{ "label": "println" }, { "label": "print" }]"#, doc);
+
+    let linter_server_items =
+        decode_completion_items(r#"[{ "label": "print" }, { "label": "eprintln" }]"#, doc);
+
+    let mut items = Vec::new();
+    merge_completion_items(&mut items, main_server_items);
+    merge_completion_items(&mut items, linter_server_items);
+
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+
+    assert_eq!(labels, ["println", "print", "eprintln"]);
+});
+
+test_with_doc!(
+    merge_completions_keeps_different_insert_text,
+    "",
+    |_, doc| {
+        let main_server_items = decode_completion_items(r#"[{ "label": "vec" }]"#, doc);
+
+        let snippet_server_items =
+            decode_completion_items(r#"[{ "label": "vec", "insertText": "vec![]" }]"#, doc);
+
+        let mut items = Vec::new();
+        merge_completion_items(&mut items, main_server_items);
+        merge_completion_items(&mut items, snippet_server_items);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].insert_text(), "vec![]");
+    }
+);
+
+#[test]
+fn decode_document_changes_in_order() {
+    let workspace_edit = serde_json::from_str::<EncodedWorkspaceEdit>(
+        r#"{
+            "documentChanges": [
+                { "kind": "create", "uri": "file:///new.rs", "options": { "ignoreIfExists": true } },
+                {
+                    "textDocument": { "uri": "file:///old.rs", "version": 3 },
+                    "edits": [{
+                        "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+                        "newText": "mod new;"
+                    }]
+                },
+                { "kind": "rename", "oldUri": "file:///a.rs", "newUri": "file:///b.rs" },
+                { "kind": "delete", "uri": "file:///old.rs", "options": { "recursive": true } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let document_changes = workspace_edit.decode(PositionEncoding::Utf16);
+
+    assert!(matches!(
+        &document_changes[..],
+        [
+            DecodedDocumentChange::CreateFile { uri, options },
+            DecodedDocumentChange::Edit(edit_list),
+            DecodedDocumentChange::RenameFile { old_uri, new_uri, .. },
+            DecodedDocumentChange::DeleteFile { options: delete_options, .. },
+        ] if uri.as_str() == "file:///new.rs"
+            && options.ignore_if_exists
+            && edit_list.version == Some(3)
+            && old_uri.as_str() == "file:///a.rs"
+            && new_uri.as_str() == "file:///b.rs"
+            && delete_options.recursive
+    ));
+}
+
+const ASTRAL_TEXT: &str = "a\u{1f600}b\u{1f600}c";
+
+fn encode_x(x: usize, encoding: PositionEncoding, doc: &Doc) -> usize {
+    EncodedPosition::encode(Position::new(x, 0), encoding, doc).character
+}
+
+fn decode_x(character: usize, encoding: PositionEncoding, doc: &Doc) -> usize {
+    EncodedPosition { line: 0, character }
+        .decode(encoding, doc)
+        .x
+}
+
+test_with_doc!(encode_utf16_astral_characters, ASTRAL_TEXT, |_, doc| {
+    let columns: Vec<usize> = [0, 1, 5, 6, 10, 11]
+        .into_iter()
+        .map(|x| encode_x(x, PositionEncoding::Utf16, doc))
+        .collect();
+
+    assert_eq!(columns, [0, 1, 3, 4, 6, 7]);
+});
+
+test_with_doc!(decode_utf16_astral_characters, ASTRAL_TEXT, |_, doc| {
+    let xs: Vec<usize> = [0, 1, 3, 4, 6, 7]
+        .into_iter()
+        .map(|character| decode_x(character, PositionEncoding::Utf16, doc))
+        .collect();
+
+    assert_eq!(xs, [0, 1, 5, 6, 10, 11]);
+
+    // Columns between the two halves of a surrogate pair land on the start of the character.
+    assert_eq!(decode_x(2, PositionEncoding::Utf16, doc), 1);
+    assert_eq!(decode_x(5, PositionEncoding::Utf16, doc), 6);
+});
+
+test_with_doc!(decode_utf8_astral_characters, ASTRAL_TEXT, |_, doc| {
+    assert_eq!(decode_x(5, PositionEncoding::Utf8, doc), 5);
+    assert_eq!(decode_x(3, PositionEncoding::Utf8, doc), 1);
+    assert_eq!(decode_x(20, PositionEncoding::Utf8, doc), 11);
+});
+
+test_with_doc!(encode_past_line_end, ASTRAL_TEXT, |_, doc| {
+    assert_eq!(encode_x(20, PositionEncoding::Utf8, doc), 11);
+    assert_eq!(encode_x(20, PositionEncoding::Utf16, doc), 7);
+});
+
+test_with_doc!(round_trip_astral_characters, ASTRAL_TEXT, |_, doc| {
+    for encoding in [PositionEncoding::Utf8, PositionEncoding::Utf16] {
+        for (x, _) in ASTRAL_TEXT.char_indices().chain([(ASTRAL_TEXT.len(), ' ')]) {
+            let character = encode_x(x, encoding, doc);
+
+            assert_eq!(decode_x(character, encoding, doc), x, "{encoding:?} at {x}");
+        }
+    }
+});
+
+fn text_edit(start: (usize, usize), end: (usize, usize), new_text: &str) -> EncodedTextEdit {
+    serde_json::from_value(json!({
+        "range": {
+            "start": { "line": start.0, "character": start.1 },
+            "end": { "line": end.0, "character": end.1 },
+        },
+        "newText": new_text,
+    }))
+    .unwrap()
+}
+
+test_with_doc!(
+    apply_text_edits_in_any_order,
+    "let a = b;\nlet c = d;",
+    |ctx, doc| {
+        let edits = vec![
+            text_edit((0, 4), (0, 5), "x"),
+            text_edit((1, 4), (1, 5), "y"),
+            text_edit((0, 8), (0, 9), "zz"),
+        ];
+
+        doc.apply_text_edits(edits, PositionEncoding::Utf8, ctx);
+
+        assert_eq!(doc.to_string(), "let x = zz;\nlet y = d;");
+    }
+);
+
+test_with_doc!(apply_adjacent_text_edits, "abcdef", |ctx, doc| {
+    let edits = vec![
+        text_edit((0, 0), (0, 2), "1"),
+        text_edit((0, 2), (0, 4), "2"),
+        text_edit((0, 4), (0, 6), "3"),
+    ];
+
+    doc.apply_text_edits(edits, PositionEncoding::Utf8, ctx);
+
+    assert_eq!(doc.to_string(), "123");
+});
+
+test_with_doc!(apply_inserts_at_same_position_in_order, "()", |ctx, doc| {
+    let edits = vec![
+        text_edit((0, 1), (0, 1), "a"),
+        text_edit((0, 1), (0, 1), ", "),
+        text_edit((0, 1), (0, 1), "b"),
+    ];
+
+    doc.apply_text_edits(edits, PositionEncoding::Utf8, ctx);
+
+    assert_eq!(doc.to_string(), "(a, b)");
+});
+
+test_with_doc!(apply_text_edits_skips_overlapping, "abcdef", |ctx, doc| {
+    let edits = vec![
+        text_edit((0, 0), (0, 4), "x"),
+        text_edit((0, 2), (0, 6), "y"),
+    ];
+
+    doc.apply_text_edits(edits, PositionEncoding::Utf8, ctx);
+
+    assert_eq!(doc.to_string(), "aby");
+});
+
+test_with_doc!(apply_empty_text_edits, "abc", |ctx, doc| {
+    doc.apply_text_edits(Vec::new(), PositionEncoding::Utf8, ctx);
+    doc.apply_text_edits(
+        vec![text_edit((0, 1), (0, 1), "")],
+        PositionEncoding::Utf8,
+        ctx,
+    );
+
+    assert_eq!(doc.to_string(), "abc");
+});
+
+test_with_doc!(undo_text_edits_together, "let a = b;", |ctx, doc| {
+    ctx.time = 1.0;
+
+    let edits = vec![
+        text_edit((0, 4), (0, 5), "x"),
+        text_edit((0, 8), (0, 9), "y"),
+    ];
+
+    doc.apply_text_edits(edits, PositionEncoding::Utf8, ctx);
+    doc.undo(ActionKind::Done, ctx);
+
+    assert_eq!(doc.to_string(), "let a = b;");
+});
+
+#[test]
+fn untitled_uri_round_trip() {
+    let path: Pooled<PathBuf> = current_dir().unwrap().join("new file.rs").as_path().into();
+
+    let file_uri = path_to_uri(&path);
+    let untitled_uri = path_to_untitled_uri(&path);
+
+    assert_eq!(
+        untitled_uri.strip_prefix("untitled:/"),
+        file_uri.strip_prefix("file:///")
+    );
+    assert_eq!(uri_to_path(&untitled_uri), Some(path));
+}
+
+#[test]
+fn type_hierarchy_orders_levels_and_stops_at_cycles() {
+    let path: Pooled<PathBuf> = PathBuf::from("/project/main.rs").as_path().into();
+    let position = |line| EncodedPosition { line, character: 0 };
+
+    let mut type_hierarchy = TypeHierarchy::new(TypeHierarchyDirection::Supertypes, path.clone());
+    type_hierarchy.add_request(0, None);
+
+    assert_eq!(type_hierarchy.take_request(0), Some(None));
+    assert_eq!(type_hierarchy.take_request(0), None);
+
+    let a = type_hierarchy.push_item(None, "A".into(), path.clone(), position(1));
+    let b = type_hierarchy.push_item(None, "B".into(), path.clone(), position(2));
+    let c = type_hierarchy.push_item(a, "C".into(), path.clone(), position(3));
+
+    // C's supertype is A again, which is shown but not expanded.
+    let cycle = type_hierarchy.push_item(c, "A".into(), path.clone(), position(1));
+
+    assert_eq!((a, b, c, cycle), (Some(0), Some(1), Some(2), None));
+    assert!(type_hierarchy.is_done());
+
+    let items = type_hierarchy.into_items();
+    let items: Vec<(&str, usize)> = items
+        .iter()
+        .map(|item| (item.name.as_str(), item.depth))
+        .collect();
+
+    assert_eq!(items, [("A", 0), ("C", 1), ("A", 2), ("B", 0)]);
+}
+
+fn push_response(server: &LanguageServer, id: usize, result: Value) {
+    let content = json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string();
+    let message = format!("Content-Length: {}\r\n\r\n{}", content.len(), content);
+
+    server.process.push_output(message.as_bytes());
+}
+
+fn receive_completion_labels(lsp: &mut Lsp, doc: &Doc) -> Vec<((usize, usize), Vec<String>)> {
+    let mut results = Vec::new();
+
+    while let Some((key, message)) = lsp.poll() {
+        let server = lsp.servers.get_mut(&key).unwrap().as_mut().unwrap();
+        let encoding = server.position_encoding();
+        let (_, method) = server.get_message_path_and_method(&message);
+
+        if let Some(MessageResult::Completion(items)) =
+            server.handle_message(method.unwrap_or_default(), &message)
+        {
+            let labels = items
+                .into_iter()
+                .map(|item| item.decode(encoding, doc).label.to_string())
+                .collect();
+
+            results.push((key, labels));
+        }
+    }
+
+    results.sort_by_key(|(key, _)| *key);
+    results
+}
+
+test_with_doc!(completions_from_two_mock_servers, "std::", |_, doc| {
+    let current_dir = current_dir().unwrap();
+    let path = current_dir.join("main.rs");
+
+    let mut lsp = Lsp::new();
+
+    for (server_index, name) in ["main", "linter"].into_iter().enumerate() {
+        let server = LanguageServer::new(name, &[], &HashMap::new(), &current_dir, &None);
+        lsp.servers.insert((0, server_index), server);
+    }
+
+    // Each server gets its own initialize request and answers it separately.
+    for ((_, server_index), server) in lsp.servers.iter_mut() {
+        let server = server.as_mut().unwrap();
+        let input = String::from_utf8_lossy(server.process.input()).into_owned();
+
+        assert!(input.contains(r#""method":"initialize""#));
+
+        let triggers = if *server_index == 0 {
+            json!([".", "::"])
+        } else {
+            json!([])
+        };
+
+        push_response(
+            server,
+            0,
+            json!({ "capabilities": { "completionProvider": { "triggerCharacters": triggers } } }),
+        );
+    }
+
+    assert!(receive_completion_labels(&mut lsp, doc).is_empty());
+
+    let main_server = lsp.servers[&(0, 0)].as_ref().unwrap();
+    let linter_server = lsp.servers[&(0, 1)].as_ref().unwrap();
+
+    assert_eq!(main_server.completion_trigger_at_end("std::"), Some("::"));
+    assert_eq!(main_server.completion_trigger_at_end("std:"), None);
+    assert_eq!(linter_server.completion_trigger_at_end("std::"), None);
+
+    let position = doc.end();
+
+    for (server_index, labels) in [(0, json!(["println", "print"])), (1, json!(["eprintln"]))] {
+        let server = lsp
+            .servers
+            .get_mut(&(0, server_index))
+            .unwrap()
+            .as_mut()
+            .unwrap();
+        let sent_request = server.completion(&path, position, Some("::"), doc);
+
+        let items: Vec<Value> = labels
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|label| json!({ "label": label }))
+            .collect();
+
+        push_response(server, sent_request.id, json!(items));
+    }
+
+    let results = receive_completion_labels(&mut lsp, doc);
+
+    assert_eq!(
+        results,
+        [
+            ((0, 0), vec!["println".to_owned(), "print".to_owned()]),
+            ((0, 1), vec!["eprintln".to_owned()]),
+        ]
+    );
+});
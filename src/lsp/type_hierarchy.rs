@@ -0,0 +1,123 @@
+use std::{collections::HashMap, iter::from_fn, path::PathBuf};
+
+use crate::pool::Pooled;
+
+use super::types::{EncodedPosition, TypeHierarchyDirection};
+
+#[derive(Debug)]
+pub(super) struct TypeHierarchyItem {
+    pub name: Pooled<String>,
+    pub path: Pooled<PathBuf>,
+    pub position: EncodedPosition,
+    pub depth: usize,
+    parent: Option<usize>,
+}
+
+// Collects the levels of a type hierarchy as the server answers requests for each item's types.
+pub(super) struct TypeHierarchy {
+    direction: TypeHierarchyDirection,
+    path: Pooled<PathBuf>,
+    // Requests that haven't been answered yet, with the index of the item they're for.
+    pending_requests: HashMap<usize, Option<usize>>,
+    items: Vec<TypeHierarchyItem>,
+}
+
+impl TypeHierarchy {
+    const MAX_DEPTH: usize = 8;
+    const MAX_ITEMS: usize = 256;
+
+    pub fn new(direction: TypeHierarchyDirection, path: Pooled<PathBuf>) -> Self {
+        Self {
+            direction,
+            path,
+            pending_requests: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn add_request(&mut self, id: usize, parent: Option<usize>) {
+        self.pending_requests.insert(id, parent);
+    }
+
+    // Returns the item that the request was for, or None if it isn't part of this hierarchy.
+    pub fn take_request(&mut self, id: usize) -> Option<Option<usize>> {
+        self.pending_requests.remove(&id)
+    }
+
+    // Returns the new item's index if its own types should be requested too.
+    pub fn push_item(
+        &mut self,
+        parent: Option<usize>,
+        name: Pooled<String>,
+        path: Pooled<PathBuf>,
+        position: EncodedPosition,
+    ) -> Option<usize> {
+        if self.items.len() >= Self::MAX_ITEMS {
+            return None;
+        }
+
+        let depth = parent.map_or(0, |parent| self.items[parent].depth + 1);
+        let is_cycle = self.ancestors(parent).any(|ancestor| {
+            ancestor.name == name
+                && ancestor.path == path
+                && ancestor.position.line == position.line
+        });
+
+        let index = self.items.len();
+
+        self.items.push(TypeHierarchyItem {
+            name,
+            path,
+            position,
+            depth,
+            parent,
+        });
+
+        (!is_cycle && depth + 1 < Self::MAX_DEPTH).then_some(index)
+    }
+
+    fn ancestors(&self, parent: Option<usize>) -> impl Iterator<Item = &TypeHierarchyItem> {
+        let mut parent = parent;
+
+        from_fn(move || {
+            let item = &self.items[parent?];
+            parent = item.parent;
+
+            Some(item)
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending_requests.is_empty()
+    }
+
+    pub fn direction(&self) -> TypeHierarchyDirection {
+        self.direction
+    }
+
+    pub fn path(&self) -> &Pooled<PathBuf> {
+        &self.path
+    }
+
+    // Items are ordered so that each one is followed by the items below it.
+    pub fn into_items(self) -> Vec<TypeHierarchyItem> {
+        let mut order = Vec::with_capacity(self.items.len());
+        self.push_item_order(None, &mut order);
+
+        let mut items: Vec<Option<TypeHierarchyItem>> = self.items.into_iter().map(Some).collect();
+
+        order
+            .into_iter()
+            .filter_map(|index| items[index].take())
+            .collect()
+    }
+
+    fn push_item_order(&self, parent: Option<usize>, order: &mut Vec<usize>) {
+        for (index, item) in self.items.iter().enumerate() {
+            if item.parent == parent {
+                order.push(index);
+                self.push_item_order(Some(index), order);
+            }
+        }
+    }
+}
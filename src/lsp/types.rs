@@ -1,17 +1,21 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 use serde_json::{value::RawValue, Value};
 
 use crate::{
     config::theme::Theme,
+    ctx::Ctx,
     geometry::position::Position,
     pool::{Pooled, STRING_POOL},
     text::doc::Doc,
     ui::color::Color,
 };
 
-use super::position_encoding::PositionEncoding;
+use super::{
+    position_encoding::PositionEncoding,
+    uri::{path_to_uri, uri_to_path},
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct EncodedPosition {
@@ -22,25 +26,30 @@ pub struct EncodedPosition {
 impl EncodedPosition {
     pub fn encode(position: Position, encoding: PositionEncoding, doc: &Doc) -> Self {
         let line = doc.get_line(position.y).unwrap_or_default();
+        let x = floor_char_boundary(line, position.x);
 
         match encoding {
             PositionEncoding::Utf8 => Self {
                 line: position.y,
-                character: position.x,
+                character: x,
             },
             PositionEncoding::Utf16 => Self {
                 line: position.y,
-                character: line[..position.x].encode_utf16().count(),
+                character: line[..x].encode_utf16().count(),
             },
         }
     }
 
     pub fn decode(self, encoding: PositionEncoding, doc: &Doc) -> Position {
         match encoding {
-            PositionEncoding::Utf8 => Position {
-                x: self.character,
-                y: self.line,
-            },
+            PositionEncoding::Utf8 => {
+                let line = doc.get_line(self.line).unwrap_or_default();
+
+                Position {
+                    x: floor_char_boundary(line, self.character),
+                    y: self.line,
+                }
+            }
             PositionEncoding::Utf16 => {
                 let line = doc.get_line(self.line).unwrap_or_default();
 
@@ -67,6 +76,16 @@ impl EncodedPosition {
     }
 }
 
+fn floor_char_boundary(line: &str, index: usize) -> usize {
+    let mut index = index.min(line.len());
+
+    while !line.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct EncodedRange {
     pub start: EncodedPosition,
@@ -83,11 +102,36 @@ impl EncodedRange {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct EncodedDiagnosticLocation {
+    pub uri: Pooled<String>,
+    pub range: EncodedRange,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncodedDiagnosticRelatedInformation {
+    pub location: EncodedDiagnosticLocation,
+    pub message: Pooled<String>,
+}
+
+impl EncodedDiagnosticRelatedInformation {
+    pub fn decode(self) -> Option<DecodedDiagnosticRelatedInformation> {
+        Some(DecodedDiagnosticRelatedInformation {
+            path: uri_to_path(&self.location.uri)?,
+            range: self.location.range,
+            message: self.message,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EncodedDiagnostic {
     pub message: Pooled<String>,
     pub range: EncodedRange,
     #[serde(default = "EncodedDiagnostic::DEFAULT_SEVERITY")]
     pub severity: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_information: Vec<EncodedDiagnosticRelatedInformation>,
 }
 
 impl EncodedDiagnostic {
@@ -102,6 +146,11 @@ impl EncodedDiagnostic {
             message: self.message,
             range: self.range.decode(encoding, doc),
             severity: self.severity,
+            related_information: self
+                .related_information
+                .into_iter()
+                .filter_map(EncodedDiagnosticRelatedInformation::decode)
+                .collect(),
         }
     }
 }
@@ -119,7 +168,7 @@ pub(super) struct EncodedFullDocumentDiagnosticParams {
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(super) struct EncodedTextEdit {
+pub struct EncodedTextEdit {
     #[serde(alias = "insert")]
     range: EncodedRange,
     new_text: Pooled<String>,
@@ -146,6 +195,8 @@ pub(super) struct EncodedCompletionItem {
     additional_text_edits: Vec<EncodedTextEdit>,
     detail: Option<Pooled<String>>,
     documentation: Option<Documentation>,
+    #[serde(default)]
+    preselect: bool,
     data: Option<Value>,
 }
 
@@ -166,6 +217,7 @@ impl EncodedCompletionItem {
                 .collect(),
             detail: self.detail,
             documentation: self.documentation,
+            preselect: self.preselect,
             data: self.data,
         }
     }
@@ -197,6 +249,36 @@ pub(super) struct EncodedLocationLink<'a> {
     pub target_range: EncodedRange,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeHierarchyDirection {
+    Supertypes,
+    Subtypes,
+}
+
+impl TypeHierarchyDirection {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    pub(super) fn method(self) -> &'static str {
+        match self {
+            Self::Supertypes => "typeHierarchy/supertypes",
+            Self::Subtypes => "typeHierarchy/subtypes",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct EncodedTypeHierarchyItem<'a> {
+    pub name: Pooled<String>,
+    pub uri: &'a str,
+    pub selection_range: EncodedRange,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub(super) enum EncodedDefinitionResult<'a> {
@@ -224,6 +306,8 @@ pub(super) struct DiagnosticOptions {}
 pub(super) struct CompletionOptions {
     #[serde(default)]
     pub resolve_provider: bool,
+    #[serde(default)]
+    pub trigger_characters: Vec<Pooled<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -261,47 +345,116 @@ pub(super) struct Message {
 }
 
 #[derive(Debug, Deserialize)]
-struct TextDocumentIdentifier {
+struct OptionalVersionedTextDocumentIdentifier {
     uri: Pooled<String>,
+    version: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct EncodedTextDocumentEdit {
-    text_document: TextDocumentIdentifier,
+    text_document: OptionalVersionedTextDocumentIdentifier,
     edits: Vec<EncodedTextEdit>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationOptions {
+    #[serde(default)]
+    pub overwrite: bool,
+    #[serde(default)]
+    pub ignore_if_exists: bool,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub ignore_if_not_exists: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum EncodedFileOperation {
+    Create {
+        uri: Pooled<String>,
+        #[serde(default)]
+        options: FileOperationOptions,
+    },
+    Rename {
+        old_uri: Pooled<String>,
+        new_uri: Pooled<String>,
+        #[serde(default)]
+        options: FileOperationOptions,
+    },
+    Delete {
+        uri: Pooled<String>,
+        #[serde(default)]
+        options: FileOperationOptions,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EncodedDocumentChange {
+    Edit(EncodedTextDocumentEdit),
+    FileOperation(EncodedFileOperation),
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(super) struct EncodedWorkspaceEdit {
     changes: Option<HashMap<Pooled<String>, Vec<EncodedTextEdit>>>,
-    document_changes: Option<Vec<EncodedTextDocumentEdit>>,
+    document_changes: Option<Vec<EncodedDocumentChange>>,
 }
 
 impl EncodedWorkspaceEdit {
-    pub fn decode(self, encoding: PositionEncoding) -> Vec<DecodedEditList> {
-        let mut edit = Vec::new();
+    pub fn decode(self, encoding: PositionEncoding) -> Vec<DecodedDocumentChange> {
+        let mut document_changes = Vec::new();
 
         if let Some(changes) = self.document_changes {
             for change in changes {
-                edit.push(DecodedEditList {
-                    uri: change.text_document.uri,
-                    encoding,
-                    encoded: change.edits,
+                document_changes.push(match change {
+                    EncodedDocumentChange::Edit(edit) => {
+                        DecodedDocumentChange::Edit(DecodedEditList {
+                            uri: edit.text_document.uri,
+                            version: edit.text_document.version,
+                            encoding,
+                            encoded: edit.edits,
+                        })
+                    }
+                    EncodedDocumentChange::FileOperation(EncodedFileOperation::Create {
+                        uri,
+                        options,
+                    }) => DecodedDocumentChange::CreateFile { uri, options },
+                    EncodedDocumentChange::FileOperation(EncodedFileOperation::Rename {
+                        old_uri,
+                        new_uri,
+                        options,
+                    }) => DecodedDocumentChange::RenameFile {
+                        old_uri,
+                        new_uri,
+                        options,
+                    },
+                    EncodedDocumentChange::FileOperation(EncodedFileOperation::Delete {
+                        uri,
+                        options,
+                    }) => DecodedDocumentChange::DeleteFile { uri, options },
                 });
             }
         } else if let Some(changes) = self.changes {
             for (uri, edits) in changes {
-                edit.push(DecodedEditList {
+                document_changes.push(DecodedDocumentChange::Edit(DecodedEditList {
                     uri,
+                    version: None,
                     encoding,
                     encoded: edits,
-                });
+                }));
             }
         }
 
-        edit
+        document_changes
     }
 }
 
@@ -337,14 +490,14 @@ pub(super) struct EncodedCodeAction {
 
 impl EncodedCodeAction {
     pub fn decode(self, encoding: PositionEncoding) -> DecodedCodeAction {
-        let edit_lists = self
+        let document_changes = self
             .edit
             .map(|edit| edit.decode(encoding))
             .unwrap_or_default();
 
         DecodedCodeAction {
             title: self.title,
-            edit_lists,
+            document_changes,
             command: self.command,
             is_preferred: self.is_preferred,
         }
@@ -386,11 +539,32 @@ impl DecodedRange {
     }
 }
 
+// The range is left encoded because decoding it requires the related doc.
+#[derive(Debug, Clone)]
+pub struct DecodedDiagnosticRelatedInformation {
+    pub path: Pooled<PathBuf>,
+    pub range: EncodedRange,
+    pub message: Pooled<String>,
+}
+
+impl DecodedDiagnosticRelatedInformation {
+    fn encode(&self) -> EncodedDiagnosticRelatedInformation {
+        EncodedDiagnosticRelatedInformation {
+            location: EncodedDiagnosticLocation {
+                uri: path_to_uri(&self.path),
+                range: self.range,
+            },
+            message: self.message.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DecodedDiagnostic {
     pub message: Pooled<String>,
     pub range: DecodedRange,
     pub severity: usize,
+    pub related_information: Vec<DecodedDiagnosticRelatedInformation>,
 }
 
 impl DecodedDiagnostic {
@@ -434,6 +608,11 @@ impl DecodedDiagnostic {
             message: self.message.clone(),
             range: self.range.encode(encoding, doc),
             severity: self.severity,
+            related_information: self
+                .related_information
+                .iter()
+                .map(DecodedDiagnosticRelatedInformation::encode)
+                .collect(),
         }
     }
 }
@@ -565,6 +744,7 @@ pub struct DecodedCompletionItem {
     pub additional_text_edits: Vec<DecodedTextEdit>,
     pub detail: Option<Pooled<String>>,
     pub documentation: Option<Documentation>,
+    pub preselect: bool,
     data: Option<Value>,
 }
 
@@ -605,6 +785,7 @@ impl DecodedCompletionItem {
                 .collect(),
             detail: self.detail,
             documentation: self.documentation,
+            preselect: self.preselect,
             data: self.data,
         }
     }
@@ -613,24 +794,40 @@ impl DecodedCompletionItem {
 #[derive(Debug)]
 pub struct DecodedEditList {
     pub uri: Pooled<String>,
+    pub version: Option<usize>,
 
     encoding: PositionEncoding,
     encoded: Vec<EncodedTextEdit>,
 }
 
 impl DecodedEditList {
-    pub fn edits(self, doc: &Doc) -> Vec<DecodedTextEdit> {
-        self.encoded
-            .into_iter()
-            .map(|diagnostic| diagnostic.decode(self.encoding, doc))
-            .collect()
+    pub fn apply(self, doc: &mut Doc, ctx: &mut Ctx) {
+        doc.apply_text_edits(self.encoded, self.encoding, ctx);
     }
 }
 
+#[derive(Debug)]
+pub enum DecodedDocumentChange {
+    Edit(DecodedEditList),
+    CreateFile {
+        uri: Pooled<String>,
+        options: FileOperationOptions,
+    },
+    RenameFile {
+        old_uri: Pooled<String>,
+        new_uri: Pooled<String>,
+        options: FileOperationOptions,
+    },
+    DeleteFile {
+        uri: Pooled<String>,
+        options: FileOperationOptions,
+    },
+}
+
 #[derive(Debug)]
 pub struct DecodedCodeAction {
     pub title: Pooled<String>,
-    pub edit_lists: Vec<DecodedEditList>,
+    pub document_changes: Vec<DecodedDocumentChange>,
     pub command: Option<Command>,
     pub is_preferred: bool,
 }
@@ -642,9 +839,62 @@ pub enum DecodedCodeActionResult {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterLabel {
+    String(Pooled<String>),
+    Offsets([usize; 2]),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParameterInformation {
+    pub label: ParameterLabel,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SignatureInformation {
     pub label: Pooled<String>,
     pub documentation: Option<Documentation>,
+    #[serde(default)]
+    pub parameters: Vec<ParameterInformation>,
+    pub active_parameter: Option<usize>,
+}
+
+impl SignatureInformation {
+    // Returns the byte range of the active parameter within the label.
+    pub fn active_parameter_range(&self, active_parameter: Option<usize>) -> Option<Range<usize>> {
+        let index = self.active_parameter.or(active_parameter)?;
+
+        match &self.parameters.get(index)?.label {
+            ParameterLabel::String(parameter_label) => {
+                let parameters_start = self.label.find('(').map_or(0, |index| index + 1);
+                let start = self.label[parameters_start..].find(parameter_label.as_str())?
+                    + parameters_start;
+
+                Some(start..start + parameter_label.len())
+            }
+            ParameterLabel::Offsets([start, end]) => {
+                let start = utf16_offset_to_index(&self.label, *start)?;
+                let end = utf16_offset_to_index(&self.label, *end)?;
+
+                Some(start..end)
+            }
+        }
+    }
+}
+
+fn utf16_offset_to_index(text: &str, offset: usize) -> Option<usize> {
+    let mut wide_index = 0;
+
+    for (index, c) in text.char_indices() {
+        if wide_index >= offset {
+            return Some(index);
+        }
+
+        wide_index += c.len_utf16();
+    }
+
+    (wide_index >= offset).then_some(text.len())
 }
 
 #[derive(Debug, Deserialize)]
@@ -653,4 +903,5 @@ pub struct SignatureHelp {
     pub signatures: Vec<SignatureInformation>,
     #[serde(default)]
     pub active_signature: usize,
+    pub active_parameter: Option<usize>,
 }
@@ -11,13 +11,15 @@ use crate::{
 };
 
 const URI_SCHEME: &str = "file:///";
+// Used for docs that haven't been saved yet, so that servers don't expect them to be on the drive.
+const UNTITLED_URI_SCHEME: &str = "untitled:/";
 
 pub fn uri_to_path(uri: &str) -> Option<Pooled<PathBuf>> {
-    if !uri.starts_with(URI_SCHEME) {
-        return None;
-    }
+    let uri = uri
+        .strip_prefix(URI_SCHEME)
+        .or_else(|| uri.strip_prefix(UNTITLED_URI_SCHEME))?;
 
-    let mut chars = uri[URI_SCHEME.len()..].chars().peekable();
+    let mut chars = uri.chars().peekable();
     let mut c = chars.next();
     let mut result = STRING_POOL.new_item();
 
@@ -61,10 +63,18 @@ fn decode_uri_char(chars: &mut Peekable<Chars>) -> Option<char> {
 }
 
 pub fn path_to_uri(path: &Path) -> Pooled<String> {
+    path_to_uri_with_scheme(path, URI_SCHEME)
+}
+
+pub fn path_to_untitled_uri(path: &Path) -> Pooled<String> {
+    path_to_uri_with_scheme(path, UNTITLED_URI_SCHEME)
+}
+
+fn path_to_uri_with_scheme(path: &Path, scheme: &str) -> Pooled<String> {
     assert!(path.is_normal());
 
     let mut result = STRING_POOL.new_item();
-    result.push_str(URI_SCHEME);
+    result.push_str(scheme);
 
     if let Some(parent) = path.parent() {
         for component in parent {
@@ -24,6 +24,7 @@ pub(super) enum SpriteKind {
 
 pub struct Gfx {
     pub(super) inner: platform_impl::gfx::Gfx,
+    pub(super) tab_width: usize,
 }
 
 impl Gfx {
@@ -33,6 +34,7 @@ impl Gfx {
     pub fn new() -> Self {
         Self {
             inner: platform_impl::gfx::Gfx,
+            tab_width: Self::TAB_WIDTH,
         }
     }
 
@@ -57,25 +59,49 @@ impl Gfx {
     }
 
     pub fn find_x_for_visual_x(&mut self, text: &str, visual_x: usize) -> usize {
-        self.find_x_for_visual_x_with_clamping(text, visual_x, true)
+        self.find_x_for_visual_x_with_tab_widths(text, visual_x, &[])
+    }
+
+    pub fn find_x_for_visual_x_with_tab_widths(
+        &mut self,
+        text: &str,
+        visual_x: usize,
+        tab_widths: &[usize],
+    ) -> usize {
+        self.find_x_for_visual_x_with_clamping(text, visual_x, tab_widths, true)
             .unwrap()
     }
 
     pub fn find_x_for_visual_x_unclamped(&mut self, text: &str, visual_x: usize) -> Option<usize> {
-        self.find_x_for_visual_x_with_clamping(text, visual_x, false)
+        self.find_x_for_visual_x_unclamped_with_tab_widths(text, visual_x, &[])
+    }
+
+    pub fn find_x_for_visual_x_unclamped_with_tab_widths(
+        &mut self,
+        text: &str,
+        visual_x: usize,
+        tab_widths: &[usize],
+    ) -> Option<usize> {
+        self.find_x_for_visual_x_with_clamping(text, visual_x, tab_widths, false)
     }
 
     fn find_x_for_visual_x_with_clamping(
         &mut self,
         text: &str,
         visual_x: usize,
+        tab_widths: &[usize],
         do_clamp: bool,
     ) -> Option<usize> {
+        let mut tab_widths = tab_widths.iter();
         let mut current_visual_x = 0;
         let mut x = 0;
 
         for grapheme in GraphemeIterator::new(text) {
-            current_visual_x += self.measure_text(grapheme);
+            current_visual_x += if grapheme == "\t" {
+                self.next_tab_width(&mut tab_widths)
+            } else {
+                self.measure_text(grapheme)
+            };
 
             if current_visual_x > visual_x {
                 return Some(x);
@@ -87,6 +113,11 @@ impl Gfx {
         (do_clamp || current_visual_x + self.measure_text("\n") > visual_x).then_some(x)
     }
 
+    // Tabs use the provided widths in order, and the default width once they run out.
+    fn next_tab_width<'a>(&self, tab_widths: &mut impl Iterator<Item = &'a usize>) -> usize {
+        tab_widths.next().copied().unwrap_or(self.tab_width)
+    }
+
     fn glyph_spans(&mut self, text: &str) -> GlyphSpans {
         self.inner.glyph_spans(text)
     }
@@ -96,6 +127,18 @@ impl Gfx {
     }
 
     pub fn add_text(&mut self, text: &str, x: f32, y: f32, color: Color) -> f32 {
+        self.add_text_with_tab_widths(text, x, y, color, &[])
+    }
+
+    pub fn add_text_with_tab_widths(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        color: Color,
+        tab_widths: &[usize],
+    ) -> f32 {
+        let mut tab_widths = tab_widths.iter();
         let glyph_spans = self.glyph_spans(text);
 
         let glyph_width = self.glyph_width();
@@ -108,7 +151,7 @@ impl Gfx {
 
             offset += match span {
                 GlyphSpan::Space => 1,
-                GlyphSpan::Tab => Self::TAB_WIDTH,
+                GlyphSpan::Tab => self.next_tab_width(&mut tab_widths),
                 GlyphSpan::Glyph {
                     origin_x,
                     origin_y,
@@ -157,13 +200,29 @@ impl Gfx {
     }
 
     pub fn add_background(&mut self, text: &str, x: f32, y: f32, color: Color) {
+        self.add_background_with_tab_widths(text, x, y, color, &[]);
+    }
+
+    pub fn add_background_with_tab_widths(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        color: Color,
+        tab_widths: &[usize],
+    ) {
         let glyph_width = self.glyph_width();
-        let width = self.measure_text(text) as f32 * glyph_width;
+        let width = self.measure_text_with_tab_widths(text, tab_widths) as f32 * glyph_width;
 
         self.add_rect(Rect::new(x, y, width, self.line_height()), color);
     }
 
     pub fn measure_text(&mut self, text: &str) -> usize {
+        self.measure_text_with_tab_widths(text, &[])
+    }
+
+    pub fn measure_text_with_tab_widths(&mut self, text: &str, tab_widths: &[usize]) -> usize {
+        let mut tab_widths = tab_widths.iter();
         let glyph_spans = self.glyph_spans(text);
 
         let mut width = 0;
@@ -173,7 +232,7 @@ impl Gfx {
 
             width += match span {
                 GlyphSpan::Space => 1,
-                GlyphSpan::Tab => Self::TAB_WIDTH,
+                GlyphSpan::Tab => self.next_tab_width(&mut tab_widths),
                 GlyphSpan::Glyph { advance, .. } => self.round_glyph_advance(advance),
             };
         }
@@ -291,6 +350,14 @@ impl Gfx {
             .set_font(font_name, font_size, self.inner.scale());
     }
 
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width.max(1);
+    }
+
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
     pub fn glyph_width(&self) -> f32 {
         self.inner.atlas_dimensions().glyph_width as f32
     }
@@ -186,6 +186,7 @@ impl WindowDelegate {
 
                 gfx
             },
+            tab_width: AnyGfx::TAB_WIDTH,
         };
 
         let app = App::new(&mut window, &mut gfx, 0.0);
@@ -1,5 +1,7 @@
 use std::{
     ffi::CString,
+    os::unix::ffi::OsStrExt,
+    path::Path,
     ptr::{null, null_mut},
     sync::Arc,
     thread::{self, JoinHandle},
@@ -20,6 +22,7 @@ const PIPE_WRITE: usize = 1;
 
 pub struct Process {
     pub output: Arc<ProcessOutput>,
+    pub error_output: Arc<ProcessOutput>,
     pub input: Vec<u8>,
 
     read_thread_join: Option<JoinHandle<()>>,
@@ -31,7 +34,20 @@ pub struct Process {
 }
 
 impl Process {
-    pub fn new(commands: &[&str], kind: ProcessKind) -> Result<Self> {
+    pub fn new(
+        commands: &[&[&str]],
+        env: &[(&str, &str)],
+        current_dir: Option<&Path>,
+        kind: ProcessKind,
+    ) -> Result<Self> {
+        let env: Vec<(CString, CString)> = env
+            .iter()
+            .filter_map(|(key, value)| Some((CString::new(*key).ok()?, CString::new(*value).ok()?)))
+            .collect();
+
+        let current_dir = current_dir
+            .and_then(|current_dir| CString::new(current_dir.as_os_str().as_bytes()).ok());
+
         let kq = unsafe { libc::kqueue() };
         let mut result_fds = [0, 0];
 
@@ -39,16 +55,18 @@ impl Process {
             return Err("Failed to create result pipe");
         }
 
-        let (read_fd, write_fd, pid) = unsafe {
+        let (read_fd, write_fd, error_fd, pid) = unsafe {
             match kind {
                 ProcessKind::Normal => {
                     let mut stdin_fds = [0, 0];
                     let mut stdout_fds = [0, 0];
+                    let mut stderr_fds = [0, 0];
 
                     if libc::pipe(stdin_fds.as_mut_ptr()) == -1
                         || libc::pipe(stdout_fds.as_mut_ptr()) == -1
+                        || libc::pipe(stderr_fds.as_mut_ptr()) == -1
                     {
-                        return Err("Failed to create stdin/stdout pipes");
+                        return Err("Failed to create stdin/stdout/stderr pipes");
                     }
 
                     let pid = libc::fork();
@@ -57,18 +75,27 @@ impl Process {
                         if pid == 0 {
                             libc::dup2(stdin_fds[PIPE_READ], libc::STDIN_FILENO);
                             libc::dup2(stdout_fds[PIPE_WRITE], libc::STDOUT_FILENO);
+                            libc::dup2(stderr_fds[PIPE_WRITE], libc::STDERR_FILENO);
                         }
 
                         libc::close(stdin_fds[PIPE_READ]);
                         libc::close(stdin_fds[PIPE_WRITE]);
                         libc::close(stdout_fds[PIPE_READ]);
                         libc::close(stdout_fds[PIPE_WRITE]);
+                        libc::close(stderr_fds[PIPE_READ]);
+                        libc::close(stderr_fds[PIPE_WRITE]);
                     } else {
                         libc::close(stdin_fds[PIPE_READ]);
                         libc::close(stdout_fds[PIPE_WRITE]);
+                        libc::close(stderr_fds[PIPE_WRITE]);
                     }
 
-                    (stdout_fds[PIPE_READ], stdin_fds[PIPE_WRITE], pid)
+                    (
+                        stdout_fds[PIPE_READ],
+                        stdin_fds[PIPE_WRITE],
+                        Some(stderr_fds[PIPE_READ]),
+                        pid,
+                    )
                 }
                 ProcessKind::Pty { width, height } => {
                     let mut window_size = libc::winsize {
@@ -81,7 +108,7 @@ impl Process {
                     let mut fd = 0;
                     let pid = libc::forkpty(&mut fd, null_mut(), null_mut(), &mut window_size);
 
-                    (fd, fd, pid)
+                    (fd, fd, None, pid)
                 }
             }
         };
@@ -96,18 +123,22 @@ impl Process {
 
                 let flags = libc::fcntl(result_fds[PIPE_WRITE], libc::F_GETFD) | libc::FD_CLOEXEC;
                 libc::fcntl(result_fds[PIPE_WRITE], libc::F_SETFD, flags);
+
+                if let Some(current_dir) = &current_dir {
+                    libc::chdir(current_dir.as_ptr());
+                }
             }
 
             for command in commands {
-                let Some(child_path) = command.split(' ').nth(0) else {
+                let Some(child_path) = command.first() else {
                     continue;
                 };
 
-                let child_path = CString::new(child_path).unwrap();
+                let child_path = CString::new(*child_path).unwrap();
 
                 let args: Vec<CString> = command
-                    .split(' ')
-                    .map(|arg| CString::new(arg).unwrap())
+                    .iter()
+                    .map(|arg| CString::new(*arg).unwrap())
                     .collect();
 
                 let args: Vec<*const i8> = args
@@ -122,6 +153,10 @@ impl Process {
                         libc::setenv(c"COLORTERM".as_ptr(), c"truecolor".as_ptr(), 1);
                     }
 
+                    for (key, value) in &env {
+                        libc::setenv(key.as_ptr(), value.as_ptr(), 1);
+                    }
+
                     libc::execvp(child_path.as_ptr(), args.as_ptr());
                 }
             }
@@ -170,8 +205,15 @@ impl Process {
             }
         }
 
+        let error_output = Arc::new(ProcessOutput::new());
+
+        if let Some(error_fd) = error_fd {
+            Self::run_error_read_thread(error_output.clone(), error_fd);
+        }
+
         Ok(Self {
             output: Arc::new(ProcessOutput::new()),
+            error_output,
             input: Vec::new(),
 
             read_thread_join: None,
@@ -274,6 +316,32 @@ impl Process {
             }
         })
     }
+
+    // Errors don't need to wake up the view, so the thread reads them until the pipe closes.
+    fn run_error_read_thread(error_output: Arc<ProcessOutput>, error_fd: i32) {
+        thread::spawn(move || {
+            let mut buffer = [0u8; 1024];
+
+            loop {
+                let bytes_read =
+                    unsafe { libc::read(error_fd, buffer.as_mut_ptr() as _, buffer.len()) };
+
+                if matches!(bytes_read, 0 | -1) {
+                    break;
+                }
+
+                let mut data = &buffer[..bytes_read as usize];
+
+                while !data.is_empty() {
+                    data = error_output.enqueue(data);
+                }
+            }
+
+            unsafe {
+                libc::close(error_fd);
+            }
+        });
+    }
 }
 
 impl Drop for Process {
@@ -285,6 +353,7 @@ impl Drop for Process {
             libc::close(self.write_fd);
 
             self.output.kill();
+            self.error_output.kill();
         }
 
         if let Some(read_thread_join) = self.read_thread_join.take() {
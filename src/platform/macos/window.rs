@@ -133,6 +133,24 @@ impl Window {
         }
     }
 
+    pub fn is_dark_mode(&self) -> Option<bool> {
+        let mtm = MainThreadMarker::new()?;
+
+        // The window's appearance is overridden by the theme, so check the app's instead.
+        let appearance = NSApplication::sharedApplication(mtm).effectiveAppearance();
+
+        unsafe {
+            let names = NSArray::from_slice(&[NSAppearanceNameAqua, NSAppearanceNameDarkAqua]);
+            let name = appearance.bestMatchFromAppearancesWithNames(&names)?;
+
+            Some(name.isEqualToString(NSAppearanceNameDarkAqua))
+        }
+    }
+
+    pub fn beep(&self) {
+        unsafe { NSBeep() };
+    }
+
     pub fn resize(&mut self, width: f64, height: f64) {
         let scale = self.ns_window.backingScaleFactor();
 
@@ -517,6 +535,7 @@ impl Window {
             0x15 => Some(Key::Four),
             0x16 => Some(Key::Six),
             0x17 => Some(Key::Five),
+            0x18 => Some(Key::Equals),
             0x19 => Some(Key::Nine),
             0x1A => Some(Key::Seven),
             0x1B => Some(Key::Minus),
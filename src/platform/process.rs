@@ -1,4 +1,7 @@
-use std::sync::{Condvar, Mutex, MutexGuard};
+use std::{
+    path::Path,
+    sync::{Condvar, Mutex, MutexGuard},
+};
 
 use super::{platform_impl, result::Result};
 
@@ -32,7 +35,7 @@ impl ProcessOutputState {
             return &[];
         }
 
-        let allowed = data.len().min(self.buffer.len() - Self::MAX_OUTPUT);
+        let allowed = data.len().min(Self::MAX_OUTPUT - self.buffer.len());
         self.buffer.extend_from_slice(&data[..allowed]);
         &data[allowed..]
     }
@@ -98,8 +101,15 @@ pub struct Process {
 }
 
 impl Process {
-    pub fn new(commands: &[&str], kind: ProcessKind) -> Result<Self> {
-        let inner = platform_impl::process::Process::new(commands, kind)?;
+    // The process starts in our current directory unless another one is given.
+    // Each command is a program followed by its arguments, they're tried in order until one starts.
+    pub fn new(
+        commands: &[&[&str]],
+        env: &[(&str, &str)],
+        current_dir: Option<&Path>,
+        kind: ProcessKind,
+    ) -> Result<Self> {
+        let inner = platform_impl::process::Process::new(commands, env, current_dir, kind)?;
 
         Ok(Self { inner })
     }
@@ -119,4 +129,14 @@ impl Process {
     pub fn input_output(&mut self) -> (&mut Vec<u8>, DequeuedProcessOutput<'_>) {
         (&mut self.inner.input, self.inner.output.dequeue())
     }
+
+    pub fn error_output(&mut self) -> DequeuedProcessOutput<'_> {
+        self.inner.error_output.dequeue()
+    }
+
+    // Lets tests act as the process by writing its output.
+    #[cfg(test)]
+    pub fn push_output(&self, data: &[u8]) {
+        self.inner.output.enqueue(data);
+    }
 }
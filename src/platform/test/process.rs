@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use crate::platform::process::{ProcessKind, ProcessOutput};
 
@@ -7,13 +7,20 @@ use super::result::Result;
 pub struct Process {
     pub input: Vec<u8>,
     pub output: Arc<ProcessOutput>,
+    pub error_output: Arc<ProcessOutput>,
 }
 
 impl Process {
-    pub fn new(_commands: &[&str], _kind: ProcessKind) -> Result<Self> {
+    pub fn new(
+        _commands: &[&[&str]],
+        _env: &[(&str, &str)],
+        _current_dir: Option<&Path>,
+        _kind: ProcessKind,
+    ) -> Result<Self> {
         Ok(Self {
             input: Vec::new(),
             output: Arc::new(ProcessOutput::new()),
+            error_output: Arc::new(ProcessOutput::new()),
         })
     }
 
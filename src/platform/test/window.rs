@@ -26,6 +26,12 @@ impl Window {
         true
     }
 
+    pub fn is_dark_mode(&self) -> Option<bool> {
+        None
+    }
+
+    pub fn beep(&self) {}
+
     pub fn msgs(&mut self) -> Drain<'_, Msg> {
         self.msgs.drain(..)
     }
@@ -19,7 +19,7 @@ impl Text {
     #[cfg(target_os = "windows")]
     const BACKUP_FONT_NAME: &str = "Consolas";
 
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     const BACKUP_FONT_NAME: &str = "Menlo";
 
     pub fn new(font_name: &str, new_inner: impl Fn(&str) -> Result<PlatformText>) -> Result<Self> {
@@ -27,11 +27,20 @@ impl Window {
         self.inner.is_focused()
     }
 
+    // Returns None if the OS doesn't report a light/dark preference.
+    pub fn is_dark_mode(&self) -> Option<bool> {
+        self.inner.is_dark_mode()
+    }
+
+    pub fn beep(&self) {
+        self.inner.beep();
+    }
+
     pub fn was_shown(&self) -> bool {
         #[cfg(target_os = "windows")]
         return true;
 
-        #[cfg(target_os = "macos")]
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
         self.inner.was_shown
     }
 
@@ -150,6 +150,7 @@ impl AppRunner {
 
                 let mut gfx = AnyGfx {
                     inner: Gfx::new(scale, hwnd).unwrap(),
+                    tab_width: AnyGfx::TAB_WIDTH,
                 };
 
                 app_runner.app = Some(App::new(&mut app_runner.window, &mut gfx, 0.0));
@@ -1,12 +1,17 @@
 use core::str;
 use std::{
+    env::vars_os,
+    ffi::OsStr,
+    iter::repeat_n,
+    os::windows::ffi::OsStrExt,
+    path::Path,
     ptr::copy_nonoverlapping,
     sync::Arc,
     thread::{self, JoinHandle},
 };
 
 use windows::{
-    core::{Result, HSTRING, PWSTR},
+    core::{Result, HSTRING, PCWSTR, PWSTR},
     Win32::{
         Foundation::{
             CloseHandle, SetHandleInformation, HANDLE, HANDLE_FLAGS, HANDLE_FLAG_INHERIT,
@@ -27,6 +32,7 @@ use crate::platform::process::{ProcessKind, ProcessOutput};
 
 pub struct Process {
     pub output: Arc<ProcessOutput>,
+    pub error_output: Arc<ProcessOutput>,
     pub input: Vec<u8>,
 
     read_thread_join: Option<JoinHandle<()>>,
@@ -40,10 +46,16 @@ pub struct Process {
 }
 
 impl Process {
-    pub fn new(commands: &[&str], kind: ProcessKind) -> Result<Self> {
+    pub fn new(
+        commands: &[&[&str]],
+        env: &[(&str, &str)],
+        current_dir: Option<&Path>,
+        kind: ProcessKind,
+    ) -> Result<Self> {
         // Used to communicate with the child process.
         let mut output_read = HANDLE::default();
         let mut input_write = HANDLE::default();
+        let mut error_read = HANDLE::default();
 
         let hconsole;
         let event;
@@ -74,8 +86,19 @@ impl Process {
                 0,
             )?;
 
+            // Only used by normal processes, pseudo consoles combine errors with their output.
+            let mut error_write = HANDLE::default();
+
+            CreatePipe(
+                &mut error_read,
+                &mut error_write,
+                Some(&security_attributes),
+                0,
+            )?;
+
             SetHandleInformation(output_read, HANDLE_FLAG_INHERIT.0, HANDLE_FLAGS(0))?;
             SetHandleInformation(input_write, HANDLE_FLAG_INHERIT.0, HANDLE_FLAGS(0))?;
+            SetHandleInformation(error_read, HANDLE_FLAG_INHERIT.0, HANDLE_FLAGS(0))?;
 
             hconsole = if let ProcessKind::Pty { width, height } = kind {
                 Some(CreatePseudoConsole(
@@ -91,20 +114,31 @@ impl Process {
                 None
             };
 
-            process_info = Self::create_process(hconsole, input_read, output_write, commands)
-                .inspect_err(|_| {
-                    let _ = CloseHandle(input_read);
-                    let _ = CloseHandle(input_write);
-                    let _ = CloseHandle(output_read);
-                    let _ = CloseHandle(output_write);
-
-                    if let Some(hconsole) = hconsole {
-                        ClosePseudoConsole(hconsole);
-                    }
-                })?;
+            process_info = Self::create_process(
+                hconsole,
+                input_read,
+                output_write,
+                error_write,
+                commands,
+                env,
+                current_dir,
+            )
+            .inspect_err(|_| {
+                let _ = CloseHandle(input_read);
+                let _ = CloseHandle(input_write);
+                let _ = CloseHandle(output_read);
+                let _ = CloseHandle(output_write);
+                let _ = CloseHandle(error_read);
+                let _ = CloseHandle(error_write);
+
+                if let Some(hconsole) = hconsole {
+                    ClosePseudoConsole(hconsole);
+                }
+            })?;
 
             CloseHandle(input_read)?;
             CloseHandle(output_write)?;
+            CloseHandle(error_write)?;
 
             event = CreateEventW(None, false, false, None)?;
         }
@@ -114,8 +148,12 @@ impl Process {
 
         let read_thread_join = Self::run_read_thread(output.clone(), output_read, event);
 
+        let error_output = Arc::new(ProcessOutput::new());
+        Self::run_error_read_thread(error_output.clone(), error_read);
+
         Ok(Self {
             output,
+            error_output,
             input,
 
             read_thread_join: Some(read_thread_join),
@@ -133,16 +171,42 @@ impl Process {
         hconsole: Option<HPCON>,
         input_read: HANDLE,
         output_write: HANDLE,
-        commands: &[&str],
+        error_write: HANDLE,
+        commands: &[&[&str]],
+        env: &[(&str, &str)],
+        current_dir: Option<&Path>,
     ) -> Result<PROCESS_INFORMATION> {
         let mut process_info = PROCESS_INFORMATION::default();
         let mut result = Ok(());
 
+        // The child inherits our environment unless extra variables need to be added.
+        let environment_block = (!env.is_empty()).then(|| Self::create_environment_block(env));
+        let creation_flags = if environment_block.is_some() {
+            EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT
+        } else {
+            EXTENDED_STARTUPINFO_PRESENT
+        };
+
+        let current_dir = current_dir.map(|current_dir| HSTRING::from(current_dir.as_os_str()));
+
         let process_heap = GetProcessHeap()?;
-        let startup_info = Self::create_process_startup_info(hconsole, input_read, output_write)?;
+        let startup_info =
+            Self::create_process_startup_info(hconsole, input_read, output_write, error_write)?;
+
+        let mut command_line = String::new();
 
         for command in commands {
-            let wide_command = HSTRING::from(*command);
+            command_line.clear();
+
+            for arg in *command {
+                if !command_line.is_empty() {
+                    command_line.push(' ');
+                }
+
+                Self::push_quoted_arg(arg, &mut command_line);
+            }
+
+            let wide_command = HSTRING::from(command_line.as_str());
             let wide_command_len = wide_command.len() + 1;
 
             let command = HeapAlloc(
@@ -159,9 +223,13 @@ impl Process {
                 None,
                 None,
                 hconsole.is_none(),
-                EXTENDED_STARTUPINFO_PRESENT,
-                None,
-                None,
+                creation_flags,
+                environment_block
+                    .as_ref()
+                    .map(|environment_block| environment_block.as_ptr() as _),
+                current_dir
+                    .as_ref()
+                    .map_or(PCWSTR::null(), |current_dir| PCWSTR(current_dir.as_ptr())),
                 &startup_info.StartupInfo,
                 &mut process_info,
             );
@@ -184,10 +252,73 @@ impl Process {
             .map(|_| process_info)
     }
 
+    // Quotes arguments so that they're split back into the same arguments by the child,
+    // following the rules used by CommandLineToArgvW.
+    fn push_quoted_arg(arg: &str, command_line: &mut String) {
+        if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\u{B}', '"']) {
+            command_line.push_str(arg);
+            return;
+        }
+
+        command_line.push('"');
+
+        let mut backslash_count = 0;
+
+        for c in arg.chars() {
+            match c {
+                '\\' => backslash_count += 1,
+                '"' => {
+                    // Backslashes before a quote are escaped, along with the quote itself.
+                    command_line.extend(repeat_n('\\', backslash_count * 2 + 1));
+                    command_line.push('"');
+                    backslash_count = 0;
+                }
+                _ => {
+                    command_line.extend(repeat_n('\\', backslash_count));
+                    command_line.push(c);
+                    backslash_count = 0;
+                }
+            }
+        }
+
+        // Backslashes before the closing quote are escaped so they don't escape it.
+        command_line.extend(repeat_n('\\', backslash_count * 2));
+        command_line.push('"');
+    }
+
+    fn create_environment_block(env: &[(&str, &str)]) -> Vec<u16> {
+        let mut environment_block = Vec::new();
+
+        for (key, value) in vars_os() {
+            if env
+                .iter()
+                .any(|(env_key, _)| OsStr::new(env_key).eq_ignore_ascii_case(&key))
+            {
+                continue;
+            }
+
+            environment_block.extend(key.encode_wide());
+            environment_block.push(b'=' as u16);
+            environment_block.extend(value.encode_wide());
+            environment_block.push(0);
+        }
+
+        for (key, value) in env {
+            environment_block.extend(key.encode_utf16());
+            environment_block.push(b'=' as u16);
+            environment_block.extend(value.encode_utf16());
+            environment_block.push(0);
+        }
+
+        environment_block.push(0);
+        environment_block
+    }
+
     unsafe fn create_process_startup_info(
         hconsole: Option<HPCON>,
         input_read: HANDLE,
         output_write: HANDLE,
+        error_write: HANDLE,
     ) -> Result<STARTUPINFOEXW> {
         let attribute_count = if hconsole.is_some() { 1 } else { 0 };
 
@@ -231,6 +362,7 @@ impl Process {
         if hconsole.is_none() {
             startup_info.StartupInfo.hStdOutput = output_write;
             startup_info.StartupInfo.hStdInput = input_read;
+            startup_info.StartupInfo.hStdError = error_write;
             startup_info.StartupInfo.dwFlags |= STARTF_USESTDHANDLES;
         }
 
@@ -300,6 +432,36 @@ impl Process {
             }
         })
     }
+
+    // Errors don't need to wake up the window, so the thread reads them until the pipe closes.
+    fn run_error_read_thread(error_output: Arc<ProcessOutput>, stderr: HANDLE) {
+        let stderr = stderr.0 as usize;
+
+        thread::spawn(move || {
+            let stderr = HANDLE(stderr as _);
+            let mut buffer = [0u8; 1024];
+
+            loop {
+                let mut bytes_read = 0;
+
+                unsafe {
+                    if ReadFile(stderr, Some(&mut buffer), Some(&mut bytes_read), None).is_err() {
+                        break;
+                    }
+                }
+
+                let mut data = &buffer[..bytes_read as usize];
+
+                while !data.is_empty() {
+                    data = error_output.enqueue(data);
+                }
+            }
+
+            unsafe {
+                let _ = CloseHandle(stderr);
+            }
+        });
+    }
 }
 
 impl Drop for Process {
@@ -317,6 +479,7 @@ impl Drop for Process {
             let _ = CloseHandle(self.stdout);
 
             self.output.kill();
+            self.error_output.kill();
         }
 
         if let Some(read_thread_join) = self.read_thread_join.take() {
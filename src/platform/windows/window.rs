@@ -6,7 +6,7 @@ use std::{
 };
 
 use windows::{
-    core::Result,
+    core::{w, Result},
     Data::Text::UnicodeCharacters,
     Win32::{
         Foundation::{
@@ -21,6 +21,7 @@ use windows::{
             Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
             Ole::CF_UNICODETEXT,
             Performance::{QueryPerformanceCounter, QueryPerformanceFrequency},
+            Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD},
             Threading::INFINITE,
         },
         UI::{
@@ -74,6 +75,7 @@ pub struct Window {
 
     is_running: bool,
     is_focused: bool,
+    is_dark_mode: Option<bool>,
 
     x: i32,
     y: i32,
@@ -121,6 +123,7 @@ impl Window {
 
             is_running: true,
             is_focused: false,
+            is_dark_mode: Self::query_is_dark_mode(),
 
             x: 0,
             y: 0,
@@ -155,6 +158,35 @@ impl Window {
         }
     }
 
+    pub fn is_dark_mode(&self) -> Option<bool> {
+        self.is_dark_mode
+    }
+
+    pub fn beep(&self) {
+        unsafe {
+            let _ = MessageBeep(MB_OK);
+        }
+    }
+
+    fn query_is_dark_mode() -> Option<bool> {
+        let mut uses_light_theme = 0u32;
+        let mut size = size_of::<u32>() as u32;
+
+        let result = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+                w!("AppsUseLightTheme"),
+                RRF_RT_REG_DWORD,
+                None,
+                Some(&mut uses_light_theme as *mut u32 as _),
+                Some(&mut size),
+            )
+        };
+
+        result.is_ok().then_some(uses_light_theme == 0)
+    }
+
     pub fn resize(&mut self, width: i32, height: i32) {
         self.width = width;
         self.height = height;
@@ -655,6 +687,11 @@ impl Window {
                     y: y - self.y as f32,
                 }));
             }
+            WM_SETTINGCHANGE => {
+                self.is_dark_mode = Self::query_is_dark_mode();
+
+                let _ = PostMessageW(Some(self.hwnd), WM_PAINT, WPARAM(0), LPARAM(0));
+            }
             WM_CLIPBOARDUPDATE => {
                 if !self.did_just_copy {
                     self.was_copy_implicit = false;
@@ -811,6 +848,7 @@ impl Window {
             0xA4 => Some(Key::LAlt),
             0xA5 => Some(Key::RAlt),
             0xBF => Some(Key::ForwardSlash),
+            0xBB => Some(Key::Equals),
             0xBD => Some(Key::Minus),
             0xBE => Some(Key::Period),
             0xC0 => Some(Key::Grave),
@@ -192,6 +192,12 @@ impl Borrow<str> for Pooled<String> {
     }
 }
 
+impl Borrow<Path> for Pooled<PathBuf> {
+    fn borrow(&self) -> &Path {
+        self.deref()
+    }
+}
+
 impl From<&str> for Pooled<String> {
     fn from(value: &str) -> Self {
         STRING_POOL.init_item(|string| string.push_str(value))
@@ -1,14 +1,19 @@
 pub mod action_history;
+pub mod case;
 pub mod compare;
 pub mod cursor;
 pub mod cursor_index;
+pub mod diff;
 pub mod doc;
+pub mod emmet;
 pub mod grapheme;
 pub mod grapheme_category;
+pub mod hex_dump;
 mod pattern;
 pub mod selection;
 pub mod syntax;
 pub mod syntax_highlighter;
+pub mod text_encoding;
 pub mod tokenizer;
 mod trie;
 
@@ -0,0 +1,58 @@
+use crate::pool::{Pooled, STRING_POOL};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CasePattern {
+    Lower,
+    Upper,
+    Capitalized,
+    Mixed,
+}
+
+impl CasePattern {
+    fn of(text: &str) -> Self {
+        let mut letters = text.chars().filter(|c| c.is_alphabetic());
+
+        let Some(first) = letters.next() else {
+            return Self::Mixed;
+        };
+
+        let mut has_lower = first.is_lowercase();
+        let mut has_upper = first.is_uppercase();
+        let mut is_rest_lower = true;
+
+        for c in letters {
+            has_lower |= c.is_lowercase();
+            has_upper |= c.is_uppercase();
+            is_rest_lower &= !c.is_uppercase();
+        }
+
+        match (has_lower, has_upper) {
+            (true, false) => Self::Lower,
+            (false, true) => Self::Upper,
+            (true, true) if first.is_uppercase() && is_rest_lower => Self::Capitalized,
+            _ => Self::Mixed,
+        }
+    }
+}
+
+// Applies the case pattern of the matched text to the replacement, e.g. replacing
+// "Foo" with "bar" gives "Bar". Mixed case falls back to the literal replacement.
+pub fn preserve_case(replacement: &str, matched: &str) -> Pooled<String> {
+    let mut result = STRING_POOL.new_item();
+
+    match CasePattern::of(matched) {
+        CasePattern::Lower => result.extend(replacement.chars().flat_map(char::to_lowercase)),
+        CasePattern::Upper => result.extend(replacement.chars().flat_map(char::to_uppercase)),
+        CasePattern::Capitalized => {
+            let mut chars = replacement.chars();
+
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.extend(chars.flat_map(char::to_lowercase));
+            }
+        }
+        CasePattern::Mixed => result.push_str(replacement),
+    }
+
+    result
+}
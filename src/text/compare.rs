@@ -15,6 +15,14 @@ pub fn compare_ignore_ascii_case(a: &str, b: &str) -> Ordering {
     a.len().cmp(&b.len())
 }
 
+pub fn is_fuzzy_match(haystack: &str, needle: &str) -> bool {
+    let mut haystack_chars = haystack.chars();
+
+    needle.chars().all(|needle_char| {
+        haystack_chars.any(|haystack_char| haystack_char.eq_ignore_ascii_case(&needle_char))
+    })
+}
+
 pub fn score_fuzzy_match(haystack: &str, needle: &str) -> f32 {
     const AWARD_DISTANCE_FALLOFF: f32 = 0.8;
     const AWARD_MATCH_BONUS: f32 = 1.0;
@@ -43,3 +51,17 @@ pub fn score_fuzzy_match(haystack: &str, needle: &str) -> f32 {
 
     score
 }
+
+// Entries that were used more often or more recently score higher.
+// The recency rank is how many other entries have been used since this one.
+pub fn score_usage(use_count: usize, recency_rank: Option<usize>) -> f32 {
+    const FREQUENCY_WEIGHT: f32 = 1.0;
+    const RECENCY_WEIGHT: f32 = 2.0;
+
+    let frequency_score = (use_count as f32).ln_1p() * FREQUENCY_WEIGHT;
+    let recency_score = recency_rank
+        .map(|rank| RECENCY_WEIGHT / (rank + 1) as f32)
+        .unwrap_or_default();
+
+    frequency_score + recency_score
+}
@@ -0,0 +1,110 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffHunkKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub kind: DiffHunkKind,
+    // Removed hunks have an empty range that starts at the line following the removed lines.
+    pub lines: Range<usize>,
+    pub saved_lines: Range<usize>,
+}
+
+// Changes with more lines than this are treated as a single hunk rather than being diffed.
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+pub fn diff_lines<T: PartialEq>(saved: &[T], current: &[T]) -> Vec<DiffHunk> {
+    let prefix_len = saved
+        .iter()
+        .zip(current)
+        .take_while(|(saved_line, line)| saved_line == line)
+        .count();
+
+    let suffix_len = saved[prefix_len..]
+        .iter()
+        .rev()
+        .zip(current[prefix_len..].iter().rev())
+        .take_while(|(saved_line, line)| saved_line == line)
+        .count();
+
+    let saved = &saved[prefix_len..saved.len() - suffix_len];
+    let current = &current[prefix_len..current.len() - suffix_len];
+
+    let mut hunks = Vec::new();
+
+    if (saved.len() + 1) * (current.len() + 1) > MAX_DIFF_CELLS {
+        push_hunk(
+            prefix_len..prefix_len + saved.len(),
+            prefix_len..prefix_len + current.len(),
+            &mut hunks,
+        );
+
+        return hunks;
+    }
+
+    // The length of the longest common subsequence of the remaining lines after each pair of lines.
+    let width = current.len() + 1;
+    let mut lcs_lens = vec![0u32; (saved.len() + 1) * width];
+
+    for i in (0..saved.len()).rev() {
+        for j in (0..current.len()).rev() {
+            lcs_lens[i * width + j] = if saved[i] == current[j] {
+                lcs_lens[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs_lens[(i + 1) * width + j].max(lcs_lens[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let (mut hunk_i, mut hunk_j) = (0, 0);
+
+    while i < saved.len() || j < current.len() {
+        if i < saved.len() && j < current.len() && saved[i] == current[j] {
+            push_hunk(
+                prefix_len + hunk_i..prefix_len + i,
+                prefix_len + hunk_j..prefix_len + j,
+                &mut hunks,
+            );
+
+            i += 1;
+            j += 1;
+
+            (hunk_i, hunk_j) = (i, j);
+        } else if j < current.len()
+            && (i == saved.len() || lcs_lens[i * width + j + 1] >= lcs_lens[(i + 1) * width + j])
+        {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+
+    push_hunk(
+        prefix_len + hunk_i..prefix_len + i,
+        prefix_len + hunk_j..prefix_len + j,
+        &mut hunks,
+    );
+
+    hunks
+}
+
+fn push_hunk(saved_lines: Range<usize>, lines: Range<usize>, hunks: &mut Vec<DiffHunk>) {
+    let kind = match (saved_lines.is_empty(), lines.is_empty()) {
+        (true, true) => return,
+        (true, false) => DiffHunkKind::Added,
+        (false, true) => DiffHunkKind::Removed,
+        (false, false) => DiffHunkKind::Modified,
+    };
+
+    hunks.push(DiffHunk {
+        kind,
+        lines,
+        saved_lines,
+    });
+}
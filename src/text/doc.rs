@@ -1,40 +1,54 @@
+mod emmet;
+mod loader;
 mod lsp;
+mod surround;
 
 #[cfg(test)]
 mod tests;
 
 use std::{
+    collections::HashSet,
     fmt::Display,
-    fs::{read_to_string, File},
-    io::{self, Write},
+    fs::{copy, create_dir_all, metadata, read, File, OpenOptions},
+    io::{self, Read, Seek, Write},
     mem::take,
     ops::RangeInclusive,
     path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+use loader::DocLoader;
 use lsp::DocLspState;
 
 use crate::{
     bit_field::define_bit_field,
-    config::language::DelimiterKind,
+    config::language::{DelimiterKind, IndentWidth},
     ctx::{ctx_with_time, Ctx},
     geometry::{position::Position, rect::Rect, visual_position::VisualPosition},
     lsp::types::DecodedDiagnostic,
     normalizable::Normalizable,
     platform::gfx::Gfx,
     pool::{Pooled, STRING_POOL},
-    text::grapheme,
+    text::{
+        grapheme,
+        hex_dump::{is_binary, write_hex_dump},
+        text_encoding::TextEncoding,
+    },
 };
 
 use super::{
     action_history::{Action, ActionHistory, ActionKind},
+    case::preserve_case,
     cursor::Cursor,
     cursor_index::{CursorIndex, CursorIndices},
+    diff::{diff_lines, DiffHunk},
     grapheme::{CharCursor, CharIterator, GraphemeCursor, GraphemeIterator},
     grapheme_category::GraphemeCategory,
-    selection::Selection,
+    selection::{Selection, SelectionStats},
     syntax::Syntax,
-    syntax_highlighter::{HighlightedLine, SyntaxHighlighter, TerminalHighlightKind},
+    syntax_highlighter::{
+        HighlightKind, HighlightedLine, SyntaxHighlighter, TerminalHighlightKind,
+    },
     tokenizer::Tokenizer,
     trie::Trie,
 };
@@ -75,6 +89,7 @@ pub enum DocFlag {
     RecenterOnBottom,
     MatchPairs,
     ShowGutter,
+    ShowInvisibleChars,
 }
 
 define_bit_field!(DocFlags, DocFlag, u16);
@@ -92,9 +107,12 @@ impl DocFlags {
         .with(DocFlag::AllowMultipleLines)
         .with(DocFlag::AllowScrollingPastBottom)
         .with(DocFlag::MatchPairs)
-        .with(DocFlag::ShowGutter);
+        .with(DocFlag::ShowGutter)
+        .with(DocFlag::ShowInvisibleChars);
 
     pub const TERMINAL: Self = Self::RAW.with(DocFlag::RecenterOnBottom);
+
+    pub const LOG: Self = Self::RAW.with(DocFlag::UpdateCursors);
 }
 
 #[derive(Debug, Default)]
@@ -141,8 +159,13 @@ pub struct Doc {
 
     lines: Vec<Pooled<String>>,
     cursors: Vec<Cursor>,
+    bookmarks: Vec<Position>,
     line_ending: LineEnding,
 
+    saved_lines: Option<Vec<Pooled<String>>>,
+    diff_hunks: Vec<DiffHunk>,
+    diff_version: Option<usize>,
+
     undo_history: ActionHistory,
     redo_history: ActionHistory,
 
@@ -150,10 +173,24 @@ pub struct Doc {
     unhighlighted_line_y: usize,
     tokenizer: Tokenizer,
     needs_tokenization: bool,
+    needs_recenter: bool,
 
     lsp_state: DocLspState,
 
     flags: DocFlags,
+    is_elastic_tabstops: bool,
+    // Elastic tab widths for every line, along with the version and tab width they were found for.
+    tab_widths: Vec<Vec<usize>>,
+    tab_widths_version: Option<(usize, usize)>,
+    word_chars: Pooled<String>,
+    detected_indent_width: Option<IndentWidth>,
+    loader: Option<DocLoader>,
+    is_binary: bool,
+    is_read_only: bool,
+    // Whether the user flipped the read-only state that was found on the drive, kept across loads.
+    is_read_only_toggled: bool,
+    blocked_edit_time: Option<f64>,
+    encoding: TextEncoding,
 }
 
 impl Doc {
@@ -161,9 +198,12 @@ impl Doc {
     #[cfg(target_os = "windows")]
     const EXPECTED_CHANGE_COUNT_ON_SAVE: usize = 2;
 
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     const EXPECTED_CHANGE_COUNT_ON_SAVE: usize = 1;
 
+    const ELASTIC_TAB_PADDING: usize = 2;
+    const BLOCKED_EDIT_DURATION: f64 = 1.0;
+
     pub fn new(
         path: Option<Pooled<PathBuf>>,
         display_name: Option<Pooled<String>>,
@@ -189,8 +229,13 @@ impl Doc {
 
             lines,
             cursors: Vec::new(),
+            bookmarks: Vec::new(),
             line_ending: LineEnding::default(),
 
+            saved_lines: None,
+            diff_hunks: Vec::new(),
+            diff_version: None,
+
             undo_history: ActionHistory::new(),
             redo_history: ActionHistory::new(),
 
@@ -198,10 +243,22 @@ impl Doc {
             unhighlighted_line_y: 0,
             tokenizer: Tokenizer::new(),
             needs_tokenization: false,
+            needs_recenter: false,
 
             lsp_state: Default::default(),
 
             flags,
+            is_elastic_tabstops: false,
+            tab_widths: Vec::new(),
+            tab_widths_version: None,
+            word_chars: STRING_POOL.new_item(),
+            detected_indent_width: None,
+            loader: None,
+            is_binary: false,
+            is_read_only: false,
+            is_read_only_toggled: false,
+            blocked_edit_time: None,
+            encoding: TextEncoding::default(),
         };
 
         doc.reset_cursors();
@@ -285,7 +342,13 @@ impl Doc {
 
         if delta_y != 0 {
             if let Some(desired_visual_x) = desired_visual_x {
-                position.x = gfx.find_x_for_visual_x(&self.lines[position.y][..], desired_visual_x);
+                let tab_widths = self.tab_widths(position.y, gfx);
+
+                position.x = gfx.find_x_for_visual_x_with_tab_widths(
+                    &self.lines[position.y][..],
+                    desired_visual_x,
+                    &tab_widths,
+                );
             } else if position.x > self.line_len(position.y) {
                 position.x = self.line_len(position.y);
             }
@@ -333,7 +396,7 @@ impl Doc {
         let side_offset = Self::side_offset(delta_x);
 
         loop {
-            let current_category = GraphemeCategory::new(self.grapheme(self.move_position(
+            let current_category = self.grapheme_category(self.grapheme(self.move_position(
                 position,
                 side_offset,
                 0,
@@ -365,7 +428,7 @@ impl Doc {
             self.move_position_skipping_category(position, delta_x, GraphemeCategory::Space, gfx);
 
         let side_offset = Self::side_offset(delta_x);
-        let starting_category = GraphemeCategory::new(self.grapheme(self.move_position(
+        let starting_category = self.grapheme_category(self.grapheme(self.move_position(
             starting_position,
             side_offset,
             0,
@@ -386,6 +449,94 @@ impl Doc {
         }
     }
 
+    // Stops at case changes and underscores within identifiers, eg. get|Foo|Bar or HTTP|Server.
+    pub fn move_position_to_next_sub_word(
+        &self,
+        position: Position,
+        delta_x: isize,
+        gfx: &mut Gfx,
+    ) -> Position {
+        let is_lowercase = |grapheme: &str| {
+            grapheme != "_"
+                && grapheme::is_alphanumeric(grapheme)
+                && !grapheme::is_uppercase(grapheme)
+        };
+
+        let starting_position =
+            self.move_position_skipping_category(position, delta_x, GraphemeCategory::Space, gfx);
+
+        let side_offset = Self::side_offset(delta_x);
+        let starting_grapheme =
+            self.grapheme(self.move_position(starting_position, side_offset, 0, gfx));
+
+        if self.grapheme_category(starting_grapheme) != GraphemeCategory::Identifier {
+            return self.move_position_to_next_word(position, delta_x, gfx);
+        }
+
+        let sub_word_start =
+            self.move_position_while(starting_position, delta_x, |grapheme| grapheme == "_", gfx);
+        let grapheme = self.grapheme(self.move_position(sub_word_start, side_offset, 0, gfx));
+
+        let ending_position = if delta_x > 0 && grapheme::is_uppercase(grapheme) {
+            let uppercase_end =
+                self.move_position_while(sub_word_start, delta_x, grapheme::is_uppercase, gfx);
+
+            if uppercase_end.x - sub_word_start.x == grapheme.len() {
+                self.move_position_while(uppercase_end, delta_x, is_lowercase, gfx)
+            } else if grapheme::is_lowercase(self.grapheme(uppercase_end)) {
+                // The last capital letter starts the next sub-word.
+                self.move_position(uppercase_end, -1, 0, gfx)
+            } else {
+                uppercase_end
+            }
+        } else if delta_x < 0 && is_lowercase(grapheme) {
+            let lowercase_start =
+                self.move_position_while(sub_word_start, delta_x, is_lowercase, gfx);
+            let previous_position = self.move_position(lowercase_start, -1, 0, gfx);
+
+            if previous_position.y == lowercase_start.y
+                && grapheme::is_uppercase(self.grapheme(previous_position))
+            {
+                previous_position
+            } else {
+                lowercase_start
+            }
+        } else if grapheme::is_uppercase(grapheme) {
+            self.move_position_while(sub_word_start, delta_x, grapheme::is_uppercase, gfx)
+        } else {
+            self.move_position_while(sub_word_start, delta_x, is_lowercase, gfx)
+        };
+
+        if ending_position == position {
+            self.move_position(ending_position, delta_x, 0, gfx)
+        } else {
+            ending_position
+        }
+    }
+
+    fn move_position_while(
+        &self,
+        mut position: Position,
+        delta_x: isize,
+        predicate: impl Fn(&str) -> bool,
+        gfx: &mut Gfx,
+    ) -> Position {
+        let side_offset = Self::side_offset(delta_x);
+
+        loop {
+            let grapheme = self.grapheme(self.move_position(position, side_offset, 0, gfx));
+            let next_position = self.move_position(position, delta_x, 0, gfx);
+
+            if grapheme == "\n" || !predicate(grapheme) || next_position == position {
+                break;
+            }
+
+            position = next_position;
+        }
+
+        position
+    }
+
     pub fn move_position_skipping_lines(
         &self,
         position: Position,
@@ -447,6 +598,19 @@ impl Doc {
         }
     }
 
+    // Converts a 1-based line and column, where the column counts chars, into a position.
+    pub fn line_column_position(&self, line: usize, column: usize) -> Position {
+        let y = line.saturating_sub(1).min(self.lines.len() - 1);
+
+        let x = self.lines[y]
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map(|(x, _)| x)
+            .unwrap_or(self.line_len(y));
+
+        Position::new(x, y)
+    }
+
     pub fn line_end(&self, y: usize) -> Position {
         Position::new(self.line_len(y), y)
     }
@@ -682,6 +846,141 @@ impl Doc {
         }
     }
 
+    pub fn reverse_lines_at_cursors(&mut self, ctx: &mut Ctx) {
+        self.rearrange_lines_at_cursors(|lines| lines.reverse(), ctx);
+    }
+
+    pub fn shuffle_lines_at_cursors(&mut self, seed: u64, ctx: &mut Ctx) {
+        // Xorshift doesn't work with a state of zero.
+        let mut state = seed | 1;
+
+        self.rearrange_lines_at_cursors(
+            |lines| {
+                for i in (1..lines.len()).rev() {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+
+                    lines.swap(i, (state % (i as u64 + 1)) as usize);
+                }
+            },
+            ctx,
+        );
+    }
+
+    // Like uniq, only consecutive duplicates are removed.
+    pub fn unique_adjacent_lines_at_cursors(&mut self, ignore_case: bool, ctx: &mut Ctx) {
+        self.rearrange_lines_at_cursors(
+            |lines| {
+                lines.dedup_by(|a, b| {
+                    if ignore_case {
+                        a.to_lowercase() == b.to_lowercase()
+                    } else {
+                        a == b
+                    }
+                })
+            },
+            ctx,
+        );
+    }
+
+    pub fn unique_lines_at_cursors(&mut self, ignore_case: bool, ctx: &mut Ctx) {
+        self.rearrange_lines_at_cursors(
+            |lines| {
+                let mut seen_lines = HashSet::new();
+
+                lines.retain(|line| {
+                    if ignore_case {
+                        seen_lines.insert(line.to_lowercase())
+                    } else {
+                        seen_lines.insert(line.to_string())
+                    }
+                });
+            },
+            ctx,
+        );
+    }
+
+    // Rearranges the lines in each selection, or the whole doc if nothing is selected.
+    fn rearrange_lines_at_cursors(
+        &mut self,
+        mut rearrange: impl FnMut(&mut Vec<&str>),
+        ctx: &mut Ctx,
+    ) {
+        let has_selection = self
+            .cursor_indices()
+            .any(|index| self.cursor(index).get_selection().is_some());
+
+        if !has_selection {
+            let positions: Vec<Position> =
+                self.cursors.iter().map(|cursor| cursor.position).collect();
+
+            self.rearrange_lines(0, self.lines.len() - 1, &mut rearrange, ctx);
+
+            for (cursor, position) in self.cursors.iter_mut().zip(positions) {
+                cursor.position = position;
+            }
+
+            for index in self.cursor_indices() {
+                let position = self.clamp_position(self.cursor(index).position);
+                self.jump_cursor(index, position, false, ctx.gfx);
+            }
+
+            return;
+        }
+
+        for index in self.cursor_indices() {
+            let Some(selection) = self.cursor(index).get_selection() else {
+                continue;
+            };
+
+            let selection = selection.trim();
+
+            let end_y =
+                self.rearrange_lines(selection.start.y, selection.end.y, &mut rearrange, ctx);
+            let end = self.line_end(end_y);
+
+            self.jump_cursor(index, Position::new(0, selection.start.y), false, ctx.gfx);
+            self.jump_cursor(index, end, true, ctx.gfx);
+        }
+    }
+
+    // Returns the last line of the rearranged lines, which may have changed if lines were removed.
+    fn rearrange_lines(
+        &mut self,
+        start_y: usize,
+        end_y: usize,
+        rearrange: &mut impl FnMut(&mut Vec<&str>),
+        ctx: &mut Ctx,
+    ) -> usize {
+        let mut text = STRING_POOL.new_item();
+
+        let mut lines: Vec<&str> = self.lines[start_y..=end_y]
+            .iter()
+            .map(|line| line.as_str())
+            .collect();
+
+        rearrange(&mut lines);
+
+        let new_end_y = start_y + lines.len().saturating_sub(1);
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+
+            text.push_str(line);
+        }
+
+        let start = Position::new(0, start_y);
+        let end = self.line_end(end_y);
+
+        self.delete(start, end, ctx);
+        self.insert(start, &text, ctx);
+
+        new_end_y
+    }
+
     fn indent_line(&mut self, y: usize, ctx: &mut Ctx) {
         self.indent(Position::new(0, y), ctx);
     }
@@ -791,6 +1090,30 @@ impl Doc {
         self.jump_cursor(index, destination, should_select, gfx);
     }
 
+    pub fn move_cursor_to_next_sub_word(
+        &mut self,
+        index: CursorIndex,
+        delta_x: isize,
+        should_select: bool,
+        gfx: &mut Gfx,
+    ) {
+        let cursor = self.cursor(index);
+        let destination = self.move_position_to_next_sub_word(cursor.position, delta_x, gfx);
+
+        self.jump_cursor(index, destination, should_select, gfx);
+    }
+
+    pub fn move_cursors_to_next_sub_word(
+        &mut self,
+        delta_x: isize,
+        should_select: bool,
+        gfx: &mut Gfx,
+    ) {
+        for index in self.cursor_indices() {
+            self.move_cursor_to_next_sub_word(index, delta_x, should_select, gfx);
+        }
+    }
+
     pub fn move_cursors_to_next_word(
         &mut self,
         delta_x: isize,
@@ -843,6 +1166,137 @@ impl Doc {
         self.jump_cursor(CursorIndex::Main, position, should_select, gfx);
     }
 
+    pub fn toggle_bookmark(&mut self, y: usize) {
+        match self
+            .bookmarks
+            .binary_search_by(|bookmark| bookmark.y.cmp(&y))
+        {
+            Ok(index) => {
+                self.bookmarks.remove(index);
+            }
+            Err(index) => self.bookmarks.insert(index, Position::new(0, y)),
+        }
+    }
+
+    pub fn is_bookmarked(&self, y: usize) -> bool {
+        self.bookmarks
+            .binary_search_by(|bookmark| bookmark.y.cmp(&y))
+            .is_ok()
+    }
+
+    pub fn bookmarks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bookmarks.iter().map(|bookmark| bookmark.y)
+    }
+
+    // Docs that track saving keep a copy of their saved lines to find what has changed since.
+    fn update_saved_lines(&mut self) {
+        self.diff_version = None;
+
+        if !self.flags.contains(DocFlag::TrackSaving) {
+            return;
+        }
+
+        let saved_lines = self.saved_lines.get_or_insert_default();
+
+        saved_lines.clear();
+        saved_lines.extend(self.lines.iter().cloned());
+    }
+
+    pub fn update_diff_hunks(&mut self) {
+        if self.diff_version == Some(self.version) {
+            return;
+        }
+
+        self.diff_version = Some(self.version);
+
+        self.diff_hunks = match &self.saved_lines {
+            Some(saved_lines) if self.loader.is_none() => diff_lines(saved_lines, &self.lines),
+            _ => Vec::new(),
+        };
+    }
+
+    // Up to date as of the last call to update_diff_hunks.
+    pub fn diff_hunks(&self) -> &[DiffHunk] {
+        &self.diff_hunks
+    }
+
+    // Returns the first line of the next or previous hunk, wrapping around the doc.
+    pub fn diff_hunk_y(&mut self, y: usize, is_reverse: bool) -> Option<usize> {
+        self.update_diff_hunks();
+
+        // Lines removed from the end of the doc are found on the last line.
+        let last_y = self.lines.len() - 1;
+        let mut hunk_ys = self
+            .diff_hunks
+            .iter()
+            .map(|hunk| hunk.lines.start.min(last_y));
+
+        if is_reverse {
+            let mut hunk_ys = hunk_ys.rev();
+
+            hunk_ys
+                .clone()
+                .find(|hunk_y| *hunk_y < y)
+                .or_else(|| hunk_ys.next())
+        } else {
+            hunk_ys
+                .clone()
+                .find(|hunk_y| *hunk_y > y)
+                .or_else(|| hunk_ys.next())
+        }
+    }
+
+    // Replaces the lines of the hunk at y with their saved contents, returns true if there was a hunk.
+    pub fn revert_diff_hunk(&mut self, y: usize, ctx: &mut Ctx) -> bool {
+        self.update_diff_hunks();
+
+        let last_y = self.lines.len() - 1;
+
+        let Some(hunk) = self.diff_hunks.iter().find(|hunk| {
+            hunk.lines.contains(&y) || (hunk.lines.is_empty() && hunk.lines.start.min(last_y) == y)
+        }) else {
+            return false;
+        };
+
+        let Some(saved_lines) = &self.saved_lines else {
+            return false;
+        };
+
+        let lines = hunk.lines.clone();
+        let mut text = STRING_POOL.new_item();
+
+        // Hunks at the end of the doc have no following line, so they're replaced from the end of the previous one.
+        let (start, end) = if lines.end <= last_y {
+            for saved_line in &saved_lines[hunk.saved_lines.clone()] {
+                text.push_str(saved_line);
+                text.push('\n');
+            }
+
+            (Position::new(0, lines.start), Position::new(0, lines.end))
+        } else {
+            for (i, saved_line) in saved_lines[hunk.saved_lines.clone()].iter().enumerate() {
+                if i > 0 || lines.start > 0 {
+                    text.push('\n');
+                }
+
+                text.push_str(saved_line);
+            }
+
+            let start = if lines.start > 0 {
+                Position::new(self.line_len(lines.start - 1), lines.start - 1)
+            } else {
+                Position::ZERO
+            };
+
+            (start, self.end())
+        };
+
+        self.delete(start, end, ctx);
+        self.insert(start, &text, ctx);
+
+        true
+    }
+
     pub fn start_cursor_selection(&mut self, index: CursorIndex) {
         let position = self.cursor(index).position;
         self.cursor_mut(index).selection_anchor = Some(position);
@@ -866,8 +1320,9 @@ impl Doc {
     fn cursor_visual_x(&self, index: CursorIndex, gfx: &mut Gfx) -> usize {
         let cursor = self.cursor(index);
         let leading_text = &self.lines[cursor.position.y][..cursor.position.x];
+        let tab_widths = self.tab_widths(cursor.position.y, gfx);
 
-        gfx.measure_text(leading_text)
+        gfx.measure_text_with_tab_widths(leading_text, &tab_widths)
     }
 
     fn update_cursor_desired_visual_x(&mut self, index: CursorIndex, gfx: &mut Gfx) {
@@ -972,7 +1427,59 @@ impl Doc {
         }
     }
 
+    pub fn selection_stats(&self) -> Option<SelectionStats> {
+        let mut stats = None;
+
+        for index in self.cursor_indices() {
+            let Some(selection) = self.cursor(index).get_selection() else {
+                continue;
+            };
+
+            let stats = stats.get_or_insert_with(SelectionStats::default);
+
+            stats.lines += selection.trim().end.y - selection.start.y + 1;
+            // Line breaks count as one character each.
+            stats.chars += selection.end.y - selection.start.y;
+
+            for y in selection.start.y..=selection.end.y {
+                let line = &self.lines[y];
+
+                let start = if y == selection.start.y {
+                    selection.start.x
+                } else {
+                    0
+                };
+                let end = if y == selection.end.y {
+                    selection.end.x
+                } else {
+                    line.len()
+                };
+
+                let mut was_whitespace = true;
+
+                for grapheme in GraphemeIterator::new(&line[start..end]) {
+                    let is_whitespace = grapheme::is_whitespace(grapheme);
+
+                    if was_whitespace && !is_whitespace {
+                        stats.words += 1;
+                    }
+
+                    was_whitespace = is_whitespace;
+                    stats.chars += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
     pub fn undo(&mut self, action_kind: ActionKind, ctx: &mut Ctx) {
+        // Popping actions that can't be applied would lose them from the history.
+        if self.is_read_only() {
+            self.blocked_edit_time = Some(ctx.time);
+            return;
+        }
+
         let mut last_popped_time = None;
         let mut were_cursors_reset = false;
 
@@ -980,6 +1487,8 @@ impl Doc {
 
         while let Some(popped_action) = action_history!(self, action_kind).pop(last_popped_time) {
             last_popped_time = Some(popped_action.time);
+            // The change may have been off-screen, so the camera should follow the cursor to it.
+            self.needs_recenter = true;
 
             match popped_action.action {
                 Action::SetCursor {
@@ -1036,6 +1545,10 @@ impl Doc {
         }
     }
 
+    pub fn take_needs_recenter(&mut self) -> bool {
+        take(&mut self.needs_recenter)
+    }
+
     pub fn add_cursors_to_action_history(&mut self, action_kind: ActionKind, time: f64) {
         if !self.flags.contains(DocFlag::TrackHistory) {
             return;
@@ -1087,6 +1600,11 @@ impl Doc {
             return;
         }
 
+        if self.is_read_only() {
+            self.blocked_edit_time = Some(ctx.time);
+            return;
+        }
+
         if action_kind == ActionKind::Done {
             self.redo_history.clear();
         }
@@ -1132,7 +1650,7 @@ impl Doc {
             self.shift_positions(start, end, Self::shift_position_by_delete, ctx);
         }
 
-        self.lsp_diagnostic(ctx);
+        self.lsp_schedule_diagnostic(ctx.time);
     }
 
     pub fn insert(&mut self, start: Position, text: &str, ctx: &mut Ctx) -> Position {
@@ -1150,6 +1668,11 @@ impl Doc {
             return start;
         }
 
+        if self.is_read_only() {
+            self.blocked_edit_time = Some(ctx.time);
+            return start;
+        }
+
         if action_kind == ActionKind::Done {
             self.redo_history.clear();
         }
@@ -1207,7 +1730,7 @@ impl Doc {
             self.shift_positions(start, position, Self::shift_position_by_insert, ctx);
         }
 
-        self.lsp_diagnostic(ctx);
+        self.lsp_schedule_diagnostic(ctx.time);
 
         position
     }
@@ -1235,6 +1758,13 @@ impl Doc {
             self.update_cursor_desired_visual_x(index, ctx.gfx);
         }
 
+        for i in 0..self.bookmarks.len() {
+            self.bookmarks[i] = shift(self, start, end, self.bookmarks[i]);
+        }
+
+        // Bookmarks on deleted lines collapse into the line that replaced them.
+        self.bookmarks.dedup_by(|a, b| a.y == b.y);
+
         for language_server in ctx.lsp.iter_servers_mut() {
             for DecodedDiagnostic { range, .. } in language_server.diagnostics_mut(self) {
                 range.start = shift(self, start, end, range.start);
@@ -1443,20 +1973,166 @@ impl Doc {
         None
     }
 
-    pub fn end(&self) -> Position {
-        self.line_end(self.lines().len() - 1)
-    }
-
-    pub fn grapheme(&self, position: Position) -> &str {
-        let position = self.clamp_position(position);
-        let line = &self.lines[position.y];
+    // Only matches that fit entirely within one of the selections are returned, in order, up to max_count.
+    pub fn search_all_in(
+        &self,
+        text: &str,
+        selections: &[Selection],
+        max_count: usize,
+    ) -> Vec<Position> {
+        let mut matches = Vec::new();
 
-        if position.x == line.len() {
-            "\n"
-        } else {
-            grapheme::at(position.x, line)
+        if text.is_empty() {
+            return matches;
         }
-    }
+
+        for selection in selections {
+            for y in selection.start.y..=selection.end.y.min(self.lines.len() - 1) {
+                for (x, _) in self.lines[y].match_indices(text) {
+                    let start = Position::new(x, y);
+                    let end = Position::new(x + text.len(), y);
+
+                    if start >= selection.start && end <= selection.end {
+                        matches.push(start);
+                    }
+                }
+            }
+        }
+
+        matches.sort();
+        matches.dedup();
+        matches.truncate(max_count);
+        matches
+    }
+
+    // Returns the index of the match at the position, and the number of matches up to max_count.
+    pub fn count_matches(
+        &self,
+        text: &str,
+        position: Position,
+        selections: Option<&[Selection]>,
+        max_count: usize,
+        gfx: &mut Gfx,
+    ) -> (Option<usize>, usize) {
+        if let Some(selections) = selections {
+            let matches = self.search_all_in(text, selections, max_count);
+            let index = matches.iter().position(|start| *start == position);
+
+            return (index, matches.len());
+        }
+
+        let mut index = None;
+        let mut count = 0;
+
+        if text.is_empty() {
+            return (index, count);
+        }
+
+        // Matches are found the same way as when searching, so overlapping matches are counted too.
+        let mut next_match = if self.lines[0].starts_with(text) {
+            Some(Position::ZERO)
+        } else {
+            self.search_forward(text, Position::ZERO, false, gfx)
+        };
+
+        while let Some(start) = next_match {
+            if count >= max_count {
+                break;
+            }
+
+            if start == position {
+                index = Some(count);
+            }
+
+            count += 1;
+            next_match = self.search_forward(text, start, false, gfx);
+        }
+
+        (index, count)
+    }
+
+    pub fn search_in(
+        &self,
+        text: &str,
+        start: Position,
+        is_reverse: bool,
+        selections: &[Selection],
+    ) -> Option<Position> {
+        let matches = self.search_all_in(text, selections, usize::MAX);
+
+        if is_reverse {
+            matches
+                .iter()
+                .rev()
+                .find(|position| Position::new(position.x + text.len(), position.y) < start)
+                .or(matches.last())
+                .copied()
+        } else {
+            matches
+                .iter()
+                .find(|position| **position >= start)
+                .or(matches.first())
+                .copied()
+        }
+    }
+
+    // The selections are updated to keep covering the same text after replacing. Returns the replacement count.
+    pub fn replace_all_in(
+        &mut self,
+        text: &str,
+        replacement: &str,
+        do_preserve_case: bool,
+        selections: &mut [Selection],
+        ctx: &mut Ctx,
+    ) -> usize {
+        let matches = self.search_all_in(text, selections, usize::MAX);
+
+        // Replace from back to front so the remaining matches stay valid.
+        for &start in matches.iter().rev() {
+            let end = Position::new(start.x + text.len(), start.y);
+
+            let replacement = if do_preserve_case {
+                preserve_case(replacement, &self.lines[start.y][start.x..end.x])
+            } else {
+                replacement.into()
+            };
+
+            self.delete(start, end, ctx);
+            let replacement_end = self.insert(start, &replacement, ctx);
+
+            // The replacement may contain line breaks, which moves later lines too.
+            for selection in selections.iter_mut() {
+                for position in [&mut selection.start, &mut selection.end] {
+                    if *position < end {
+                        continue;
+                    }
+
+                    if position.y == end.y {
+                        position.x = replacement_end.x + position.x - end.x;
+                    }
+
+                    position.y = position.y - end.y + replacement_end.y;
+                }
+            }
+        }
+
+        matches.len()
+    }
+
+    pub fn end(&self) -> Position {
+        self.line_end(self.lines().len() - 1)
+    }
+
+    pub fn grapheme(&self, position: Position) -> &str {
+        let position = self.clamp_position(position);
+        let line = &self.lines[position.y];
+
+        if position.x == line.len() {
+            "\n"
+        } else {
+            grapheme::at(position.x, line)
+        }
+    }
 
     // It's ok for the x position to equal the length of the line.
     // That represents the cursor being right before the newline sequence.
@@ -1478,10 +2154,18 @@ impl Doc {
     ) -> VisualPosition {
         let position = self.clamp_position(position);
         let leading_text = &self.lines[position.y][..position.x];
+        let tab_widths = self.tab_widths(position.y, gfx);
 
-        let visual_x = gfx.line_padding_x()
-            + gfx.measure_text(leading_text) as f32 * gfx.glyph_width()
-            - camera_position.x;
+        let mut leading_width = gfx.measure_text_with_tab_widths(leading_text, &tab_widths);
+
+        // Right-to-left lines are drawn mirrored, so positions are measured from the line's end.
+        if self.is_line_rtl(position.y) {
+            leading_width = gfx.measure_text_with_tab_widths(&self.lines[position.y], &tab_widths)
+                - leading_width;
+        }
+
+        let visual_x =
+            gfx.line_padding_x() + leading_width as f32 * gfx.glyph_width() - camera_position.x;
 
         let visual_y = position.y as f32 * gfx.line_height() - camera_position.y;
 
@@ -1498,7 +2182,12 @@ impl Doc {
         let visual_x = position.x;
 
         position = self.clamp_position(position);
-        position.x = gfx.find_x_for_visual_x(&self.lines[position.y], visual_x);
+
+        let tab_widths = self.tab_widths(position.y, gfx);
+        let visual_x = self.logical_visual_x(position.y, visual_x, &tab_widths, gfx);
+
+        position.x =
+            gfx.find_x_for_visual_x_with_tab_widths(&self.lines[position.y], visual_x, &tab_widths);
 
         position
     }
@@ -1519,11 +2208,209 @@ impl Doc {
             return None;
         }
 
-        position.x = gfx.find_x_for_visual_x_unclamped(&self.lines[position.y], position.x)?;
+        let tab_widths = self.tab_widths(position.y, gfx);
+        let visual_x = self.logical_visual_x(position.y, position.x, &tab_widths, gfx);
+
+        position.x = gfx.find_x_for_visual_x_unclamped_with_tab_widths(
+            &self.lines[position.y],
+            visual_x,
+            &tab_widths,
+        )?;
 
         Some(position)
     }
 
+    pub fn detect_indent_width(&mut self) {
+        self.detected_indent_width =
+            IndentWidth::detect(self.lines.iter().map(|line| line.as_str()));
+    }
+
+    pub fn detected_indent_width(&self) -> Option<IndentWidth> {
+        self.detected_indent_width
+    }
+
+    pub fn set_elastic_tabstops(&mut self, is_elastic_tabstops: bool) {
+        self.is_elastic_tabstops = is_elastic_tabstops;
+    }
+
+    pub fn set_word_chars(&mut self, word_chars: &str) {
+        if self.word_chars.as_str() != word_chars {
+            self.word_chars.clear();
+            self.word_chars.push_str(word_chars);
+        }
+    }
+
+    pub fn grapheme_category(&self, grapheme: &str) -> GraphemeCategory {
+        GraphemeCategory::with_word_chars(grapheme, &self.word_chars)
+    }
+
+    // With elastic tabstops, each tab is widened so that the tab separated cells in its
+    // column line up across the block of consecutive lines that share that column.
+    // An empty result means tabs keep their default width.
+    pub fn tab_widths(&self, y: usize, gfx: &mut Gfx) -> Vec<usize> {
+        let mut tab_widths = Vec::new();
+
+        if !self.is_elastic_tabstops {
+            return tab_widths;
+        }
+
+        if self.tab_widths_version == Some((self.version, gfx.tab_width())) {
+            if let Some(tab_widths) = self.tab_widths.get(y) {
+                return tab_widths.clone();
+            }
+        }
+
+        let line = &self.lines[y];
+        let mut cell_start = 0;
+
+        for (column, (cell_end, _)) in line.match_indices('\t').enumerate() {
+            let cell_width = gfx.measure_text(&line[cell_start..cell_end]);
+            let mut column_width = cell_width;
+
+            for other_y in (0..y).rev() {
+                let Some(other_cell_width) = self.elastic_cell_width(other_y, column, gfx) else {
+                    break;
+                };
+
+                column_width = column_width.max(other_cell_width);
+            }
+
+            for other_y in y + 1..self.lines.len() {
+                let Some(other_cell_width) = self.elastic_cell_width(other_y, column, gfx) else {
+                    break;
+                };
+
+                column_width = column_width.max(other_cell_width);
+            }
+
+            let column_width = (column_width + Self::ELASTIC_TAB_PADDING).max(gfx.tab_width());
+
+            tab_widths.push(column_width - cell_width);
+            cell_start = cell_end + 1;
+        }
+
+        tab_widths
+    }
+
+    // Finds the tab widths of every line at once, so that drawing doesn't need to
+    // look through the surrounding lines of each line again.
+    pub fn update_tab_widths(&mut self, gfx: &mut Gfx) {
+        let version = Some((self.version, gfx.tab_width()));
+
+        if !self.is_elastic_tabstops || self.tab_widths_version == version {
+            return;
+        }
+
+        self.tab_widths_version = version;
+
+        let cell_widths: Vec<Vec<usize>> = self
+            .lines
+            .iter()
+            .map(|line| {
+                let mut cell_start = 0;
+
+                line.match_indices('\t')
+                    .map(|(cell_end, _)| {
+                        let cell_width = gfx.measure_text(&line[cell_start..cell_end]);
+                        cell_start = cell_end + 1;
+
+                        cell_width
+                    })
+                    .collect()
+            })
+            .collect();
+
+        self.tab_widths.clear();
+        self.tab_widths.resize_with(self.lines.len(), Vec::new);
+
+        let column_count = cell_widths.iter().map(Vec::len).max().unwrap_or(0);
+
+        // Columns are filled in order, so each line's widths can be pushed as they're found.
+        for column in 0..column_count {
+            let mut y = 0;
+
+            while y < cell_widths.len() {
+                if cell_widths[y].len() <= column {
+                    y += 1;
+                    continue;
+                }
+
+                let block_start = y;
+                let mut column_width = 0;
+
+                while y < cell_widths.len() && cell_widths[y].len() > column {
+                    column_width = column_width.max(cell_widths[y][column]);
+                    y += 1;
+                }
+
+                let column_width = (column_width + Self::ELASTIC_TAB_PADDING).max(gfx.tab_width());
+
+                for (tab_widths, cell_widths) in self.tab_widths[block_start..y]
+                    .iter_mut()
+                    .zip(&cell_widths[block_start..y])
+                {
+                    tab_widths.push(column_width - cell_widths[column]);
+                }
+            }
+        }
+    }
+
+    pub fn measure_line_range(
+        &self,
+        y: usize,
+        start_x: usize,
+        end_x: usize,
+        gfx: &mut Gfx,
+    ) -> usize {
+        let line = &self.lines[y];
+        let tab_widths = self.tab_widths(y, gfx);
+        let tab_widths = Self::tab_widths_from(&tab_widths, line, start_x);
+
+        gfx.measure_text_with_tab_widths(&line[start_x..end_x], tab_widths)
+    }
+
+    pub fn tab_widths_from<'a>(tab_widths: &'a [usize], line: &str, x: usize) -> &'a [usize] {
+        let preceding_tabs = line[..x].matches('\t').count();
+
+        &tab_widths[preceding_tabs.min(tab_widths.len())..]
+    }
+
+    fn elastic_cell_width(&self, y: usize, column: usize, gfx: &mut Gfx) -> Option<usize> {
+        let line = &self.lines[y];
+        let mut cell_start = 0;
+
+        for (i, (cell_end, _)) in line.match_indices('\t').enumerate() {
+            if i == column {
+                return Some(gfx.measure_text(&line[cell_start..cell_end]));
+            }
+
+            cell_start = cell_end + 1;
+        }
+
+        None
+    }
+
+    pub fn is_line_rtl(&self, y: usize) -> bool {
+        grapheme::is_rtl_line(&self.lines[y])
+    }
+
+    // Flips a visual x on a right-to-left line into the cell it would have if drawn left-to-right.
+    fn logical_visual_x(
+        &self,
+        y: usize,
+        visual_x: usize,
+        tab_widths: &[usize],
+        gfx: &mut Gfx,
+    ) -> usize {
+        if !self.is_line_rtl(y) {
+            return visual_x;
+        }
+
+        let line_width = gfx.measure_text_with_tab_widths(&self.lines[y], tab_widths);
+
+        line_width.saturating_sub(visual_x + 1)
+    }
+
     fn visual_to_position_with_visual_x(
         &self,
         visual: VisualPosition,
@@ -1536,13 +2423,41 @@ impl Doc {
         )
     }
 
+    // Blank lines are left alone, they're handled by trim_blank_lines.
     pub fn trim_trailing_whitespace(&mut self, ctx: &mut Ctx) {
+        self.trim_trailing_whitespace_on_lines(false, ctx);
+    }
+
+    // Lines with content keep their trailing whitespace, only blank lines are emptied.
+    pub fn trim_blank_lines(&mut self, ctx: &mut Ctx) {
+        self.trim_trailing_whitespace_on_lines(true, ctx);
+    }
+
+    fn trim_trailing_whitespace_on_lines(&mut self, is_blank: bool, ctx: &mut Ctx) {
         for y in 0..self.lines.len() {
-            self.trim_trailing_whitespace_at(y, ctx);
+            if self.is_line_whitespace(y) != is_blank {
+                continue;
+            }
+
+            // Whitespace before a cursor is kept so that trimming doesn't move it mid-edit.
+            let min_x = self
+                .cursors
+                .iter()
+                .filter(|cursor| cursor.position.y == y)
+                .map(|cursor| cursor.position.x)
+                .max()
+                .unwrap_or_default();
+
+            self.trim_trailing_whitespace_after(Position::new(min_x, y), ctx);
         }
     }
 
     pub fn trim_trailing_whitespace_at(&mut self, y: usize, ctx: &mut Ctx) {
+        self.trim_trailing_whitespace_after(Position::new(0, y), ctx);
+    }
+
+    fn trim_trailing_whitespace_after(&mut self, position: Position, ctx: &mut Ctx) {
+        let y = position.y;
         let line = &self.lines[y];
         let mut whitespace_start = 0;
 
@@ -1562,6 +2477,8 @@ impl Doc {
             }
         }
 
+        let whitespace_start = whitespace_start.max(position.x);
+
         if whitespace_start < line.len() {
             let start = Position::new(whitespace_start, y);
             let end = Position::new(line.len(), y);
@@ -1596,10 +2513,16 @@ impl Doc {
 
         self.is_saved = true;
         self.version = 0;
+        self.tab_widths_version = None;
     }
 
     pub fn clear(&mut self, ctx: &mut Ctx) {
+        self.loader = None;
+        self.is_binary = false;
+        self.encoding = TextEncoding::default();
         self.line_ending = LineEnding::default();
+        self.bookmarks.clear();
+        self.saved_lines = None;
 
         self.mark_line_dirty(0);
         self.reset_edit_state();
@@ -1621,6 +2544,11 @@ impl Doc {
     }
 
     pub fn save(&mut self, path: Option<Pooled<PathBuf>>, ctx: &mut Ctx) -> io::Result<()> {
+        if self.loader.is_some() {
+            // Saving now would cut off the part of the file that hasn't been loaded yet.
+            return Err(io::Error::other("The file hasn't finished loading"));
+        }
+
         if self.is_saved
             && path
                 .as_ref()
@@ -1629,8 +2557,47 @@ impl Doc {
             return Ok(());
         }
 
+        if path.is_none() && self.path.is_none() {
+            return Ok(());
+        }
+
+        if self.is_binary {
+            return Err(io::Error::other("Binary files can't be saved"));
+        }
+
         let string = self.to_string();
 
+        // Servers know docs by their URI, which changes when an untitled doc is saved or a doc is moved.
+        let needs_lsp_reopen = matches!(self.path, DocPath::InMemory(_))
+            || path
+                .as_ref()
+                .is_some_and(|path| Some(path) != self.path.some());
+
+        if needs_lsp_reopen {
+            self.lsp_did_close(ctx);
+        }
+
+        let result = self.write_to_drive(path, &string, ctx);
+
+        if needs_lsp_reopen {
+            self.lsp_did_open(&string, ctx);
+        }
+
+        result?;
+
+        self.is_saved = true;
+        self.update_saved_lines();
+        self.lsp_text_document_notification("textDocument/didSave", ctx);
+
+        Ok(())
+    }
+
+    fn write_to_drive(
+        &mut self,
+        path: Option<Pooled<PathBuf>>,
+        string: &str,
+        ctx: &mut Ctx,
+    ) -> io::Result<()> {
         if let Some(path) = path {
             self.set_path_on_drive(path, ctx.current_dir)?;
         }
@@ -1639,7 +2606,35 @@ impl Doc {
             return Ok(());
         };
 
-        File::create(path)?.write_all(string.as_bytes())?;
+        let mut bytes = Vec::new();
+        self.encoding.encode(string, &mut bytes)?;
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.exists()) {
+            create_dir_all(parent).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("Couldn't create {}: {}", parent.display(), err),
+                )
+            })?;
+        }
+
+        if ctx.config.backup_on_save && path.exists() {
+            let backup_dir = ctx.config.backup_dir.as_ref().map(|dir| dir.as_str());
+            Self::write_backup(path, backup_dir)?;
+        }
+
+        let mut file = File::create(path).map_err(|err| {
+            if err.kind() == io::ErrorKind::PermissionDenied {
+                io::Error::new(
+                    err.kind(),
+                    format!("You don't have permission to write to {}", path.display()),
+                )
+            } else {
+                err
+            }
+        })?;
+
+        file.write_all(&bytes)?;
 
         self.path = match take(&mut self.path) {
             DocPath::None => DocPath::None,
@@ -1651,26 +2646,82 @@ impl Doc {
             }
         };
 
-        self.is_saved = true;
-        self.lsp_text_document_notification("textDocument/didSave", ctx);
+        Ok(())
+    }
+
+    // Relative backup directories are relative to the file being backed up.
+    fn write_backup(path: &Path, backup_dir: Option<&str>) -> io::Result<()> {
+        let Some(file_name) = path.file_name() else {
+            return Ok(());
+        };
+
+        let mut backup_name = file_name.to_owned();
+
+        let backup_path = if let Some(backup_dir) = backup_dir {
+            let backup_dir = path
+                .parent()
+                .map(|parent| parent.join(backup_dir))
+                .unwrap_or_else(|| backup_dir.into());
+
+            create_dir_all(&backup_dir)?;
+
+            // Timestamps keep older backups from being overwritten.
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+
+            backup_name.push(format!(".{timestamp}~"));
+            backup_dir.join(backup_name)
+        } else {
+            backup_name.push("~");
+            path.with_file_name(backup_name)
+        };
+
+        copy(path, backup_path)?;
 
         Ok(())
     }
 
     pub fn load(&mut self, ctx: &mut Ctx) -> io::Result<()> {
+        self.load_with_encoding(None, ctx)
+    }
+
+    // The encoding is detected from the file's contents unless one is given.
+    pub fn load_with_encoding(
+        &mut self,
+        encoding: Option<TextEncoding>,
+        ctx: &mut Ctx,
+    ) -> io::Result<()> {
         self.clear(ctx);
 
         let Some(path) = self.path.some() else {
             return Ok(());
         };
 
-        let string = read_to_string(path)?;
+        let bytes = read(path)?;
+        let encoding = encoding.unwrap_or_else(|| TextEncoding::detect(&bytes));
+
+        self.update_read_only(Self::can_write(path));
+
+        let Some(string) = encoding
+            .decode(&bytes)
+            .filter(|string| encoding != TextEncoding::Utf8 || !is_binary(string.as_bytes()))
+        else {
+            self.load_hex_dump(&bytes, ctx);
+
+            return Ok(());
+        };
+
+        self.encoding = encoding;
 
         let (line_ending, len) = self.line_ending_and_len(&string);
 
-        self.insert(Position::ZERO, &string[..len], ctx);
+        self.insert_ignoring_read_only(Position::ZERO, &string[..len], ctx);
         self.reset_edit_state();
+        self.update_saved_lines();
         self.line_ending = line_ending;
+        self.detect_indent_width();
 
         self.path = match take(&mut self.path) {
             DocPath::None => DocPath::None,
@@ -1683,15 +2734,185 @@ impl Doc {
         Ok(())
     }
 
+    // Files that aren't text are shown as a hex dump that can't be edited.
+    fn load_hex_dump(&mut self, bytes: &[u8], ctx: &mut Ctx) {
+        let mut text = STRING_POOL.new_item();
+        write_hex_dump(bytes, &mut text);
+
+        self.insert_ignoring_read_only(Position::ZERO, &text, ctx);
+        self.reset_edit_state();
+        self.is_binary = true;
+
+        self.path = match take(&mut self.path) {
+            DocPath::None => DocPath::None,
+            DocPath::InMemory(path) => DocPath::OnDrive(path),
+            DocPath::OnDrive(path) => DocPath::OnDrive(path),
+        };
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.is_binary
+    }
+
+    // Binary docs are always read-only since they're shown as a hex dump,
+    // and docs that are still loading can't be edited until all of their text is present.
+    pub fn is_read_only(&self) -> bool {
+        self.is_read_only || self.is_binary || self.loader.is_some()
+    }
+
+    pub fn toggle_read_only(&mut self) {
+        self.is_read_only = !self.is_read_only;
+        self.is_read_only_toggled = !self.is_read_only_toggled;
+    }
+
+    // Used to give feedback when an edit is attempted in a read-only doc.
+    pub fn was_edit_blocked(&self, time: f64) -> bool {
+        self.blocked_edit_time
+            .is_some_and(|blocked_edit_time| time - blocked_edit_time < Self::BLOCKED_EDIT_DURATION)
+    }
+
+    fn update_read_only(&mut self, can_write: bool) {
+        self.is_read_only = if self.is_read_only_toggled {
+            can_write
+        } else {
+            !can_write
+        };
+    }
+
+    // Files that can be read but not written are opened as read-only,
+    // rather than letting edits be made that can't be saved.
+    fn can_write(path: &Path) -> bool {
+        let is_read_only = metadata(path).is_ok_and(|metadata| metadata.permissions().readonly());
+
+        // Opening without truncating checks the current user's permissions without changing the file.
+        !is_read_only
+            && !matches!(
+                OpenOptions::new().write(true).open(path),
+                Err(err) if err.kind() == io::ErrorKind::PermissionDenied
+            )
+    }
+
+    // Loading still needs to replace the text of read-only docs.
+    pub fn insert_ignoring_read_only(&mut self, start: Position, text: &str, ctx: &mut Ctx) {
+        let is_read_only = take(&mut self.is_read_only);
+        self.insert(start, text, ctx);
+        self.is_read_only = is_read_only;
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    // Large files are loaded over multiple updates to keep the editor responsive.
+    pub fn load_in_background(&mut self, ctx: &mut Ctx) -> io::Result<()> {
+        let Some(path) = self.path.some() else {
+            return Ok(());
+        };
+
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+
+        self.update_read_only(Self::can_write(path));
+
+        let mut bom = [0; 3];
+        let bom_len = file.read(&mut bom)?;
+        file.rewind()?;
+
+        // Only plain UTF-8 can be decoded in chunks.
+        if len < DocLoader::MIN_LEN || TextEncoding::detect(&bom[..bom_len]) != TextEncoding::Utf8 {
+            return self.load(ctx);
+        }
+
+        self.clear(ctx);
+
+        self.path = match take(&mut self.path) {
+            DocPath::None => DocPath::None,
+            DocPath::InMemory(path) => DocPath::OnDrive(path),
+            DocPath::OnDrive(path) => DocPath::OnDrive(path),
+        };
+
+        self.loader = Some(DocLoader::new(file, len));
+
+        Ok(())
+    }
+
+    pub fn update_load(&mut self, ctx: &mut Ctx) -> io::Result<()> {
+        // The loader is taken while appending so that its text isn't blocked as an edit.
+        let Some(mut loader) = self.loader.take() else {
+            return Ok(());
+        };
+
+        let mut text = STRING_POOL.new_item();
+
+        let is_done = match loader.next_text(&mut text) {
+            Ok(is_done) => is_done,
+            // Let a regular load show the file as a hex dump instead.
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => return self.load(ctx),
+            Err(err) => {
+                self.clear(ctx);
+                return Err(err);
+            }
+        };
+
+        let needs_line_ending = !loader.has_line_ending && text.contains(['\r', '\n']);
+
+        if needs_line_ending {
+            loader.has_line_ending = true;
+            (self.line_ending, _) = self.line_ending_and_len(&text);
+        }
+
+        let is_saved = self.is_saved;
+
+        // Appending shouldn't move cursors that are already at the end of the doc.
+        self.do_skip_shifting = true;
+        self.insert_ignoring_read_only(self.end(), &text, ctx);
+        self.do_skip_shifting = false;
+
+        self.is_saved = is_saved;
+
+        if is_done {
+            self.undo_history.clear();
+            self.redo_history.clear();
+            self.is_saved = true;
+            self.version = 0;
+
+            self.update_saved_lines();
+            self.detect_indent_width();
+
+            let text = self.to_string();
+            self.lsp_did_open(&text, ctx);
+        } else {
+            self.loader = Some(loader);
+        }
+
+        Ok(())
+    }
+
+    pub fn load_progress(&self) -> Option<f32> {
+        self.loader.as_ref().map(DocLoader::progress)
+    }
+
     pub fn reload(&mut self, ctx: &mut Ctx) -> io::Result<()> {
+        if self.loader.is_some() || self.is_binary {
+            return self.load(ctx);
+        }
+
         let Some(path) = self.path.on_drive() else {
             return Ok(());
         };
 
-        let string = read_to_string(path)?;
+        let string = self
+            .encoding
+            .decode(&read(path)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, self.encoding.name()))?;
+
+        let can_write = Self::can_write(path);
 
         self.start_skipping_shifting(ctx.time);
 
+        // Reloading still needs to replace the text of read-only docs.
+        self.is_read_only = false;
+
         self.delete(Position::ZERO, self.end(), ctx);
 
         let (line_ending, len) = self.line_ending_and_len(&string);
@@ -1699,9 +2920,13 @@ impl Doc {
         self.line_ending = line_ending;
         self.insert(Position::ZERO, &string[..len], ctx);
 
+        self.update_read_only(can_write);
+        self.detect_indent_width();
+
         self.stop_skipping_shifting(ctx);
 
         self.is_saved = true;
+        self.update_saved_lines();
 
         Ok(())
     }
@@ -1755,6 +2980,18 @@ impl Doc {
         (LineEnding::default(), string.len())
     }
 
+    // Follows a file that was moved on the drive, unsaved changes are kept rather than written.
+    pub fn set_moved_path(&mut self, path: Pooled<PathBuf>, ctx: &mut Ctx) -> io::Result<()> {
+        self.lsp_did_close(ctx);
+
+        let result = self.set_path_on_drive(path, ctx.current_dir);
+
+        let string = self.to_string();
+        self.lsp_did_open(&string, ctx);
+
+        result
+    }
+
     fn set_path_on_drive(&mut self, path: Pooled<PathBuf>, current_dir: &Path) -> io::Result<()> {
         self.path = DocPath::OnDrive(if path.is_normal() {
             path
@@ -1815,21 +3052,36 @@ impl Doc {
 
     pub fn copy_at_cursors(&self, text: &mut String) -> bool {
         let mut was_copy_implicit = true;
+        let mut has_copied = false;
 
         for index in self.cursor_indices() {
             let cursor = self.cursor(index);
+            let selection = cursor.get_selection();
 
-            if let Some(selection) = cursor.get_selection() {
+            // Multiple cursors on one line only copy that line once.
+            if selection.is_none()
+                && self.cursors[..self.unwrap_cursor_index(index)]
+                    .iter()
+                    .any(|other| {
+                        other.get_selection().is_none() && other.position.y == cursor.position.y
+                    })
+            {
+                continue;
+            }
+
+            if has_copied {
+                text.push('\n');
+            }
+
+            has_copied = true;
+
+            if let Some(selection) = selection {
                 was_copy_implicit = false;
 
                 self.collect_string(selection.start, selection.end, text);
             } else {
                 self.copy_line_at_position(cursor.position, text);
             }
-
-            if self.unwrap_cursor_index(index) != self.cursors_len() - 1 {
-                text.push('\n');
-            }
         }
 
         was_copy_implicit
@@ -1948,10 +3200,30 @@ impl Doc {
             .highlight_line_from_terminal_colors(colors, y);
     }
 
+    // Used by docs without a language to color whole lines.
+    pub fn highlight_line_with_kind(&mut self, foreground: HighlightKind, y: usize) {
+        let len = self.lines[y].len();
+        self.syntax_highlighter
+            .highlight_line_with_kind(foreground, len, y);
+    }
+
     pub fn highlighted_lines(&self) -> &[HighlightedLine] {
         self.syntax_highlighter.highlighted_lines()
     }
 
+    // Lines that haven't been highlighted since they last changed are treated as code.
+    pub fn is_position_in_string_or_comment(&self, position: Position) -> bool {
+        if position.y >= self.unhighlighted_line_y {
+            return false;
+        }
+
+        let Some(highlighted_line) = self.highlighted_lines().get(position.y) else {
+            return false;
+        };
+
+        highlighted_line.is_string_or_comment_at(position.x, self.lines[position.y].len())
+    }
+
     pub fn combine_overlapping_cursors(&mut self) {
         for index in self.cursor_indices().rev() {
             let cursor = self.cursor(index);
@@ -2081,7 +3353,7 @@ impl Doc {
         }
     }
 
-    pub fn get_completion_prefix<'a>(&'a self, gfx: &mut Gfx) -> Option<&'a str> {
+    pub fn get_completion_prefix<'a>(&'a self, ctx: &mut Ctx) -> Option<&'a str> {
         let prefix_end = self.cursor(CursorIndex::Main).position;
 
         if prefix_end.x == 0 {
@@ -2091,7 +3363,7 @@ impl Doc {
         let mut prefix_start = prefix_end;
 
         while prefix_start.x > 0 {
-            let next_start = self.move_position(prefix_start, -1, 0, gfx);
+            let next_start = self.move_position(prefix_start, -1, 0, ctx.gfx);
 
             let grapheme = self.grapheme(next_start);
 
@@ -2100,9 +3372,9 @@ impl Doc {
                 continue;
             }
 
-            // These characters aren't included in the completion prefix
+            // Trigger characters aren't included in the completion prefix
             // but they should still trigger a completion.
-            if !matches!(grapheme, "." | ":") && prefix_start == prefix_end {
+            if prefix_start == prefix_end && self.lsp_completion_trigger(ctx).is_none() {
                 return None;
             }
 
@@ -0,0 +1,63 @@
+use crate::{
+    ctx::Ctx,
+    geometry::position::Position,
+    text::{
+        cursor_index::CursorIndex,
+        emmet::{self, EmmetSyntax},
+    },
+};
+
+use super::Doc;
+
+impl Doc {
+    // Expands the abbreviation before each cursor, placing cursors at the expansion's tabstops.
+    pub fn expand_abbreviation_at_cursors(&mut self, syntax: EmmetSyntax, ctx: &mut Ctx) {
+        let indent_width = ctx.config.indent_width_for_doc(self);
+        let indent = indent_width.grapheme().repeat(indent_width.len());
+
+        // Cursors added for tabstops go at the end of the list, so existing indices stay valid.
+        for i in 0..self.cursors_len() {
+            let index = CursorIndex::Some(i);
+            let cursor = self.cursor(index);
+
+            if cursor.get_selection().is_some() {
+                continue;
+            }
+
+            let position = cursor.position;
+            let line = &self.lines[position.y][..position.x];
+            let start_x = emmet::abbreviation_start(line, syntax);
+
+            let base_indent_len = line.len() - line.trim_start().len();
+            let base_indent = &line[..base_indent_len.min(start_x)];
+
+            let Some(expansion) = emmet::expand(&line[start_x..], syntax, base_indent, &indent)
+            else {
+                continue;
+            };
+
+            let start = Position::new(start_x, position.y);
+
+            self.delete(start, position, ctx);
+            self.insert(start, &expansion.text, ctx);
+
+            for (i, offset) in expansion.tabstops.iter().enumerate() {
+                let text = &expansion.text[..*offset];
+                let y = start.y + text.matches('\n').count();
+
+                let x = match text.rfind('\n') {
+                    Some(newline_index) => offset - newline_index - 1,
+                    None => start.x + offset,
+                };
+
+                let tabstop = Position::new(x, y);
+
+                if i == 0 {
+                    self.jump_cursor(index, tabstop, false, ctx.gfx);
+                } else {
+                    self.add_cursor_at(tabstop, ctx.gfx);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    str,
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
+};
+
+// Reads a file on a background thread so that large files can be shown while they load.
+pub(super) struct DocLoader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    bytes: Vec<u8>,
+    loaded_len: usize,
+    len: usize,
+    pub has_line_ending: bool,
+}
+
+impl DocLoader {
+    pub const MIN_LEN: usize = 1024 * 1024;
+    pub const CHUNK_LEN: usize = 64 * 1024;
+    const MAX_LEN_PER_UPDATE: usize = 512 * 1024;
+
+    pub fn new(mut file: File, len: usize) -> Self {
+        let (sender, receiver) = channel();
+
+        thread::spawn(move || loop {
+            let mut chunk = vec![0; Self::CHUNK_LEN];
+
+            match file.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read_len) => {
+                    chunk.truncate(read_len);
+
+                    // The receiver is gone if the load was canceled.
+                    if sender.send(Ok(chunk)).is_err() {
+                        break;
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    let _ = sender.send(Err(err));
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver,
+            bytes: Vec::new(),
+            loaded_len: 0,
+            len,
+            has_line_ending: false,
+        }
+    }
+
+    // Adds the text that's ready to be inserted, and returns whether the whole file has been read.
+    pub fn next_text(&mut self, text: &mut String) -> io::Result<bool> {
+        let mut is_done = false;
+        let mut received_len = 0;
+
+        while received_len < Self::MAX_LEN_PER_UPDATE {
+            match self.receiver.try_recv() {
+                Ok(chunk) => {
+                    let chunk = chunk?;
+
+                    received_len += chunk.len();
+                    self.bytes.extend_from_slice(&chunk);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    is_done = true;
+                    break;
+                }
+            }
+        }
+
+        self.loaded_len += received_len;
+
+        let mut decode_len = self.bytes.len();
+
+        // Wait for the next chunk to find out if this is the start of a CRLF.
+        if !is_done && self.bytes.last() == Some(&b'\r') {
+            decode_len -= 1;
+        }
+
+        let decoded = match str::from_utf8(&self.bytes[..decode_len]) {
+            Ok(decoded) => decoded,
+            // The last character was split between chunks.
+            Err(err) if err.error_len().is_none() && !is_done => {
+                str::from_utf8(&self.bytes[..err.valid_up_to()]).unwrap_or_default()
+            }
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+
+        text.push_str(decoded);
+
+        let decoded_len = decoded.len();
+        self.bytes.drain(..decoded_len);
+
+        Ok(is_done)
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.loaded_len as f32 / self.len.max(1) as f32
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 use std::collections::HashMap;
 
 use crate::{
@@ -5,51 +8,117 @@ use crate::{
     geometry::position::Position,
     lsp::{
         language_server::LanguageServer,
-        types::{DecodedRange, DecodedTextEdit},
+        position_encoding::PositionEncoding,
+        types::{DecodedTextEdit, EncodedTextEdit, TypeHierarchyDirection},
         LspExpectedResponse, LspSentRequest,
     },
+    pool::{Pooled, STRING_POOL},
 };
 
 use crate::text::cursor_index::CursorIndex;
 
-use super::{Doc, DocFlag};
+use super::{Doc, DocFlag, DocPath};
 
 #[derive(Debug, Default)]
 pub(super) struct DocLspState {
-    expected_responses: HashMap<&'static str, LspExpectedResponse>,
+    // Expected responses for each method, keyed by the index of the server they were sent to.
+    expected_responses: HashMap<&'static str, HashMap<usize, LspExpectedResponse>>,
     is_open: bool,
     debounced_requests: HashMap<&'static str, Option<Position>>,
+    diagnostic_time: Option<f64>,
 }
 
 impl Doc {
+    const LSP_DIAGNOSTIC_DELAY: f64 = 0.3;
+
     pub fn get_language_server_mut<'a>(&self, ctx: &'a mut Ctx) -> Option<&'a mut LanguageServer> {
+        self.get_main_language_server_mut(ctx)
+            .map(|(_, language_server)| language_server)
+    }
+
+    // Requests with results that can't be merged between servers are only sent to the main server.
+    fn get_main_language_server_mut<'a>(
+        &self,
+        ctx: &'a mut Ctx,
+    ) -> Option<(usize, &'a mut LanguageServer)> {
+        self.get_language_servers_mut(ctx)
+            .min_by_key(|(server_index, _)| *server_index)
+    }
+
+    pub fn get_language_server_at_mut<'a>(
+        &self,
+        server_index: usize,
+        ctx: &'a mut Ctx,
+    ) -> Option<&'a mut LanguageServer> {
         if !self.flags.contains(DocFlag::AllowLanguageServer) {
             return None;
         }
 
         ctx.lsp
-            .get_language_server_mut(self, ctx.config, ctx.current_dir)
+            .get_language_server_at_mut(server_index, self, ctx.config, ctx.current_dir)
+    }
+
+    pub fn get_language_servers_mut<'a>(
+        &self,
+        ctx: &'a mut Ctx,
+    ) -> impl Iterator<Item = (usize, &'a mut LanguageServer)> {
+        let allows_language_server = self.flags.contains(DocFlag::AllowLanguageServer);
+
+        ctx.lsp
+            .get_language_servers_mut(self, ctx.config, ctx.current_dir)
+            .filter(move |_| allows_language_server)
     }
 
     fn lsp_add_expected_response(
         &mut self,
+        server_index: usize,
         sent_request: LspSentRequest,
         position: Option<Position>,
     ) {
-        self.lsp_state.expected_responses.insert(
-            sent_request.method,
-            LspExpectedResponse {
-                id: sent_request.id,
-                position,
-                version: self.version,
-            },
-        );
+        self.lsp_state
+            .expected_responses
+            .entry(sent_request.method)
+            .or_default()
+            .insert(
+                server_index,
+                LspExpectedResponse {
+                    id: sent_request.id,
+                    position,
+                    version: self.version,
+                },
+            );
+    }
+
+    // Sends a request to every server attached to the doc so that their results can be merged.
+    fn lsp_send_request_to_all(
+        &mut self,
+        position: Option<Position>,
+        ctx: &mut Ctx,
+        send_request: impl Fn(&mut LanguageServer, &Self) -> Option<LspSentRequest>,
+    ) -> Option<()> {
+        let sent_requests: Vec<(usize, LspSentRequest)> = self
+            .get_language_servers_mut(ctx)
+            .filter_map(|(server_index, language_server)| {
+                Some((server_index, send_request(language_server, self)?))
+            })
+            .collect();
+
+        if sent_requests.is_empty() {
+            return None;
+        }
+
+        for (server_index, sent_request) in sent_requests {
+            self.lsp_add_expected_response(server_index, sent_request, position);
+        }
+
+        Some(())
     }
 
     pub fn lsp_is_response_expected(
         &mut self,
         method: &str,
         id: Option<usize>,
+        server_index: usize,
         ctx: &mut Ctx,
     ) -> bool {
         let Some(id) = id else {
@@ -57,18 +126,29 @@ impl Doc {
             return true;
         };
 
-        let Some(expected_response) = self.lsp_state.expected_responses.get(method).copied() else {
+        let Some(expected_responses) = self.lsp_state.expected_responses.get_mut(method) else {
             // Expected responses don't need to be tracked for this method.
             return true;
         };
 
+        let Some(expected_response) = expected_responses.get(&server_index).copied() else {
+            return false;
+        };
+
         if expected_response.id != id {
             return false;
         }
 
-        self.lsp_state.expected_responses.remove(method);
+        expected_responses.remove(&server_index);
 
-        if self.lsp_debounced_request(method) {
+        // Wait for every server to respond before sending a debounced request.
+        let is_last_response = expected_responses.is_empty();
+
+        if is_last_response {
+            self.lsp_state.expected_responses.remove(method);
+        }
+
+        if is_last_response && self.lsp_debounced_request(method) {
             match method {
                 "textDocument/completion" => self.lsp_completion(ctx),
                 "textDocument/diagnostic" => self.lsp_diagnostic(ctx),
@@ -98,14 +178,16 @@ impl Doc {
         }
 
         let language = ctx.config.get_language_for_doc(self)?;
-        let language_server = self.get_language_server_mut(ctx)?;
         let language_id = language.lsp.language_id.as_ref()?;
         let path = self.path.some()?;
+        let is_untitled = matches!(self.path, DocPath::InMemory(_));
 
-        language_server.did_open(path, language_id, self.version, text);
-        self.lsp_diagnostic(ctx);
+        for (_, language_server) in self.get_language_servers_mut(ctx) {
+            language_server.did_open(path, is_untitled, language_id, self.version, text);
+        }
 
         self.lsp_state.is_open = true;
+        self.lsp_schedule_diagnostic(ctx.time);
 
         Some(())
     }
@@ -118,6 +200,7 @@ impl Doc {
         self.lsp_text_document_notification("textDocument/didClose", ctx)?;
 
         self.lsp_state.is_open = false;
+        self.lsp_state.diagnostic_time = None;
 
         Some(())
     }
@@ -133,26 +216,49 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
         let path = self.path.some()?;
 
-        language_server.did_change(path, self.version, start, end, text, self);
+        for (_, language_server) in self.get_language_servers_mut(ctx) {
+            language_server.did_change(path, self.version, start, end, text, self);
+        }
 
         Some(())
     }
 
+    pub fn lsp_update(&mut self, ctx: &mut Ctx) {
+        if self
+            .lsp_state
+            .diagnostic_time
+            .is_some_and(|diagnostic_time| ctx.time >= diagnostic_time)
+        {
+            self.lsp_state.diagnostic_time = None;
+            self.lsp_diagnostic(ctx);
+        }
+    }
+
+    pub fn lsp_is_diagnostic_pending(&self) -> bool {
+        self.lsp_state.diagnostic_time.is_some()
+    }
+
+    // Edits usually come in bursts while typing, so wait until they stop before pulling diagnostics.
+    pub(super) fn lsp_schedule_diagnostic(&mut self, time: f64) {
+        if !self.lsp_state.is_open {
+            return;
+        }
+
+        self.lsp_state.diagnostic_time = Some(time + Self::LSP_DIAGNOSTIC_DELAY);
+    }
+
     pub fn lsp_diagnostic(&mut self, ctx: &mut Ctx) -> Option<()> {
         if self.lsp_debounce_request("textDocument/diagnostic", None) {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
-        let path = self.path.some()?;
+        let path = self.path.some()?.clone();
 
-        let sent_request = language_server.diagnostic(path)?;
-        self.lsp_add_expected_response(sent_request, None);
-
-        Some(())
+        self.lsp_send_request_to_all(None, ctx, |language_server, _| {
+            language_server.diagnostic(&path)
+        })
     }
 
     pub fn lsp_completion(&mut self, ctx: &mut Ctx) -> Option<()> {
@@ -162,15 +268,52 @@ impl Doc {
             return None;
         }
 
-        self.get_completion_prefix(ctx.gfx)?;
+        let prefix = self.get_completion_prefix(ctx)?;
 
-        let language_server = self.get_language_server_mut(ctx)?;
-        let path = self.path.some()?;
+        let trigger = if prefix.is_empty() {
+            self.lsp_completion_trigger(ctx)
+        } else {
+            None
+        };
 
-        let sent_request = language_server.completion(path, position, self);
-        self.lsp_add_expected_response(sent_request, Some(position));
+        let path = self.path.some()?.clone();
 
-        Some(())
+        self.lsp_send_request_to_all(Some(position), ctx, |language_server, doc| {
+            Some(language_server.completion(
+                &path,
+                position,
+                trigger.as_ref().map(|trigger| trigger.as_str()),
+                doc,
+            ))
+        })
+    }
+
+    // Returns the trigger that the text before the cursor ends with, if any.
+    pub(super) fn lsp_completion_trigger(&self, ctx: &mut Ctx) -> Option<Pooled<String>> {
+        // Without a language server these still trigger completions from the doc's words.
+        const FALLBACK_TRIGGERS: [&str; 2] = [".", ":"];
+
+        let position = self.cursor(CursorIndex::Main).position;
+        let text = &self.get_line(position.y)?[..position.x];
+
+        let mut has_language_server = false;
+
+        for (_, language_server) in self.get_language_servers_mut(ctx) {
+            has_language_server = true;
+
+            if let Some(trigger) = language_server.completion_trigger_at_end(text) {
+                return Some(trigger.into());
+            }
+        }
+
+        if has_language_server {
+            return None;
+        }
+
+        FALLBACK_TRIGGERS
+            .into_iter()
+            .find(|trigger| text.ends_with(trigger))
+            .map(Into::into)
     }
 
     pub fn lsp_code_action(&mut self, ctx: &mut Ctx) -> Option<()> {
@@ -178,8 +321,7 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
-        let path = self.path.some()?;
+        let path = self.path.some()?.clone();
 
         let cursor = self.cursor(CursorIndex::Main);
 
@@ -189,10 +331,9 @@ impl Doc {
             (cursor.position, cursor.position)
         };
 
-        let sent_request = language_server.code_action(path, start, end, self);
-        self.lsp_add_expected_response(sent_request, Some(cursor.position));
-
-        Some(())
+        self.lsp_send_request_to_all(Some(cursor.position), ctx, |language_server, doc| {
+            Some(language_server.code_action(&path, start, end, doc))
+        })
     }
 
     pub fn lsp_prepare_rename(&mut self, ctx: &mut Ctx) -> Option<()> {
@@ -200,12 +341,12 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
+        let (server_index, language_server) = self.get_main_language_server_mut(ctx)?;
         let path = self.path.some()?;
         let position = self.cursor(CursorIndex::Main).position;
 
         let sent_request = language_server.prepare_rename(path, position, self);
-        self.lsp_add_expected_response(sent_request, Some(position));
+        self.lsp_add_expected_response(server_index, sent_request, Some(position));
 
         Some(())
     }
@@ -229,12 +370,12 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
+        let (server_index, language_server) = self.get_main_language_server_mut(ctx)?;
         let path = self.path.some()?;
         let position = self.cursor(CursorIndex::Main).position;
 
         let sent_request = language_server.references(path, position, self);
-        self.lsp_add_expected_response(sent_request, Some(position));
+        self.lsp_add_expected_response(server_index, sent_request, Some(position));
 
         Some(())
     }
@@ -244,11 +385,30 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
+        let (server_index, language_server) = self.get_main_language_server_mut(ctx)?;
         let path = self.path.some()?;
 
         let sent_request = language_server.definition(path, position, self);
-        self.lsp_add_expected_response(sent_request, None);
+        self.lsp_add_expected_response(server_index, sent_request, None);
+
+        Some(())
+    }
+
+    pub fn lsp_prepare_type_hierarchy(
+        &mut self,
+        direction: TypeHierarchyDirection,
+        ctx: &mut Ctx,
+    ) -> Option<()> {
+        if !self.lsp_state.is_open {
+            return None;
+        }
+
+        let (server_index, language_server) = self.get_main_language_server_mut(ctx)?;
+        let path = self.path.some()?;
+        let position = self.cursor(CursorIndex::Main).position;
+
+        let sent_request = language_server.prepare_type_hierarchy(direction, path, position, self);
+        self.lsp_add_expected_response(server_index, sent_request, Some(position));
 
         Some(())
     }
@@ -263,13 +423,13 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
+        let (server_index, language_server) = self.get_main_language_server_mut(ctx)?;
         let path = self.path.some()?;
         let position = self.cursor(CursorIndex::Main).position;
 
         let sent_request =
             language_server.signature_help(path, position, trigger_char, is_retrigger, self);
-        self.lsp_add_expected_response(sent_request, Some(position));
+        self.lsp_add_expected_response(server_index, sent_request, Some(position));
 
         Some(())
     }
@@ -279,13 +439,11 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
-        let path = self.path.some()?;
-
-        let sent_request = language_server.hover(path, position, self);
-        self.lsp_add_expected_response(sent_request, None);
+        let path = self.path.some()?.clone();
 
-        Some(())
+        self.lsp_send_request_to_all(None, ctx, |language_server, doc| {
+            Some(language_server.hover(&path, position, doc))
+        })
     }
 
     pub fn lsp_formatting(&self, ctx: &mut Ctx) -> Option<()> {
@@ -312,15 +470,35 @@ impl Doc {
             return None;
         }
 
-        let language_server = self.get_language_server_mut(ctx)?;
         let path = self.path.some()?;
 
-        language_server.text_document_notification(path, method);
+        for (_, language_server) in self.get_language_servers_mut(ctx) {
+            language_server.text_document_notification(path, method);
+        }
 
         Some(())
     }
 
-    pub fn lsp_apply_edit_list(&mut self, edits: &mut [DecodedTextEdit], ctx: &mut Ctx) {
+    pub fn apply_text_edits(
+        &mut self,
+        edits: Vec<EncodedTextEdit>,
+        encoding: PositionEncoding,
+        ctx: &mut Ctx,
+    ) {
+        let edits = edits
+            .into_iter()
+            .map(|edit| edit.decode(encoding, self))
+            .collect();
+
+        self.apply_decoded_text_edits(edits, ctx);
+    }
+
+    // Edits are applied from the end of the doc to the start so that the remaining ranges stay valid.
+    // Edits that start at the same position are combined in their original order, and edits that
+    // overlap one that has already been applied are skipped.
+    pub fn apply_decoded_text_edits(&mut self, mut edits: Vec<DecodedTextEdit>, ctx: &mut Ctx) {
+        edits.sort_by_key(|edit| edit.range.start);
+
         let needs_skip_shifting = edits
             .iter()
             .any(|edit| edit.range.start == Position::ZERO && edit.range.end == self.end());
@@ -329,21 +507,39 @@ impl Doc {
             self.start_skipping_shifting(ctx.time);
         }
 
-        for i in 0..edits.len() {
-            let current_edit = &edits[i];
+        let mut applied_start = None;
+        let mut new_text = STRING_POOL.new_item();
+
+        for edits in edits.chunk_by(|a, b| a.range.start == b.range.start).rev() {
+            let start = edits[0].range.start;
+            let mut end = start;
+
+            new_text.clear();
+
+            for DecodedTextEdit {
+                range,
+                new_text: edit_text,
+            } in edits
+            {
+                // Only one edit at this position can replace text, the others must be inserts.
+                if applied_start.is_some_and(|applied_start| range.end > applied_start)
+                    || (end != start && range.end != start)
+                {
+                    continue;
+                }
+
+                end = end.max(range.end);
+                new_text.push_str(edit_text);
+            }
 
-            let DecodedRange { start, end } = current_edit.range;
+            if start == end && new_text.is_empty() {
+                continue;
+            }
 
             self.delete(start, end, ctx);
-            let insert_end = self.insert(start, &current_edit.new_text, ctx);
-
-            for DecodedTextEdit { range, .. } in edits.iter_mut().skip(i + 1) {
-                range.start = self.shift_position_by_delete(start, end, range.start);
-                range.end = self.shift_position_by_delete(start, end, range.end);
+            self.insert(start, &new_text, ctx);
 
-                range.start = self.shift_position_by_insert(start, insert_end, range.start);
-                range.end = self.shift_position_by_insert(start, insert_end, range.end);
-            }
+            applied_start = Some(start);
         }
 
         if needs_skip_shifting {
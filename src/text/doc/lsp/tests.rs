@@ -0,0 +1,59 @@
+use crate::{
+    geometry::position::Position,
+    lsp::types::{DecodedRange, DecodedTextEdit},
+    tests::test_with_doc,
+};
+
+fn text_edit(start: Position, end: Position, new_text: &str) -> DecodedTextEdit {
+    DecodedTextEdit {
+        range: DecodedRange { start, end },
+        new_text: new_text.into(),
+    }
+}
+
+test_with_doc!(
+    apply_replacement_then_insert_at_same_start,
+    "let a = b;",
+    |ctx, doc| {
+        let edits = vec![
+            text_edit(Position::new(4, 0), Position::new(5, 0), "x"),
+            text_edit(Position::new(4, 0), Position::new(4, 0), ": i32"),
+        ];
+
+        doc.apply_decoded_text_edits(edits, ctx);
+
+        assert_eq!(doc.to_string(), "let x: i32 = b;");
+    }
+);
+
+test_with_doc!(
+    apply_insert_then_replacement_at_same_start,
+    "let a = b;",
+    |ctx, doc| {
+        let edits = vec![
+            text_edit(Position::new(4, 0), Position::new(4, 0), "mut "),
+            text_edit(Position::new(4, 0), Position::new(5, 0), "value"),
+            text_edit(Position::new(8, 0), Position::new(9, 0), "c"),
+        ];
+
+        doc.apply_decoded_text_edits(edits, ctx);
+
+        assert_eq!(doc.to_string(), "let mut value = c;");
+    }
+);
+
+test_with_doc!(
+    apply_only_first_replacement_at_same_start,
+    "abcdef",
+    |ctx, doc| {
+        let edits = vec![
+            text_edit(Position::new(0, 0), Position::new(2, 0), "x"),
+            text_edit(Position::new(0, 0), Position::new(4, 0), "y"),
+            text_edit(Position::new(0, 0), Position::new(0, 0), "z"),
+        ];
+
+        doc.apply_decoded_text_edits(edits, ctx);
+
+        assert_eq!(doc.to_string(), "xzcdef");
+    }
+);
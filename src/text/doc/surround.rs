@@ -0,0 +1,183 @@
+use crate::{
+    ctx::Ctx,
+    geometry::position::Position,
+    platform::gfx::Gfx,
+    text::{cursor_index::CursorIndex, grapheme_category::GraphemeCategory},
+};
+
+use super::Doc;
+
+impl Doc {
+    // Either side of a pair can be used to refer to it, other graphemes surround text with themselves.
+    pub fn surrounding_pair(grapheme: &str) -> (&str, &str) {
+        match grapheme {
+            "(" | ")" => ("(", ")"),
+            "[" | "]" => ("[", "]"),
+            "{" | "}" => ("{", "}"),
+            "<" | ">" => ("<", ">"),
+            _ => (grapheme, grapheme),
+        }
+    }
+
+    // Surrounds each cursor's selection, or the word under it if nothing is selected.
+    pub fn surround_at_cursors(&mut self, grapheme: &str, ctx: &mut Ctx) {
+        let (open, close) = Self::surrounding_pair(grapheme);
+
+        for index in self.cursor_indices() {
+            let cursor = self.cursor(index);
+
+            let selection = cursor.get_selection().or_else(|| {
+                let word = self.select_current_word_at_position(cursor.position, ctx.gfx);
+                let is_word = self.grapheme_category(self.grapheme(word.start))
+                    == GraphemeCategory::Identifier;
+
+                (word.start != word.end && is_word).then_some(word)
+            });
+
+            let Some(selection) = selection else {
+                self.insert_at_cursor(index, open, ctx);
+                self.insert_at_cursor(index, close, ctx);
+                self.move_cursor(index, -1, 0, false, ctx.gfx);
+
+                continue;
+            };
+
+            let cursor_position = cursor.position;
+            let had_selection = cursor.get_selection().is_some();
+
+            self.insert(selection.end, close, ctx);
+
+            if had_selection {
+                // Reset the selection to prevent it being expanded by the insert.
+                self.set_cursor_selection(index, Some(selection));
+                self.insert(selection.start, open, ctx);
+            } else {
+                self.insert(selection.start, open, ctx);
+
+                // Words are on a single line, so the cursor just moves past the opening grapheme.
+                let cursor_position =
+                    Position::new(cursor_position.x + open.len(), cursor_position.y);
+                self.jump_cursor(index, cursor_position, false, ctx.gfx);
+            }
+        }
+    }
+
+    pub fn delete_surrounding_at_cursors(&mut self, grapheme: &str, ctx: &mut Ctx) {
+        let (open, close) = Self::surrounding_pair(grapheme);
+
+        for index in self.cursor_indices() {
+            let Some((open_start, close_start)) =
+                self.find_surrounding_pair_at_cursor(index, open, close, ctx.gfx)
+            else {
+                continue;
+            };
+
+            let close_end = self.move_position(close_start, 1, 0, ctx.gfx);
+            self.delete(close_start, close_end, ctx);
+
+            let open_end = self.move_position(open_start, 1, 0, ctx.gfx);
+            self.delete(open_start, open_end, ctx);
+        }
+    }
+
+    pub fn change_surrounding_at_cursors(&mut self, from: &str, to: &str, ctx: &mut Ctx) {
+        let (open, close) = Self::surrounding_pair(from);
+        let (new_open, new_close) = Self::surrounding_pair(to);
+
+        for index in self.cursor_indices() {
+            let Some((open_start, close_start)) =
+                self.find_surrounding_pair_at_cursor(index, open, close, ctx.gfx)
+            else {
+                continue;
+            };
+
+            let close_end = self.move_position(close_start, 1, 0, ctx.gfx);
+            self.delete(close_start, close_end, ctx);
+            self.insert(close_start, new_close, ctx);
+
+            let open_end = self.move_position(open_start, 1, 0, ctx.gfx);
+            self.delete(open_start, open_end, ctx);
+            self.insert(open_start, new_open, ctx);
+        }
+    }
+
+    // Returns the start of the opening and closing graphemes of the innermost pair around the cursor.
+    fn find_surrounding_pair_at_cursor(
+        &self,
+        index: CursorIndex,
+        open: &str,
+        close: &str,
+        gfx: &mut Gfx,
+    ) -> Option<(Position, Position)> {
+        let cursor = self.cursor(index);
+        let (start, end) = cursor
+            .get_selection()
+            .map(|selection| (selection.start, selection.end))
+            .unwrap_or((cursor.position, cursor.position));
+
+        let is_nestable = open != close;
+
+        // Brackets are only paired with others in the same context, eg. both in code or both in a string.
+        let is_in_string_or_comment = self.is_position_in_string_or_comment(start);
+        let is_same_context = |position| {
+            !is_nestable
+                || self.is_position_in_string_or_comment(position) == is_in_string_or_comment
+        };
+
+        let mut position = start;
+        let mut depth = 0;
+
+        let open_start = loop {
+            let next_position = self.move_position(position, -1, 0, gfx);
+
+            if next_position == position {
+                return None;
+            }
+
+            position = next_position;
+
+            let grapheme = self.grapheme(position);
+
+            let is_in_context = is_same_context(position);
+
+            if is_in_context && grapheme == open {
+                if depth == 0 {
+                    break position;
+                }
+
+                depth -= 1;
+            } else if is_in_context && is_nestable && grapheme == close {
+                depth += 1;
+            }
+        };
+
+        let mut position = end;
+        let mut depth = 0;
+
+        let close_start = loop {
+            let grapheme = self.grapheme(position);
+
+            let is_in_context = is_same_context(position);
+
+            if is_in_context && grapheme == close {
+                if depth == 0 {
+                    break position;
+                }
+
+                depth -= 1;
+            } else if is_in_context && is_nestable && grapheme == open {
+                depth += 1;
+            }
+
+            let next_position = self.move_position(position, 1, 0, gfx);
+
+            if next_position == position {
+                return None;
+            }
+
+            position = next_position;
+        };
+
+        Some((open_start, close_start))
+    }
+}
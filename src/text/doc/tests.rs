@@ -1,10 +1,30 @@
+use std::{
+    env::temp_dir,
+    fs::{
+        create_dir_all, metadata, read_dir, read_to_string, remove_dir_all, remove_file, rename,
+        set_permissions, File,
+    },
+    io::{self, Write},
+    thread,
+};
+
 use crate::{
     ctx::ctx_with_time,
-    geometry::position::Position,
+    geometry::{position::Position, rect::Rect, visual_position::VisualPosition},
+    platform::gfx::Gfx,
     tests::{test_with_doc, HELLO_GOODBYE_TEXT},
-    text::{action_history::ActionKind, cursor_index::CursorIndex},
+    text::{
+        action_history::ActionKind,
+        cursor_index::CursorIndex,
+        pattern::Pattern,
+        selection::{Selection, SelectionStats},
+        syntax::{Syntax, SyntaxRange, SyntaxToken},
+        syntax_highlighter::HighlightKind,
+    },
 };
 
+use super::{loader::DocLoader, Doc, DocFlags};
+
 test_with_doc!(search_forward, HELLO_GOODBYE_TEXT, |ctx, doc| {
     let position = doc.search("world", doc.line_end(0), false, ctx.gfx);
     assert_eq!(position, Some(Position::new(8, 1)));
@@ -65,6 +85,49 @@ test_with_doc!(repeated_search_backward, HELLO_GOODBYE_TEXT, |ctx, doc| {
     assert_eq!(position, Some(Position::new(8, 1)));
 });
 
+fn sub_word_stops(doc: &Doc, start: Position, delta_x: isize, gfx: &mut Gfx) -> Vec<usize> {
+    let mut stops = Vec::new();
+    let mut position = start;
+
+    loop {
+        let next_position = doc.move_position_to_next_sub_word(position, delta_x, gfx);
+
+        if next_position == position || next_position.y != start.y {
+            break;
+        }
+
+        stops.push(next_position.x);
+        position = next_position;
+    }
+
+    stops
+}
+
+test_with_doc!(sub_word_camel_case, "getFooBar HTTPServer", |ctx, doc| {
+    let stops = sub_word_stops(doc, Position::ZERO, 1, ctx.gfx);
+    assert_eq!(stops, [3, 6, 9, 14, 20]);
+
+    let stops = sub_word_stops(doc, doc.line_end(0), -1, ctx.gfx);
+    assert_eq!(stops, [14, 10, 6, 3, 0]);
+});
+
+test_with_doc!(
+    sub_word_screaming_snake_case,
+    "SCREAMING_SNAKE",
+    |ctx, doc| {
+        let stops = sub_word_stops(doc, Position::ZERO, 1, ctx.gfx);
+        assert_eq!(stops, [9, 15]);
+
+        let stops = sub_word_stops(doc, doc.line_end(0), -1, ctx.gfx);
+        assert_eq!(stops, [10, 0]);
+    }
+);
+
+test_with_doc!(sub_word_mixed, "parse_HTTPResponse2(x_y)", |ctx, doc| {
+    let stops = sub_word_stops(doc, Position::ZERO, 1, ctx.gfx);
+    assert_eq!(stops, [5, 10, 19, 20, 21, 23, 24]);
+});
+
 test_with_doc!(select_next_occurances, HELLO_GOODBYE_TEXT, |ctx, doc| {
     doc.jump_cursor(CursorIndex::Main, Position::new(6, 0), false, ctx.gfx);
 
@@ -106,3 +169,809 @@ test_with_doc!(multi_cursor_undo, HELLO_GOODBYE_TEXT, |ctx, doc| {
     assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(0, 1));
     assert_eq!(doc.cursors_len(), 2);
 });
+
+test_with_doc!(
+    undo_and_redo_need_recenter,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        let ctx = ctx_with_time!(ctx, 1.0);
+
+        doc.jump_cursor(CursorIndex::Main, doc.line_end(1), false, ctx.gfx);
+        doc.insert_at_cursors("!", ctx);
+        doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+
+        assert!(!doc.take_needs_recenter());
+
+        doc.undo(ActionKind::Done, ctx);
+        assert_eq!(doc.cursor(CursorIndex::Main).position, doc.line_end(1));
+        assert!(doc.take_needs_recenter());
+        assert!(!doc.take_needs_recenter());
+
+        doc.undo(ActionKind::Undone, ctx);
+        assert!(doc.take_needs_recenter());
+
+        // There's nothing left to redo.
+        doc.undo(ActionKind::Undone, ctx);
+        assert!(!doc.take_needs_recenter());
+    }
+);
+
+test_with_doc!(read_only_blocks_edits, HELLO_GOODBYE_TEXT, |ctx, doc| {
+    doc.toggle_read_only();
+
+    doc.insert(Position::ZERO, "test", ctx);
+    doc.delete(Position::ZERO, Position::new(5, 0), ctx);
+
+    assert_eq!(doc.to_string(), HELLO_GOODBYE_TEXT);
+    assert!(doc.was_edit_blocked(ctx.time));
+    assert!(!doc.was_edit_blocked(ctx.time + 10.0));
+
+    doc.toggle_read_only();
+    doc.insert(Position::ZERO, "test", ctx);
+
+    assert_eq!(doc.to_string(), "testhello world\ngoodbye world");
+});
+
+test_with_doc!(read_only_allows_internal_inserts, "log", |ctx, doc| {
+    doc.toggle_read_only();
+
+    let end = doc.end();
+    doc.insert_ignoring_read_only(end, "\nmore", ctx);
+
+    assert_eq!(doc.to_string(), "log\nmore");
+    assert!(doc.is_read_only());
+    assert!(!doc.was_edit_blocked(ctx.time));
+});
+
+test_with_doc!(read_only_keeps_history, HELLO_GOODBYE_TEXT, |ctx, doc| {
+    let ctx = ctx_with_time!(ctx, 1.0);
+
+    doc.insert(Position::ZERO, "test", ctx);
+    doc.toggle_read_only();
+    doc.undo(ActionKind::Done, ctx);
+
+    assert_eq!(doc.to_string(), "testhello world\ngoodbye world");
+    assert!(doc.was_edit_blocked(ctx.time));
+
+    doc.toggle_read_only();
+    doc.undo(ActionKind::Done, ctx);
+
+    assert_eq!(doc.to_string(), HELLO_GOODBYE_TEXT);
+
+    doc.toggle_read_only();
+    doc.undo(ActionKind::Undone, ctx);
+
+    assert_eq!(doc.to_string(), HELLO_GOODBYE_TEXT);
+
+    doc.toggle_read_only();
+    doc.undo(ActionKind::Undone, ctx);
+
+    assert_eq!(doc.to_string(), "testhello world\ngoodbye world");
+});
+
+test_with_doc!(surround_selection_and_word, "one two", |ctx, doc| {
+    let ctx = ctx_with_time!(ctx, 1.0);
+
+    doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+    doc.jump_cursor(CursorIndex::Main, Position::new(3, 0), true, ctx.gfx);
+    doc.add_cursor_at(Position::new(5, 0), ctx.gfx);
+    doc.surround_at_cursors("(", ctx);
+
+    assert_eq!(doc.to_string(), "(one) (two)");
+    assert_eq!(
+        doc.cursor(CursorIndex::Some(0))
+            .get_selection()
+            .map(|s| (s.start, s.end)),
+        Some((Position::new(1, 0), Position::new(4, 0)))
+    );
+    assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(8, 0));
+
+    doc.undo(ActionKind::Done, ctx);
+    assert_eq!(doc.to_string(), "one two");
+});
+
+test_with_doc!(
+    change_and_delete_surrounding,
+    "call({ a: [1, 2] }, \"x\")",
+    |ctx, doc| {
+        doc.jump_cursor(CursorIndex::Main, Position::new(9, 0), false, ctx.gfx);
+        doc.change_surrounding_at_cursors("}", "[", ctx);
+
+        assert_eq!(doc.to_string(), "call([ a: [1, 2] ], \"x\")");
+
+        doc.delete_surrounding_at_cursors("(", ctx);
+
+        assert_eq!(doc.to_string(), "call[ a: [1, 2] ], \"x\"");
+
+        doc.jump_cursor(CursorIndex::Main, Position::new(21, 0), false, ctx.gfx);
+        doc.delete_surrounding_at_cursors("\"", ctx);
+
+        assert_eq!(doc.to_string(), "call[ a: [1, 2] ], x");
+    }
+);
+
+test_with_doc!(
+    strings_and_comments_hide_brackets,
+    "f(\"a)\", b) // c)\n/* d",
+    |ctx, doc| {
+        let syntax = Syntax {
+            has_identifiers: false,
+            keywords: Default::default(),
+            tokens: vec![SyntaxToken {
+                pattern: Pattern::parse("//%.*".into()).unwrap(),
+                kind: HighlightKind::Comment,
+            }],
+            ranges: vec![
+                SyntaxRange {
+                    start: Pattern::parse("\"".into()).unwrap(),
+                    end: Pattern::parse("\"".into()).unwrap(),
+                    escape: None,
+                    kind: HighlightKind::String,
+                },
+                SyntaxRange {
+                    start: Pattern::parse("/%*".into()).unwrap(),
+                    end: Pattern::parse("%*/".into()).unwrap(),
+                    escape: None,
+                    kind: HighlightKind::Comment,
+                },
+            ],
+        };
+
+        let bounds = Rect::new(0.0, 0.0, 1000.0, 1000.0);
+        doc.update_highlights(VisualPosition::ZERO, bounds, &syntax, ctx.gfx);
+
+        assert!(!doc.is_position_in_string_or_comment(Position::new(1, 0)));
+        assert!(doc.is_position_in_string_or_comment(Position::new(4, 0)));
+        assert!(!doc.is_position_in_string_or_comment(Position::new(6, 0)));
+        assert!(doc.is_position_in_string_or_comment(Position::new(16, 0)));
+        assert!(doc.is_position_in_string_or_comment(Position::new(18, 0)));
+        assert!(doc.is_position_in_string_or_comment(Position::new(4, 1)));
+
+        doc.jump_cursor(CursorIndex::Main, Position::new(8, 0), false, ctx.gfx);
+        doc.delete_surrounding_at_cursors("(", ctx);
+
+        assert_eq!(doc.to_string(), "f\"a)\", b // c)\n/* d");
+    }
+);
+
+test_with_doc!(word_chars_join_words, "my-variable $el", |ctx, doc| {
+    let word = doc.select_current_word_at_position(Position::new(4, 0), ctx.gfx);
+    assert_eq!(word.end, Position::new(11, 0));
+
+    doc.set_word_chars("-$");
+
+    let word = doc.select_current_word_at_position(Position::new(4, 0), ctx.gfx);
+    assert_eq!(
+        (word.start, word.end),
+        (Position::ZERO, Position::new(11, 0))
+    );
+
+    let position = doc.move_position_to_next_word(Position::new(12, 0), 1, ctx.gfx);
+    assert_eq!(position, Position::new(15, 0));
+});
+
+test_with_doc!(
+    bookmarks_shift_with_edits,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        doc.toggle_bookmark(1);
+
+        doc.insert(Position::ZERO, "first\n", ctx);
+        assert_eq!(doc.bookmarks().collect::<Vec<_>>(), [2]);
+
+        doc.delete(Position::ZERO, Position::new(0, 1), ctx);
+        assert_eq!(doc.bookmarks().collect::<Vec<_>>(), [1]);
+
+        doc.toggle_bookmark(1);
+        assert!(!doc.is_bookmarked(1));
+    }
+);
+
+test_with_doc!(
+    copy_line_once_with_cursors_on_same_line,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        doc.jump_cursor(CursorIndex::Main, Position::new(2, 0), false, ctx.gfx);
+        doc.add_cursor_at(Position::new(7, 0), ctx.gfx);
+
+        let mut text = String::new();
+        let was_copy_implicit = doc.copy_at_cursors(&mut text);
+
+        assert!(was_copy_implicit);
+        assert_eq!(text, "hello world\n");
+    }
+);
+
+test_with_doc!(
+    selection_stats_sum_cursors,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        assert_eq!(doc.selection_stats(), None);
+
+        doc.jump_cursor(CursorIndex::Main, Position::new(3, 0), false, ctx.gfx);
+        doc.jump_cursor(CursorIndex::Main, Position::new(4, 1), true, ctx.gfx);
+        doc.add_cursor_at(Position::new(8, 1), ctx.gfx);
+        doc.jump_cursor(CursorIndex::Main, Position::new(13, 1), true, ctx.gfx);
+
+        assert_eq!(
+            doc.selection_stats(),
+            Some(SelectionStats {
+                chars: 18,
+                words: 4,
+                lines: 3,
+            })
+        );
+    }
+);
+
+test_with_doc!(
+    elastic_tabstops_align_columns,
+    "a\tb\nccc\td\n\nx\ty",
+    |ctx, doc| {
+        assert!(doc.tab_widths(0, ctx.gfx).is_empty());
+
+        doc.set_elastic_tabstops(true);
+
+        assert_eq!(doc.tab_widths(0, ctx.gfx), [4]);
+        assert_eq!(doc.tab_widths(1, ctx.gfx), [2]);
+        assert_eq!(doc.tab_widths(3, ctx.gfx), [3]);
+
+        let uncached_tab_widths: Vec<Vec<usize>> = (0..doc.lines().len())
+            .map(|y| doc.tab_widths(y, ctx.gfx))
+            .collect();
+
+        doc.update_tab_widths(ctx.gfx);
+
+        for (y, tab_widths) in uncached_tab_widths.iter().enumerate() {
+            assert_eq!(&doc.tab_widths(y, ctx.gfx), tab_widths);
+        }
+
+        // Cached widths from before an edit are ignored until they are updated.
+        doc.insert(Position::new(3, 1), "\tlonger", ctx);
+
+        assert_eq!(doc.tab_widths(1, ctx.gfx), [2, 2]);
+
+        doc.update_tab_widths(ctx.gfx);
+
+        assert_eq!(doc.tab_widths(1, ctx.gfx), [2, 2]);
+        assert_eq!(doc.tab_widths(3, ctx.gfx), [3]);
+    }
+);
+
+test_with_doc!(
+    trim_trailing_whitespace_keeps_cursors,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        doc.insert(doc.line_end(1), "  ", ctx);
+        doc.jump_cursor(CursorIndex::Main, doc.line_end(0), false, ctx.gfx);
+        doc.insert_at_cursors("  ", ctx);
+        doc.jump_cursor(CursorIndex::Main, Position::new(12, 0), false, ctx.gfx);
+        doc.add_cursor_at(Position::new(3, 1), ctx.gfx);
+
+        doc.trim_trailing_whitespace(ctx);
+
+        assert_eq!(doc.to_string(), "hello world \ngoodbye world");
+        assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(3, 1));
+        assert_eq!(
+            doc.cursor(CursorIndex::Some(0)).position,
+            Position::new(12, 0)
+        );
+    }
+);
+
+test_with_doc!(
+    trim_blank_lines_keeps_content_lines,
+    "hello  \n    \n\t\nworld ",
+    |ctx, doc| {
+        doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+
+        doc.trim_blank_lines(ctx);
+
+        assert_eq!(doc.to_string(), "hello  \n\n\nworld ");
+    }
+);
+
+test_with_doc!(
+    trim_trailing_whitespace_keeps_blank_lines,
+    "hello  \n    \n\t\nworld ",
+    |ctx, doc| {
+        doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+
+        doc.trim_trailing_whitespace(ctx);
+
+        assert_eq!(doc.to_string(), "hello\n    \n\t\nworld");
+    }
+);
+
+test_with_doc!(count_search_matches, HELLO_GOODBYE_TEXT, |ctx, doc| {
+    assert_eq!(
+        doc.count_matches("o", Position::new(9, 1), None, 10, ctx.gfx),
+        (Some(4), 5)
+    );
+    assert_eq!(
+        doc.count_matches("o", Position::ZERO, None, 2, ctx.gfx),
+        (None, 2)
+    );
+    assert_eq!(
+        doc.count_matches("", Position::ZERO, None, 10, ctx.gfx),
+        (None, 0)
+    );
+
+    let selections = [Selection {
+        start: Position::ZERO,
+        end: doc.line_end(0),
+    }];
+
+    assert_eq!(
+        doc.count_matches("o", Position::new(7, 0), Some(&selections), 10, ctx.gfx),
+        (Some(1), 2)
+    );
+    assert_eq!(
+        doc.count_matches("o", Position::new(7, 0), Some(&selections), 1, ctx.gfx),
+        (None, 1)
+    );
+});
+
+test_with_doc!(count_overlapping_search_matches, "aaa\naa", |ctx, doc| {
+    assert_eq!(
+        doc.count_matches("aa", Position::new(1, 0), None, 10, ctx.gfx),
+        (Some(1), 3)
+    );
+});
+
+test_with_doc!(search_within_selections, "ab ab\nab ab\nab ab", |_, doc| {
+    let selections = [
+        Selection {
+            start: Position::new(1, 0),
+            end: Position::new(5, 0),
+        },
+        Selection {
+            start: Position::new(0, 2),
+            end: Position::new(2, 2),
+        },
+    ];
+
+    assert_eq!(
+        doc.search_all_in("ab", &selections, usize::MAX),
+        vec![Position::new(3, 0), Position::new(0, 2)]
+    );
+    assert_eq!(
+        doc.search_in("ab", Position::new(1, 2), false, &selections),
+        Some(Position::new(3, 0))
+    );
+    assert_eq!(
+        doc.search_in("ab", Position::new(1, 2), true, &selections),
+        Some(Position::new(3, 0))
+    );
+});
+
+test_with_doc!(replace_all_within_selections, "ab ab ab\nab", |ctx, doc| {
+    let mut selections = [
+        Selection {
+            start: Position::new(0, 0),
+            end: Position::new(2, 0),
+        },
+        Selection {
+            start: Position::new(6, 0),
+            end: Position::new(8, 0),
+        },
+    ];
+
+    let count = doc.replace_all_in("ab", "xyz", false, &mut selections, ctx);
+
+    assert_eq!(count, 2);
+    assert_eq!(doc.to_string(), "xyz ab xyz\nab");
+    assert_eq!(selections[0].end, Position::new(3, 0));
+    assert_eq!(selections[1].start, Position::new(7, 0));
+    assert_eq!(selections[1].end, Position::new(10, 0));
+});
+
+test_with_doc!(
+    replace_all_with_line_breaks_within_selections,
+    "a-b c-d\ne-f",
+    |ctx, doc| {
+        let mut selections = [
+            Selection {
+                start: Position::new(0, 0),
+                end: Position::new(3, 0),
+            },
+            Selection {
+                start: Position::new(4, 0),
+                end: Position::new(3, 1),
+            },
+        ];
+
+        let count = doc.replace_all_in("-", "\n", false, &mut selections, ctx);
+
+        assert_eq!(count, 3);
+        assert_eq!(doc.to_string(), "a\nb c\nd\ne\nf");
+        assert_eq!(selections[0].start, Position::new(0, 0));
+        assert_eq!(selections[0].end, Position::new(1, 1));
+        assert_eq!(selections[1].start, Position::new(2, 1));
+        assert_eq!(selections[1].end, Position::new(1, 4));
+    }
+);
+
+test_with_doc!(reverse_selected_lines, "a\nb\nc\nd", |ctx, doc| {
+    let ctx = ctx_with_time!(ctx, 1.0);
+
+    doc.jump_cursor(CursorIndex::Main, Position::new(0, 1), false, ctx.gfx);
+    doc.jump_cursor(CursorIndex::Main, Position::new(0, 3), true, ctx.gfx);
+    doc.reverse_lines_at_cursors(ctx);
+
+    assert_eq!(doc.to_string(), "a\nc\nb\nd");
+
+    doc.undo(ActionKind::Done, ctx);
+
+    assert_eq!(doc.to_string(), "a\nb\nc\nd");
+});
+
+test_with_doc!(reverse_all_lines, "a\nb\nc", |ctx, doc| {
+    doc.jump_cursor(CursorIndex::Main, Position::new(1, 2), false, ctx.gfx);
+    doc.reverse_lines_at_cursors(ctx);
+
+    assert_eq!(doc.to_string(), "c\nb\na");
+    assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(1, 2));
+});
+
+test_with_doc!(shuffle_lines_with_seed, "a\nb\nc\nd\ne", |ctx, doc| {
+    doc.shuffle_lines_at_cursors(7, ctx);
+
+    let shuffled_text = doc.to_string();
+
+    let mut lines: Vec<&str> = shuffled_text.lines().collect();
+    lines.sort();
+    assert_eq!(lines, ["a", "b", "c", "d", "e"]);
+
+    let mut other_doc = Doc::new(None, None, DocFlags::MULTI_LINE);
+    other_doc.insert(Position::ZERO, "a\nb\nc\nd\ne", ctx);
+    other_doc.shuffle_lines_at_cursors(7, ctx);
+
+    assert_eq!(other_doc.to_string(), shuffled_text);
+});
+
+test_with_doc!(unique_adjacent_lines, "a\na\nb\na\nB\nb", |ctx, doc| {
+    doc.unique_adjacent_lines_at_cursors(false, ctx);
+    assert_eq!(doc.to_string(), "a\nb\na\nB\nb");
+
+    doc.unique_adjacent_lines_at_cursors(true, ctx);
+    assert_eq!(doc.to_string(), "a\nb\na\nB");
+});
+
+test_with_doc!(unique_selected_lines, "a\nb\nA\nb\na\nend", |ctx, doc| {
+    let ctx = ctx_with_time!(ctx, 1.0);
+
+    doc.jump_cursor(CursorIndex::Main, Position::ZERO, false, ctx.gfx);
+    doc.jump_cursor(CursorIndex::Main, Position::new(1, 4), true, ctx.gfx);
+    doc.unique_lines_at_cursors(true, ctx);
+
+    assert_eq!(doc.to_string(), "a\nb\nend");
+    assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(1, 1));
+
+    doc.undo(ActionKind::Done, ctx);
+    assert_eq!(doc.to_string(), "a\nb\nA\nb\na\nend");
+});
+
+test_with_doc!(load_unwritable_file_as_read_only, "", |ctx, _| {
+    let path = temp_dir().join("keylime_load_unwritable_file_as_read_only.txt");
+    File::create(&path).unwrap().write_all(b"text").unwrap();
+
+    let mut permissions = metadata(&path).unwrap().permissions();
+    permissions.set_readonly(true);
+    set_permissions(&path, permissions.clone()).unwrap();
+
+    let mut doc = Doc::new(Some(path.as_path().into()), None, DocFlags::MULTI_LINE);
+    let result = doc.load(ctx);
+
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    let _ = set_permissions(&path, permissions);
+    let _ = remove_file(&path);
+
+    result.unwrap();
+
+    assert!(doc.is_read_only());
+
+    doc.insert(Position::ZERO, "more ", ctx);
+    assert_eq!(doc.to_string(), "text");
+});
+
+test_with_doc!(reload_updates_read_only_state, "", |ctx, _| {
+    let path = temp_dir().join("keylime_reload_updates_read_only_state.txt");
+    File::create(&path).unwrap().write_all(b"text").unwrap();
+
+    let mut permissions = metadata(&path).unwrap().permissions();
+    permissions.set_readonly(true);
+    set_permissions(&path, permissions.clone()).unwrap();
+
+    let mut doc = Doc::new(Some(path.as_path().into()), None, DocFlags::MULTI_LINE);
+    let result = doc.load(ctx);
+
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    set_permissions(&path, permissions).unwrap();
+
+    result.unwrap();
+    assert!(doc.is_read_only());
+
+    doc.reload(ctx).unwrap();
+    assert!(!doc.is_read_only());
+
+    // Toggling read-only is kept when the file is loaded again.
+    doc.toggle_read_only();
+    doc.load(ctx).unwrap();
+
+    let _ = remove_file(&path);
+
+    assert!(doc.is_read_only());
+});
+
+test_with_doc!(save_creates_parent_dirs, "text", |ctx, doc| {
+    let dir = temp_dir().join("keylime_save_creates_parent_dirs");
+    let path = dir.join("new/file.txt");
+
+    let result = doc.save(Some(path.as_path().into()), ctx);
+    let saved_text = read_to_string(&path);
+
+    let _ = remove_dir_all(&dir);
+
+    result.unwrap();
+    assert_eq!(saved_text.unwrap(), "text");
+});
+
+#[test]
+fn write_backups() {
+    let dir = temp_dir().join("keylime_write_backups");
+    let path = dir.join("file.txt");
+
+    create_dir_all(&dir).unwrap();
+    File::create(&path).unwrap().write_all(b"old").unwrap();
+
+    Doc::write_backup(&path, None).unwrap();
+    Doc::write_backup(&path, Some("backups")).unwrap();
+
+    let adjacent_backup = read_to_string(dir.join("file.txt~"));
+    let dir_backups: Vec<_> = read_dir(dir.join("backups"))
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name())
+        .collect();
+
+    let _ = remove_dir_all(&dir);
+
+    assert_eq!(adjacent_backup.unwrap(), "old");
+    assert_eq!(dir_backups.len(), 1);
+
+    let backup_name = dir_backups[0].to_string_lossy();
+    assert!(backup_name.starts_with("file.txt.") && backup_name.ends_with('~'));
+}
+
+fn load_in_chunks(name: &str, bytes: &[u8]) -> io::Result<String> {
+    let path = temp_dir().join(name);
+    File::create(&path)?.write_all(bytes)?;
+
+    let mut loader = DocLoader::new(File::open(&path)?, bytes.len());
+    let mut text = String::new();
+
+    let result = loop {
+        match loader.next_text(&mut text) {
+            Ok(true) => break Ok(text),
+            Ok(false) => thread::yield_now(),
+            Err(err) => break Err(err),
+        }
+    };
+
+    let _ = remove_file(&path);
+
+    result
+}
+
+#[test]
+fn load_file_in_chunks() {
+    // Put a CRLF and a multi-byte character across the boundaries between chunks.
+    let mut text = "a".repeat(DocLoader::CHUNK_LEN - 1);
+    text.push_str("\r\n");
+    text.push_str(&"b".repeat(DocLoader::CHUNK_LEN - 2));
+    text.push_str("\u{e9}\r\nend\r");
+
+    let loaded_text = load_in_chunks("keylime_load_file_in_chunks.txt", text.as_bytes()).unwrap();
+
+    assert_eq!(loaded_text, text);
+}
+
+#[test]
+fn load_invalid_utf8_in_chunks() {
+    let result = load_in_chunks("keylime_load_invalid_utf8_in_chunks.txt", b"a\xffb");
+
+    assert!(result.is_err());
+}
+
+test_with_doc!(edits_are_blocked_while_loading, "", |ctx, _| {
+    let path = temp_dir().join("keylime_edits_are_blocked_while_loading.txt");
+    let text = "a\n".repeat(DocLoader::MIN_LEN);
+    File::create(&path)
+        .unwrap()
+        .write_all(text.as_bytes())
+        .unwrap();
+
+    let mut doc = Doc::new(Some(path.as_path().into()), None, DocFlags::MULTI_LINE);
+    doc.load_in_background(ctx).unwrap();
+
+    assert!(doc.load_progress().is_some());
+    assert!(doc.is_read_only());
+
+    doc.insert(Position::ZERO, "edit", ctx);
+    assert!(doc.was_edit_blocked(ctx.time));
+
+    while doc.load_progress().is_some() {
+        doc.update_load(ctx).unwrap();
+        thread::yield_now();
+    }
+
+    let _ = remove_file(&path);
+
+    assert!(!doc.is_read_only());
+    assert!(doc.is_saved());
+    assert_eq!(doc.to_string(), text);
+
+    doc.insert(Position::ZERO, "edit", ctx);
+    assert!(!doc.is_saved());
+    assert!(doc.get_line(0).unwrap().starts_with("edit"));
+});
+
+test_with_doc!(load_binary_file_as_hex_dump, "", |ctx, _| {
+    let path = temp_dir().join("keylime_load_binary_file_as_hex_dump.bin");
+    File::create(&path)
+        .unwrap()
+        .write_all(b"\xff\x00abc")
+        .unwrap();
+
+    let mut doc = Doc::new(Some(path.as_path().into()), None, DocFlags::MULTI_LINE);
+    let result = doc.load(ctx);
+    let _ = remove_file(&path);
+    result.unwrap();
+
+    let text = doc.to_string();
+
+    assert!(doc.is_binary());
+    assert!(text.starts_with("00000000  ff 00 61 62 63 "));
+
+    // The hex dump can't be edited or saved over the original file.
+    doc.insert(Position::ZERO, "text", ctx);
+    assert_eq!(doc.to_string(), text);
+
+    let save_path = path.with_extension("txt");
+    assert!(doc.save(Some(save_path.as_path().into()), ctx).is_err());
+    assert!(!save_path.exists());
+});
+
+test_with_doc!(
+    rtl_lines_are_mirrored,
+    "\u{05D0}\u{05D1}\nab",
+    |ctx, doc| {
+        assert!(doc.is_line_rtl(0));
+        assert!(!doc.is_line_rtl(1));
+
+        // The last grapheme of a right-to-left line is drawn first.
+        assert_eq!(doc.logical_visual_x(0, 0, &[], ctx.gfx), 3);
+        assert_eq!(doc.logical_visual_x(0, 3, &[], ctx.gfx), 0);
+        assert_eq!(doc.logical_visual_x(0, 10, &[], ctx.gfx), 0);
+        assert_eq!(doc.logical_visual_x(1, 0, &[], ctx.gfx), 0);
+    }
+);
+
+const COMBINING_AND_EMOJI_TEXT: &str = "e\u{301}x\n\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}y";
+
+test_with_doc!(
+    move_cursor_over_grapheme_clusters,
+    COMBINING_AND_EMOJI_TEXT,
+    |ctx, doc| {
+        doc.jump_cursors(Position::ZERO, false, ctx.gfx);
+        doc.move_cursors(1, 0, false, ctx.gfx);
+        assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(3, 0));
+
+        doc.jump_cursors(Position::new(0, 1), false, ctx.gfx);
+        doc.move_cursors(1, 0, false, ctx.gfx);
+        assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(18, 1));
+
+        doc.move_cursors(-1, 0, false, ctx.gfx);
+        assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(0, 1));
+    }
+);
+
+test_with_doc!(
+    click_lands_on_grapheme_boundaries,
+    COMBINING_AND_EMOJI_TEXT,
+    |ctx, doc| {
+        let line = doc.get_line(0).unwrap();
+        assert_eq!(ctx.gfx.find_x_for_visual_x(line, 1), 0);
+        assert_eq!(ctx.gfx.find_x_for_visual_x(line, 3), 3);
+
+        let line = doc.get_line(1).unwrap();
+        assert_eq!(ctx.gfx.find_x_for_visual_x(line, 5), 0);
+        assert_eq!(ctx.gfx.find_x_for_visual_x(line, 18), 18);
+
+        // Moving between lines keeps the cursor out of the middle of clusters.
+        doc.jump_cursors(Position::new(3, 0), false, ctx.gfx);
+        doc.move_cursors(0, 1, false, ctx.gfx);
+        assert_eq!(doc.cursor(CursorIndex::Main).position, Position::new(0, 1));
+    }
+);
+
+test_with_doc!(
+    navigate_and_revert_changes,
+    HELLO_GOODBYE_TEXT,
+    |ctx, doc| {
+        doc.update_saved_lines();
+
+        doc.insert(Position::ZERO, "new line\n", ctx);
+        doc.delete(Position::new(0, 2), doc.end(), ctx);
+        doc.insert(Position::new(0, 2), "farewell world\nthe end", ctx);
+
+        assert_eq!(doc.diff_hunk_y(0, false), Some(2));
+        assert_eq!(doc.diff_hunk_y(2, false), Some(0));
+        assert_eq!(doc.diff_hunk_y(0, true), Some(2));
+
+        assert!(!doc.revert_diff_hunk(1, ctx_with_time!(ctx, 1.0)));
+        assert!(doc.revert_diff_hunk(3, ctx_with_time!(ctx, 1.0)));
+        assert_eq!(doc.to_string(), "new line\nhello world\ngoodbye world");
+
+        let ctx = ctx_with_time!(ctx, 2.0);
+
+        assert!(doc.revert_diff_hunk(0, ctx));
+        assert_eq!(doc.to_string(), HELLO_GOODBYE_TEXT);
+        assert_eq!(doc.diff_hunk_y(0, false), None);
+
+        doc.undo(ActionKind::Done, ctx);
+        assert_eq!(doc.to_string(), "new line\nhello world\ngoodbye world");
+    }
+);
+
+test_with_doc!(
+    completion_prefix_without_language_server,
+    "foo.\nfoo::\nfoo-\nfoo.ba",
+    |ctx, doc| {
+        let mut prefix_at = |x, y| {
+            doc.jump_cursors(Position::new(x, y), false, ctx.gfx);
+            doc.get_completion_prefix(ctx).map(str::to_owned)
+        };
+
+        assert_eq!(prefix_at(4, 0).as_deref(), Some(""));
+        assert_eq!(prefix_at(5, 1).as_deref(), Some(""));
+        assert_eq!(prefix_at(4, 2), None);
+        assert_eq!(prefix_at(6, 3).as_deref(), Some("ba"));
+    }
+);
+
+test_with_doc!(
+    line_column_position_counts_chars,
+    "héllo\nwörld",
+    |_, doc| {
+        assert_eq!(doc.line_column_position(1, 1), Position::new(0, 0));
+        assert_eq!(doc.line_column_position(1, 3), Position::new(3, 0));
+        assert_eq!(doc.line_column_position(2, 3), Position::new(3, 1));
+        assert_eq!(doc.line_column_position(2, 99), Position::new(6, 1));
+        assert_eq!(doc.line_column_position(99, 1), Position::new(0, 1));
+    }
+);
+
+test_with_doc!(set_moved_path_keeps_unsaved_changes, "saved", |ctx, doc| {
+    let dir = temp_dir().join("keylime_set_moved_path_keeps_unsaved_changes");
+    let from = dir.join("from.txt");
+    let to = dir.join("to.txt");
+
+    let _ = remove_dir_all(&dir);
+    doc.save(Some(from.as_path().into()), ctx).unwrap();
+    doc.insert(doc.end(), " and unsaved", ctx);
+
+    rename(&from, &to).unwrap();
+    let result = doc.set_moved_path(to.as_path().into(), ctx);
+    let moved_text = read_to_string(&to);
+
+    let _ = remove_dir_all(&dir);
+
+    result.unwrap();
+    assert_eq!(moved_text.unwrap(), "saved");
+    assert_eq!(doc.path().some_path(), Some(to.as_path()));
+    assert!(!doc.is_saved());
+});
@@ -0,0 +1,533 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmmetSyntax {
+    Html,
+    Css,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Expansion {
+    pub text: String,
+    // Byte offsets into the text where cursors should be placed.
+    pub tabstops: Vec<usize>,
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+const CSS_KEYWORDS: &[(&str, &str)] = &[
+    ("db", "display: block"),
+    ("di", "display: inline"),
+    ("dib", "display: inline-block"),
+    ("df", "display: flex"),
+    ("dg", "display: grid"),
+    ("dn", "display: none"),
+    ("posa", "position: absolute"),
+    ("posr", "position: relative"),
+    ("posf", "position: fixed"),
+    ("poss", "position: sticky"),
+    ("tac", "text-align: center"),
+    ("tal", "text-align: left"),
+    ("tar", "text-align: right"),
+    ("fwb", "font-weight: bold"),
+    ("fwn", "font-weight: normal"),
+    ("fsi", "font-style: italic"),
+    ("ovh", "overflow: hidden"),
+    ("ova", "overflow: auto"),
+    ("curp", "cursor: pointer"),
+];
+
+const CSS_PROPERTIES: &[(&str, &str)] = &[
+    ("m", "margin"),
+    ("mt", "margin-top"),
+    ("mr", "margin-right"),
+    ("mb", "margin-bottom"),
+    ("ml", "margin-left"),
+    ("p", "padding"),
+    ("pt", "padding-top"),
+    ("pr", "padding-right"),
+    ("pb", "padding-bottom"),
+    ("pl", "padding-left"),
+    ("w", "width"),
+    ("h", "height"),
+    ("maw", "max-width"),
+    ("mah", "max-height"),
+    ("miw", "min-width"),
+    ("mih", "min-height"),
+    ("t", "top"),
+    ("r", "right"),
+    ("b", "bottom"),
+    ("l", "left"),
+    ("d", "display"),
+    ("pos", "position"),
+    ("c", "color"),
+    ("bg", "background"),
+    ("bgc", "background-color"),
+    ("bd", "border"),
+    ("bdrs", "border-radius"),
+    ("fz", "font-size"),
+    ("fw", "font-weight"),
+    ("ff", "font-family"),
+    ("lh", "line-height"),
+    ("ta", "text-align"),
+    ("op", "opacity"),
+    ("z", "z-index"),
+    ("g", "gap"),
+];
+
+// Limits keep large or nested multipliers (eg. "ul*999>li*999") from expanding to huge amounts of text.
+const MAX_COUNT: usize = 1000;
+const MAX_ELEMENTS: usize = 1000;
+
+// Properties that take plain numbers rather than lengths.
+const UNITLESS_CSS_PROPERTIES: &[&str] = &["z-index", "opacity", "line-height", "font-weight"];
+
+// Returns the byte index where the abbreviation ending at the end of the line prefix starts.
+pub fn abbreviation_start(line: &str, syntax: EmmetSyntax) -> usize {
+    let mut start = line.len();
+    let mut depth = 0usize;
+    let mut is_in_tag = false;
+
+    for (i, c) in line.char_indices().rev() {
+        match c {
+            '}' if syntax == EmmetSyntax::Html => depth += 1,
+            '{' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            _ if c.is_whitespace() => break,
+            '<' => {
+                is_in_tag = true;
+                break;
+            }
+            '"' | '\'' | ';' | ':' | '{' | '}' | '(' | ')' => break,
+            _ => {}
+        }
+
+        start = i;
+    }
+
+    if depth > 0 {
+        return line.len();
+    }
+
+    // Skip leftovers of preceding markup, eg. the end of an opening tag.
+    if is_in_tag {
+        if let Some(tag_end) = line[start..].find('>') {
+            start += tag_end + 1;
+        }
+    }
+
+    let abbreviation = &line[start..];
+    let trimmed = abbreviation.trim_start_matches(['>', '+', '^']);
+
+    start + abbreviation.len() - trimmed.len()
+}
+
+pub fn expand(
+    abbreviation: &str,
+    syntax: EmmetSyntax,
+    base_indent: &str,
+    indent: &str,
+) -> Option<Expansion> {
+    if abbreviation.is_empty() {
+        return None;
+    }
+
+    let mut expansion = match syntax {
+        EmmetSyntax::Html => expand_html(abbreviation, base_indent, indent)?,
+        EmmetSyntax::Css => expand_css(abbreviation)?,
+    };
+
+    if expansion.tabstops.is_empty() {
+        expansion.tabstops.push(expansion.text.len());
+    }
+
+    Some(expansion)
+}
+
+fn expand_css(abbreviation: &str) -> Option<Expansion> {
+    if let Some((_, keyword)) = CSS_KEYWORDS.iter().find(|(name, _)| *name == abbreviation) {
+        return Some(Expansion {
+            text: format!("{keyword};"),
+            tabstops: Vec::new(),
+        });
+    }
+
+    let value_start = abbreviation
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(abbreviation.len());
+
+    let (name, value) = abbreviation.split_at(value_start);
+    let (_, property) = CSS_PROPERTIES.iter().find(|(other, _)| *other == name)?;
+
+    if value.is_empty() {
+        let text = format!("{property}: ;");
+
+        return Some(Expansion {
+            tabstops: vec![text.len() - 1],
+            text,
+        });
+    }
+
+    let unit_start = value
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || *c == '.' || (*i == 0 && *c == '-')))
+        .map(|(i, _)| i)
+        .unwrap_or(value.len());
+
+    let (number, unit) = value.split_at(unit_start);
+    number.parse::<f64>().ok()?;
+
+    let unit = match unit {
+        "" if number == "0" || UNITLESS_CSS_PROPERTIES.contains(property) => "",
+        "" => "px",
+        "p" => "%",
+        "e" => "em",
+        "r" => "rem",
+        "x" => "ex",
+        unit if unit.chars().all(|c| c.is_ascii_alphabetic()) => unit,
+        _ => return None,
+    };
+
+    Some(Expansion {
+        text: format!("{property}: {number}{unit};"),
+        tabstops: Vec::new(),
+    })
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    id: Option<String>,
+    classes: Vec<String>,
+    text: Option<String>,
+    count: usize,
+    children: Vec<Self>,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    // How many levels are left to climb after a run of "^".
+    climb: usize,
+}
+
+impl Parser {
+    fn parse(abbreviation: &str) -> Option<Vec<Element>> {
+        let mut parser = Self {
+            chars: abbreviation.chars().collect(),
+            position: 0,
+            climb: 0,
+        };
+
+        let mut elements = parser.parse_siblings()?;
+
+        // Climbing past the top level just continues the top level.
+        while parser.peek().is_some() {
+            parser.climb = 0;
+            elements.extend(parser.parse_siblings()?);
+        }
+
+        Some(elements)
+    }
+
+    fn parse_siblings(&mut self) -> Option<Vec<Element>> {
+        let mut siblings = Vec::new();
+
+        loop {
+            let mut element = self.parse_element()?;
+
+            match self.next() {
+                Some('>') => {
+                    element.children = self.parse_siblings()?;
+                    siblings.push(element);
+
+                    // The children either reached the end or climbed out of one or more levels.
+                    if self.climb == 0 {
+                        return Some(siblings);
+                    }
+
+                    self.climb -= 1;
+
+                    if self.climb > 0 {
+                        return Some(siblings);
+                    }
+                }
+                Some('+') => siblings.push(element),
+                Some('^') => {
+                    self.climb = 1;
+
+                    while self.peek() == Some('^') {
+                        self.next();
+                        self.climb += 1;
+                    }
+
+                    siblings.push(element);
+
+                    return Some(siblings);
+                }
+                None => {
+                    siblings.push(element);
+
+                    return Some(siblings);
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_element(&mut self) -> Option<Element> {
+        let mut element = Element {
+            name: self.parse_name(),
+            count: 1,
+            ..Default::default()
+        };
+
+        loop {
+            match self.peek() {
+                Some('.') => {
+                    self.next();
+                    element.classes.push(self.parse_non_empty_name()?);
+                }
+                Some('#') => {
+                    self.next();
+                    element.id = Some(self.parse_non_empty_name()?);
+                }
+                Some('{') => {
+                    self.next();
+                    element.text = Some(self.parse_text()?);
+                }
+                Some('*') => {
+                    self.next();
+                    element.count = self
+                        .parse_name()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|count| *count > 0)?
+                        .min(MAX_COUNT);
+                }
+                _ => break,
+            }
+        }
+
+        let is_empty =
+            element.name.is_empty() && element.id.is_none() && element.classes.is_empty();
+
+        if is_empty && element.text.is_none() {
+            return None;
+        }
+
+        Some(element)
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '$'))
+        {
+            self.next();
+            name.push(c);
+        }
+
+        name
+    }
+
+    fn parse_non_empty_name(&mut self) -> Option<String> {
+        let name = self.parse_name();
+
+        (!name.is_empty()).then_some(name)
+    }
+
+    fn parse_text(&mut self) -> Option<String> {
+        let mut text = String::new();
+
+        loop {
+            match self.next()? {
+                '}' => return Some(text),
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+
+        Some(c)
+    }
+}
+
+struct HtmlRenderer<'a> {
+    base_indent: &'a str,
+    indent: &'a str,
+    expansion: Expansion,
+}
+
+impl HtmlRenderer<'_> {
+    fn render_siblings(&mut self, elements: &[Element], parent_name: &str, level: usize) {
+        for element in elements {
+            for number in 1..=element.count {
+                self.render_element(element, parent_name, number, level);
+            }
+        }
+    }
+
+    fn render_element(
+        &mut self,
+        element: &Element,
+        parent_name: &str,
+        number: usize,
+        level: usize,
+    ) {
+        self.start_line(level);
+
+        let text = element.text.as_deref().map(|text| numbered(text, number));
+
+        if element.name.is_empty() && element.id.is_none() && element.classes.is_empty() {
+            self.push(text.as_deref().unwrap_or_default());
+            return;
+        }
+
+        let name = if element.name.is_empty() {
+            implicit_name(parent_name).to_owned()
+        } else {
+            numbered(&element.name, number)
+        };
+
+        self.push("<");
+        self.push(&name);
+
+        if let Some(id) = &element.id {
+            self.push(" id=\"");
+            self.push(&numbered(id, number));
+            self.push("\"");
+        }
+
+        if !element.classes.is_empty() {
+            self.push(" class=\"");
+
+            for (i, class) in element.classes.iter().enumerate() {
+                if i > 0 {
+                    self.push(" ");
+                }
+
+                self.push(&numbered(class, number));
+            }
+
+            self.push("\"");
+        }
+
+        self.push(">");
+
+        if VOID_ELEMENTS.contains(&name.as_str()) {
+            return;
+        }
+
+        if let Some(text) = &text {
+            self.push(text);
+        }
+
+        if element.children.is_empty() {
+            if text.is_none() {
+                self.expansion.tabstops.push(self.expansion.text.len());
+            }
+        } else {
+            self.render_siblings(&element.children, &name, level + 1);
+            self.start_line(level);
+        }
+
+        self.push("</");
+        self.push(&name);
+        self.push(">");
+    }
+
+    fn start_line(&mut self, level: usize) {
+        if self.expansion.text.is_empty() {
+            return;
+        }
+
+        self.expansion.text.push('\n');
+        self.expansion.text.push_str(self.base_indent);
+
+        for _ in 0..level {
+            self.expansion.text.push_str(self.indent);
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.expansion.text.push_str(text);
+    }
+}
+
+fn expand_html(abbreviation: &str, base_indent: &str, indent: &str) -> Option<Expansion> {
+    let elements = Parser::parse(abbreviation)?;
+
+    if element_count(&elements) > MAX_ELEMENTS {
+        return None;
+    }
+
+    let mut renderer = HtmlRenderer {
+        base_indent,
+        indent,
+        expansion: Expansion {
+            text: String::new(),
+            tabstops: Vec::new(),
+        },
+    };
+
+    renderer.render_siblings(&elements, "", 0);
+
+    Some(renderer.expansion)
+}
+
+fn element_count(elements: &[Element]) -> usize {
+    elements.iter().fold(0, |total, element| {
+        let count = element
+            .count
+            .saturating_mul(element_count(&element.children).saturating_add(1));
+
+        total.saturating_add(count)
+    })
+}
+
+fn implicit_name(parent_name: &str) -> &'static str {
+    match parent_name {
+        "ul" | "ol" => "li",
+        "table" | "tbody" | "thead" | "tfoot" => "tr",
+        "tr" => "td",
+        "select" | "optgroup" => "option",
+        _ => "div",
+    }
+}
+
+// Replaces runs of "$" with the element's number, padded with zeros to the length of the run.
+fn numbered(text: &str, number: usize) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+
+        let mut width = 1;
+
+        while chars.next_if_eq(&'$').is_some() {
+            width += 1;
+        }
+
+        result.push_str(&format!("{number:0width$}"));
+    }
+
+    result
+}
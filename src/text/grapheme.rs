@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 #[derive(Debug, Clone)]
 pub struct GraphemeCursor {
     inner: unicode_segmentation::GraphemeCursor,
@@ -15,7 +17,15 @@ impl GraphemeCursor {
     pub fn next_boundary(&mut self, text: &str) -> Option<usize> {
         let index = self.inner.cur_cursor();
 
-        if index < self.len && text.as_bytes()[index] < 0x7F {
+        let bytes = text.as_bytes();
+
+        // ASCII is its own grapheme unless it's followed by something like a combining mark.
+        if index < self.len
+            && bytes[index] < 0x7F
+            && bytes
+                .get(index + 1)
+                .is_none_or(|next_byte| *next_byte < 0x7F)
+        {
             self.inner.set_cursor(index + 1);
             return Some(index + 1);
         }
@@ -116,6 +126,80 @@ pub fn is_ascii_hexdigit(grapheme: &str) -> bool {
     grapheme.chars().all(|c| c.is_ascii_hexdigit())
 }
 
+// Control characters, zero-width characters, and bidi controls that would otherwise be hidden.
+// Joiners are excluded since they're a normal part of emoji and some scripts.
+pub fn is_invisible(grapheme: &str) -> bool {
+    grapheme.chars().any(is_invisible_char)
+}
+
+fn is_invisible_char(c: char) -> bool {
+    (c.is_control() && c != '\t')
+        || matches!(
+            c,
+            '\u{00AD}'
+                | '\u{061C}'
+                | '\u{200B}'
+                | '\u{200E}'
+                | '\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{2069}'
+                | '\u{FEFF}'
+        )
+}
+
+// C0 controls and DEL have matching control pictures, eg. "␀" for NUL,
+// other invisible characters are written as their code point, eg. "<U+200B>".
+pub fn push_invisible_substitute(grapheme: &str, text: &mut String) {
+    for c in grapheme.chars() {
+        if !is_invisible_char(c) {
+            text.push(c);
+            continue;
+        }
+
+        let control_picture = match c {
+            '\0'..='\u{1F}' => char::from_u32(0x2400 + c as u32),
+            '\u{7F}' => Some('\u{2421}'),
+            _ => None,
+        };
+
+        match control_picture {
+            Some(control_picture) => text.push(control_picture),
+            None => {
+                let _ = write!(text, "<U+{:04X}>", c as u32);
+            }
+        }
+    }
+}
+
+// Lines are right-to-left when they contain right-to-left letters and no left-to-right ones.
+// Mixed lines aren't reordered, they're shown left-to-right in logical order.
+pub fn is_rtl_line(line: &str) -> bool {
+    let mut has_rtl_chars = false;
+
+    for c in line.chars() {
+        if is_rtl_char(c) {
+            has_rtl_chars = true;
+        } else if c.is_alphabetic() {
+            return false;
+        }
+    }
+
+    has_rtl_chars
+}
+
+// Hebrew, Arabic, Syriac, Thaana, and other scripts written from right to left.
+fn is_rtl_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFE}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct CharCursor {
     index: usize,
@@ -10,11 +10,24 @@ pub enum GraphemeCategory {
 
 impl GraphemeCategory {
     pub fn new(grapheme: &str) -> Self {
+        Self::with_word_chars(grapheme, "")
+    }
+
+    // Word chars are extra characters that count as part of identifiers, eg. "-" in CSS.
+    pub fn with_word_chars(grapheme: &str, word_chars: &str) -> Self {
+        let is_word_char = || {
+            let mut chars = grapheme.chars();
+
+            chars
+                .next()
+                .is_some_and(|c| chars.next().is_none() && word_chars.contains(c))
+        };
+
         if grapheme == "\n" {
             Self::Newline
         } else if grapheme::is_whitespace(grapheme) {
             Self::Space
-        } else if grapheme == "_" || grapheme::is_alphanumeric(grapheme) {
+        } else if grapheme == "_" || grapheme::is_alphanumeric(grapheme) || is_word_char() {
             Self::Identifier
         } else {
             Self::Symbol
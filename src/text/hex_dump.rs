@@ -0,0 +1,45 @@
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+// Text files rarely contain null bytes, so finding one near the start suggests a binary file.
+const BINARY_CHECK_LEN: usize = 8000;
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0)
+}
+
+// Lines look like: "00000010  6c 6f 20 77 6f 72 6c 64  0a 00 01 02 03 04 05 06  |lo world........|"
+pub fn write_hex_dump(bytes: &[u8], text: &mut String) {
+    for (i, line_bytes) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+
+        let _ = write!(text, "{:08x} ", i * BYTES_PER_LINE);
+
+        for j in 0..BYTES_PER_LINE {
+            if j % (BYTES_PER_LINE / 2) == 0 {
+                text.push(' ');
+            }
+
+            match line_bytes.get(j) {
+                Some(byte) => {
+                    let _ = write!(text, "{byte:02x} ");
+                }
+                None => text.push_str("   "),
+            }
+        }
+
+        text.push_str(" |");
+
+        for byte in line_bytes {
+            text.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            });
+        }
+
+        text.push('|');
+    }
+}
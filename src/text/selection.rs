@@ -1,6 +1,13 @@
 use crate::geometry::position::Position;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionStats {
+    pub chars: usize,
+    pub words: usize,
+    pub lines: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Selection {
     pub start: Position,
     pub end: Position,
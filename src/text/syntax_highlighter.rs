@@ -14,6 +14,8 @@ use super::{
 pub enum TerminalHighlightKind {
     Foreground,
     Background,
+    Black,
+    White,
     Red,
     Green,
     Yellow,
@@ -23,6 +25,8 @@ pub enum TerminalHighlightKind {
 
     BrightForeground,
     BrightBackground,
+    BrightBlack,
+    BrightWhite,
     BrightRed,
     BrightGreen,
     BrightYellow,
@@ -108,6 +112,31 @@ impl HighlightedLine {
     pub fn highlights(&self) -> &[Highlight] {
         &self.highlights
     }
+
+    pub fn is_string_or_comment_at(&self, x: usize, line_len: usize) -> bool {
+        let is_string_or_comment = |highlight: &Highlight| {
+            matches!(
+                highlight.foreground,
+                HighlightKind::String | HighlightKind::Comment
+            )
+        };
+
+        if x < line_len {
+            return self
+                .highlights
+                .iter()
+                .find(|highlight| highlight.start <= x && x < highlight.end)
+                .is_some_and(is_string_or_comment);
+        }
+
+        // At the end of the line only unfinished ranges and comments that reach the end are still open.
+        self.highlights.last().is_some_and(|highlight| {
+            highlight.end == line_len
+                && is_string_or_comment(highlight)
+                && (self.unfinished_range_index.is_some()
+                    || highlight.foreground == HighlightKind::Comment)
+        })
+    }
 }
 
 pub struct SyntaxHighlighter {
@@ -364,6 +393,22 @@ impl SyntaxHighlighter {
         }
     }
 
+    pub fn highlight_line_with_kind(&mut self, foreground: HighlightKind, len: usize, y: usize) {
+        if self.highlighted_lines.len() <= y {
+            self.highlighted_lines.resize(y + 1, HighlightedLine::new());
+        }
+
+        let highlighted_line = &mut self.highlighted_lines[y];
+
+        highlighted_line.clear();
+        highlighted_line.push(Highlight {
+            start: 0,
+            end: len,
+            foreground,
+            background: None,
+        });
+    }
+
     pub fn scroll_highlighted_lines(&mut self, region: RangeInclusive<usize>, delta_y: isize) {
         let start = *region.start();
         let end = *region.end();
@@ -1,4 +1,13 @@
-use crate::text::pattern::PatternMatch;
+use crate::text::{
+    case::preserve_case,
+    compare::{is_fuzzy_match, score_usage},
+    diff::{diff_lines, DiffHunk, DiffHunkKind},
+    emmet::{abbreviation_start, expand, EmmetSyntax},
+    grapheme,
+    hex_dump::{is_binary, write_hex_dump},
+    pattern::PatternMatch,
+    text_encoding::TextEncoding,
+};
 
 use super::pattern::Pattern;
 
@@ -142,3 +151,262 @@ fn match_negative_class() -> Result<(), &'static str> {
 fn prevent_capturing_nothing() {
     assert_eq!(Pattern::parse("a(%.*)b".into()), Err("may capture nothing"));
 }
+
+#[test]
+fn preserve_case_of_match() {
+    assert_eq!(*preserve_case("bar", "foo"), "bar");
+    assert_eq!(*preserve_case("bar", "FOO"), "BAR");
+    assert_eq!(*preserve_case("bar", "Foo"), "Bar");
+    assert_eq!(*preserve_case("barBaz", "Foo"), "Barbaz");
+    assert_eq!(*preserve_case("bar", "fOo"), "bar");
+    assert_eq!(*preserve_case("bar", "123"), "bar");
+}
+
+#[test]
+fn fuzzy_match_in_order() {
+    assert!(is_fuzzy_match("to_string", "tstr"));
+    assert!(is_fuzzy_match("ToString", "tostr"));
+    assert!(is_fuzzy_match("anything", ""));
+    assert!(!is_fuzzy_match("to_string", "strto"));
+    assert!(!is_fuzzy_match("len", "lens"));
+}
+
+#[test]
+fn usage_scores_favor_frequent_and_recent() {
+    assert_eq!(score_usage(0, None), 0.0);
+    assert!(score_usage(5, None) > score_usage(1, None));
+    assert!(score_usage(1, Some(0)) > score_usage(1, Some(3)));
+    assert!(score_usage(1, Some(3)) > score_usage(1, None));
+}
+
+#[test]
+fn invisible_graphemes() {
+    assert!(grapheme::is_invisible("\0"));
+    assert!(grapheme::is_invisible("\u{200B}"));
+    assert!(grapheme::is_invisible("\u{202E}"));
+    assert!(grapheme::is_invisible("\u{FEFF}"));
+    assert!(!grapheme::is_invisible("\t"));
+    assert!(!grapheme::is_invisible("a"));
+    assert!(!grapheme::is_invisible("\u{1F468}\u{200D}\u{1F469}"));
+}
+
+#[test]
+fn rtl_lines() {
+    assert!(grapheme::is_rtl_line("\u{05E9}\u{05DC}\u{05D5}\u{05DD}"));
+    assert!(grapheme::is_rtl_line(
+        "\u{0645}\u{0631}\u{062D}\u{0628}\u{0627} 123!"
+    ));
+    assert!(!grapheme::is_rtl_line(
+        "\u{05E9}\u{05DC}\u{05D5}\u{05DD} hello"
+    ));
+    assert!(!grapheme::is_rtl_line("hello world"));
+    assert!(!grapheme::is_rtl_line("123 !?"));
+    assert!(!grapheme::is_rtl_line(""));
+}
+
+#[test]
+fn invisible_grapheme_substitutes() {
+    let substitute = |grapheme| {
+        let mut text = String::new();
+        grapheme::push_invisible_substitute(grapheme, &mut text);
+        text
+    };
+
+    assert_eq!(substitute("\0"), "\u{2400}");
+    assert_eq!(substitute("\u{1B}"), "\u{241B}");
+    assert_eq!(substitute("\u{7F}"), "\u{2421}");
+    assert_eq!(substitute("\u{200B}"), "<U+200B>");
+    assert_eq!(substitute("\u{202E}"), "<U+202E>");
+    assert_eq!(substitute("\r\n"), "\u{240D}\u{240A}");
+}
+
+#[test]
+fn hex_dump_lines() {
+    let mut text = String::new();
+    write_hex_dump(b"hello world\n\x00\x01\x02\x03\xffA", &mut text);
+
+    assert_eq!(
+        text,
+        "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a 00 01 02 03  |hello world.....|\n\
+         00000010  ff 41                                             |.A|"
+    );
+}
+
+#[test]
+fn detect_binary_bytes() {
+    assert!(is_binary(b"\x7fELF\x02\x01\x01\x00"));
+    assert!(!is_binary("plain text \u{e9}".as_bytes()));
+}
+
+#[test]
+fn detect_encoding_from_bom() {
+    assert_eq!(
+        TextEncoding::detect(b"\xef\xbb\xbfhi"),
+        TextEncoding::Utf8Bom
+    );
+    assert_eq!(
+        TextEncoding::detect(b"\xff\xfeh\0"),
+        TextEncoding::Utf16LeBom
+    );
+    assert_eq!(
+        TextEncoding::detect(b"\xfe\xff\0h"),
+        TextEncoding::Utf16BeBom
+    );
+    assert_eq!(TextEncoding::detect(b"hi"), TextEncoding::Utf8);
+}
+
+#[test]
+fn encoding_round_trip() {
+    let text = "caf\u{e9}\r\nline";
+
+    for encoding in TextEncoding::ALL {
+        let mut bytes = Vec::new();
+        encoding.encode(text, &mut bytes).unwrap();
+
+        assert_eq!(
+            TextEncoding::detect(&bytes) == encoding,
+            matches!(
+                encoding,
+                TextEncoding::Utf8
+                    | TextEncoding::Utf8Bom
+                    | TextEncoding::Utf16LeBom
+                    | TextEncoding::Utf16BeBom
+            )
+        );
+        assert_eq!(encoding.decode(&bytes).as_deref(), Some(text));
+    }
+}
+
+#[test]
+fn encode_utf16_keeps_bom_state() {
+    let mut bytes = Vec::new();
+    TextEncoding::Utf16Le.encode("hi", &mut bytes).unwrap();
+
+    assert_eq!(bytes, b"h\0i\0");
+
+    bytes.clear();
+    TextEncoding::Utf16BeBom.encode("hi", &mut bytes).unwrap();
+
+    assert_eq!(bytes, b"\xfe\xff\0h\0i");
+    assert_eq!(
+        TextEncoding::Utf16Be.decode(b"\0h\0i").as_deref(),
+        Some("hi")
+    );
+}
+
+#[test]
+fn encode_unrepresentable_latin1() {
+    let mut bytes = Vec::new();
+
+    assert!(TextEncoding::Latin1
+        .encode("\u{1f600}", &mut bytes)
+        .is_err());
+}
+
+#[test]
+fn expand_html_abbreviation() {
+    let expansion = expand("ul#nav>li.item$*2>a", EmmetSyntax::Html, "  ", "\t").unwrap();
+
+    assert_eq!(
+        expansion.text,
+        concat!(
+            "<ul id=\"nav\">\n",
+            "  \t<li class=\"item1\">\n",
+            "  \t\t<a></a>\n",
+            "  \t</li>\n",
+            "  \t<li class=\"item2\">\n",
+            "  \t\t<a></a>\n",
+            "  \t</li>\n",
+            "  </ul>",
+        )
+    );
+
+    let tabstops: Vec<&str> = expansion
+        .tabstops
+        .iter()
+        .map(|offset| &expansion.text[..*offset])
+        .map(|text| &text[text.len() - 3..])
+        .collect();
+
+    assert_eq!(tabstops, ["<a>", "<a>"]);
+
+    let expansion = expand("div>p{hi}^.footer+br", EmmetSyntax::Html, "", "  ").unwrap();
+
+    assert_eq!(
+        expansion.text,
+        "<div>\n  <p>hi</p>\n</div>\n<div class=\"footer\"></div>\n<br>"
+    );
+
+    assert!(expand("ul>", EmmetSyntax::Html, "", "  ").is_none());
+}
+
+#[test]
+fn expand_large_html_abbreviation() {
+    let expand_html = |abbreviation| expand(abbreviation, EmmetSyntax::Html, "", "");
+
+    let expansion = expand_html("li*99999999").unwrap();
+    assert_eq!(expansion.text.matches("<li>").count(), 1000);
+
+    assert!(expand_html("ul*999>li*999").is_none());
+    assert!(expand_html("div*1000>div*1000>div*1000>div*1000>div*1000").is_none());
+    assert!(expand_html("li*99999999999999999999999").is_none());
+}
+
+#[test]
+fn expand_css_abbreviation() {
+    let expand_css = |abbreviation| expand(abbreviation, EmmetSyntax::Css, "", "").unwrap().text;
+
+    assert_eq!(expand_css("m10"), "margin: 10px;");
+    assert_eq!(expand_css("w50p"), "width: 50%;");
+    assert_eq!(expand_css("z-1"), "z-index: -1;");
+    assert_eq!(expand_css("df"), "display: flex;");
+    assert_eq!(expand("c", EmmetSyntax::Css, "", "").unwrap().tabstops, [7]);
+}
+
+#[test]
+fn find_abbreviation_start() {
+    assert_eq!(abbreviation_start("    <p>ul>li", EmmetSyntax::Html), 7);
+    assert_eq!(abbreviation_start("x p{a b}", EmmetSyntax::Html), 2);
+    assert_eq!(abbreviation_start("a { m10", EmmetSyntax::Css), 4);
+}
+
+#[test]
+fn diff_saved_and_current_lines() {
+    let saved = ["a", "b", "c", "d"];
+
+    assert_eq!(diff_lines(&saved, &saved), Vec::new());
+
+    assert_eq!(
+        diff_lines(&saved, &["a", "x", "b", "c", "d"]),
+        vec![DiffHunk {
+            kind: DiffHunkKind::Added,
+            lines: 1..2,
+            saved_lines: 1..1,
+        }]
+    );
+
+    assert_eq!(
+        diff_lines(&saved, &["a", "x", "c", "d"]),
+        vec![DiffHunk {
+            kind: DiffHunkKind::Modified,
+            lines: 1..2,
+            saved_lines: 1..2,
+        }]
+    );
+
+    assert_eq!(
+        diff_lines(&saved, &["b", "c"]),
+        vec![
+            DiffHunk {
+                kind: DiffHunkKind::Removed,
+                lines: 0..0,
+                saved_lines: 0..1,
+            },
+            DiffHunk {
+                kind: DiffHunkKind::Removed,
+                lines: 2..2,
+                saved_lines: 3..4,
+            },
+        ]
+    );
+}
@@ -0,0 +1,122 @@
+use std::io;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16LeBom,
+    Utf16Be,
+    Utf16BeBom,
+    Latin1,
+}
+
+impl TextEncoding {
+    pub const ALL: [Self; 7] = [
+        Self::Utf8,
+        Self::Utf8Bom,
+        Self::Utf16Le,
+        Self::Utf16LeBom,
+        Self::Utf16Be,
+        Self::Utf16BeBom,
+        Self::Latin1,
+    ];
+
+    // Files without a BOM are assumed to be UTF-8.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(UTF8_BOM) {
+            Self::Utf8Bom
+        } else if bytes.starts_with(UTF16_LE_BOM) {
+            Self::Utf16LeBom
+        } else if bytes.starts_with(UTF16_BE_BOM) {
+            Self::Utf16BeBom
+        } else {
+            Self::Utf8
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf8Bom => "UTF-8 with BOM",
+            Self::Utf16Le => "UTF-16 LE",
+            Self::Utf16LeBom => "UTF-16 LE with BOM",
+            Self::Utf16Be => "UTF-16 BE",
+            Self::Utf16BeBom => "UTF-16 BE with BOM",
+            Self::Latin1 => "Latin-1",
+        }
+    }
+
+    fn bom(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8 | Self::Utf16Le | Self::Utf16Be | Self::Latin1 => &[],
+            Self::Utf8Bom => UTF8_BOM,
+            Self::Utf16LeBom => UTF16_LE_BOM,
+            Self::Utf16BeBom => UTF16_BE_BOM,
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+
+        match self {
+            Self::Utf8 | Self::Utf8Bom => String::from_utf8(bytes.to_vec()).ok(),
+            Self::Utf16Le | Self::Utf16LeBom | Self::Utf16Be | Self::Utf16BeBom => {
+                if !bytes.len().is_multiple_of(2) {
+                    return None;
+                }
+
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|unit| {
+                        if matches!(self, Self::Utf16Le | Self::Utf16LeBom) {
+                            u16::from_le_bytes([unit[0], unit[1]])
+                        } else {
+                            u16::from_be_bytes([unit[0], unit[1]])
+                        }
+                    })
+                    .collect();
+
+                String::from_utf16(&units).ok()
+            }
+            Self::Latin1 => Some(bytes.iter().map(|byte| *byte as char).collect()),
+        }
+    }
+
+    pub fn encode(&self, text: &str, bytes: &mut Vec<u8>) -> io::Result<()> {
+        bytes.extend_from_slice(self.bom());
+
+        match self {
+            Self::Utf8 | Self::Utf8Bom => bytes.extend_from_slice(text.as_bytes()),
+            Self::Utf16Le | Self::Utf16LeBom => {
+                for unit in text.encode_utf16() {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            Self::Utf16Be | Self::Utf16BeBom => {
+                for unit in text.encode_utf16() {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            Self::Latin1 => {
+                for c in text.chars() {
+                    let Ok(byte) = u8::try_from(c) else {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("'{c}' can't be saved as {}", self.name()),
+                        ));
+                    };
+
+                    bytes.push(byte);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
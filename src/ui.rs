@@ -1,15 +1,17 @@
-mod camera;
+pub mod camera;
 pub mod color;
 pub mod command_palette;
 pub mod controller;
 pub mod core;
 pub mod editor;
+pub mod file_tree;
 pub mod msg;
 mod pane;
 mod pane_list;
 mod popup;
+pub mod problems_panel;
 mod result_list;
-mod slot_list;
+pub mod slot_list;
 pub mod status_bar;
 pub mod tab;
 pub mod terminal;
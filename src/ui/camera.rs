@@ -1,4 +1,9 @@
-use crate::{geometry::visual_position::VisualPosition, input::mouse_scroll::MouseScrollKind};
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    config::Config, geometry::visual_position::VisualPosition, input::mouse_scroll::MouseScrollKind,
+};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum CameraRecenterKind {
@@ -6,6 +11,10 @@ pub enum CameraRecenterKind {
     None,
     OnScrollBorder,
     OnCursor,
+    // Unlike the other kinds, these always move the camera, even if the cursor is visible.
+    CursorAtCenter,
+    CursorAtTop,
+    CursorAtBottom,
 }
 
 #[derive(Debug, Default)]
@@ -21,6 +30,21 @@ impl CameraRecenterRequest {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct CameraAnimation {
+    pub is_smooth: bool,
+    pub speed: f32,
+}
+
+impl CameraAnimation {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            is_smooth: config.smooth_scrolling,
+            speed: config.scroll_speed,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum CameraState {
     Locked,
@@ -63,6 +87,11 @@ impl CameraAxis {
         }
     }
 
+    // The scroll-off setting can widen the margin kept around the cursor, but never shrinks it.
+    pub fn scroll_border_lines(scroll_off: usize) -> usize {
+        scroll_off.max(Self::RECENTER_DISTANCE)
+    }
+
     pub fn is_moving(&self) -> bool {
         self.velocity != 0.0 || matches!(self.state, CameraState::MovingWithLerp { .. })
     }
@@ -72,6 +101,7 @@ impl CameraAxis {
         recenter_request: CameraRecenterRequest,
         max_position: f32,
         view_size: f32,
+        animation: CameraAnimation,
         dt: f32,
     ) {
         if matches!(self.state, CameraState::Locked) {
@@ -85,13 +115,31 @@ impl CameraAxis {
         self.handle_recenter_request(recenter_request, view_size);
         self.handle_recenter(view_size);
 
+        if !animation.is_smooth {
+            self.skip_to_target();
+            self.position = self.position.clamp(0.0, max_position);
+            return;
+        }
+
+        let dt = dt * animation.speed;
+
         if let CameraState::MovingWithLerp {
             target_position,
             is_end_expected,
         } = self.state
         {
+            // Keep the target reachable so that fast repeated scrolls don't build up a backlog
+            // of movement that the camera has to catch up on after hitting an edge.
+            let target_position = target_position.clamp(-view_size, max_position + view_size);
+
+            self.state = CameraState::MovingWithLerp {
+                target_position,
+                is_end_expected,
+            };
+
             self.velocity = 0.0;
-            self.position += (target_position - self.position) * dt * Self::PRECISE_SCROLL_SPEED;
+            self.position +=
+                (target_position - self.position) * (dt * Self::PRECISE_SCROLL_SPEED).min(1.0);
 
             let is_at_target = (self.position - target_position).abs() < 0.5
                 || (target_position < 0.0 && self.position < 0.0)
@@ -119,6 +167,25 @@ impl CameraAxis {
         self.position = self.position.clamp(0.0, max_position);
     }
 
+    fn skip_to_target(&mut self) {
+        if let CameraState::MovingWithLerp {
+            target_position,
+            is_end_expected,
+        } = self.state
+        {
+            self.position = target_position;
+
+            if !is_end_expected {
+                self.state = CameraState::MovingWithVelocity;
+            }
+        } else {
+            // Travel the full distance that the velocity would have covered while slowing down.
+            self.position += self.velocity / -Self::SCROLL_FRICTION.ln();
+        }
+
+        self.velocity = 0.0;
+    }
+
     pub fn skip_animation(
         &mut self,
         recenter_request: CameraRecenterRequest,
@@ -184,7 +251,15 @@ impl CameraAxis {
             return None;
         };
 
-        if !needs_recenter(target_position - self.position, scroll_border, view_size) {
+        let is_forced = matches!(
+            kind,
+            CameraRecenterKind::CursorAtCenter
+                | CameraRecenterKind::CursorAtTop
+                | CameraRecenterKind::CursorAtBottom
+        );
+
+        if !is_forced && !needs_recenter(target_position - self.position, scroll_border, view_size)
+        {
             self.state = CameraState::MovingWithVelocity;
             return None;
         }
@@ -202,13 +277,23 @@ impl CameraAxis {
                     target_position - scroll_border_max
                 }
             }
+            CameraRecenterKind::CursorAtTop => target_position - scroll_border_min,
+            CameraRecenterKind::CursorAtBottom => target_position - scroll_border_max,
             _ => target_position - view_size / 2.0,
         };
 
         // We can't move the camera past the top of the document,
         // (eg. if the cursor is on the first line, it might be too close to the edge of the
         // screen according to RECENTER_DISTANCE, but there's nothing we can do about it, so stop animating).
-        Some((visual_distance + self.position).clamp(0.0, self.max_position) - self.position)
+        let visual_distance =
+            (visual_distance + self.position).clamp(0.0, self.max_position) - self.position;
+
+        if is_forced && visual_distance.abs() < 0.5 {
+            self.state = CameraState::MovingWithVelocity;
+            return None;
+        }
+
+        Some(visual_distance)
     }
 
     pub fn recenter(&mut self, kind: CameraRecenterKind) {
@@ -277,6 +362,16 @@ impl CameraAxis {
         };
     }
 
+    pub fn jump_to(&mut self, position: f32) {
+        if matches!(self.state, CameraState::Locked) {
+            return;
+        }
+
+        self.position = position.clamp(0.0, self.max_position);
+        self.velocity = 0.0;
+        self.state = CameraState::MovingWithVelocity;
+    }
+
     pub fn jump_visual_distance(&mut self, visual_distance: f32) {
         self.position += visual_distance;
     }
@@ -307,6 +402,10 @@ impl CameraAxis {
     pub fn position(&self) -> f32 {
         self.position
     }
+
+    pub fn max_position(&self) -> f32 {
+        self.max_position
+    }
 }
 
 pub struct Camera {
@@ -0,0 +1,24 @@
+use crate::config::Config;
+
+use super::CameraAxis;
+
+#[test]
+fn default_scroll_off_keeps_scroll_border() {
+    let scroll_off = Config::default().scroll_off;
+
+    assert_eq!(
+        CameraAxis::scroll_border_lines(scroll_off),
+        CameraAxis::RECENTER_DISTANCE
+    );
+    assert_eq!(
+        CameraAxis::scroll_border_lines(0),
+        CameraAxis::RECENTER_DISTANCE
+    );
+}
+
+#[test]
+fn scroll_off_widens_scroll_border() {
+    let scroll_off = CameraAxis::RECENTER_DISTANCE + 6;
+
+    assert_eq!(CameraAxis::scroll_border_lines(scroll_off), scroll_off);
+}
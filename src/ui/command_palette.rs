@@ -1,16 +1,23 @@
 pub mod all_actions_mode;
+pub mod all_bookmarks_mode;
 pub mod all_diagnostics_mode;
 pub mod all_files_mode;
+mod buffer_results;
+pub mod compare_with_buffer_mode;
 pub mod file_explorer_mode;
 pub mod find_in_files_mode;
 pub mod go_to_line_mode;
 mod incremental_results;
+pub mod language_server_logs_mode;
 mod mode;
 pub mod references_mode;
-pub mod rename_mode;
+pub mod reopen_with_encoding_mode;
 pub mod search_mode;
+pub mod surround_mode;
+pub mod switch_buffer_mode;
+pub mod type_hierarchy_mode;
 
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use crate::{
     ctx::Ctx,
@@ -19,11 +26,19 @@ use crate::{
         rect::Rect,
         sides::{Side, Sides},
     },
-    input::{action::ActionName, editing_actions::handle_select_all},
+    input::{
+        action::{action_keybind, ActionName},
+        editing_actions::handle_select_all,
+        mods::Mods,
+    },
     lsp::{position_encoding::PositionEncoding, types::EncodedPosition},
     platform::gfx::Gfx,
     pool::Pooled,
-    text::doc::{Doc, DocFlags},
+    text::{
+        compare::score_usage,
+        doc::{Doc, DocFlags},
+        text_encoding::TextEncoding,
+    },
     ui::msg::Msg,
 };
 
@@ -49,6 +64,11 @@ pub enum CommandPaletteMetaData {
         path: Pooled<PathBuf>,
         position: Position,
     },
+    PathWithEncodedPosition {
+        path: Pooled<PathBuf>,
+        encoding: PositionEncoding,
+        position: EncodedPosition,
+    },
     DiagnosticWithPosition {
         path: Pooled<PathBuf>,
         position: Position,
@@ -60,6 +80,9 @@ pub enum CommandPaletteMetaData {
         position: EncodedPosition,
         severity: usize,
     },
+    LanguageServer((usize, usize)),
+    Encoding(TextEncoding),
+    Doc(SlotId),
 }
 
 pub enum CommandPaletteAction {
@@ -73,6 +96,11 @@ pub struct CommandPalette {
     doc: Doc,
     last_updated_version: Option<usize>,
 
+    history: HashMap<String, Vec<String>>,
+    history_index: Option<usize>,
+    use_counts: HashMap<String, HashMap<String, usize>>,
+    last_inputs: HashMap<String, String>,
+
     result_list: ResultList<CommandPaletteResult>,
 
     parent_bounds: Rect,
@@ -82,6 +110,7 @@ pub struct CommandPalette {
 
 impl CommandPalette {
     const MAX_VISIBLE_RESULTS: usize = 20;
+    const MAX_HISTORY_LEN: usize = 64;
 
     pub fn new(parent_id: WidgetId, ui: &mut Ui) -> Self {
         let widget_id = ui.new_widget(
@@ -102,6 +131,11 @@ impl CommandPalette {
             doc: Doc::new(None, None, DocFlags::SINGLE_LINE),
             last_updated_version: None,
 
+            history: HashMap::new(),
+            history_index: None,
+            use_counts: HashMap::new(),
+            last_inputs: HashMap::new(),
+
             result_list,
             parent_bounds: Rect::ZERO,
 
@@ -140,6 +174,10 @@ impl CommandPalette {
                 Msg::GainedFocus | Msg::FocusedChildChanged => {
                     ctx.ui.focus(self.result_list.widget_id())
                 }
+                Msg::Action(action_keybind!(key: Up, mods: Mods::NONE | Mods::ALT))
+                    if self.navigate_history(true, ctx) => {}
+                Msg::Action(action_keybind!(key: Down, mods: Mods::NONE | Mods::ALT))
+                    if self.navigate_history(false, ctx) => {}
                 Msg::Action(action) => {
                     let Some(mut mode) = self.mode.take() else {
                         continue;
@@ -212,6 +250,8 @@ impl CommandPalette {
 
     fn submit(&mut self, kind: ResultListSubmitKind, editor: &mut Editor, ctx: &mut Ctx) {
         self.complete_result(editor, ctx);
+        self.add_history_entry();
+        self.remember_input();
 
         let Some(mut mode) = self.mode.take() else {
             return;
@@ -226,6 +266,94 @@ impl CommandPalette {
         }
     }
 
+    fn add_history_entry(&mut self) {
+        self.history_index = None;
+
+        let Some(mode) = &self.mode else {
+            return;
+        };
+
+        let Some(entry) = mode.history_entry(self).filter(|entry| !entry.is_empty()) else {
+            return;
+        };
+
+        let entry = entry.to_owned();
+
+        *self
+            .use_counts
+            .entry(mode.title().to_owned())
+            .or_default()
+            .entry(entry.clone())
+            .or_default() += 1;
+
+        let history = self.history.entry(mode.title().to_owned()).or_default();
+
+        history.retain(|existing_entry| *existing_entry != entry);
+        history.push(entry);
+
+        if history.len() > Self::MAX_HISTORY_LEN {
+            history.remove(0);
+        }
+    }
+
+    fn remember_input(&mut self) {
+        let Some(mode) = self.mode.as_ref().filter(|mode| mode.remembers_input()) else {
+            return;
+        };
+
+        self.last_inputs
+            .insert(mode.title().to_owned(), self.input().to_owned());
+    }
+
+    fn navigate_history(&mut self, is_backward: bool, ctx: &mut Ctx) -> bool {
+        let Some(history) = self
+            .mode
+            .as_ref()
+            .and_then(|mode| self.history.get(mode.title()))
+        else {
+            return false;
+        };
+
+        let history_index = match (self.history_index, is_backward) {
+            (None, true) => history.len().checked_sub(1),
+            (None, false) => return false,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) => Some(index + 1).filter(|index| *index < history.len()),
+        };
+
+        self.history_index = history_index;
+        self.doc.clear(ctx);
+
+        if let Some(entry) = history_index.and_then(|index| history.get(index)) {
+            self.doc.insert(Position::ZERO, entry, ctx);
+        }
+
+        true
+    }
+
+    // Scores how often and how recently an entry was submitted this session in the given mode.
+    fn usage_score(
+        history: &HashMap<String, Vec<String>>,
+        use_counts: &HashMap<String, HashMap<String, usize>>,
+        title: &str,
+        entry: &str,
+    ) -> f32 {
+        let recency_rank = history.get(title).and_then(|history| {
+            history
+                .iter()
+                .rev()
+                .position(|history_entry| history_entry == entry)
+        });
+
+        let use_count = use_counts
+            .get(title)
+            .and_then(|use_counts| use_counts.get(entry))
+            .copied()
+            .unwrap_or_default();
+
+        score_usage(use_count, recency_rank)
+    }
+
     fn complete_result(&mut self, editor: &mut Editor, ctx: &mut Ctx) {
         let Some(mut mode) = self.mode.take() else {
             return;
@@ -305,6 +433,17 @@ impl CommandPalette {
             theme.normal,
         );
 
+        if let Some(status) = mode.status() {
+            let status_x = bounds.width - (gfx.measure_text(status) + 1) as f32 * gfx.glyph_width();
+
+            gfx.add_text(
+                status,
+                status_x,
+                gfx.border_width() + gfx.tab_padding_y(),
+                theme.subtle,
+            );
+        }
+
         let doc_bounds = self.tab.doc_bounds(ui);
 
         gfx.add_bordered_rect(
@@ -328,6 +467,7 @@ impl CommandPalette {
         ctx: &mut Ctx,
     ) {
         ctx.ui.focus(self.widget_id);
+        self.history_index = None;
 
         let do_reuse = self.mode.as_ref().is_some_and(|previous_mode| {
             previous_mode.is_reusable() && previous_mode.title() == mode.title()
@@ -340,6 +480,8 @@ impl CommandPalette {
             return;
         }
 
+        self.remember_input();
+
         self.doc.clear(ctx);
         self.tab.skip_cursor_animations(&self.doc, ctx);
         self.result_list.reset();
@@ -347,6 +489,12 @@ impl CommandPalette {
         self.mode = None;
 
         mode.on_open(self, CommandPaletteEventArgs::new(editor, ctx));
+
+        if let Some(last_input) = self.last_inputs.get(mode.title()) {
+            self.doc.insert(Position::ZERO, last_input, ctx);
+            handle_select_all(&mut self.doc, ctx.gfx);
+        }
+
         self.mode = Some(mode);
 
         self.update_results(editor, ctx);
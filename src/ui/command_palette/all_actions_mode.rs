@@ -1,7 +1,10 @@
 use crate::{
     input::action::{Action, ActionName},
-    pool::format_pooled,
-    text::grapheme::{self, CharCursor},
+    pool::{format_pooled, Pooled},
+    text::{
+        compare::{compare_ignore_ascii_case, is_fuzzy_match, score_fuzzy_match},
+        grapheme::{self, CharCursor},
+    },
     ui::{core::WidgetId, msg::Msg, result_list::ResultListSubmitKind},
 };
 
@@ -17,7 +20,7 @@ impl CommandPaletteMode for AllActionsMode {
         "All Actions"
     }
 
-    fn on_open(&mut self, command_palette: &mut CommandPalette, _: CommandPaletteEventArgs) {
+    fn on_open(&mut self, command_palette: &mut CommandPalette, args: CommandPaletteEventArgs) {
         for action_name in ActionName::VARIANTS {
             let mut text = format_pooled!("{:?}", action_name);
 
@@ -41,6 +44,26 @@ impl CommandPaletteMode for AllActionsMode {
                 char_cursor.previous_boundary(&text);
             }
 
+            let mut keybind_texts: Vec<Pooled<String>> = args
+                .ctx
+                .config
+                .keymaps
+                .iter()
+                .filter(|(_, keymap_action_name)| *keymap_action_name == action_name)
+                .map(|(keybind, _)| format_pooled!("{}", keybind))
+                .collect();
+
+            keybind_texts.sort_by(|a, b| a.cmp(b));
+
+            for (i, keybind_text) in keybind_texts.iter().enumerate() {
+                text.push_str(if i == 0 { " (" } else { ", " });
+                text.push_str(keybind_text);
+            }
+
+            if !keybind_texts.is_empty() {
+                text.push(')');
+            }
+
             command_palette.result_list.push(CommandPaletteResult {
                 text,
                 meta_data: CommandPaletteMetaData::ActionName(*action_name),
@@ -68,4 +91,45 @@ impl CommandPaletteMode for AllActionsMode {
 
         CommandPaletteAction::Close
     }
+
+    fn on_update_results(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        _: CommandPaletteEventArgs,
+    ) {
+        command_palette.result_list.set_focused_index(0);
+
+        let title = self.title();
+        let input = command_palette.doc.get_line(0).unwrap_or_default();
+        let history = &command_palette.history;
+        let use_counts = &command_palette.use_counts;
+
+        // Commonly used actions are ranked higher, but only among the ones that match the input.
+        let score = |text: &str| {
+            let usage_score = if is_fuzzy_match(text, input) {
+                CommandPalette::usage_score(history, use_counts, title, text)
+            } else {
+                0.0
+            };
+
+            score_fuzzy_match(text, input) + usage_score
+        };
+
+        command_palette.result_list.sort_by(|a, b| {
+            score(&b.text).total_cmp(&score(&a.text)).then_with(|| {
+                if input.is_empty() {
+                    compare_ignore_ascii_case(&a.text, &b.text)
+                } else {
+                    a.text.len().cmp(&b.text.len())
+                }
+            })
+        });
+    }
+
+    fn history_entry<'a>(&self, command_palette: &'a CommandPalette) -> Option<&'a str> {
+        command_palette
+            .result_list
+            .get_focused()
+            .map(|result| result.text.as_str())
+    }
 }
@@ -0,0 +1,38 @@
+use crate::{geometry::position::Position, ui::result_list::ResultListSubmitKind};
+
+use super::{
+    find_in_files_mode::FindInFilesMode,
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction,
+};
+
+pub struct AllBookmarksMode;
+
+impl CommandPaletteMode for AllBookmarksMode {
+    fn title(&self) -> &str {
+        "All Bookmarks"
+    }
+
+    fn on_open(&mut self, command_palette: &mut CommandPalette, args: CommandPaletteEventArgs) {
+        for doc in args.editor.docs() {
+            for y in doc.bookmarks() {
+                let position = Position::new(0, y);
+
+                if let Some(result) =
+                    FindInFilesMode::position_to_result(position, args.ctx.current_dir, doc)
+                {
+                    command_palette.result_list.push(result);
+                }
+            }
+        }
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        kind: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        FindInFilesMode::jump_to_path_with_position(command_palette, args, kind)
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 };
 
 use crate::{
-    ctx::Ctx,
+    config::ignore::IgnoreRules,
     pool::{format_pooled, Pooled, PATH_POOL},
     ui::result_list::ResultListSubmitKind,
 };
@@ -19,6 +19,7 @@ use super::{
 
 pub struct AllFilesMode {
     root: PathBuf,
+    ignore_rules: Option<IgnoreRules>,
     needs_new_results: bool,
     pending_dir_entries: VecDeque<ReadDir>,
     pending_results: Vec<CommandPaletteResult>,
@@ -28,20 +29,30 @@ impl AllFilesMode {
     pub fn new() -> Self {
         Self {
             root: PathBuf::new(),
+            ignore_rules: None,
             needs_new_results: false,
             pending_dir_entries: VecDeque::new(),
             pending_results: Vec::new(),
         }
     }
 
-    fn handle_entry(&mut self, entry: DirEntry, ctx: &mut Ctx) {
+    fn handle_entry(&mut self, entry: DirEntry) {
         let path = Pooled::new(entry.path(), &PATH_POOL);
+        let is_dir = path.is_dir();
 
-        if ctx.config.is_path_ignored(&path) {
+        if self
+            .ignore_rules
+            .as_ref()
+            .is_some_and(|ignore_rules| ignore_rules.is_ignored(&path, is_dir))
+        {
             return;
         }
 
-        if path.is_dir() {
+        if is_dir {
+            if let Some(ignore_rules) = &mut self.ignore_rules {
+                ignore_rules.load_dir(&path);
+            }
+
             if let Ok(entries) = read_dir(path) {
                 self.pending_dir_entries.push_back(entries);
             }
@@ -85,6 +96,7 @@ impl CommandPaletteMode for AllFilesMode {
 
         self.root.clear();
         self.root.push(&args.ctx.current_dir);
+        self.ignore_rules = Some(IgnoreRules::load(&self.root, args.ctx.config));
 
         if let Ok(entries) = read_dir(&self.root) {
             self.pending_dir_entries.push_back(entries);
@@ -127,7 +139,7 @@ impl CommandPaletteMode for AllFilesMode {
                     continue;
                 };
 
-                self.handle_entry(entry, args.ctx);
+                self.handle_entry(entry);
 
                 if start_time.elapsed().as_secs_f32() > IncrementalResults::TARGET_STEP_TIME {
                     self.pending_dir_entries.push_front(entries);
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use crate::{pool::format_pooled, text::compare::score_fuzzy_match, ui::slot_list::SlotId};
+
+use super::{
+    mode::CommandPaletteEventArgs, CommandPalette, CommandPaletteMetaData, CommandPaletteResult,
+};
+
+// Lists open buffers in recently used order, optionally leaving one of them out.
+pub fn push_buffer_results(
+    command_palette: &mut CommandPalette,
+    args: &CommandPaletteEventArgs,
+    skipped_doc_id: Option<SlotId>,
+) {
+    command_palette.result_list.drain();
+
+    let current_dir: &Path = args.ctx.current_dir;
+
+    for (doc_id, doc) in args.editor.recent_docs() {
+        if Some(doc_id) == skipped_doc_id {
+            continue;
+        }
+
+        let text = match doc
+            .path()
+            .on_drive()
+            .and_then(|path| path.strip_prefix(current_dir).ok())
+        {
+            Some(relative_path) => format_pooled!("{}", relative_path.display()),
+            None => doc.file_name().into(),
+        };
+
+        command_palette.result_list.push(CommandPaletteResult {
+            text,
+            meta_data: CommandPaletteMetaData::Doc(doc_id),
+        });
+    }
+}
+
+// The sort is stable, so buffers that match equally well stay in recently used order.
+pub fn sort_buffer_results(command_palette: &mut CommandPalette) {
+    command_palette.result_list.sort_by(|a, b| {
+        let input = command_palette.doc.get_line(0).unwrap_or_default();
+
+        let a_score = score_fuzzy_match(&a.text, input);
+        let b_score = score_fuzzy_match(&b.text, input);
+
+        b_score.total_cmp(&a_score)
+    });
+}
@@ -0,0 +1,62 @@
+use crate::ui::result_list::ResultListSubmitKind;
+
+use super::{
+    buffer_results::{push_buffer_results, sort_buffer_results},
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction, CommandPaletteMetaData, CommandPaletteResult,
+};
+
+pub struct CompareWithBufferMode;
+
+impl CommandPaletteMode for CompareWithBufferMode {
+    fn title(&self) -> &str {
+        "Compare with Buffer"
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        _: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        let Some(CommandPaletteResult {
+            meta_data: CommandPaletteMetaData::Doc(other_doc_id),
+            ..
+        }) = command_palette.result_list.get_focused()
+        else {
+            return CommandPaletteAction::Close;
+        };
+
+        let (pane, doc_list) = args.editor.last_focused_pane_and_doc_list(args.ctx.ui);
+
+        let Some(doc_id) = pane
+            .get_focused_tab_with_data(doc_list, args.ctx.ui)
+            .map(|(tab, _)| tab.data_id())
+        else {
+            return CommandPaletteAction::Close;
+        };
+
+        args.editor.open_diff(doc_id, *other_doc_id, args.ctx);
+
+        CommandPaletteAction::Close
+    }
+
+    fn on_update_results(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+    ) {
+        let (pane, doc_list) = args.editor.last_focused_pane_and_doc_list(args.ctx.ui);
+        let focused_doc_id = pane
+            .get_focused_tab_with_data(doc_list, args.ctx.ui)
+            .map(|(tab, _)| tab.data_id());
+
+        push_buffer_results(command_palette, &args, focused_doc_id);
+
+        command_palette.result_list.set_focused_index(0);
+
+        if !command_palette.input().is_empty() {
+            sort_buffer_results(command_palette);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    fs::{self, copy, create_dir_all, read_dir, remove_file},
+    fs::{copy, create_dir_all, read_dir},
     io,
     path::{Component, Path, PathBuf},
 };
@@ -10,8 +10,12 @@ use crate::{
     geometry::position::Position,
     input::action::{action_name, Action},
     normalizable::Normalizable,
-    platform::{gfx::Gfx, recycle::recycle},
-    pool::{Pooled, PATH_POOL, STRING_POOL},
+    platform::{
+        dialog::{message, MessageKind, MessageResponse},
+        gfx::Gfx,
+        recycle::recycle,
+    },
+    pool::{format_pooled, Pooled, PATH_POOL, STRING_POOL},
     text::{cursor_index::CursorIndex, doc::Doc},
     ui::{color::Color, core::Ui, editor::Editor, result_list::ResultListSubmitKind},
 };
@@ -24,7 +28,7 @@ use super::{
 #[cfg(target_os = "windows")]
 const PATH_SEPARATORS: &[&str] = &["/", "\\"];
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 const PATH_SEPARATORS: &[&str] = &["/"];
 
 const PREFERRED_PATH_SEPARATOR: &str = "/";
@@ -108,7 +112,7 @@ impl FileExplorerMode {
             let mut new_path = path.clone();
             new_path.set_file_name(text);
 
-            let _ = rename(path, new_path, args.editor, args.ctx);
+            let _ = args.editor.rename_file(path, new_path, args.ctx);
         }
 
         command_palette.doc.clear(args.ctx);
@@ -275,6 +279,23 @@ impl CommandPaletteMode for FileExplorerMode {
                 true
             }
             action_name!(DeleteForward) if cursor.position == doc.end() => {
+                if let Some(CommandPaletteResult {
+                    meta_data: CommandPaletteMetaData::Path(path),
+                    ..
+                }) = command_palette.result_list.get_focused()
+                {
+                    let text = format_pooled!(
+                        "Do you want to move {} to the recycle bin?",
+                        path.display()
+                    );
+
+                    if message("Delete File", &text, MessageKind::YesNo, args.ctx.window)
+                        != MessageResponse::Yes
+                    {
+                        return true;
+                    }
+                }
+
                 let focused_result_index = command_palette.result_list.focused_index();
                 let mut deleted_path = None;
 
@@ -335,7 +356,8 @@ impl CommandPaletteMode for FileExplorerMode {
 
                         copy(&self.clipboard_path, path).is_ok()
                     } else {
-                        rename(&self.clipboard_path, path, args.editor, args.ctx)
+                        args.editor
+                            .rename_file(&self.clipboard_path, path, args.ctx)
                             .inspect(|_| self.clear_clipboard())
                             .is_ok()
                     };
@@ -565,16 +587,3 @@ fn update_path_for_copy(path: &mut PathBuf) {
 
     path.set_file_name(file_name);
 }
-
-fn rename(from: &Path, to: Pooled<PathBuf>, editor: &mut Editor, ctx: &mut Ctx) -> io::Result<()> {
-    let from = from.normalized(ctx.current_dir)?;
-
-    if let Some(doc) = editor.find_doc_mut(&from) {
-        remove_file(from)?;
-        doc.save(Some(to), ctx)?;
-
-        return Ok(());
-    }
-
-    fs::rename(from, to)
-}
@@ -6,14 +6,15 @@ use std::{
 };
 
 use crate::{
+    config::ignore::IgnoreRules,
     ctx::Ctx,
     geometry::position::Position,
-    pool::{format_pooled, Pooled, PATH_POOL},
+    pool::{format_pooled, Pooled, PATH_POOL, STRING_POOL},
     text::{
         cursor_index::CursorIndex,
         doc::{Doc, DocFlags},
     },
-    ui::result_list::ResultListSubmitKind,
+    ui::{editor::Editor, result_list::ResultListSubmitKind},
 };
 
 use super::{
@@ -24,6 +25,7 @@ use super::{
 
 pub struct FindInFilesMode {
     root: PathBuf,
+    ignore_rules: Option<IgnoreRules>,
     incremental_results: IncrementalResults,
     pending_doc: Option<Doc>,
     pending_dir_entries: VecDeque<ReadDir>,
@@ -35,6 +37,7 @@ impl FindInFilesMode {
     pub fn new() -> Self {
         Self {
             root: PathBuf::new(),
+            ignore_rules: None,
             incremental_results: IncrementalResults::new(Some(Self::MAX_RESULTS_LEN)),
             pending_doc: None,
             pending_dir_entries: VecDeque::new(),
@@ -78,6 +81,7 @@ impl FindInFilesMode {
         let Some(CommandPaletteResult {
             meta_data:
                 meta_data @ (CommandPaletteMetaData::PathWithPosition { path, .. }
+                | CommandPaletteMetaData::PathWithEncodedPosition { path, .. }
                 | CommandPaletteMetaData::DiagnosticWithPosition { path, .. }
                 | CommandPaletteMetaData::DiagnosticWithEncodedPosition { path, .. }),
             ..
@@ -98,6 +102,9 @@ impl FindInFilesMode {
 
         let position = match meta_data {
             CommandPaletteMetaData::PathWithPosition { position, .. } => *position,
+            CommandPaletteMetaData::PathWithEncodedPosition {
+                encoding, position, ..
+            } => position.decode(*encoding, doc),
             CommandPaletteMetaData::DiagnosticWithPosition { position, .. } => *position,
             CommandPaletteMetaData::DiagnosticWithEncodedPosition {
                 encoding, position, ..
@@ -116,15 +123,25 @@ impl FindInFilesMode {
         entry: DirEntry,
         start_time: Instant,
         command_palette: &mut CommandPalette,
+        editor: &Editor,
         ctx: &mut Ctx,
     ) {
         let path = entry.path();
+        let is_dir = path.is_dir();
 
-        if ctx.config.is_path_ignored(&path) {
+        if self
+            .ignore_rules
+            .as_ref()
+            .is_some_and(|ignore_rules| ignore_rules.is_ignored(&path, is_dir))
+        {
             return;
         }
 
-        if path.is_dir() {
+        if is_dir {
+            if let Some(ignore_rules) = &mut self.ignore_rules {
+                ignore_rules.load_dir(&path);
+            }
+
             if let Ok(entries) = read_dir(path) {
                 self.pending_dir_entries.push_back(entries);
             }
@@ -133,9 +150,24 @@ impl FindInFilesMode {
         }
 
         let path = Pooled::new(path, &PATH_POOL);
+
+        // Open docs are searched using their current contents, which may not be saved yet.
+        let open_doc_text = editor.find_doc(&path).map(|open_doc| {
+            STRING_POOL
+                .init_item(|text| open_doc.collect_string(Position::ZERO, open_doc.end(), text))
+        });
+
         let mut doc = Doc::new(Some(path), None, DocFlags::RAW);
 
-        if doc.load(ctx).is_ok() {
+        let is_loaded = if let Some(open_doc_text) = open_doc_text {
+            doc.insert(Position::ZERO, &open_doc_text, ctx);
+
+            true
+        } else {
+            doc.load(ctx).is_ok()
+        };
+
+        if is_loaded {
             self.pending_doc = Some(doc);
             self.handle_doc(start_time, command_palette, ctx);
         }
@@ -229,6 +261,7 @@ impl CommandPaletteMode for FindInFilesMode {
 
         self.root.clear();
         self.root.push(&args.ctx.current_dir);
+        self.ignore_rules = Some(IgnoreRules::load(&self.root, args.ctx.config));
 
         if let Ok(entries) = read_dir(&self.root) {
             self.pending_dir_entries.push_back(entries);
@@ -254,7 +287,7 @@ impl CommandPaletteMode for FindInFilesMode {
                     continue;
                 };
 
-                self.handle_entry(entry, start_time, command_palette, args.ctx);
+                self.handle_entry(entry, start_time, command_palette, args.editor, args.ctx);
 
                 match self
                     .incremental_results
@@ -279,4 +312,12 @@ impl CommandPaletteMode for FindInFilesMode {
     fn is_animating(&self) -> bool {
         !self.incremental_results.is_finished()
     }
+
+    fn history_entry<'a>(&self, command_palette: &'a CommandPalette) -> Option<&'a str> {
+        Some(command_palette.input())
+    }
+
+    fn remembers_input(&self) -> bool {
+        true
+    }
 }
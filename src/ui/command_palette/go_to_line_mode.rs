@@ -39,4 +39,8 @@ impl CommandPaletteMode for GoToLineMode {
 
         CommandPaletteAction::Close
     }
+
+    fn history_entry<'a>(&self, command_palette: &'a CommandPalette) -> Option<&'a str> {
+        Some(command_palette.input())
+    }
 }
@@ -0,0 +1,48 @@
+use crate::{pool::format_pooled, ui::result_list::ResultListSubmitKind};
+
+use super::{
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction, CommandPaletteMetaData, CommandPaletteResult,
+};
+
+pub struct LanguageServerLogsMode;
+
+impl CommandPaletteMode for LanguageServerLogsMode {
+    fn title(&self) -> &str {
+        "Language Server Logs"
+    }
+
+    fn on_open(&mut self, command_palette: &mut CommandPalette, args: CommandPaletteEventArgs) {
+        for (server_key, server) in args.ctx.lsp.iter_servers() {
+            let (language_index, _) = server_key;
+
+            let Some(language) = args.ctx.config.languages.get(language_index) else {
+                continue;
+            };
+
+            command_palette.result_list.push(CommandPaletteResult {
+                text: format_pooled!("{} ({})", server.name(), language.name),
+                meta_data: CommandPaletteMetaData::LanguageServer(server_key),
+            });
+        }
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        _: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        let Some(CommandPaletteResult {
+            meta_data: CommandPaletteMetaData::LanguageServer(server_key),
+            ..
+        }) = command_palette.result_list.get_focused()
+        else {
+            return CommandPaletteAction::Stay;
+        };
+
+        args.editor.open_language_server_log(*server_key, args.ctx);
+
+        CommandPaletteAction::Close
+    }
+}
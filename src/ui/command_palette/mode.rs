@@ -78,6 +78,11 @@ pub trait CommandPaletteMode {
         (&result.text, theme.normal)
     }
 
+    // Shown next to the title, eg. to give the number of matches.
+    fn status(&self) -> Option<&str> {
+        None
+    }
+
     fn is_animating(&self) -> bool {
         false
     }
@@ -85,4 +90,13 @@ pub trait CommandPaletteMode {
     fn is_reusable(&self) -> bool {
         false
     }
+
+    fn remembers_input(&self) -> bool {
+        false
+    }
+
+    // Returning None opts the mode out of input history.
+    fn history_entry<'a>(&self, _: &'a CommandPalette) -> Option<&'a str> {
+        None
+    }
 }
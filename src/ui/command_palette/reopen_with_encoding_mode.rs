@@ -0,0 +1,53 @@
+use crate::{
+    platform::dialog::{message, MessageKind},
+    text::text_encoding::TextEncoding,
+    ui::result_list::ResultListSubmitKind,
+};
+
+use super::{
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction, CommandPaletteMetaData, CommandPaletteResult,
+};
+
+pub struct ReopenWithEncodingMode;
+
+impl CommandPaletteMode for ReopenWithEncodingMode {
+    fn title(&self) -> &str {
+        "Reopen with Encoding"
+    }
+
+    fn on_open(&mut self, command_palette: &mut CommandPalette, _: CommandPaletteEventArgs) {
+        for encoding in TextEncoding::ALL {
+            command_palette.result_list.push(CommandPaletteResult {
+                text: encoding.name().into(),
+                meta_data: CommandPaletteMetaData::Encoding(encoding),
+            });
+        }
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        _: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        let Some(CommandPaletteResult {
+            meta_data: CommandPaletteMetaData::Encoding(encoding),
+            ..
+        }) = command_palette.result_list.get_focused()
+        else {
+            return CommandPaletteAction::Stay;
+        };
+
+        if let Err(err) = args.editor.reopen_with_encoding(*encoding, args.ctx) {
+            message(
+                "Error Reopening File",
+                &err.to_string(),
+                MessageKind::Ok,
+                args.ctx.window,
+            );
+        }
+
+        CommandPaletteAction::Close
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
     ctx::Ctx,
     geometry::position::Position,
+    input::action::{action_name, Action},
     platform::gfx::Gfx,
-    text::{cursor_index::CursorIndex, doc::Doc, selection::Selection},
+    pool::{format_pooled, Pooled},
+    text::{case::preserve_case, cursor_index::CursorIndex, doc::Doc, selection::Selection},
     ui::{core::Ui, editor::Editor, result_list::ResultListSubmitKind, tab::Tab},
 };
 
@@ -13,23 +15,50 @@ use super::{
 
 pub struct SearchMode {
     start: Position,
+    selections: Vec<Selection>,
+    is_in_selection: bool,
+    status: Option<Pooled<String>>,
 }
 
 impl SearchMode {
     pub fn new() -> Self {
         Self {
             start: Position::ZERO,
+            selections: Vec::new(),
+            is_in_selection: false,
+            status: None,
         }
     }
 }
 
 impl CommandPaletteMode for SearchMode {
     fn title(&self) -> &str {
-        "Search"
+        if self.is_in_selection {
+            "Search in Selection"
+        } else {
+            "Search"
+        }
     }
 
     fn on_open(&mut self, _: &mut CommandPalette, args: CommandPaletteEventArgs) {
         self.start = start(args.editor, args.ctx.ui);
+        self.selections = selections(args.editor, args.ctx.ui);
+    }
+
+    fn on_action(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        action: Action,
+    ) -> bool {
+        if !matches!(action, action_name!(ToggleSearchInSelection)) {
+            return false;
+        }
+
+        self.is_in_selection = !self.is_in_selection && !self.selections.is_empty();
+        self.on_update_results(command_palette, args);
+
+        true
     }
 
     fn on_update_results(
@@ -37,7 +66,15 @@ impl CommandPaletteMode for SearchMode {
         command_palette: &mut CommandPalette,
         args: CommandPaletteEventArgs,
     ) {
-        preview_search(self.start, command_palette, args.editor, args.ctx);
+        let selections = self.is_in_selection.then_some(self.selections.as_slice());
+
+        self.status = preview_search(
+            self.start,
+            selections,
+            command_palette,
+            args.editor,
+            args.ctx,
+        );
     }
 
     fn on_submit(
@@ -53,10 +90,12 @@ impl CommandPaletteMode for SearchMode {
         };
 
         let search_term = command_palette.input();
+        let selections = self.is_in_selection.then_some(self.selections.as_slice());
 
-        search(
+        self.status = search(
             search_term,
             None,
+            selections,
             tab,
             doc,
             kind == ResultListSubmitKind::Alternate,
@@ -66,14 +105,29 @@ impl CommandPaletteMode for SearchMode {
         CommandPaletteAction::Stay
     }
 
+    fn status(&self) -> Option<&str> {
+        self.status.as_deref().map(String::as_str)
+    }
+
     fn is_reusable(&self) -> bool {
         true
     }
+
+    fn history_entry<'a>(&self, command_palette: &'a CommandPalette) -> Option<&'a str> {
+        Some(command_palette.input())
+    }
+
+    fn remembers_input(&self) -> bool {
+        true
+    }
 }
 
 pub struct SearchAndReplaceMode {
     start: Position,
     search_term: Option<String>,
+    selections: Vec<Selection>,
+    is_in_selection: bool,
+    status: Option<Pooled<String>>,
 }
 
 impl SearchAndReplaceMode {
@@ -81,21 +135,79 @@ impl SearchAndReplaceMode {
         Self {
             start: Position::ZERO,
             search_term: None,
+            selections: Vec::new(),
+            is_in_selection: false,
+            status: None,
         }
     }
+
+    fn replace_all(&mut self, command_palette: &CommandPalette, args: CommandPaletteEventArgs) {
+        let Some(search_term) = &self.search_term else {
+            return;
+        };
+
+        let (pane, doc_list) = args.editor.last_focused_pane_and_doc_list_mut(args.ctx.ui);
+
+        let Some((_, doc)) = pane.get_focused_tab_with_data_mut(doc_list, args.ctx.ui) else {
+            return;
+        };
+
+        let mut whole_doc = [Selection {
+            start: Position::ZERO,
+            end: doc.end(),
+        }];
+
+        let selections = if self.is_in_selection {
+            &mut self.selections[..]
+        } else {
+            &mut whole_doc[..]
+        };
+
+        let count = doc.replace_all_in(
+            search_term,
+            command_palette.input(),
+            args.ctx.config.preserve_case_on_replace,
+            selections,
+            args.ctx,
+        );
+
+        self.status = Some(format_pooled!("Replaced {}", count));
+    }
 }
 
 impl CommandPaletteMode for SearchAndReplaceMode {
     fn title(&self) -> &str {
-        if self.search_term.is_none() {
-            "Search and Replace: Search"
-        } else {
-            "Search and Replace: Replace"
+        match (self.search_term.is_none(), self.is_in_selection) {
+            (true, false) => "Search and Replace: Search",
+            (false, false) => "Search and Replace: Replace",
+            (true, true) => "Search and Replace in Selection: Search",
+            (false, true) => "Search and Replace in Selection: Replace",
         }
     }
 
     fn on_open(&mut self, _: &mut CommandPalette, args: CommandPaletteEventArgs) {
         self.start = start(args.editor, args.ctx.ui);
+        self.selections = selections(args.editor, args.ctx.ui);
+    }
+
+    fn on_action(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        action: Action,
+    ) -> bool {
+        match action {
+            action_name!(ToggleSearchInSelection) => {
+                self.is_in_selection = !self.is_in_selection && !self.selections.is_empty();
+                self.on_update_results(command_palette, args);
+            }
+            action_name!(ReplaceAll) if self.search_term.is_some() => {
+                self.replace_all(command_palette, args);
+            }
+            _ => return false,
+        }
+
+        true
     }
 
     fn on_update_results(
@@ -107,7 +219,15 @@ impl CommandPaletteMode for SearchAndReplaceMode {
             return;
         }
 
-        preview_search(self.start, command_palette, args.editor, args.ctx);
+        let selections = self.is_in_selection.then_some(self.selections.as_slice());
+
+        self.status = preview_search(
+            self.start,
+            selections,
+            command_palette,
+            args.editor,
+            args.ctx,
+        );
     }
 
     fn on_submit(
@@ -142,7 +262,14 @@ impl CommandPaletteMode for SearchAndReplaceMode {
                 if has_match {
                     let replace_term = command_palette.input();
 
-                    doc.insert_at_cursor(CursorIndex::Main, replace_term, args.ctx);
+                    if args.ctx.config.preserve_case_on_replace {
+                        let matched = &doc.get_line(start.y).unwrap_or_default()[start.x..end.x];
+                        let replace_term = preserve_case(replace_term, matched);
+
+                        doc.insert_at_cursor(CursorIndex::Main, &replace_term, args.ctx);
+                    } else {
+                        doc.insert_at_cursor(CursorIndex::Main, replace_term, args.ctx);
+                    }
 
                     let end = doc.cursor(CursorIndex::Main).position;
 
@@ -154,9 +281,12 @@ impl CommandPaletteMode for SearchAndReplaceMode {
             }
         }
 
-        search(
+        let selections = self.is_in_selection.then_some(self.selections.as_slice());
+
+        self.status = search(
             search_term,
             None,
+            selections,
             tab,
             doc,
             kind == ResultListSubmitKind::Alternate,
@@ -165,6 +295,18 @@ impl CommandPaletteMode for SearchAndReplaceMode {
 
         CommandPaletteAction::Stay
     }
+
+    fn status(&self) -> Option<&str> {
+        self.status.as_deref().map(String::as_str)
+    }
+
+    fn history_entry<'a>(&self, command_palette: &'a CommandPalette) -> Option<&'a str> {
+        Some(command_palette.input())
+    }
+
+    fn remembers_input(&self) -> bool {
+        true
+    }
 }
 
 fn start(editor: &Editor, ui: &Ui) -> Position {
@@ -177,35 +319,65 @@ fn start(editor: &Editor, ui: &Ui) -> Position {
     doc.cursor(CursorIndex::Main).position
 }
 
+// Searching within the selection uses the selections from when the search was opened,
+// since the search itself moves the cursors.
+fn selections(editor: &Editor, ui: &Ui) -> Vec<Selection> {
+    let (pane, doc_list) = editor.last_focused_pane_and_doc_list(ui);
+
+    let Some((_, doc)) = pane.get_focused_tab_with_data(doc_list, ui) else {
+        return Vec::new();
+    };
+
+    let mut selections: Vec<Selection> = doc
+        .cursor_indices()
+        .filter_map(|index| doc.cursor(index).get_selection())
+        .collect();
+
+    selections.sort_by_key(|selection| selection.start);
+    selections
+}
+
 fn preview_search(
     start: Position,
+    selections: Option<&[Selection]>,
     command_palette: &CommandPalette,
     editor: &mut Editor,
     ctx: &mut Ctx,
-) {
+) -> Option<Pooled<String>> {
     let (pane, doc_list) = editor.last_focused_pane_and_doc_list_mut(ctx.ui);
-
-    let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) else {
-        return;
-    };
+    let (tab, doc) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui)?;
 
     let search_term = command_palette.input();
 
-    search(search_term, Some(start), tab, doc, false, ctx.gfx);
+    search(
+        search_term,
+        Some(start),
+        selections,
+        tab,
+        doc,
+        false,
+        ctx.gfx,
+    )
 }
 
 fn search(
     search_term: &str,
     start: Option<Position>,
+    selections: Option<&[Selection]>,
     tab: &mut Tab,
     doc: &mut Doc,
     is_reverse: bool,
     gfx: &mut Gfx,
-) {
+) -> Option<Pooled<String>> {
     let cursor_position = doc.cursor(CursorIndex::Main).position;
     let start = start.unwrap_or(cursor_position);
 
-    if let Some(position) = doc.search(search_term, start, is_reverse, gfx) {
+    let position = match selections {
+        Some(selections) => doc.search_in(search_term, start, is_reverse, selections),
+        None => doc.search(search_term, start, is_reverse, gfx),
+    };
+
+    if let Some(position) = position {
         let end = Position::new(position.x + search_term.len(), position.y);
 
         doc.jump_cursors(position, false, gfx);
@@ -217,4 +389,43 @@ fn search(
     } else if cursor_position != start {
         doc.jump_cursors(start, false, gfx);
     }
+
+    match_status(search_term, position, selections, doc, gfx)
+}
+
+fn match_status(
+    search_term: &str,
+    position: Option<Position>,
+    selections: Option<&[Selection]>,
+    doc: &Doc,
+    gfx: &mut Gfx,
+) -> Option<Pooled<String>> {
+    const MAX_COUNTED_MATCHES: usize = 1000;
+
+    if search_term.is_empty() {
+        return None;
+    }
+
+    let position = position.unwrap_or(Position::ZERO);
+    let (index, count) = doc.count_matches(
+        search_term,
+        position,
+        selections,
+        MAX_COUNTED_MATCHES + 1,
+        gfx,
+    );
+
+    let status = match (index, count) {
+        (_, 0) => format_pooled!("No Matches"),
+        (Some(index), count) if count > MAX_COUNTED_MATCHES => {
+            format_pooled!("{} of {}+", index + 1, MAX_COUNTED_MATCHES)
+        }
+        (None, count) if count > MAX_COUNTED_MATCHES => {
+            format_pooled!("{}+ Matches", MAX_COUNTED_MATCHES)
+        }
+        (Some(index), count) => format_pooled!("{} of {}", index + 1, count),
+        (None, count) => format_pooled!("{} Matches", count),
+    };
+
+    Some(status)
 }
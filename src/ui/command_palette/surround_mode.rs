@@ -0,0 +1,72 @@
+use crate::{
+    text::grapheme::{self, GraphemeIterator},
+    ui::result_list::ResultListSubmitKind,
+};
+
+use super::{
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction,
+};
+
+pub enum SurroundModeKind {
+    Add,
+    Change,
+    Delete,
+}
+
+// Input is the grapheme to surround with or delete, or the grapheme to change followed by its replacement, eg. "{[".
+pub struct SurroundMode {
+    kind: SurroundModeKind,
+}
+
+impl SurroundMode {
+    pub fn new(kind: SurroundModeKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl CommandPaletteMode for SurroundMode {
+    fn title(&self) -> &str {
+        match self.kind {
+            SurroundModeKind::Add => "Surround With",
+            SurroundModeKind::Change => "Change Surrounding",
+            SurroundModeKind::Delete => "Delete Surrounding",
+        }
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        _: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        let mut graphemes = GraphemeIterator::new(command_palette.input())
+            .filter(|grapheme| !grapheme::is_whitespace(grapheme));
+
+        let Some(first) = graphemes.next() else {
+            return CommandPaletteAction::Close;
+        };
+
+        let second = graphemes.next();
+
+        let (pane, doc_list) = args.editor.last_focused_pane_and_doc_list_mut(args.ctx.ui);
+
+        let Some((_, doc)) = pane.get_focused_tab_with_data_mut(doc_list, args.ctx.ui) else {
+            return CommandPaletteAction::Close;
+        };
+
+        match self.kind {
+            SurroundModeKind::Add => doc.surround_at_cursors(first, args.ctx),
+            SurroundModeKind::Change => {
+                let Some(second) = second else {
+                    return CommandPaletteAction::Stay;
+                };
+
+                doc.change_surrounding_at_cursors(first, second, args.ctx);
+            }
+            SurroundModeKind::Delete => doc.delete_surrounding_at_cursors(first, args.ctx),
+        }
+
+        CommandPaletteAction::Close
+    }
+}
@@ -0,0 +1,53 @@
+use crate::ui::result_list::ResultListSubmitKind;
+
+use super::{
+    buffer_results::{push_buffer_results, sort_buffer_results},
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction, CommandPaletteMetaData, CommandPaletteResult,
+};
+
+pub struct SwitchBufferMode;
+
+impl CommandPaletteMode for SwitchBufferMode {
+    fn title(&self) -> &str {
+        "Switch Buffer"
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        _: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        let Some(CommandPaletteResult {
+            meta_data: CommandPaletteMetaData::Doc(doc_id),
+            ..
+        }) = command_palette.result_list.get_focused()
+        else {
+            return CommandPaletteAction::Close;
+        };
+
+        args.editor.focus_doc(*doc_id, args.ctx);
+
+        CommandPaletteAction::Close
+    }
+
+    fn on_update_results(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+    ) {
+        push_buffer_results(command_palette, &args, None);
+
+        if command_palette.input().is_empty() {
+            // The focused buffer is listed first, so default to the one before it.
+            let index = command_palette.result_list.len().min(2).saturating_sub(1);
+            command_palette.result_list.set_focused_index(index);
+
+            return;
+        }
+
+        command_palette.result_list.set_focused_index(0);
+        sort_buffer_results(command_palette);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{lsp::types::TypeHierarchyDirection, ui::result_list::ResultListSubmitKind};
+
+use super::{
+    find_in_files_mode::FindInFilesMode,
+    mode::{CommandPaletteEventArgs, CommandPaletteMode},
+    CommandPalette, CommandPaletteAction, CommandPaletteResult,
+};
+
+pub struct TypeHierarchyMode {
+    direction: TypeHierarchyDirection,
+    results: Vec<CommandPaletteResult>,
+}
+
+impl TypeHierarchyMode {
+    pub fn new(direction: TypeHierarchyDirection, results: Vec<CommandPaletteResult>) -> Self {
+        Self { direction, results }
+    }
+}
+
+impl CommandPaletteMode for TypeHierarchyMode {
+    fn title(&self) -> &str {
+        self.direction.title()
+    }
+
+    fn on_open(&mut self, command_palette: &mut CommandPalette, _: CommandPaletteEventArgs) {
+        command_palette.result_list.append(&mut self.results);
+    }
+
+    fn on_submit(
+        &mut self,
+        command_palette: &mut CommandPalette,
+        args: CommandPaletteEventArgs,
+        kind: ResultListSubmitKind,
+    ) -> CommandPaletteAction {
+        FindInFilesMode::jump_to_path_with_position(command_palette, args, kind)
+    }
+}
@@ -2,20 +2,30 @@ use crate::{
     config::Config,
     ctx::Ctx,
     input::action::action_name,
+    normalizable::Normalizable,
+    platform::dialog::{message, MessageKind},
     ui::{
         command_palette::{
             all_actions_mode::AllActionsMode,
+            all_bookmarks_mode::AllBookmarksMode,
             all_diagnostics_mode::AllDiagnosticsMode,
             all_files_mode::AllFilesMode,
+            compare_with_buffer_mode::CompareWithBufferMode,
             file_explorer_mode::FileExplorerMode,
             find_in_files_mode::FindInFilesMode,
             go_to_line_mode::GoToLineMode,
+            language_server_logs_mode::LanguageServerLogsMode,
+            reopen_with_encoding_mode::ReopenWithEncodingMode,
             search_mode::{SearchAndReplaceMode, SearchMode},
+            surround_mode::{SurroundMode, SurroundModeKind},
+            switch_buffer_mode::SwitchBufferMode,
             CommandPalette,
         },
         core::{Ui, WidgetId, WidgetSettings},
         editor::Editor,
+        file_tree::FileTree,
         msg::Msg,
+        problems_panel::ProblemsPanel,
         terminal::Terminal,
     },
 };
@@ -23,12 +33,18 @@ use crate::{
 use super::core::WidgetLayout;
 
 pub struct Controller {
+    font_size_offset: f32,
     widget_id: WidgetId,
 }
 
 impl Controller {
+    const ZOOM_STEP: f32 = 1.0;
+    const MIN_FONT_SIZE: f32 = 6.0;
+    const MAX_FONT_SIZE: f32 = 72.0;
+
     pub fn new(parent_id: WidgetId, ui: &mut Ui) -> Self {
         Self {
+            font_size_offset: 0.0,
             widget_id: ui.new_widget(
                 parent_id,
                 WidgetSettings {
@@ -42,6 +58,8 @@ impl Controller {
     pub fn receive_msgs(
         &mut self,
         editor: &mut Editor,
+        file_tree: &mut FileTree,
+        problems_panel: &mut ProblemsPanel,
         terminal: &Terminal,
         command_palette: &mut CommandPalette,
         ctx: &mut Ctx,
@@ -57,11 +75,27 @@ impl Controller {
                         ctx.ui.focus(terminal_id);
                     }
                 }
+                Msg::Action(action_name!(ToggleFileTree)) => file_tree.toggle(ctx),
+                Msg::Action(action_name!(ToggleProblems)) => problems_panel.toggle(ctx),
+                Msg::Action(action_name!(ZoomIn)) => self.zoom(Self::ZOOM_STEP, ctx),
+                Msg::Action(action_name!(ZoomOut)) => self.zoom(-Self::ZOOM_STEP, ctx),
+                Msg::Action(action_name!(ResetZoom)) => {
+                    self.zoom(-self.font_size_offset, ctx);
+                }
                 Msg::Action(action_name!(OpenAllActions)) => {
                     command_palette.open(Box::new(AllActionsMode), editor, ctx);
                 }
                 Msg::Action(action_name!(OpenFileExplorer)) => {
-                    command_palette.open(Box::new(FileExplorerMode::new(None)), editor, ctx);
+                    let starting_path = terminal
+                        .current_dir(ctx.ui)
+                        .filter(|_| ctx.ui.is_focused(terminal.widget_id()))
+                        .and_then(|current_dir| current_dir.normalized(ctx.current_dir).ok());
+
+                    command_palette.open(
+                        Box::new(FileExplorerMode::new(starting_path)),
+                        editor,
+                        ctx,
+                    );
                 }
                 Msg::Action(action_name!(OpenConfig)) => {
                     let config_dir = Config::dir(ctx.current_dir);
@@ -87,14 +121,72 @@ impl Controller {
                 Msg::Action(action_name!(OpenAllDiagnostics)) => {
                     command_palette.open(Box::new(AllDiagnosticsMode), editor, ctx);
                 }
+                Msg::Action(action_name!(OpenAllBookmarks)) => {
+                    command_palette.open(Box::new(AllBookmarksMode), editor, ctx);
+                }
+                Msg::Action(action_name!(OpenSwitchBuffer)) => {
+                    command_palette.open(Box::new(SwitchBufferMode), editor, ctx);
+                }
+                Msg::Action(action_name!(OpenCompareWithBuffer)) => {
+                    command_palette.open(Box::new(CompareWithBufferMode), editor, ctx);
+                }
                 Msg::Action(action_name!(OpenGoToLine)) => {
                     command_palette.open(Box::new(GoToLineMode), editor, ctx);
                 }
+                Msg::Action(action_name!(OpenLanguageServerLogs)) => {
+                    command_palette.open(Box::new(LanguageServerLogsMode), editor, ctx);
+                }
+                Msg::Action(action_name!(ReopenWithEncoding)) => {
+                    command_palette.open(Box::new(ReopenWithEncodingMode), editor, ctx);
+                }
+                Msg::Action(action_name!(Surround)) => {
+                    let mode = SurroundMode::new(SurroundModeKind::Add);
+                    command_palette.open(Box::new(mode), editor, ctx);
+                }
+                Msg::Action(action_name!(ChangeSurrounding)) => {
+                    let mode = SurroundMode::new(SurroundModeKind::Change);
+                    command_palette.open(Box::new(mode), editor, ctx);
+                }
+                Msg::Action(action_name!(DeleteSurrounding)) => {
+                    let mode = SurroundMode::new(SurroundModeKind::Delete);
+                    command_palette.open(Box::new(mode), editor, ctx);
+                }
+                Msg::OpenFileAtLine { path, line, column } => {
+                    if let Err(err) = editor.open_file_at_line(&path, line, column, ctx) {
+                        message(
+                            "Error Opening File",
+                            &err.to_string(),
+                            MessageKind::Ok,
+                            ctx.window,
+                        );
+                    }
+                }
                 _ => ctx.ui.skip(self.widget_id, msg),
             }
         }
     }
 
+    fn zoom(&mut self, delta: f32, ctx: &mut Ctx) {
+        let last_font_size = self.font_size(ctx.config);
+
+        self.font_size_offset = (ctx.config.font_size + self.font_size_offset + delta)
+            .clamp(Self::MIN_FONT_SIZE, Self::MAX_FONT_SIZE)
+            - ctx.config.font_size;
+
+        let font_size = self.font_size(ctx.config);
+
+        if font_size == last_font_size {
+            return;
+        }
+
+        ctx.gfx.set_font(&ctx.config.font, font_size);
+        ctx.ui.broadcast(WidgetId::ROOT, Msg::FontChanged);
+    }
+
+    pub fn font_size(&self, config: &Config) -> f32 {
+        config.font_size + self.font_size_offset
+    }
+
     pub fn widget_id(&self) -> WidgetId {
         self.widget_id
     }
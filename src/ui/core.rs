@@ -4,6 +4,7 @@ use crate::{
     config::Config,
     geometry::{rect::Rect, visual_position::VisualPosition},
     input::{
+        chord::ChordState,
         mouse_button::MouseButton,
         mouse_scroll::MouseScroll,
         mousebind::{Mousebind, MousebindKind},
@@ -108,6 +109,7 @@ pub struct Ui {
     grabbed_borders: Option<GrabbedBorders>,
     hovered_borders: HoveredBorders,
     is_dragging: bool,
+    chord_state: ChordState,
 }
 
 impl Ui {
@@ -133,6 +135,7 @@ impl Ui {
             grabbed_borders: None,
             hovered_borders: Default::default(),
             is_dragging: false,
+            chord_state: ChordState::new(),
         }
     }
 
@@ -245,17 +248,33 @@ impl Ui {
         }
     }
 
-    pub fn send_window_msgs(&mut self, config: &Config, window: &mut Window) {
+    pub fn send_window_msgs(&mut self, config: &Config, window: &mut Window, time: f64) {
+        self.chord_state.update(time);
+        self.send_chord_msgs();
+
         for msg in window.msgs() {
-            let msg = match msg {
-                Msg::Action(action) => Msg::Action(action.translate(&config.keymaps)),
-                _ => msg,
-            };
+            match msg {
+                Msg::Action(action) => {
+                    self.chord_state.push(action, config, time);
+                }
+                Msg::Grapheme(grapheme) => self.chord_state.push_grapheme(grapheme, time),
+                _ => self.send(WidgetId::ROOT, msg),
+            }
 
+            self.send_chord_msgs();
+        }
+    }
+
+    fn send_chord_msgs(&mut self) {
+        while let Some(msg) = self.chord_state.pop() {
             self.send(WidgetId::ROOT, msg);
         }
     }
 
+    pub fn is_chord_pending(&self, time: f64) -> bool {
+        self.chord_state.is_pending(time)
+    }
+
     pub fn send(&mut self, to_widget_id: WidgetId, msg: Msg) {
         let widget = self.widget_mut(to_widget_id);
 
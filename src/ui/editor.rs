@@ -1,36 +1,48 @@
 use std::{
     env::set_current_dir,
+    fs::{self, create_dir_all, read_dir, File},
     io,
     path::{Path, PathBuf},
 };
 
 use completion_list::{CompletionList, CompletionListResult};
 use cursor_history::CursorHistory;
-use doc_io::confirm_close_all;
+use diff_view::new_diff_doc;
+use doc_io::{confirm_close, confirm_close_all};
 use editor_pane::EditorPane;
 use examine_popup::ExaminePopup;
+use lsp_log::LspLogs;
+use rename_popup::RenamePopup;
+use session::{Session, SessionPane, SessionTab};
 use signature_help_popup::SignatureHelpPopup;
 
 use crate::{
+    config::Config,
     ctx::Ctx,
     geometry::position::Position,
     input::{
         action::{action_keybind, action_name},
-        mods::Mods,
+        mods::{Mod, Mods},
     },
     lsp::{
-        types::{DecodedCompletionItem, DecodedEditList, DecodedHover},
+        types::{
+            DecodedCodeActionResult, DecodedCompletionItem, DecodedDocumentChange, DecodedHover,
+            DecodedRange,
+        },
         uri::uri_to_path,
     },
     normalizable::Normalizable,
     platform::{
         dialog::{find_file, message, FindFileKind, MessageKind},
         file_watcher::FileWatcher,
+        gfx::Gfx,
+        recycle::recycle,
     },
     pool::Pooled,
     text::{
         cursor_index::CursorIndex,
         doc::{Doc, DocFlags},
+        text_encoding::TextEncoding,
     },
     ui::msg::Msg,
 };
@@ -43,9 +55,13 @@ use super::{
 
 pub mod completion_list;
 mod cursor_history;
+mod diff_view;
 mod doc_io;
 pub mod editor_pane;
 mod examine_popup;
+mod lsp_log;
+mod rename_popup;
+mod session;
 mod signature_help_popup;
 
 pub struct Editor {
@@ -54,11 +70,17 @@ pub struct Editor {
 
     handled_position: Option<Position>,
     handled_doc_id: Option<SlotId>,
+    handled_path: Option<Pooled<PathBuf>>,
     cursor_history: CursorHistory,
+    recent_doc_ids: Vec<SlotId>,
+    recent_tab_cycle_index: Option<usize>,
+    closed_tabs: Vec<(Pooled<PathBuf>, Position)>,
 
     hover_timer: f32,
 
     examine_popup: ExaminePopup,
+    rename_popup: RenamePopup,
+    lsp_logs: LspLogs,
     pub signature_help_popup: SignatureHelpPopup,
     pub completion_list: CompletionList,
     widget_id: WidgetId,
@@ -66,6 +88,7 @@ pub struct Editor {
 
 impl Editor {
     const HOVER_TIME: f32 = 0.5;
+    const MAX_CLOSED_TABS: usize = 32;
 
     pub fn new(parent_id: WidgetId, ctx: &mut Ctx) -> Self {
         let widget_id = ctx.ui.new_widget(parent_id, Default::default());
@@ -76,11 +99,17 @@ impl Editor {
 
             handled_position: None,
             handled_doc_id: None,
+            handled_path: None,
             cursor_history: CursorHistory::new(),
+            recent_doc_ids: Vec::new(),
+            recent_tab_cycle_index: None,
+            closed_tabs: Vec::new(),
 
             hover_timer: 0.0,
 
             examine_popup: ExaminePopup::new(widget_id, ctx),
+            rename_popup: RenamePopup::new(widget_id, ctx),
+            lsp_logs: LspLogs::new(),
             signature_help_popup: SignatureHelpPopup::new(widget_id, ctx),
             completion_list: CompletionList::new(widget_id, ctx),
             widget_id,
@@ -95,8 +124,14 @@ impl Editor {
         self.completion_list.is_animating(ctx)
             || self.signature_help_popup.is_animating(ctx)
             || self.examine_popup.is_animating(ctx)
+            || self.rename_popup.is_animating(ctx)
             || self.panes.is_animating(ctx)
             || self.hover_timer > 0.0
+            || self.doc_list.iter().any(Doc::lsp_is_diagnostic_pending)
+            || self
+                .doc_list
+                .iter()
+                .any(|doc| doc.load_progress().is_some() || doc.was_edit_blocked(ctx.time))
     }
 
     pub fn receive_msgs(&mut self, ctx: &mut Ctx) {
@@ -140,6 +175,14 @@ impl Editor {
                     self.completion_list.hide(ctx)
                 }
                 Msg::TabHoverChanged => self.hover_timer = Self::HOVER_TIME,
+                Msg::TabClosed { path, position } => {
+                    if self.closed_tabs.len() == Self::MAX_CLOSED_TABS {
+                        self.closed_tabs.remove(0);
+                    }
+
+                    self.closed_tabs.push((path, position));
+                }
+                Msg::Action(action_name!(ReopenClosedTab)) => self.reopen_closed_tab(ctx),
                 Msg::Action(action_name!(OpenFolder)) => {
                     if let Ok(path) = find_file(FindFileKind::OpenFolder, ctx.window) {
                         if let Err(err) = Self::open_folder(&path, ctx) {
@@ -176,6 +219,19 @@ impl Editor {
                             .show(position, tab.widget_id(), true, doc, ctx);
                     }
                 }
+                Msg::Action(action_name!(ShowSignatureHelp)) => {
+                    let pane = self.panes.get_last_focused_mut(ctx.ui).unwrap();
+
+                    if let Some((tab, doc)) =
+                        pane.get_focused_tab_with_data_mut(&mut self.doc_list, ctx.ui)
+                    {
+                        self.signature_help_popup
+                            .show_manually(doc, tab.widget_id(), ctx);
+                    }
+                }
+                Msg::Action(action_name!(GoToRelatedInformation)) => {
+                    self.go_to_related_information(ctx);
+                }
                 Msg::Action(action_name!(UndoCursorPosition)) => {
                     self.cursor_history
                         .undo(&mut self.panes, &mut self.doc_list, ctx);
@@ -184,13 +240,38 @@ impl Editor {
                     self.cursor_history
                         .redo(&mut self.panes, &mut self.doc_list, ctx);
                 }
+                Msg::Action(action_name!(RestoreSession)) => {
+                    if let Err(err) = self.restore_session(ctx) {
+                        message(
+                            "Error Restoring Session",
+                            &err.to_string(),
+                            MessageKind::Ok,
+                            ctx.window,
+                        );
+                    }
+                }
+                Msg::Action(action_name!(CycleRecentTabs)) => self.cycle_recent_tabs(ctx),
+                Msg::Action(action_name!(NextBookmark)) => self.jump_to_bookmark(true, ctx),
+                Msg::Action(action_name!(PreviousBookmark)) => self.jump_to_bookmark(false, ctx),
                 _ => ctx.ui.skip(self.widget_id, msg),
             }
         }
 
+        // Up and Down belong to the completion list while it's open.
+        if let Some(tab) = self
+            .panes
+            .get_last_focused(ctx.ui)
+            .and_then(|pane| pane.get_focused_tab(ctx.ui))
+            .filter(|_| !self.completion_list.is_visible(ctx))
+        {
+            self.signature_help_popup
+                .receive_tab_msgs(tab.widget_id(), ctx);
+        }
+
         self.panes.receive_msgs(&mut self.doc_list, ctx);
         self.signature_help_popup.receive_msgs(ctx);
         self.examine_popup.receive_msgs(ctx);
+        self.rename_popup.receive_msgs(&self.doc_list, ctx);
 
         let pane = self.panes.get_last_focused_mut(ctx.ui).unwrap();
 
@@ -206,10 +287,34 @@ impl Editor {
         self.panes.update(&mut self.doc_list, ctx, dt);
         self.reload_changed_files(file_watcher, ctx);
 
+        for doc in self.doc_list.iter_mut() {
+            if let Err(err) = doc.update_load(ctx) {
+                message(
+                    "Failed to Load File",
+                    &err.to_string(),
+                    MessageKind::Ok,
+                    ctx.window,
+                );
+            }
+
+            doc.lsp_update(ctx);
+        }
+
+        self.lsp_logs.update(&mut self.doc_list, ctx);
+
         self.update_hover(ctx, dt);
 
         self.panes.remove_excess(ctx.ui, |pane| !pane.has_tabs());
 
+        self.recent_doc_ids
+            .retain(|doc_id| self.doc_list.get(*doc_id).is_some());
+
+        let mods = ctx.window.mods();
+
+        if !mods.contains(Mod::Ctrl) && !mods.contains(Mod::Cmd) {
+            self.recent_tab_cycle_index = None;
+        }
+
         let is_cursor_visible = self.is_cursor_visible(ctx);
 
         ctx.ui
@@ -223,6 +328,7 @@ impl Editor {
         else {
             self.signature_help_popup.hide(ctx.ui);
             self.completion_list.hide(ctx);
+            self.rename_popup.hide(ctx.ui);
 
             return;
         };
@@ -232,14 +338,27 @@ impl Editor {
         let doc_id = tab.data_id();
         let position = doc.cursor(CursorIndex::Main).position;
 
-        self.cursor_history
-            .update(self.handled_doc_id, doc_id, self.handled_position, position);
+        self.cursor_history.update(
+            self.handled_doc_id,
+            self.handled_path.take(),
+            doc_id,
+            self.handled_position,
+            position,
+        );
 
         self.signature_help_popup.update(tab, doc, ctx, dt);
         self.examine_popup.update(tab, doc, ctx, dt);
+        self.rename_popup.update(tab, doc, ctx, dt);
+
+        // Keep the order stable while cycling so repeated presses walk further back.
+        if self.recent_tab_cycle_index.is_none() {
+            self.recent_doc_ids.retain(|recent_id| *recent_id != doc_id);
+            self.recent_doc_ids.insert(0, doc_id);
+        }
 
         self.handled_position = Some(position);
         self.handled_doc_id = Some(doc_id);
+        self.handled_path = CursorHistory::doc_path(doc);
     }
 
     fn update_hover(&mut self, ctx: &mut Ctx, dt: f32) {
@@ -279,31 +398,102 @@ impl Editor {
     ) -> Option<()> {
         let result = result?;
 
-        self.lsp_apply_edit_lists(result.edit_lists, ctx);
+        self.lsp_apply_document_changes(result.document_changes, ctx);
 
         let command = result.command?;
         let pane = self.panes.get_last_focused_mut(ctx.ui).unwrap();
         let (_, doc) = pane.get_focused_tab_with_data_mut(&mut self.doc_list, ctx.ui)?;
-        let language_server = doc.get_language_server_mut(ctx)?;
+        let language_server = doc.get_language_server_at_mut(result.server_index, ctx)?;
 
         language_server.execute_command(&command.command, &command.arguments);
 
         Some(())
     }
 
-    pub fn lsp_apply_edit_lists(
+    // Later changes can depend on earlier ones, so stop at the first one that can't be applied.
+    pub fn lsp_apply_document_changes(
         &mut self,
-        edit_lists: Vec<DecodedEditList>,
+        document_changes: Vec<DecodedDocumentChange>,
         ctx: &mut Ctx,
     ) -> Option<()> {
-        for edit_list in edit_lists {
-            let path = uri_to_path(&edit_list.uri)?;
+        for document_change in document_changes {
+            match document_change {
+                DecodedDocumentChange::Edit(edit_list) => {
+                    let path = uri_to_path(&edit_list.uri)?;
+
+                    if let Some(version) = edit_list.version {
+                        if self
+                            .find_doc(&path)
+                            .is_some_and(|doc| doc.version() != version)
+                        {
+                            return None;
+                        }
+                    }
 
-            self.with_doc(path, ctx, |doc, ctx| {
-                let edits = &mut edit_list.edits(doc);
+                    self.with_doc(path, ctx, |doc, ctx| edit_list.apply(doc, ctx));
+                }
+                DecodedDocumentChange::CreateFile { uri, options } => {
+                    let path = uri_to_path(&uri)?;
 
-                doc.lsp_apply_edit_list(edits, ctx);
-            });
+                    if path.exists() && !options.overwrite {
+                        if options.ignore_if_exists {
+                            continue;
+                        }
+
+                        return None;
+                    }
+
+                    if let Some(parent) = path.parent() {
+                        create_dir_all(parent).ok()?;
+                    }
+
+                    File::create(&path).ok()?;
+
+                    if let Some(doc) = self.find_doc_mut(&path) {
+                        doc.reload(ctx).ok()?;
+                    }
+                }
+                DecodedDocumentChange::RenameFile {
+                    old_uri,
+                    new_uri,
+                    options,
+                } => {
+                    let old_path = uri_to_path(&old_uri)?;
+                    let new_path = uri_to_path(&new_uri)?;
+
+                    if new_path.exists() && !options.overwrite {
+                        if options.ignore_if_exists {
+                            continue;
+                        }
+
+                        return None;
+                    }
+
+                    if let Some(parent) = new_path.parent() {
+                        create_dir_all(parent).ok()?;
+                    }
+
+                    self.rename_file(&old_path, new_path, ctx).ok()?;
+                }
+                DecodedDocumentChange::DeleteFile { uri, options } => {
+                    let path = uri_to_path(&uri)?;
+
+                    if !path.exists() {
+                        if options.ignore_if_not_exists {
+                            continue;
+                        }
+
+                        return None;
+                    }
+
+                    if path.is_dir() && !options.recursive && read_dir(&path).ok()?.next().is_some()
+                    {
+                        return None;
+                    }
+
+                    recycle(&path).ok()?;
+                }
+            }
         }
 
         Some(())
@@ -313,13 +503,50 @@ impl Editor {
         &mut self,
         items: Vec<DecodedCompletionItem>,
         needs_resolve: bool,
+        server_index: usize,
         doc_id: SlotId,
         ctx: &mut Ctx,
     ) -> Option<()> {
         let doc = self.doc_list.get(doc_id)?;
 
+        self.completion_list.lsp_update_completion_results(
+            items,
+            needs_resolve,
+            server_index,
+            doc,
+            ctx,
+        );
+
+        Some(())
+    }
+
+    pub fn lsp_resolve_completion_item(
+        &mut self,
+        id: Option<usize>,
+        item: DecodedCompletionItem,
+        server_index: usize,
+        doc_id: SlotId,
+        ctx: &mut Ctx,
+    ) -> Option<()> {
+        let doc = self.doc_list.get_mut(doc_id)?;
+
         self.completion_list
-            .lsp_update_completion_results(items, needs_resolve, doc, ctx);
+            .lsp_resolve_completion_item(id, item, server_index, doc, ctx);
+
+        Some(())
+    }
+
+    pub fn lsp_update_code_action_results(
+        &mut self,
+        results: Vec<DecodedCodeActionResult>,
+        server_index: usize,
+        doc_id: SlotId,
+        ctx: &mut Ctx,
+    ) -> Option<()> {
+        let doc = self.doc_list.get(doc_id)?;
+
+        self.completion_list
+            .lsp_update_code_action_results(results, server_index, doc, ctx);
 
         Some(())
     }
@@ -337,6 +564,72 @@ impl Editor {
         Some(())
     }
 
+    pub fn reopen_with_encoding(
+        &mut self,
+        encoding: TextEncoding,
+        ctx: &mut Ctx,
+    ) -> io::Result<()> {
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        let Some((_, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) else {
+            return Ok(());
+        };
+
+        if !confirm_close(doc, "reopening it", true, ctx) {
+            return Ok(());
+        }
+
+        doc.load_with_encoding(Some(encoding), ctx)
+    }
+
+    pub fn open_language_server_log(
+        &mut self,
+        server_key: (usize, usize),
+        ctx: &mut Ctx,
+    ) -> Option<()> {
+        let doc_id = self
+            .lsp_logs
+            .get_or_add_doc(server_key, &mut self.doc_list, ctx)?;
+
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+        pane.add_tab(doc_id, doc_list, ctx);
+
+        Some(())
+    }
+
+    pub fn open_diff(&mut self, doc_id: SlotId, other_doc_id: SlotId, ctx: &mut Ctx) -> Option<()> {
+        let doc = self.doc_list.get(doc_id)?;
+        let other_doc = self.doc_list.get(other_doc_id)?;
+
+        let diff_doc = new_diff_doc(doc, other_doc, ctx);
+        let diff_doc_id = self.doc_list.add(diff_doc);
+
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+        pane.add_tab(diff_doc_id, doc_list, ctx);
+
+        Some(())
+    }
+
+    pub fn lsp_show_rename(
+        &mut self,
+        range: DecodedRange,
+        placeholder: &str,
+        doc_id: SlotId,
+        ctx: &mut Ctx,
+    ) -> Option<()> {
+        let pane = self.panes.get_last_focused_mut(ctx.ui)?;
+        let (tab, _) = pane.get_focused_tab_with_data_mut(&mut self.doc_list, ctx.ui)?;
+
+        if tab.data_id() != doc_id {
+            return None;
+        }
+
+        self.rename_popup
+            .show(range, placeholder, tab.widget_id(), doc_id, ctx);
+
+        Some(())
+    }
+
     pub fn with_doc(
         &mut self,
         path: Pooled<PathBuf>,
@@ -366,6 +659,29 @@ impl Editor {
         }
     }
 
+    pub fn rename_file(
+        &mut self,
+        from: &Path,
+        to: Pooled<PathBuf>,
+        ctx: &mut Ctx,
+    ) -> io::Result<()> {
+        let from = from.normalized(ctx.current_dir)?;
+
+        fs::rename(&from, &to)?;
+
+        if let Some(doc) = self.find_doc_mut(&from) {
+            doc.set_moved_path(to, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn find_doc(&self, path: &Path) -> Option<&Doc> {
+        self.doc_list
+            .iter()
+            .find(|doc| doc.path().some_path() == Some(path))
+    }
+
     pub fn find_doc_mut(&mut self, path: &Path) -> Option<&mut Doc> {
         self.doc_list
             .iter_mut()
@@ -414,6 +730,8 @@ impl Editor {
         } else if self.examine_popup.is_open() {
             self.examine_popup.draw(ctx);
         }
+
+        self.rename_popup.draw(ctx);
     }
 
     fn is_cursor_visible(&self, ctx: &mut Ctx) -> bool {
@@ -457,8 +775,165 @@ impl Editor {
         self.panes.remove_focused(ctx.ui);
     }
 
-    pub fn on_close(&mut self, ctx: &mut Ctx) {
+    // Returns false if the user cancelled closing one of the tabs.
+    fn close_all_panes(&mut self, ctx: &mut Ctx) -> bool {
+        loop {
+            let Some(pane) = self.panes.get_last_focused_mut(ctx.ui) else {
+                return true;
+            };
+
+            if !pane.close_all_tabs(&mut self.doc_list, ctx) {
+                return false;
+            }
+
+            if self.panes.len() == 1 {
+                return true;
+            }
+
+            self.panes.remove_focused(ctx.ui);
+        }
+    }
+
+    pub fn on_close(&mut self, terminal_dir: Option<&Path>, ctx: &mut Ctx) {
         confirm_close_all(&mut self.doc_list, "exiting", ctx);
+
+        if ctx.config.restore_session {
+            let config_dir = Config::dir(ctx.current_dir);
+            let _ = self
+                .session(ctx.ui, ctx.gfx, ctx.current_dir, terminal_dir)
+                .save(&config_dir);
+        }
+    }
+
+    // Only tabs for files on the drive are saved, unnamed docs can't be reopened.
+    fn session(
+        &self,
+        ui: &Ui,
+        gfx: &Gfx,
+        current_dir: &Path,
+        terminal_dir: Option<&Path>,
+    ) -> Session {
+        let focused_pane_id = self.panes.get_last_focused(ui).map(|pane| pane.widget_id());
+
+        let mut panes = Vec::new();
+        let mut focused_pane_index = 0;
+
+        for pane in self.panes.iter() {
+            let pane_focused_tab_index = pane.focused_tab_index(ui);
+
+            let mut tabs = Vec::new();
+            let mut focused_tab_index = 0;
+
+            for (i, tab) in pane.iter_tabs().enumerate() {
+                let Some(doc) = self.doc_list.get(tab.data_id()) else {
+                    continue;
+                };
+
+                let Some(path) = doc.path().on_drive() else {
+                    continue;
+                };
+
+                if i == pane_focused_tab_index {
+                    focused_tab_index = tabs.len();
+                }
+
+                let position = doc.cursor(CursorIndex::Main).position;
+                let camera_position = tab.camera.position();
+
+                tabs.push(SessionTab {
+                    path: path.to_owned(),
+                    x: position.x,
+                    y: position.y,
+                    // Scroll positions are stored in cells so that they survive font size changes.
+                    scroll_x: cells(camera_position.x, gfx.glyph_width()),
+                    scroll_y: cells(camera_position.y, gfx.line_height()),
+                    is_pinned: tab.is_pinned(),
+                });
+            }
+
+            if tabs.is_empty() {
+                continue;
+            }
+
+            if Some(pane.widget_id()) == focused_pane_id {
+                focused_pane_index = panes.len();
+            }
+
+            panes.push(SessionPane {
+                tabs,
+                focused_tab_index,
+            });
+        }
+
+        Session {
+            dir: current_dir.to_owned(),
+            panes,
+            focused_pane_index,
+            terminal_dir: terminal_dir.map(Path::to_owned),
+        }
+    }
+
+    // Returns the directory the terminal was in, if there was one.
+    pub fn restore_session(&mut self, ctx: &mut Ctx) -> io::Result<Option<PathBuf>> {
+        let config_dir = Config::dir(ctx.current_dir);
+        let session = Session::load(&config_dir, ctx.current_dir)?;
+
+        // The session replaces whatever is currently open.
+        if !self.close_all_panes(ctx) {
+            return Ok(None);
+        }
+
+        let mut pane_ids = Vec::new();
+
+        for (i, session_pane) in session.panes.iter().enumerate() {
+            if i > 0 {
+                self.add_pane(ctx);
+            }
+
+            let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+            let mut focused_tab_index = None;
+
+            for (j, session_tab) in session_pane.tabs.iter().enumerate() {
+                // Files that were moved or deleted since the session was saved are skipped.
+                if pane.open_file(&session_tab.path, doc_list, ctx).is_err() {
+                    continue;
+                }
+
+                let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) else {
+                    continue;
+                };
+
+                doc.jump_cursors(Position::new(session_tab.x, session_tab.y), false, ctx.gfx);
+
+                tab.camera.reset();
+                tab.camera
+                    .horizontal
+                    .jump_visual_distance(session_tab.scroll_x * ctx.gfx.glyph_width());
+                tab.camera
+                    .vertical
+                    .jump_visual_distance(session_tab.scroll_y * ctx.gfx.line_height());
+
+                if session_tab.is_pinned && !tab.is_pinned() {
+                    pane.toggle_pin_focused_tab(ctx.ui);
+                }
+
+                if j <= session_pane.focused_tab_index {
+                    focused_tab_index = Some(pane.focused_tab_index(ctx.ui));
+                }
+            }
+
+            if let Some(focused_tab_index) = focused_tab_index {
+                pane.focus_tab_at_index(focused_tab_index, ctx.ui);
+            }
+
+            pane_ids.push(pane.widget_id());
+        }
+
+        if let Some(pane_id) = pane_ids.get(session.focused_pane_index) {
+            ctx.ui.focus(*pane_id);
+        }
+
+        Ok(session.terminal_dir)
     }
 
     pub fn last_focused_pane_and_doc_list(&self, ui: &Ui) -> (&EditorPane, &SlotList<Doc>) {
@@ -475,6 +950,88 @@ impl Editor {
         )
     }
 
+    pub fn open_file_at_position(
+        &mut self,
+        path: &Path,
+        position: Position,
+        ctx: &mut Ctx,
+    ) -> io::Result<()> {
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        pane.open_file(path, doc_list, ctx)?;
+
+        if let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) {
+            doc.jump_cursors(position, false, ctx.gfx);
+            tab.camera.recenter();
+        }
+
+        ctx.ui.focus(pane.widget_id());
+
+        Ok(())
+    }
+
+    // The line and column are 1-based, and the column counts chars rather than bytes.
+    pub fn open_file_at_line(
+        &mut self,
+        path: &Path,
+        line: usize,
+        column: usize,
+        ctx: &mut Ctx,
+    ) -> io::Result<()> {
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        pane.open_file(path, doc_list, ctx)?;
+
+        if let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) {
+            let position = doc.line_column_position(line, column);
+
+            doc.jump_cursors(position, false, ctx.gfx);
+            tab.camera.recenter();
+        }
+
+        ctx.ui.focus(pane.widget_id());
+
+        Ok(())
+    }
+
+    pub fn focus_doc(&mut self, doc_id: SlotId, ctx: &mut Ctx) {
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        pane.add_tab(doc_id, doc_list, ctx);
+
+        ctx.ui.focus(pane.widget_id());
+    }
+
+    fn reopen_closed_tab(&mut self, ctx: &mut Ctx) {
+        let Some((path, position)) = self.closed_tabs.pop() else {
+            return;
+        };
+
+        if let Err(err) = self.open_file_at_position(&path, position, ctx) {
+            message(
+                "Error Reopening Tab",
+                &err.to_string(),
+                MessageKind::Ok,
+                ctx.window,
+            );
+        }
+    }
+
+    fn cycle_recent_tabs(&mut self, ctx: &mut Ctx) {
+        let len = self.recent_doc_ids.len();
+
+        if len < 2 {
+            return;
+        }
+
+        let index = self
+            .recent_tab_cycle_index
+            .map_or(1, |index| (index + 1) % len);
+
+        self.recent_tab_cycle_index = Some(index);
+        self.focus_doc(self.recent_doc_ids[index], ctx);
+    }
+
     pub fn open_folder(path: &Path, ctx: &mut Ctx) -> io::Result<()> {
         let new_current_dir = path.normalized(ctx.current_dir)?;
 
@@ -488,7 +1045,118 @@ impl Editor {
         Ok(())
     }
 
+    fn go_to_related_information(&mut self, ctx: &mut Ctx) -> Option<()> {
+        let pane = self.panes.get_last_focused_mut(ctx.ui)?;
+        let (_, doc) = pane.get_focused_tab_with_data_mut(&mut self.doc_list, ctx.ui)?;
+
+        let position = doc.cursor(CursorIndex::Main).position;
+        let encoding = doc.get_language_server_mut(ctx)?.position_encoding();
+
+        let related_information = ctx
+            .lsp
+            .get_diagnostic_at(position, doc)?
+            .related_information
+            .first()?
+            .clone();
+
+        let (pane, doc_list) = self.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        if let Err(err) = pane.open_file(&related_information.path, doc_list, ctx) {
+            message(
+                "Error Opening File",
+                &err.to_string(),
+                MessageKind::Ok,
+                ctx.window,
+            );
+
+            return None;
+        }
+
+        let (tab, doc) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui)?;
+        let position = related_information.range.decode(encoding, doc).start;
+
+        doc.jump_cursors(position, false, ctx.gfx);
+        tab.camera.recenter();
+
+        Some(())
+    }
+
+    // Cycles through bookmarks in every open doc, ordered by doc and then by line.
+    fn jump_to_bookmark(&mut self, is_forward: bool, ctx: &mut Ctx) {
+        let pane = self.panes.get_last_focused(ctx.ui).unwrap();
+
+        let current = pane
+            .get_focused_tab_with_data(&self.doc_list, ctx.ui)
+            .map(|(tab, doc)| (tab.data_id(), doc.cursor(CursorIndex::Main).position.y));
+
+        let mut current_key = None;
+        let mut bookmarks = Vec::new();
+
+        for (i, (doc_id, doc)) in self.doc_list.enumerate().enumerate() {
+            if let Some((current_doc_id, current_y)) = current {
+                if doc_id == current_doc_id {
+                    current_key = Some((i, current_y));
+                }
+            }
+
+            bookmarks.extend(doc.bookmarks().map(|y| (i, y, doc_id)));
+        }
+
+        let bookmark = match current_key {
+            Some(current_key) if is_forward => bookmarks
+                .iter()
+                .find(|(i, y, _)| (*i, *y) > current_key)
+                .or(bookmarks.first()),
+            Some(current_key) => bookmarks
+                .iter()
+                .rev()
+                .find(|(i, y, _)| (*i, *y) < current_key)
+                .or(bookmarks.last()),
+            None if is_forward => bookmarks.first(),
+            None => bookmarks.last(),
+        };
+
+        let Some((_, y, doc_id)) = bookmark.copied() else {
+            return;
+        };
+
+        let pane = self.panes.get_last_focused_mut(ctx.ui).unwrap();
+        pane.add_tab(doc_id, &mut self.doc_list, ctx);
+
+        if let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(&mut self.doc_list, ctx.ui) {
+            doc.jump_cursors(Position::new(0, y), false, ctx.gfx);
+            tab.camera.recenter();
+        }
+    }
+
+    pub fn docs(&self) -> impl Iterator<Item = &Doc> {
+        self.doc_list.iter()
+    }
+
+    // Docs in most recently focused order, followed by any that haven't been focused yet.
+    pub fn recent_docs(&self) -> impl Iterator<Item = (SlotId, &Doc)> {
+        let recent_docs = self
+            .recent_doc_ids
+            .iter()
+            .filter_map(|doc_id| Some((*doc_id, self.doc_list.get(*doc_id)?)));
+
+        let other_docs = self
+            .doc_list
+            .enumerate()
+            .filter(|(doc_id, _)| !self.recent_doc_ids.contains(doc_id));
+
+        recent_docs.chain(other_docs)
+    }
+
     pub fn files(&self) -> impl Iterator<Item = &Path> {
         self.doc_list.iter().filter_map(|doc| doc.path().on_drive())
     }
 }
+
+fn cells(visual_distance: f32, cell_size: f32) -> f32 {
+    if cell_size > 0.0 {
+        visual_distance / cell_size
+    } else {
+        0.0
+    }
+}
@@ -1,17 +1,23 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::{hash_map::Entry, HashMap},
+    mem,
+};
 
 use crate::{
     ctx::Ctx,
     geometry::{position::Position, rect::Rect, visual_position::VisualPosition},
     lsp::{
+        merge_completion_items,
         types::{
             Command, DecodedCodeAction, DecodedCodeActionResult, DecodedCompletionItem,
-            DecodedEditList, DecodedRange,
+            DecodedDocumentChange, DecodedRange, Documentation,
         },
         LspSentRequest,
     },
     pool::Pooled,
-    text::{compare::score_fuzzy_match, cursor_index::CursorIndex, doc::Doc},
+    text::{compare::is_fuzzy_match, cursor_index::CursorIndex, doc::Doc},
     ui::{
         core::{WidgetId, WidgetSettings},
         popup::{Popup, PopupAlignment},
@@ -27,32 +33,95 @@ enum CompletionResolveState {
     Resolved,
 }
 
+#[derive(Debug)]
+struct LspCompletion {
+    item: DecodedCompletionItem,
+    resolve_state: CompletionResolveState,
+    server_index: usize,
+}
+
+impl Borrow<DecodedCompletionItem> for LspCompletion {
+    fn borrow(&self) -> &DecodedCompletionItem {
+        &self.item
+    }
+}
+
 #[derive(Debug)]
 enum CompletionResult {
     SimpleCompletion(Pooled<String>),
-    Completion {
-        item: DecodedCompletionItem,
-        resolve_state: CompletionResolveState,
+    Completion(Box<LspCompletion>),
+    Command {
+        command: Command,
+        server_index: usize,
+    },
+    CodeAction {
+        code_action: DecodedCodeAction,
+        server_index: usize,
     },
-    Command(Command),
-    CodeAction(DecodedCodeAction),
 }
 
 impl CompletionResult {
+    fn lsp_completion(&self) -> Option<&LspCompletion> {
+        match self {
+            Self::Completion(completion) => Some(completion),
+            _ => None,
+        }
+    }
+
+    fn lsp_completion_mut(&mut self) -> Option<&mut LspCompletion> {
+        match self {
+            Self::Completion(completion) => Some(completion),
+            _ => None,
+        }
+    }
+
     pub fn label(&self) -> &str {
         match self {
             Self::SimpleCompletion(text) => text,
-            Self::Completion { item, .. } => &item.label,
-            Self::Command(command) => &command.title,
-            Self::CodeAction(code_action) => &code_action.title,
+            Self::Completion(completion) => &completion.item.label,
+            Self::Command { command, .. } => &command.title,
+            Self::CodeAction { code_action, .. } => &code_action.title,
         }
     }
 }
 
+// An accepted completion that is waiting to be resolved before being applied.
+#[derive(Debug, Clone, Copy)]
+struct LspAcceptedCompletion {
+    id: usize,
+    server_index: usize,
+    version: usize,
+}
+
+// Responses from different servers that share a state are merged into the same results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LspResultsState {
+    method: &'static str,
+    version: usize,
+    position: Position,
+}
+
+impl LspResultsState {
+    fn new(method: &'static str, doc: &Doc) -> Self {
+        Self {
+            method,
+            version: doc.version(),
+            position: doc.cursor(CursorIndex::Main).position,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CompletionDocumentation {
+    detail: Option<Pooled<String>>,
+    documentation: Option<Documentation>,
+}
+
 #[derive(Debug, Default)]
 pub struct CompletionListResult {
-    pub edit_lists: Vec<DecodedEditList>,
+    pub document_changes: Vec<DecodedDocumentChange>,
     pub command: Option<Command>,
+    pub server_index: usize,
 }
 
 pub struct CompletionList {
@@ -62,7 +131,10 @@ pub struct CompletionList {
     result_list: ResultList<CompletionResult>,
     prefix: String,
 
-    lsp_expected_responses: HashMap<usize, usize>,
+    lsp_expected_responses: HashMap<(usize, usize), usize>,
+    lsp_accepted_completion: Option<LspAcceptedCompletion>,
+    lsp_results_state: Option<LspResultsState>,
+    resolved_documentation: HashMap<String, CompletionDocumentation>,
 
     detail_popup: Popup,
     documentation_popup: Popup,
@@ -90,6 +162,9 @@ impl CompletionList {
             prefix: String::new(),
 
             lsp_expected_responses: HashMap::new(),
+            lsp_accepted_completion: None,
+            lsp_results_state: None,
+            resolved_documentation: HashMap::new(),
 
             detail_popup: Popup::new(widget_id, ctx),
             documentation_popup: Popup::new(widget_id, ctx),
@@ -102,6 +177,10 @@ impl CompletionList {
             || self.documentation_popup.is_animating(ctx)
     }
 
+    pub fn is_visible(&self, ctx: &Ctx) -> bool {
+        ctx.ui.is_visible(self.result_list.widget_id())
+    }
+
     pub fn receive_msgs(
         &mut self,
         doc: Option<&mut Doc>,
@@ -139,17 +218,26 @@ impl CompletionList {
             self.update_results(doc, ctx);
         }
 
-        if let Some(CompletionResult::Completion {
+        if let Some(LspCompletion {
             item,
             resolve_state: resolve_state @ CompletionResolveState::NeedsRequest,
-        }) = self.result_list.get_focused_mut()
+            server_index,
+        }) = self
+            .result_list
+            .get_focused_mut()
+            .and_then(CompletionResult::lsp_completion_mut)
         {
             *resolve_state = CompletionResolveState::NeedsResponse;
 
-            if let Some(sent_request) = Self::lsp_completion_item_resolve(item, doc, ctx) {
+            let server_index = *server_index;
+
+            if let Some(sent_request) =
+                Self::lsp_completion_item_resolve(item, server_index, doc, ctx)
+            {
                 let index = self.result_list.focused_index();
 
-                self.lsp_expected_responses.insert(sent_request.id, index);
+                self.lsp_expected_responses
+                    .insert((server_index, sent_request.id), index);
             }
         }
 
@@ -157,27 +245,35 @@ impl CompletionList {
     }
 
     fn set_popups_shown(&mut self, ctx: &mut Ctx) {
-        let Some(CompletionResult::Completion {
+        let Some(LspCompletion {
             item,
             resolve_state,
-        }) = self.result_list.get_focused()
+            ..
+        }) = self
+            .result_list
+            .get_focused()
+            .and_then(CompletionResult::lsp_completion)
         else {
             self.detail_popup.hide(ctx.ui);
             self.documentation_popup.hide(ctx.ui);
             return;
         };
 
-        if *resolve_state != CompletionResolveState::Resolved {
-            return;
-        }
+        // Results are re-requested as the prefix changes, reuse earlier documentation until then.
+        let (detail, documentation) = match self.resolved_documentation.get(item.label.as_str()) {
+            Some(resolved) if *resolve_state != CompletionResolveState::Resolved => {
+                (&resolved.detail, &resolved.documentation)
+            }
+            _ => (&item.detail, &item.documentation),
+        };
 
-        if let Some(detail) = &item.detail {
+        if let Some(detail) = detail {
             self.detail_popup.show(detail, "", ctx);
         } else {
             self.detail_popup.hide(ctx.ui);
         }
 
-        if let Some(documentation) = &item.documentation {
+        if let Some(documentation) = documentation {
             self.documentation_popup
                 .show(documentation.text(), documentation.extension(), ctx);
         } else {
@@ -251,10 +347,11 @@ impl CompletionList {
 
     fn lsp_completion_item_resolve(
         item: &DecodedCompletionItem,
+        server_index: usize,
         doc: &Doc,
         ctx: &mut Ctx,
     ) -> Option<LspSentRequest> {
-        let language_server = doc.get_language_server_mut(ctx)?;
+        let language_server = doc.get_language_server_at_mut(server_index, ctx)?;
 
         Some(language_server.completion_item_resolve(item.clone(), doc))
     }
@@ -277,26 +374,52 @@ impl CompletionList {
         &mut self,
         id: Option<usize>,
         item: DecodedCompletionItem,
+        server_index: usize,
+        doc: &mut Doc,
         ctx: &mut Ctx,
     ) {
         let Some(id) = id else {
             return;
         };
 
-        let Entry::Occupied(index) = self.lsp_expected_responses.entry(id) else {
+        if let Some(accepted_completion) =
+            self.lsp_accepted_completion.take_if(|accepted_completion| {
+                accepted_completion.id == id && accepted_completion.server_index == server_index
+            })
+        {
+            if accepted_completion.version == doc.version() {
+                self.perform_completion_item(item, doc, ctx);
+            }
+
+            return;
+        }
+
+        let Entry::Occupied(index) = self.lsp_expected_responses.entry((server_index, id)) else {
             return;
         };
 
         let index = index.remove();
 
-        let Some(CompletionResult::Completion {
+        let Some(LspCompletion {
             item: existing_item,
             resolve_state,
-        }) = &mut self.result_list.get_mut(index)
+            ..
+        }) = self
+            .result_list
+            .get_mut(index)
+            .and_then(CompletionResult::lsp_completion_mut)
         else {
             return;
         };
 
+        self.resolved_documentation.insert(
+            item.label.to_string(),
+            CompletionDocumentation {
+                detail: item.detail.clone(),
+                documentation: item.documentation.clone(),
+            },
+        );
+
         *existing_item = item;
         *resolve_state = CompletionResolveState::Resolved;
 
@@ -305,12 +428,31 @@ impl CompletionList {
 
     pub fn lsp_update_completion_results(
         &mut self,
-        mut items: Vec<DecodedCompletionItem>,
+        items: Vec<DecodedCompletionItem>,
         needs_resolve: bool,
+        server_index: usize,
         doc: &Doc,
         ctx: &mut Ctx,
     ) {
+        let results_state = LspResultsState::new("textDocument/completion", doc);
+        let mut completions = Vec::new();
+
+        if self.lsp_results_state == Some(results_state) {
+            completions.extend(self.result_list.drain().filter_map(|result| match result {
+                CompletionResult::Completion(mut completion) => {
+                    // Pending resolves are forgotten once the results are rebuilt.
+                    if completion.resolve_state == CompletionResolveState::NeedsResponse {
+                        completion.resolve_state = CompletionResolveState::NeedsRequest;
+                    }
+
+                    Some(*completion)
+                }
+                _ => None,
+            }));
+        }
+
         self.clear_results();
+        self.lsp_results_state = Some(results_state);
 
         let resolve_state = if needs_resolve {
             CompletionResolveState::NeedsRequest
@@ -318,27 +460,34 @@ impl CompletionList {
             CompletionResolveState::Resolved
         };
 
-        if items.is_empty() {
+        merge_completion_items(
+            &mut completions,
+            items.into_iter().map(|item| LspCompletion {
+                item,
+                resolve_state,
+                server_index,
+            }),
+        );
+
+        if completions.is_empty() {
             self.add_token_results(doc, ctx);
         }
 
-        items.sort_by(|a, b| a.sort_text().cmp(b.sort_text()));
-
-        items.sort_by(|a, b| {
-            let a = a.filter_text();
-            let b = b.filter_text();
+        completions
+            .retain(|completion| is_fuzzy_match(completion.item.filter_text(), &self.prefix));
+        completions.sort_by(|a, b| a.item.sort_text().cmp(b.item.sort_text()));
 
-            let a_score = score_fuzzy_match(a, &self.prefix);
-            let b_score = score_fuzzy_match(b, &self.prefix);
+        let preselected_index = completions
+            .iter()
+            .position(|completion| completion.item.preselect);
 
-            b_score.total_cmp(&a_score).then(a.len().cmp(&b.len()))
-        });
+        for completion in completions {
+            self.result_list
+                .push(CompletionResult::Completion(Box::new(completion)));
+        }
 
-        for item in items {
-            self.result_list.push(CompletionResult::Completion {
-                item,
-                resolve_state,
-            });
+        if let Some(index) = preselected_index {
+            self.result_list.set_focused_index(index);
         }
 
         self.show_results(ctx);
@@ -347,14 +496,33 @@ impl CompletionList {
     pub fn lsp_update_code_action_results(
         &mut self,
         results: Vec<DecodedCodeActionResult>,
+        server_index: usize,
+        doc: &Doc,
         ctx: &mut Ctx,
     ) {
+        let results_state = LspResultsState::new("textDocument/codeAction", doc);
+
+        let existing_results: Vec<CompletionResult> =
+            if self.lsp_results_state == Some(results_state) {
+                self.result_list.drain().collect()
+            } else {
+                Vec::new()
+            };
+
         self.hide(ctx);
+        self.lsp_results_state = Some(results_state);
+
+        for result in existing_results {
+            self.result_list.push(result);
+        }
 
         for result in results {
             match result {
                 DecodedCodeActionResult::Command(command) => {
-                    self.result_list.push(CompletionResult::Command(command));
+                    self.result_list.push(CompletionResult::Command {
+                        command,
+                        server_index,
+                    });
                 }
                 DecodedCodeActionResult::CodeAction(code_action) => {
                     let index = if code_action.is_preferred {
@@ -363,8 +531,13 @@ impl CompletionList {
                         self.result_list.len()
                     };
 
-                    self.result_list
-                        .insert(index, CompletionResult::CodeAction(code_action));
+                    self.result_list.insert(
+                        index,
+                        CompletionResult::CodeAction {
+                            code_action,
+                            server_index,
+                        },
+                    );
                 }
             }
         }
@@ -381,7 +554,7 @@ impl CompletionList {
     fn update_results(&mut self, doc: &mut Doc, ctx: &mut Ctx) {
         self.prefix.clear();
 
-        let Some(prefix) = doc.get_completion_prefix(ctx.gfx) else {
+        let Some(prefix) = doc.get_completion_prefix(ctx) else {
             self.hide(ctx);
 
             return;
@@ -405,6 +578,7 @@ impl CompletionList {
         ctx.ui.hide(self.documentation_popup.widget_id());
 
         self.clear_results();
+        self.resolved_documentation.clear();
     }
 
     fn add_token_results(&mut self, doc: &Doc, ctx: &mut Ctx) {
@@ -424,6 +598,7 @@ impl CompletionList {
         self.needs_results = false;
         self.result_list.drain();
         self.lsp_expected_responses.clear();
+        self.lsp_results_state = None;
     }
 
     fn perform_result_action(
@@ -439,24 +614,73 @@ impl CompletionList {
 
                 None
             }
-            CompletionResult::Completion { mut item, .. } => {
-                self.perform_completion_item(&item, doc, ctx);
-                doc.lsp_apply_edit_list(&mut item.additional_text_edits, ctx);
+            CompletionResult::Completion(completion) => {
+                let LspCompletion {
+                    item,
+                    resolve_state,
+                    server_index,
+                } = *completion;
+
+                // Resolve the item first because it may add edits that are needed by the completion.
+                if resolve_state != CompletionResolveState::Resolved {
+                    if let Some(sent_request) =
+                        Self::lsp_completion_item_resolve(&item, server_index, doc, ctx)
+                    {
+                        self.lsp_accepted_completion = Some(LspAcceptedCompletion {
+                            id: sent_request.id,
+                            server_index,
+                            version: doc.version(),
+                        });
+
+                        return None;
+                    }
+                }
+
+                self.perform_completion_item(item, doc, ctx);
 
                 None
             }
-            CompletionResult::Command(command) => Some(CompletionListResult {
+            CompletionResult::Command {
+                command,
+                server_index,
+            } => Some(CompletionListResult {
                 command: Some(command),
+                server_index,
                 ..Default::default()
             }),
-            CompletionResult::CodeAction(code_action) => Some(CompletionListResult {
-                edit_lists: code_action.edit_lists,
+            CompletionResult::CodeAction {
+                code_action,
+                server_index,
+            } => Some(CompletionListResult {
+                document_changes: code_action.document_changes,
                 command: code_action.command,
+                server_index,
             }),
         }
     }
 
-    fn perform_completion_item(&self, item: &DecodedCompletionItem, doc: &mut Doc, ctx: &mut Ctx) {
+    fn perform_completion_item(
+        &self,
+        mut item: DecodedCompletionItem,
+        doc: &mut Doc,
+        ctx: &mut Ctx,
+    ) {
+        let main_position = doc.cursor(CursorIndex::Main).position;
+
+        let mut additional_text_edits = mem::take(&mut item.additional_text_edits);
+        additional_text_edits.sort_by_key(|edit| Reverse(edit.range.start));
+
+        // Edits after the cursor go first so the completion is inserted where the cursor was.
+        let split_index =
+            additional_text_edits.partition_point(|edit| edit.range.start >= main_position);
+        let edits_before = additional_text_edits.split_off(split_index);
+
+        doc.apply_decoded_text_edits(additional_text_edits, ctx);
+        self.insert_completion_item(&item, doc, ctx);
+        doc.apply_decoded_text_edits(edits_before, ctx);
+    }
+
+    fn insert_completion_item(&self, item: &DecodedCompletionItem, doc: &mut Doc, ctx: &mut Ctx) {
         let insert_text = item.insert_text();
 
         let Some(DecodedRange { start, end }) = item.range() else {
@@ -1,7 +1,16 @@
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+
 use crate::{
     ctx::Ctx,
     geometry::position::Position,
-    text::{cursor_index::CursorIndex, doc::Doc},
+    pool::Pooled,
+    text::{
+        cursor_index::CursorIndex,
+        doc::{Doc, DocPath},
+    },
     ui::{
         core::Ui,
         pane_list::PaneList,
@@ -14,11 +23,21 @@ use super::editor_pane::EditorPane;
 struct CursorHistoryItem {
     position: Position,
     doc_id: SlotId,
+    // Used to reopen the doc if it was closed after the item was recorded.
+    path: Option<Pooled<PathBuf>>,
 }
 
 impl CursorHistoryItem {
-    fn new(position: Position, doc_id: SlotId) -> Self {
-        Self { position, doc_id }
+    fn new(position: Position, doc_id: SlotId, path: Option<Pooled<PathBuf>>) -> Self {
+        Self {
+            position,
+            doc_id,
+            path,
+        }
+    }
+
+    fn is_on_same_line(&self, other: &Self) -> bool {
+        self.doc_id == other.doc_id && self.position.y == other.position.y
     }
 }
 
@@ -30,6 +49,7 @@ pub struct CursorHistory {
 
 impl CursorHistory {
     const CURSOR_DISTANCE_THRESHOLD: usize = 10;
+    const MAX_ITEMS: usize = 100;
 
     pub fn new() -> Self {
         Self {
@@ -42,6 +62,7 @@ impl CursorHistory {
     pub fn update(
         &mut self,
         last_doc_id: Option<SlotId>,
+        last_path: Option<Pooled<PathBuf>>,
         doc_id: SlotId,
         last_position: Option<Position>,
         position: Position,
@@ -61,12 +82,29 @@ impl CursorHistory {
         }
 
         self.redo_history.clear();
-        self.undo_history
-            .push(CursorHistoryItem::new(last_position, last_doc_id));
+        Self::push_item(
+            &mut self.undo_history,
+            CursorHistoryItem::new(last_position, last_doc_id, last_path),
+        );
 
         Some(())
     }
 
+    fn push_item(history: &mut Vec<CursorHistoryItem>, item: CursorHistoryItem) {
+        if history
+            .last()
+            .is_some_and(|last_item| last_item.is_on_same_line(&item))
+        {
+            history.pop();
+        }
+
+        if history.len() >= Self::MAX_ITEMS {
+            history.remove(0);
+        }
+
+        history.push(item);
+    }
+
     pub fn undo(
         &mut self,
         panes: &mut PaneList<EditorPane, Doc>,
@@ -112,7 +150,7 @@ impl CursorHistory {
             return None;
         }
 
-        push_history.push(Self::get_item(panes, doc_list, ctx.ui)?);
+        Self::push_item(push_history, Self::get_item(panes, doc_list, ctx.ui)?);
 
         while let Some(item) = pop_history.pop() {
             if Self::jump_to_item(item, panes, doc_list, ctx) {
@@ -134,7 +172,18 @@ impl CursorHistory {
         let doc = doc_list.get(doc_id)?;
         let cursor = doc.cursor(CursorIndex::Main);
 
-        Some(CursorHistoryItem::new(cursor.position, doc_id))
+        Some(CursorHistoryItem::new(
+            cursor.position,
+            doc_id,
+            Self::doc_path(doc),
+        ))
+    }
+
+    pub fn doc_path(doc: &Doc) -> Option<Pooled<PathBuf>> {
+        match doc.path() {
+            DocPath::OnDrive(path) => Some(path.clone()),
+            _ => None,
+        }
     }
 
     fn jump_to_item(
@@ -143,33 +192,71 @@ impl CursorHistory {
         doc_list: &mut SlotList<Doc>,
         ctx: &mut Ctx,
     ) -> bool {
-        let Some(doc) = doc_list.get_mut(item.doc_id) else {
-            return false;
+        // The doc may have been closed, or its slot may now hold a different file.
+        let Some(doc) = doc_list
+            .get_mut(item.doc_id)
+            .filter(|doc| item.path.is_none() || Self::doc_path(doc) == item.path)
+        else {
+            return Self::reopen_item(item, panes, doc_list, ctx);
         };
 
-        let focused_pane = panes.get_last_focused_mut(ctx.ui).unwrap();
+        let Some(focused_pane) = panes.get_last_focused_mut(ctx.ui) else {
+            return false;
+        };
 
         if !Self::focus_tab_for_doc_id(focused_pane, item.doc_id, ctx.ui) {
-            for pane in panes.iter() {
-                if !Self::focus_tab_for_doc_id(pane, item.doc_id, ctx.ui) {
-                    continue;
-                }
+            let Some(pane) = panes
+                .iter()
+                .find(|pane| Self::focus_tab_for_doc_id(pane, item.doc_id, ctx.ui))
+            else {
+                // The doc is still open but no longer has a tab to show it in.
+                return false;
+            };
 
-                ctx.ui.focus(pane.widget_id());
-                break;
-            }
+            ctx.ui.focus(pane.widget_id());
         }
 
         doc.jump_cursor(CursorIndex::Main, item.position, false, ctx.gfx);
+        Self::recenter_focused_tab(panes, ctx);
 
+        true
+    }
+
+    fn reopen_item(
+        item: CursorHistoryItem,
+        panes: &mut PaneList<EditorPane, Doc>,
+        doc_list: &mut SlotList<Doc>,
+        ctx: &mut Ctx,
+    ) -> bool {
+        let Some(path) = item.path else {
+            return false;
+        };
+
+        let Some(focused_pane) = panes.get_last_focused_mut(ctx.ui) else {
+            return false;
+        };
+
+        if focused_pane.open_file(&path, doc_list, ctx).is_err() {
+            return false;
+        }
+
+        let Some((_, doc)) = focused_pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) else {
+            return false;
+        };
+
+        doc.jump_cursor(CursorIndex::Main, item.position, false, ctx.gfx);
+        Self::recenter_focused_tab(panes, ctx);
+
+        true
+    }
+
+    fn recenter_focused_tab(panes: &mut PaneList<EditorPane, Doc>, ctx: &mut Ctx) {
         if let Some(tab) = panes
             .get_last_focused_mut(ctx.ui)
             .and_then(|pane| pane.get_focused_tab_mut(ctx.ui))
         {
             tab.camera.recenter();
         }
-
-        true
     }
 
     fn focus_tab_for_doc_id(pane: &EditorPane, doc_id: SlotId, ui: &mut Ui) -> bool {
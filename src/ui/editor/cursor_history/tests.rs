@@ -0,0 +1,74 @@
+use std::env::temp_dir;
+
+use crate::{
+    geometry::position::Position,
+    tests::test_with_doc,
+    text::{
+        cursor_index::CursorIndex,
+        doc::{Doc, DocFlags},
+    },
+    ui::{
+        core::WidgetId, editor::editor_pane::EditorPane, pane_list::PaneList, slot_list::SlotList,
+        tab::Tab,
+    },
+};
+
+use super::CursorHistory;
+
+test_with_doc!(undo_skips_stale_items, "", |ctx, _| {
+    let mut doc_list = SlotList::new();
+    let mut panes = PaneList::new(WidgetId::ROOT, ctx.ui);
+    let pane = EditorPane::new(&mut doc_list, panes.widget_id(), ctx);
+    panes.add(pane, ctx.ui);
+
+    let doc_id = panes
+        .get_last_focused(ctx.ui)
+        .and_then(|pane| pane.get_focused_tab(ctx.ui))
+        .map(Tab::data_id)
+        .unwrap();
+
+    if let Some(doc) = doc_list.get_mut(doc_id) {
+        doc.insert(Position::ZERO, &"line\n".repeat(20), ctx);
+        doc.jump_cursors(Position::ZERO, false, ctx.gfx);
+    }
+
+    let closed_doc_id = doc_list.add(Doc::new(None, None, DocFlags::MULTI_LINE));
+    doc_list.remove(closed_doc_id);
+
+    let mut cursor_history = CursorHistory::new();
+
+    // The doc was closed and has no path to reopen it from.
+    cursor_history.update(
+        Some(closed_doc_id),
+        None,
+        doc_id,
+        Some(Position::new(0, 5)),
+        Position::ZERO,
+    );
+
+    // The doc is still open, but it isn't the file that was recorded.
+    cursor_history.update(
+        Some(doc_id),
+        Some(
+            temp_dir()
+                .join("keylime_undo_skips_stale_items.txt")
+                .as_path()
+                .into(),
+        ),
+        closed_doc_id,
+        Some(Position::new(0, 10)),
+        Position::ZERO,
+    );
+
+    cursor_history.undo(&mut panes, &mut doc_list, ctx);
+
+    assert!(cursor_history.undo_history.is_empty());
+    assert_eq!(
+        doc_list
+            .get(doc_id)
+            .unwrap()
+            .cursor(CursorIndex::Main)
+            .position,
+        Position::ZERO
+    );
+});
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    ctx::Ctx,
+    geometry::position::Position,
+    pool::{format_pooled, STRING_POOL},
+    text::{
+        diff::diff_lines,
+        doc::{Doc, DocFlags},
+        syntax_highlighter::{HighlightKind, TerminalHighlightKind},
+    },
+};
+
+// Creates a read-only doc showing the lines of both docs inline,
+// with lines only in the other doc marked as removed and lines only in the doc marked as added.
+pub fn new_diff_doc(doc: &Doc, other_doc: &Doc, ctx: &mut Ctx) -> Doc {
+    const UNCHANGED_PREFIX: &str = "  ";
+    const REMOVED_PREFIX: &str = "- ";
+    const ADDED_PREFIX: &str = "+ ";
+
+    let display_name = format_pooled!("{} vs {}", other_doc.file_name(), doc.file_name());
+    let mut diff_doc = Doc::new(None, Some(display_name), DocFlags::LOG);
+
+    let lines = doc.lines();
+    let other_lines = other_doc.lines();
+
+    let mut text = STRING_POOL.new_item();
+    let mut line_kinds = Vec::new();
+    let mut y = 0;
+
+    let mut push_line = |prefix: &str, line: &str, kind: Option<TerminalHighlightKind>| {
+        if !line_kinds.is_empty() {
+            text.push('\n');
+        }
+
+        text.push_str(prefix);
+        text.push_str(line);
+        line_kinds.push(kind);
+    };
+
+    for hunk in diff_lines(other_lines, lines) {
+        for line in &lines[y..hunk.lines.start] {
+            push_line(UNCHANGED_PREFIX, line, None);
+        }
+
+        for line in &other_lines[hunk.saved_lines] {
+            push_line(REMOVED_PREFIX, line, Some(TerminalHighlightKind::Red));
+        }
+
+        for line in &lines[hunk.lines.clone()] {
+            push_line(ADDED_PREFIX, line, Some(TerminalHighlightKind::Green));
+        }
+
+        y = hunk.lines.end;
+    }
+
+    for line in &lines[y..] {
+        push_line(UNCHANGED_PREFIX, line, None);
+    }
+
+    diff_doc.insert(Position::ZERO, &text, ctx);
+
+    for (y, kind) in line_kinds.iter().enumerate() {
+        let kind = kind
+            .map(HighlightKind::Terminal)
+            .unwrap_or(HighlightKind::Normal);
+        diff_doc.highlight_line_with_kind(kind, y);
+    }
+
+    // Start at the first change, since there's usually little to see before it.
+    let first_change_y = line_kinds.iter().position(Option::is_some).unwrap_or(0);
+
+    diff_doc.jump_cursors(Position::new(0, first_change_y), false, ctx.gfx);
+    diff_doc.toggle_read_only();
+
+    diff_doc
+}
@@ -0,0 +1,48 @@
+use crate::{
+    geometry::position::Position,
+    tests::test_with_doc,
+    text::{
+        cursor_index::CursorIndex,
+        doc::{Doc, DocFlags},
+        syntax_highlighter::{HighlightKind, TerminalHighlightKind},
+    },
+};
+
+use super::new_diff_doc;
+
+test_with_doc!(
+    diff_doc_marks_removed_and_added_lines,
+    "a\nB\nc\nd",
+    |ctx, doc| {
+        let mut other_doc = Doc::new(None, None, DocFlags::MULTI_LINE);
+        other_doc.insert(Position::ZERO, "a\nb\nc", ctx);
+
+        let diff_doc = new_diff_doc(doc, &other_doc, ctx);
+
+        let lines: Vec<&str> = diff_doc.lines().iter().map(|line| line.as_str()).collect();
+
+        assert_eq!(lines, ["  a", "- b", "+ B", "  c", "+ d"]);
+
+        let foregrounds: Vec<Option<HighlightKind>> = diff_doc
+            .highlighted_lines()
+            .iter()
+            .map(|line| {
+                line.highlights()
+                    .first()
+                    .map(|highlight| highlight.foreground)
+            })
+            .collect();
+
+        let red = Some(HighlightKind::Terminal(TerminalHighlightKind::Red));
+        let green = Some(HighlightKind::Terminal(TerminalHighlightKind::Green));
+        let normal = Some(HighlightKind::Normal);
+
+        assert_eq!(foregrounds, [normal, red, green, normal, green]);
+
+        assert_eq!(
+            diff_doc.cursor(CursorIndex::Main).position,
+            Position::new(0, 1)
+        );
+        assert!(diff_doc.is_read_only());
+    }
+);
@@ -1,4 +1,7 @@
-use std::{io, path::Path};
+#[cfg(test)]
+mod tests;
+
+use std::{fs::canonicalize, io, path::Path};
 
 use crate::{
     ctx::Ctx,
@@ -44,10 +47,20 @@ pub fn try_save(doc: &mut Doc, ctx: &mut Ctx) -> bool {
         None
     };
 
+    let save_path = path.as_ref().or(doc.path().some());
+
+    if !save_path.is_none_or(|save_path| confirm_create_parent_dir(save_path, ctx)) {
+        return false;
+    }
+
     if ctx.config.trim_trailing_whitespace {
         doc.trim_trailing_whitespace(ctx);
     }
 
+    if ctx.config.trim_blank_lines {
+        doc.trim_blank_lines(ctx);
+    }
+
     if ctx.config.format_on_save {
         doc.lsp_formatting(ctx);
     }
@@ -65,6 +78,27 @@ pub fn try_save(doc: &mut Doc, ctx: &mut Ctx) -> bool {
     }
 }
 
+// Saving into a folder that doesn't exist creates it, so the user is asked first.
+fn confirm_create_parent_dir(path: &Path, ctx: &mut Ctx) -> bool {
+    let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty() && !parent.exists())
+    else {
+        return true;
+    };
+
+    if !ctx.config.confirm_create_dirs_on_save {
+        return true;
+    }
+
+    let text = format_pooled!(
+        "{} doesn't exist. Do you want to create it?",
+        parent.display()
+    );
+
+    message("Missing Folder", &text, MessageKind::YesNo, ctx.window) == MessageResponse::Yes
+}
+
 pub fn open_or_reuse(
     doc_list: &mut SlotList<Doc>,
     path: &Path,
@@ -78,8 +112,21 @@ pub fn open_or_reuse(
         }
     }
 
+    // Different paths can still lead to the same file through symlinks.
+    if let Ok(canonical_path) = canonicalize(&path) {
+        for (id, doc) in doc_list.enumerate() {
+            let Some(doc_path) = doc.path().some() else {
+                continue;
+            };
+
+            if canonicalize(doc_path).is_ok_and(|doc_path| doc_path == canonical_path) {
+                return Ok(id);
+            }
+        }
+    }
+
     let mut doc = Doc::new(Some(path), None, DocFlags::MULTI_LINE);
-    doc.load(ctx)?;
+    doc.load_in_background(ctx)?;
 
     Ok(doc_list.add(doc))
 }
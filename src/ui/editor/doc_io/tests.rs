@@ -0,0 +1,61 @@
+use std::{
+    env::temp_dir,
+    fs::{read_to_string, remove_dir_all},
+};
+
+use crate::{
+    config::Config,
+    ctx::Ctx,
+    geometry::position::Position,
+    tests::test_with_doc,
+    text::doc::{Doc, DocFlags},
+};
+
+use super::try_save;
+
+test_with_doc!(save_asks_before_creating_parent_dirs, "", |ctx, _| {
+    let dir = temp_dir().join("keylime_save_asks_before_creating_parent_dirs");
+    let path = dir.join("new/file.txt");
+
+    let mut doc = Doc::new(Some(path.as_path().into()), None, DocFlags::MULTI_LINE);
+    doc.insert(Position::ZERO, "text", ctx);
+
+    // The test dialog never confirms, so nothing is created.
+    let is_saved = try_save(&mut doc, ctx);
+    let dir_exists = dir.exists();
+
+    let _ = remove_dir_all(&dir);
+
+    assert!(!is_saved);
+    assert!(!dir_exists);
+    assert!(!doc.is_saved());
+});
+
+test_with_doc!(save_creates_parent_dirs_without_asking, "", |ctx, _| {
+    let dir = temp_dir().join("keylime_save_creates_parent_dirs_without_asking");
+    let path = dir.join("new/file.txt");
+
+    let mut config = Config::default();
+    config.confirm_create_dirs_on_save = false;
+
+    let ctx = &mut Ctx {
+        window: ctx.window,
+        gfx: ctx.gfx,
+        ui: ctx.ui,
+        config: &config,
+        lsp: ctx.lsp,
+        current_dir: ctx.current_dir,
+        time: ctx.time,
+    };
+
+    let mut doc = Doc::new(Some(path.as_path().into()), None, DocFlags::MULTI_LINE);
+    doc.insert(Position::ZERO, "text", ctx);
+
+    let is_saved = try_save(&mut doc, ctx);
+    let saved_text = read_to_string(&path);
+
+    let _ = remove_dir_all(&dir);
+
+    assert!(is_saved);
+    assert_eq!(saved_text.unwrap(), "text");
+});
@@ -8,13 +8,17 @@ use crate::{
     ctx::Ctx,
     normalizable::Normalizable,
     platform::dialog::{find_file, message, FindFileKind, MessageKind},
-    text::doc::{Doc, DocFlags},
+    text::{
+        cursor_index::CursorIndex,
+        doc::{Doc, DocFlags},
+    },
     ui::{
         core::WidgetId,
         msg::Msg,
         pane::Pane,
         pane_list::PaneWrapper,
         slot_list::{SlotId, SlotList},
+        tab::Tab,
     },
 };
 
@@ -103,9 +107,18 @@ impl EditorPane {
             return false;
         }
 
+        let closed_msg = doc.path().on_drive().map(|path| Msg::TabClosed {
+            path: path.into(),
+            position: doc.cursor(CursorIndex::Main).position,
+        });
+
         self.inner.remove_tab(doc_list, ctx.ui);
         ctx.ui.send_to_parent(self.widget_id, Msg::HideEditorPopups);
 
+        if let Some(closed_msg) = closed_msg {
+            ctx.ui.send_to_parent(self.widget_id, closed_msg);
+        }
+
         if doc_list.get(doc_id).is_some_and(|doc| doc.usages() == 0) {
             if let Some(mut doc) = doc_list.remove(doc_id) {
                 doc.clear(ctx)
@@ -115,6 +128,35 @@ impl EditorPane {
         true
     }
 
+    fn close_unpinned_tabs(
+        &mut self,
+        kept_tab_id: Option<WidgetId>,
+        doc_list: &mut SlotList<Doc>,
+        ctx: &mut Ctx,
+    ) {
+        loop {
+            let Some(index) = self
+                .iter_tabs()
+                .position(|tab| !tab.is_pinned() && Some(tab.widget_id()) != kept_tab_id)
+            else {
+                break;
+            };
+
+            self.focus_tab_at_index(index, ctx.ui);
+
+            if !self.remove_tab(doc_list, ctx) {
+                return;
+            }
+        }
+
+        if let Some(index) = self
+            .iter_tabs()
+            .position(|tab| Some(tab.widget_id()) == kept_tab_id)
+        {
+            self.focus_tab_at_index(index, ctx.ui);
+        }
+    }
+
     pub fn close_all_tabs(&mut self, doc_list: &mut SlotList<Doc>, ctx: &mut Ctx) -> bool {
         while self.has_tabs() {
             if !self.remove_tab(doc_list, ctx) {
@@ -155,6 +197,13 @@ impl PaneWrapper<Doc> for EditorPane {
                 Msg::Action(action_name!(CloseTab)) => {
                     self.remove_tab(doc_list, ctx);
                 }
+                Msg::Action(action_name!(CloseAllTabs)) => {
+                    self.close_unpinned_tabs(None, doc_list, ctx);
+                }
+                Msg::Action(action_name!(CloseOtherTabs)) => {
+                    let focused_tab_id = self.get_focused_tab(ctx.ui).map(Tab::widget_id);
+                    self.close_unpinned_tabs(focused_tab_id, doc_list, ctx);
+                }
                 Msg::Action(action_name!(ReloadFile)) => {
                     if let Some((_, doc)) =
                         self.inner.get_focused_tab_with_data_mut(doc_list, ctx.ui)
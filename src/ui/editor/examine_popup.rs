@@ -1,7 +1,10 @@
+use std::fmt::Write;
+
 use crate::{
     ctx::Ctx,
     geometry::{position::Position, rect::Rect},
     lsp::types::{DecodedDiagnostic, DecodedHover, DecodedRange, HoverContents},
+    pool::{Pooled, STRING_POOL},
     text::doc::Doc,
     ui::{
         core::{Ui, WidgetId, WidgetSettings},
@@ -30,6 +33,10 @@ pub struct ExaminePopup {
     kind: ExaminePopupKind,
     position: Position,
     desired_position: Position,
+    // The doc version and position of the shown hover, which further hovers can be merged into.
+    hover_state: Option<(usize, Position)>,
+    hover_text: Pooled<String>,
+    hover_extension: Pooled<String>,
 }
 
 impl ExaminePopup {
@@ -50,6 +57,9 @@ impl ExaminePopup {
             kind: ExaminePopupKind::None,
             position: Position::ZERO,
             desired_position: Position::ZERO,
+            hover_state: None,
+            hover_text: STRING_POOL.new_item(),
+            hover_extension: STRING_POOL.new_item(),
         }
     }
 
@@ -117,6 +127,15 @@ impl ExaminePopup {
     }
 
     pub fn lsp_set_hover(&mut self, hover: Option<DecodedHover>, doc: &Doc, ctx: &mut Ctx) {
+        // Each server attached to the doc responds separately, so show their hovers together.
+        if self.hover_state == Some((doc.version(), self.desired_position)) {
+            if let Some(hover) = hover {
+                self.merge_hover(&hover.contents, ctx);
+            }
+
+            return;
+        }
+
         let data = match hover {
             Some(hover) => ExaminePopupData::Hover(hover.contents, hover.range),
             None => ExaminePopupData::None,
@@ -131,29 +150,75 @@ impl ExaminePopup {
 
     pub fn hide(&mut self, ui: &mut Ui) {
         self.kind = ExaminePopupKind::None;
+        self.hover_state = None;
         self.popup.hide(ui);
     }
 
+    fn merge_hover(&mut self, contents: &HoverContents, ctx: &mut Ctx) {
+        let text = contents.text();
+
+        if text.is_empty() || self.hover_text.contains(text.as_str()) {
+            return;
+        }
+
+        self.hover_text.push_str("\n\n");
+        self.hover_text.push_str(&text);
+
+        self.popup
+            .show(&self.hover_text, &self.hover_extension, ctx);
+    }
+
     fn set_data(&mut self, kind: ExaminePopupData, doc: &Doc, ctx: &mut Ctx) {
         self.hide(ctx.ui);
 
         match kind {
             ExaminePopupData::None => {}
             ExaminePopupData::Diagnostic(diagnostic) => {
-                self.popup.show(&diagnostic.message, "", ctx);
+                let text = Self::diagnostic_text(diagnostic, ctx);
+
+                self.popup.show(&text, "", ctx);
                 self.position = diagnostic.visible_range(doc).start;
                 self.kind = ExaminePopupKind::Diagnostic;
             }
             ExaminePopupData::Hover(contents, range) => {
-                self.popup.show(&contents.text(), contents.extension(), ctx);
+                self.hover_text.clear();
+                self.hover_text.push_str(&contents.text());
+                self.hover_extension.clear();
+                self.hover_extension.push_str(contents.extension());
+
+                self.popup
+                    .show(&self.hover_text, &self.hover_extension, ctx);
                 self.position = range
                     .map(|range| range.start)
                     .unwrap_or(self.desired_position);
                 self.kind = ExaminePopupKind::Hover;
+                self.hover_state = Some((doc.version(), self.desired_position));
             }
         }
     }
 
+    fn diagnostic_text(diagnostic: &DecodedDiagnostic, ctx: &Ctx) -> Pooled<String> {
+        let mut text = STRING_POOL.new_item();
+        text.push_str(&diagnostic.message);
+
+        for information in &diagnostic.related_information {
+            let path = information
+                .path
+                .strip_prefix(ctx.current_dir.as_path())
+                .unwrap_or(&information.path);
+
+            let _ = write!(
+                text,
+                "\n{}:{}: {}",
+                path.display(),
+                information.range.start.line + 1,
+                information.message
+            );
+        }
+
+        text
+    }
+
     pub fn widget_id(&self) -> WidgetId {
         self.widget_id
     }
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use crate::{
+    ctx::Ctx,
+    pool::{format_pooled, STRING_POOL},
+    text::doc::{Doc, DocFlags},
+    ui::slot_list::{SlotId, SlotList},
+};
+
+#[derive(Debug)]
+struct LspLogView {
+    doc_id: SlotId,
+    log_generation: usize,
+    log_len: usize,
+    doc_version: Option<usize>,
+}
+
+// Read-only docs that mirror the logs of language servers.
+pub struct LspLogs {
+    views: HashMap<(usize, usize), LspLogView>,
+}
+
+impl LspLogs {
+    pub fn new() -> Self {
+        Self {
+            views: HashMap::new(),
+        }
+    }
+
+    pub fn get_or_add_doc(
+        &mut self,
+        server_key: (usize, usize),
+        doc_list: &mut SlotList<Doc>,
+        ctx: &mut Ctx,
+    ) -> Option<SlotId> {
+        if let Some(view) = self
+            .views
+            .get(&server_key)
+            .filter(|view| doc_list.get(view.doc_id).is_some())
+        {
+            return Some(view.doc_id);
+        }
+
+        let server = ctx.lsp.get_server(server_key)?;
+        let display_name = format_pooled!("{} Log", server.name());
+        let mut doc = Doc::new(None, Some(display_name), DocFlags::LOG);
+        doc.toggle_read_only();
+
+        let doc_id = doc_list.add(doc);
+
+        self.views.insert(
+            server_key,
+            LspLogView {
+                doc_id,
+                log_generation: 0,
+                log_len: 0,
+                doc_version: None,
+            },
+        );
+
+        self.update(doc_list, ctx);
+
+        Some(doc_id)
+    }
+
+    pub fn update(&mut self, doc_list: &mut SlotList<Doc>, ctx: &mut Ctx) {
+        self.views.retain(|server_key, view| {
+            let Some(doc) = doc_list.get_mut(view.doc_id) else {
+                return false;
+            };
+
+            let Some(server) = ctx.lsp.get_server(*server_key) else {
+                return true;
+            };
+
+            let log = server.log();
+
+            let is_in_sync = view.log_generation == server.log_generation()
+                && view.doc_version == Some(doc.version());
+
+            if is_in_sync && view.log_len == log.len() {
+                return true;
+            }
+
+            let start = if is_in_sync { view.log_len } else { 0 };
+            let text = STRING_POOL.init_item(|text| text.push_str(&log[start..]));
+
+            view.log_generation = server.log_generation();
+            view.log_len = log.len();
+
+            if !is_in_sync {
+                doc.clear(ctx);
+            }
+
+            let end = doc.end();
+            doc.insert_ignoring_read_only(end, &text, ctx);
+
+            view.doc_version = Some(doc.version());
+
+            true
+        });
+    }
+}
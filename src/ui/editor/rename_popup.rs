@@ -0,0 +1,208 @@
+use crate::{
+    ctx::Ctx,
+    geometry::{position::Position, rect::Rect, sides::Sides},
+    input::{action::action_keybind, editing_actions::handle_select_all, mods::Mods},
+    lsp::types::DecodedRange,
+    text::{
+        doc::{Doc, DocFlags},
+        grapheme::{self, GraphemeIterator},
+    },
+    ui::{
+        core::{Ui, WidgetId, WidgetSettings},
+        msg::Msg,
+        slot_list::{SlotId, SlotList},
+        tab::Tab,
+    },
+};
+
+pub struct RenamePopup {
+    widget_id: WidgetId,
+    tab: Tab,
+    doc: Doc,
+    doc_id: Option<SlotId>,
+    range: DecodedRange,
+}
+
+impl RenamePopup {
+    pub fn new(parent_id: WidgetId, ctx: &mut Ctx) -> Self {
+        let widget_id = ctx.ui.new_widget(
+            parent_id,
+            WidgetSettings {
+                is_shown: false,
+                wants_msgs: false,
+                popup: Some(Rect::ZERO),
+                is_owned_by_parent: false,
+                ..Default::default()
+            },
+        );
+
+        let tab = Tab::new(widget_id, SlotId::ZERO, ctx.ui);
+
+        Self {
+            widget_id,
+            tab,
+            doc: Doc::new(None, None, DocFlags::SINGLE_LINE),
+            doc_id: None,
+            range: DecodedRange {
+                start: Position::ZERO,
+                end: Position::ZERO,
+            },
+        }
+    }
+
+    pub fn is_animating(&self, ctx: &Ctx) -> bool {
+        self.tab.is_animating(ctx)
+    }
+
+    pub fn receive_msgs(&mut self, doc_list: &SlotList<Doc>, ctx: &mut Ctx) {
+        while let Some(msg) = ctx.ui.msg(self.tab.widget_id()) {
+            match msg {
+                Msg::Action(action_keybind!(key: Enter, mods: Mods::NONE)) => {
+                    self.submit(doc_list, ctx)
+                }
+                Msg::Action(action_keybind!(key: Escape, mods: Mods::NONE)) => self.hide(ctx.ui),
+                _ => self.tab.receive_msg(msg, &mut self.doc, ctx),
+            }
+        }
+    }
+
+    pub fn update(&mut self, tab: &Tab, doc: &Doc, ctx: &mut Ctx, dt: f32) {
+        if !self.is_open() {
+            return;
+        }
+
+        if !ctx.ui.is_focused(self.widget_id) || self.doc_id != Some(tab.data_id()) {
+            self.hide(ctx.ui);
+            return;
+        }
+
+        let tab_bounds = tab.doc_bounds(ctx.ui);
+        let position = doc
+            .position_to_visual(self.range.start, tab.camera.position(), ctx.gfx)
+            .offset_by(tab_bounds);
+
+        if !tab_bounds.contains_position(position) {
+            self.hide(ctx.ui);
+            return;
+        }
+
+        // Cover the whole symbol so that the new name is previewed in its place.
+        let symbol_width = doc
+            .get_line(self.range.start.y)
+            .filter(|line| self.range.end.y == self.range.start.y && self.range.end.x <= line.len())
+            .map(|_| {
+                doc.measure_line_range(
+                    self.range.start.y,
+                    self.range.start.x,
+                    self.range.end.x,
+                    ctx.gfx,
+                )
+            })
+            .unwrap_or_default();
+
+        let gfx = &mut ctx.gfx;
+        let input_width = gfx.measure_text(self.input()).max(symbol_width) as f32
+            * gfx.glyph_width()
+            + Tab::cursor_width(gfx);
+
+        let bounds = Rect::new(
+            position.x - gfx.line_padding_x(),
+            position.y,
+            input_width + gfx.line_padding_x() * 2.0,
+            gfx.line_height(),
+        );
+
+        ctx.ui
+            .set_popup(self.widget_id, Some(bounds.add_margin(gfx.border_width())));
+        ctx.ui.set_popup(self.tab.widget_id(), Some(bounds));
+
+        self.tab.update(&mut self.doc, ctx, dt);
+    }
+
+    pub fn draw(&mut self, ctx: &mut Ctx) {
+        if !ctx.ui.is_visible(self.widget_id) {
+            return;
+        }
+
+        let gfx = &mut ctx.gfx;
+        let theme = &ctx.config.theme;
+        let bounds = ctx.ui.bounds(self.widget_id);
+
+        let border_color = if self.is_input_valid() {
+            theme.border
+        } else {
+            theme.error
+        };
+
+        gfx.begin(Some(bounds));
+
+        gfx.add_bordered_rect(
+            bounds.relative_to(bounds),
+            Sides::ALL,
+            theme.background,
+            border_color,
+        );
+
+        gfx.end();
+
+        self.tab.draw(Default::default(), &mut self.doc, ctx);
+    }
+
+    pub fn show(
+        &mut self,
+        range: DecodedRange,
+        placeholder: &str,
+        parent_id: WidgetId,
+        doc_id: SlotId,
+        ctx: &mut Ctx,
+    ) {
+        ctx.ui.reparent_widget(self.widget_id, parent_id);
+
+        self.doc_id = Some(doc_id);
+        self.range = range;
+
+        self.doc.clear(ctx);
+        self.doc.insert(Position::ZERO, placeholder, ctx);
+        handle_select_all(&mut self.doc, ctx.gfx);
+        self.tab.skip_cursor_animations(&self.doc, ctx);
+
+        ctx.ui.focus(self.widget_id);
+    }
+
+    fn submit(&mut self, doc_list: &SlotList<Doc>, ctx: &mut Ctx) {
+        if !self.is_input_valid() {
+            return;
+        }
+
+        if let Some(doc) = self.doc_id.and_then(|doc_id| doc_list.get(doc_id)) {
+            doc.lsp_rename(self.input(), ctx);
+        }
+
+        self.hide(ctx.ui);
+    }
+
+    fn is_input_valid(&self) -> bool {
+        let input = self.input();
+
+        !input.is_empty()
+            && !grapheme::is_ascii_digit(grapheme::at(0, input))
+            && GraphemeIterator::new(input).all(|grapheme| !grapheme::is_whitespace(grapheme))
+    }
+
+    fn input(&self) -> &str {
+        self.doc.get_line(0).unwrap_or_default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.doc_id.is_some()
+    }
+
+    pub fn hide(&mut self, ui: &mut Ui) {
+        self.doc_id = None;
+        ui.hide(self.widget_id);
+    }
+
+    pub fn widget_id(&self) -> WidgetId {
+        self.widget_id
+    }
+}
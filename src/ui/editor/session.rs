@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SessionTab {
+    pub path: PathBuf,
+    pub x: usize,
+    pub y: usize,
+    #[serde(default)]
+    pub scroll_x: f32,
+    #[serde(default)]
+    pub scroll_y: f32,
+    #[serde(default)]
+    pub is_pinned: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SessionPane {
+    pub tabs: Vec<SessionTab>,
+    pub focused_tab_index: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Session {
+    // Stored to tell apart workspaces whose paths map to the same file name.
+    pub dir: PathBuf,
+    pub panes: Vec<SessionPane>,
+    pub focused_pane_index: usize,
+    // Where the terminal's shell was, so that it can start there next time.
+    #[serde(default)]
+    pub terminal_dir: Option<PathBuf>,
+}
+
+impl Session {
+    const SESSIONS_DIR: &str = "sessions";
+
+    // Each workspace directory gets its own session file.
+    pub fn path(config_dir: &Path, dir: &Path) -> PathBuf {
+        let mut file_name: String = dir
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+
+        file_name.push_str(".json");
+
+        config_dir.join(Self::SESSIONS_DIR).join(file_name)
+    }
+
+    pub fn load(config_dir: &Path, dir: &Path) -> io::Result<Self> {
+        let text = read_to_string(Self::path(config_dir, dir))?;
+        let session: Self = serde_json::from_str(&text).map_err(io::Error::other)?;
+
+        if session.dir != dir {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "No session was saved for this folder",
+            ));
+        }
+
+        Ok(session)
+    }
+
+    pub fn save(&self, config_dir: &Path) -> io::Result<()> {
+        let path = Self::path(config_dir, &self.dir);
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        write(path, text)
+    }
+}
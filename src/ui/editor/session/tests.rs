@@ -0,0 +1,69 @@
+use std::{env::temp_dir, fs::remove_dir_all, path::Path};
+
+use super::{Session, SessionPane, SessionTab};
+
+#[test]
+fn session_save_load_round_trip() {
+    let config_dir = temp_dir().join("keylime_session_save_load_round_trip");
+    let dir = Path::new("/project");
+
+    let session = Session {
+        dir: dir.to_owned(),
+        panes: vec![
+            SessionPane {
+                tabs: vec![
+                    SessionTab {
+                        path: dir.join("src/main.rs"),
+                        x: 4,
+                        y: 120,
+                        scroll_x: 0.0,
+                        scroll_y: 98.5,
+                        is_pinned: true,
+                    },
+                    SessionTab {
+                        path: dir.join("README.md"),
+                        x: 0,
+                        y: 3,
+                        scroll_x: 12.0,
+                        scroll_y: 0.0,
+                        is_pinned: false,
+                    },
+                ],
+                focused_tab_index: 1,
+            },
+            SessionPane {
+                tabs: vec![SessionTab {
+                    path: dir.join("Cargo.toml"),
+                    x: 1,
+                    y: 2,
+                    scroll_x: 0.0,
+                    scroll_y: 0.0,
+                    is_pinned: false,
+                }],
+                focused_tab_index: 0,
+            },
+        ],
+        focused_pane_index: 1,
+        terminal_dir: Some(dir.join("src")),
+    };
+
+    let save_result = session.save(&config_dir);
+    let loaded = Session::load(&config_dir, dir);
+    let other_dir_loaded = Session::load(&config_dir, Path::new("/other"));
+
+    let _ = remove_dir_all(&config_dir);
+
+    assert!(save_result.is_ok());
+    assert_eq!(loaded.ok(), Some(session));
+    assert!(other_dir_loaded.is_err());
+}
+
+#[test]
+fn session_tab_without_scroll_position() {
+    let tab: SessionTab =
+        serde_json::from_str(r#"{ "path": "/project/main.rs", "x": 2, "y": 7 }"#).unwrap();
+
+    assert_eq!((tab.x, tab.y), (2, 7));
+    assert_eq!((tab.scroll_x, tab.scroll_y), (0.0, 0.0));
+    assert!(!tab.is_pinned);
+}
@@ -1,10 +1,12 @@
 use crate::{
     ctx::Ctx,
     geometry::{position::Position, rect::Rect},
+    input::{action::action_keybind, mods::Mods},
     lsp::types::SignatureHelp,
     text::{cursor_index::CursorIndex, doc::Doc},
     ui::{
         core::{Ui, WidgetId, WidgetSettings},
+        msg::Msg,
         popup::{Popup, PopupAlignment},
         tab::Tab,
     },
@@ -15,6 +17,7 @@ pub struct SignatureHelpPopup {
 
     help_position: Position,
     help: Option<SignatureHelp>,
+    selected_signature: Option<usize>,
 
     label_popup: Popup,
     documentation_popup: Popup,
@@ -37,6 +40,7 @@ impl SignatureHelpPopup {
 
             help_position: Position::ZERO,
             help: None,
+            selected_signature: None,
 
             label_popup: Popup::new(widget_id, ctx),
             documentation_popup: Popup::new(widget_id, ctx),
@@ -52,6 +56,56 @@ impl SignatureHelpPopup {
         self.documentation_popup.receive_msgs(ctx);
     }
 
+    // Intercepts Up and Down in the tab so that they cycle through signatures instead of moving the cursor.
+    pub fn receive_tab_msgs(&mut self, tab_widget_id: WidgetId, ctx: &mut Ctx) {
+        if self
+            .help
+            .as_ref()
+            .is_none_or(|help| help.signatures.len() < 2)
+        {
+            return;
+        }
+
+        let mut skipped_msgs = Vec::new();
+
+        while let Some(msg) = ctx.ui.msg(tab_widget_id) {
+            match msg {
+                Msg::Action(action_keybind!(key: Up, mods: Mods::NONE)) => {
+                    self.cycle_signature(false, ctx);
+                }
+                Msg::Action(action_keybind!(key: Down, mods: Mods::NONE)) => {
+                    self.cycle_signature(true, ctx);
+                }
+                _ => skipped_msgs.push(msg),
+            }
+        }
+
+        for msg in skipped_msgs {
+            ctx.ui.send(tab_widget_id, msg);
+        }
+    }
+
+    fn cycle_signature(&mut self, is_forward: bool, ctx: &mut Ctx) -> Option<()> {
+        let help = self.help.as_mut()?;
+        let len = help.signatures.len();
+
+        help.active_signature = if is_forward {
+            (help.active_signature + 1) % len
+        } else {
+            (help.active_signature + len - 1) % len
+        };
+
+        self.selected_signature = Some(help.active_signature);
+        self.show_active_signature(ctx)
+    }
+
+    pub fn show_manually(&mut self, doc: &mut Doc, parent_id: WidgetId, ctx: &mut Ctx) {
+        ctx.ui.reparent_widget(self.widget_id, parent_id);
+
+        let is_retrigger = self.help.is_some();
+        self.trigger(doc, None, is_retrigger, ctx);
+    }
+
     pub fn show(
         &mut self,
         doc: &mut Doc,
@@ -119,6 +173,22 @@ impl SignatureHelpPopup {
     ) -> Option<()> {
         self.help = help;
 
+        if let Some(help) = &mut self.help {
+            // Keep the signature that was picked by the user while retriggering for the same call.
+            match self.selected_signature {
+                Some(selected_signature) if selected_signature < help.signatures.len() => {
+                    help.active_signature = selected_signature;
+                }
+                _ => self.selected_signature = None,
+            }
+        } else {
+            self.selected_signature = None;
+        }
+
+        self.show_active_signature(ctx)
+    }
+
+    fn show_active_signature(&mut self, ctx: &mut Ctx) -> Option<()> {
         self.label_popup.hide(ctx.ui);
         self.documentation_popup.hide(ctx.ui);
 
@@ -129,6 +199,12 @@ impl SignatureHelpPopup {
 
         self.label_popup.show(&active_signature.label, "", ctx);
 
+        if let Some(range) =
+            active_signature.active_parameter_range(signature_help.active_parameter)
+        {
+            self.label_popup.set_underline(range);
+        }
+
         let documentation = active_signature.documentation.as_ref()?;
 
         self.documentation_popup
@@ -143,6 +219,7 @@ impl SignatureHelpPopup {
 
     pub fn hide(&mut self, ui: &mut Ui) {
         self.help = None;
+        self.selected_signature = None;
 
         self.label_popup.hide(ui);
         self.documentation_popup.hide(ui);
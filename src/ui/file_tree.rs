@@ -0,0 +1,233 @@
+use std::{
+    collections::HashSet,
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::ignore::IgnoreRules,
+    ctx::Ctx,
+    input::action::action_name,
+    platform::{
+        dialog::{message, MessageKind, MessageResponse},
+        recycle::recycle,
+    },
+    pool::{format_pooled, Pooled, PATH_POOL, STRING_POOL},
+    ui::{
+        core::{WidgetScale, WidgetSettings},
+        msg::Msg,
+    },
+};
+
+use super::{
+    core::WidgetId,
+    editor::Editor,
+    result_list::{ResultList, ResultListInput},
+};
+
+struct FileTreeEntry {
+    path: Pooled<PathBuf>,
+    text: Pooled<String>,
+    is_dir: bool,
+}
+
+pub struct FileTree {
+    root: PathBuf,
+    expanded_dirs: HashSet<PathBuf>,
+    result_list: ResultList<FileTreeEntry>,
+
+    widget_id: WidgetId,
+}
+
+impl FileTree {
+    const INDENT: &str = "  ";
+
+    pub fn new(parent_id: WidgetId, ctx: &mut Ctx) -> Self {
+        let widget_id = ctx.ui.new_widget(
+            parent_id,
+            WidgetSettings {
+                is_shown: false,
+                scale: WidgetScale::Fractional(0.25),
+                ..Default::default()
+            },
+        );
+
+        Self {
+            root: PathBuf::new(),
+            expanded_dirs: HashSet::new(),
+            result_list: ResultList::new(widget_id, ctx.ui),
+
+            widget_id,
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.result_list.is_animating()
+    }
+
+    pub fn receive_msgs(&mut self, editor: &mut Editor, ctx: &mut Ctx) {
+        match self.result_list.receive_msgs(ctx) {
+            ResultListInput::Submit { .. } => self.submit(editor, ctx),
+            ResultListInput::Close => ctx.ui.hide(self.widget_id),
+            _ => {}
+        }
+
+        while let Some(msg) = ctx.ui.msg(self.widget_id) {
+            match msg {
+                Msg::Action(action_name!(MoveRight)) => self.set_focused_expanded(true, ctx),
+                Msg::Action(action_name!(MoveLeft)) => self.set_focused_expanded(false, ctx),
+                Msg::Action(action_name!(DeleteForward)) => self.delete_focused(ctx),
+                _ => ctx.ui.skip(self.widget_id, msg),
+            }
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Ctx, dt: f32) {
+        if self.root != ctx.current_dir.as_path() {
+            self.root.clear();
+            self.root.push(ctx.current_dir.as_path());
+            self.expanded_dirs.clear();
+
+            self.update_entries(ctx);
+        }
+
+        self.result_list.update(ctx, dt, |entry| &entry.text);
+    }
+
+    pub fn draw(&self, ctx: &mut Ctx) {
+        self.result_list.draw(ctx, |entry, theme| {
+            let color = if entry.is_dir {
+                theme.keyword
+            } else {
+                theme.normal
+            };
+
+            (entry.text.as_str(), color)
+        });
+    }
+
+    pub fn toggle(&mut self, ctx: &mut Ctx) {
+        if ctx.ui.is_focused(self.widget_id) {
+            ctx.ui.hide(self.widget_id);
+        } else {
+            self.update_entries(ctx);
+            ctx.ui.focus(self.widget_id);
+        }
+    }
+
+    fn submit(&mut self, editor: &mut Editor, ctx: &mut Ctx) {
+        let Some(entry) = self.result_list.get_focused() else {
+            return;
+        };
+
+        if entry.is_dir {
+            let is_expanded = self.expanded_dirs.contains(entry.path.as_path());
+            self.set_focused_expanded(!is_expanded, ctx);
+
+            return;
+        }
+
+        let (pane, doc_list) = editor.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        if pane.open_file(&entry.path, doc_list, ctx).is_ok() {
+            ctx.ui.focus(pane.widget_id());
+        }
+    }
+
+    fn set_focused_expanded(&mut self, is_expanded: bool, ctx: &mut Ctx) {
+        let Some(entry) = self.result_list.get_focused().filter(|entry| entry.is_dir) else {
+            return;
+        };
+
+        if is_expanded {
+            self.expanded_dirs.insert(entry.path.to_path_buf());
+        } else {
+            self.expanded_dirs.remove(entry.path.as_path());
+        }
+
+        self.update_entries(ctx);
+    }
+
+    fn delete_focused(&mut self, ctx: &mut Ctx) {
+        let Some(entry) = self.result_list.get_focused() else {
+            return;
+        };
+
+        let text = format_pooled!(
+            "Do you want to move {} to the recycle bin?",
+            entry.path.display()
+        );
+
+        if message("Delete File", &text, MessageKind::YesNo, ctx.window) != MessageResponse::Yes {
+            return;
+        }
+
+        if recycle(&entry.path).is_ok() {
+            self.update_entries(ctx);
+        }
+    }
+
+    fn update_entries(&mut self, ctx: &mut Ctx) {
+        let focused_index = self.result_list.focused_index();
+
+        self.result_list.drain();
+
+        let root: Pooled<PathBuf> = self.root.as_path().into();
+        let mut ignore_rules = IgnoreRules::load(&root, ctx.config);
+        self.push_dir_entries(&root, 0, &mut ignore_rules);
+
+        self.result_list.set_focused_index(focused_index);
+    }
+
+    fn push_dir_entries(&mut self, dir: &Path, depth: usize, ignore_rules: &mut IgnoreRules) {
+        let Ok(entries) = read_dir(dir) else {
+            return;
+        };
+
+        let mut paths: Vec<(bool, Pooled<PathBuf>)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| Pooled::new(entry.path(), &PATH_POOL))
+            .map(|path| (path.is_dir(), path))
+            .filter(|(is_dir, path)| !ignore_rules.is_ignored(path, *is_dir))
+            .collect();
+
+        paths.sort_by(|(a_is_dir, a), (b_is_dir, b)| b_is_dir.cmp(a_is_dir).then(a.cmp(b)));
+
+        for (is_dir, path) in paths {
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let is_expanded = is_dir && self.expanded_dirs.contains(path.as_path());
+
+            let text = STRING_POOL.init_item(|text| {
+                for _ in 0..depth {
+                    text.push_str(Self::INDENT);
+                }
+
+                text.push_str(match (is_dir, is_expanded) {
+                    (true, true) => "- ",
+                    (true, false) => "+ ",
+                    _ => Self::INDENT,
+                });
+
+                text.push_str(file_name);
+            });
+
+            self.result_list.push(FileTreeEntry {
+                path: path.clone(),
+                text,
+                is_dir,
+            });
+
+            if is_expanded {
+                ignore_rules.load_dir(&path);
+                self.push_dir_entries(&path, depth + 1, ignore_rules);
+            }
+        }
+    }
+
+    pub fn widget_id(&self) -> WidgetId {
+        self.widget_id
+    }
+}
@@ -1,5 +1,7 @@
+use std::path::PathBuf;
+
 use crate::{
-    geometry::rect::Rect,
+    geometry::{position::Position, rect::Rect},
     input::{action::Action, mouse_scroll::MouseScroll, mousebind::Mousebind},
     pool::Pooled,
 };
@@ -30,4 +32,13 @@ pub enum Msg {
     HideExaminePopup,
     HideEditorPopups,
     TabHoverChanged,
+    OpenFileAtLine {
+        path: Pooled<PathBuf>,
+        line: usize,
+        column: usize,
+    },
+    TabClosed {
+        path: Pooled<PathBuf>,
+        position: Position,
+    },
 }
@@ -17,7 +17,7 @@ use crate::{
     },
     text::doc::Doc,
     ui::{
-        camera::{CameraAxis, CameraRecenterRequest},
+        camera::{CameraAnimation, CameraAxis, CameraRecenterRequest},
         core::WidgetLayout,
         msg::Msg,
     },
@@ -201,8 +201,13 @@ impl<T> PaneTabBar<T> {
         let view_size = ctx.ui.bounds(self.widget_id).width;
         let max_position = (tab_x - view_size).max(0.0);
 
-        self.camera
-            .animate(recenter_request, max_position, view_size, dt);
+        self.camera.animate(
+            recenter_request,
+            max_position,
+            view_size,
+            CameraAnimation::new(ctx.config),
+            dt,
+        );
     }
 
     fn recenter_request(&self, view: &PaneView, ctx: &Ctx) -> CameraRecenterRequest {
@@ -258,7 +263,10 @@ impl<T> PaneTabBar<T> {
                 let tab_bounds = tab.bounds();
                 let tab_center_x = tab_bounds.center_x();
 
-                if focused_index < index {
+                // Pinned and unpinned tabs can't be dragged past each other.
+                if tabs[index].is_pinned() != tabs[focused_index].is_pinned() {
+                    false
+                } else if focused_index < index {
                     focused_tab_right > tab_center_x && focused_tab_right < tab_bounds.right()
                 } else if focused_index > index {
                     focused_tab_left < tab_center_x && focused_tab_left > tab_bounds.left()
@@ -308,11 +316,15 @@ impl<T> PaneTabBar<T> {
                 continue;
             };
 
-            self.draw_tab(i, false, background, doc, ctx);
+            self.draw_tab(i, false, tabs[i].is_pinned(), background, doc, ctx);
         }
 
         let focused_tab_bounds = Self::get_tab_doc(focused_index, tabs, data_list, get_doc)
-            .map(|doc| self.draw_tab(focused_index, true, background, doc, ctx))
+            .map(|doc| {
+                let is_pinned = tabs[focused_index].is_pinned();
+
+                self.draw_tab(focused_index, true, is_pinned, background, doc, ctx)
+            })
             .unwrap_or_default();
 
         let gfx = &mut ctx.gfx;
@@ -346,6 +358,7 @@ impl<T> PaneTabBar<T> {
         &self,
         index: usize,
         is_focused: bool,
+        is_pinned: bool,
         background: Option<Color>,
         doc: &Doc,
         ctx: &mut Ctx,
@@ -375,6 +388,21 @@ impl<T> PaneTabBar<T> {
 
         let text_x = (bounds.x + gfx.glyph_width() * 2.0).floor();
         let text_y = gfx.border_width() + gfx.tab_padding_y();
+
+        if is_pinned {
+            let marker_size = (gfx.glyph_width() * 0.5).floor();
+
+            gfx.add_rect(
+                Rect::new(
+                    (bounds.x + gfx.glyph_width() * 0.75).floor(),
+                    ((bounds.height - marker_size) / 2.0).floor(),
+                    marker_size,
+                    marker_size,
+                ),
+                theme.emphasized,
+            );
+        }
+
         let text_width = gfx.add_text(doc.file_name(), text_x, text_y, text_color);
 
         if !doc.is_saved() {
@@ -523,6 +551,9 @@ impl<T> Pane<T> {
                         ctx.ui.skip(self.widget_id, msg);
                     }
                 }
+                Msg::Action(action_name!(TogglePinTab)) => {
+                    self.toggle_pin_focused_tab(ctx.ui);
+                }
                 _ => ctx.ui.skip(self.widget_id, msg),
             }
         }
@@ -640,7 +671,9 @@ impl<T> Pane<T> {
             .add_usage();
 
         let tab_id = tab.widget_id();
-        let index = (self.focused_tab_index(ctx.ui) + 1).min(self.tabs.len());
+        let index = (self.focused_tab_index(ctx.ui) + 1)
+            .min(self.tabs.len())
+            .max(self.pinned_tab_count());
 
         self.tabs.insert(index, tab);
         self.tab_bar.tab_animation_states.insert(
@@ -683,6 +716,42 @@ impl<T> Pane<T> {
         self.focus_tab_at_index(focused_index, ui);
     }
 
+    // Pinned tabs stay in front of the others, in the order that they were pinned.
+    pub fn toggle_pin_focused_tab(&mut self, ui: &mut Ui) {
+        let index = self.focused_tab_index(ui);
+
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return;
+        };
+
+        let is_pinned = !tab.is_pinned();
+        tab.set_pinned(is_pinned);
+
+        let pinned_tab_count = self.pinned_tab_count();
+
+        let new_index = if is_pinned {
+            pinned_tab_count - 1
+        } else {
+            pinned_tab_count
+        };
+
+        let tab = self.tabs.remove(index);
+        let tab_id = tab.widget_id();
+        self.tabs.insert(new_index, tab);
+
+        let animation_state = self.tab_bar.tab_animation_states.remove(index);
+        self.tab_bar
+            .tab_animation_states
+            .insert(new_index, animation_state);
+
+        ui.move_child(tab_id, new_index);
+        self.view.set_focused_index(new_index, ui);
+    }
+
+    fn pinned_tab_count(&self) -> usize {
+        self.tabs.iter().filter(|tab| tab.is_pinned()).count()
+    }
+
     fn focus_next_tab(&self, ui: &mut Ui) -> bool {
         let focused_index = self.focused_tab_index(ui);
 
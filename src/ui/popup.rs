@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use crate::{
     ctx::Ctx,
     geometry::{position::Position, rect::Rect, sides::Sides, visual_position::VisualPosition},
@@ -28,6 +30,8 @@ pub struct Popup {
     doc: Doc,
     widget_id: WidgetId,
     extension: String,
+    text_offset: usize,
+    underline: Option<(Position, Position)>,
 }
 
 impl Popup {
@@ -52,6 +56,8 @@ impl Popup {
             doc: Doc::new(None, None, DocFlags::RAW),
             widget_id,
             extension: String::new(),
+            text_offset: 0,
+            underline: None,
         }
     }
 
@@ -150,6 +156,12 @@ impl Popup {
         }
 
         self.tab.draw((foreground, None), &mut self.doc, ctx);
+
+        if let Some((start, end)) = self.underline {
+            let color = ctx.config.theme.normal;
+
+            self.tab.draw_underline(start, end, color, &self.doc, ctx);
+        }
     }
 
     pub fn hide(&self, ui: &mut Ui) {
@@ -157,6 +169,7 @@ impl Popup {
     }
 
     pub fn show(&mut self, text: &str, extension: &str, ctx: &mut Ctx) {
+        let trimmed_start_len = text.len() - text.trim_start().len();
         let text = text.trim();
         let mut char_cursor = CharCursor::new(0, text.len());
 
@@ -180,6 +193,9 @@ impl Popup {
         self.doc.clear(ctx);
         self.doc.insert(Position::ZERO, text, ctx);
 
+        self.text_offset = trimmed_start_len + char_cursor.index();
+        self.underline = None;
+
         self.tab.camera.reset();
 
         self.extension.clear();
@@ -188,6 +204,27 @@ impl Popup {
         ctx.ui.show(self.widget_id);
     }
 
+    // Underlines a byte range of the text that was last shown.
+    pub fn set_underline(&mut self, range: Range<usize>) {
+        self.underline = self
+            .offset_to_position(range.start)
+            .zip(self.offset_to_position(range.end));
+    }
+
+    fn offset_to_position(&self, offset: usize) -> Option<Position> {
+        let mut offset = offset.checked_sub(self.text_offset)?;
+
+        for (y, line) in self.doc.lines().iter().enumerate() {
+            if offset <= line.len() {
+                return Some(Position::new(offset, y));
+            }
+
+            offset -= line.len() + 1;
+        }
+
+        None
+    }
+
     pub fn widget_id(&self) -> WidgetId {
         self.widget_id
     }
@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+
+use crate::{
+    ctx::Ctx,
+    geometry::position::Position,
+    lsp::{
+        position_encoding::PositionEncoding,
+        types::{DecodedDiagnostic, EncodedPosition},
+    },
+    pool::{format_pooled, Pooled},
+    ui::core::{WidgetScale, WidgetSettings},
+};
+
+use super::{
+    core::WidgetId,
+    editor::Editor,
+    result_list::{ResultList, ResultListInput},
+};
+
+#[derive(Clone, Copy)]
+enum ProblemPosition {
+    Decoded(Position),
+    Encoded {
+        encoding: PositionEncoding,
+        position: EncodedPosition,
+    },
+}
+
+impl ProblemPosition {
+    fn line_and_x(&self) -> (usize, usize) {
+        match self {
+            Self::Decoded(position) => (position.y, position.x),
+            Self::Encoded { position, .. } => (position.line, position.character),
+        }
+    }
+}
+
+struct Problem {
+    path: Pooled<PathBuf>,
+    position: ProblemPosition,
+    severity: usize,
+    message: Pooled<String>,
+}
+
+struct ProblemsEntry {
+    path: Pooled<PathBuf>,
+    // Entries without a position are headers for the file that the following problems are in.
+    position: Option<ProblemPosition>,
+    severity: usize,
+    text: Pooled<String>,
+}
+
+pub struct ProblemsPanel {
+    diagnostics_generation: Option<usize>,
+    result_list: ResultList<ProblemsEntry>,
+
+    widget_id: WidgetId,
+}
+
+impl ProblemsPanel {
+    const INDENT: &str = "  ";
+
+    pub fn new(parent_id: WidgetId, ctx: &mut Ctx) -> Self {
+        let widget_id = ctx.ui.new_widget(
+            parent_id,
+            WidgetSettings {
+                is_shown: false,
+                scale: WidgetScale::Fractional(0.25),
+                ..Default::default()
+            },
+        );
+
+        Self {
+            diagnostics_generation: None,
+            result_list: ResultList::new(widget_id, ctx.ui),
+
+            widget_id,
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.result_list.is_animating()
+    }
+
+    pub fn receive_msgs(&mut self, editor: &mut Editor, ctx: &mut Ctx) {
+        match self.result_list.receive_msgs(ctx) {
+            ResultListInput::Submit { .. } => self.submit(editor, ctx),
+            ResultListInput::Close => ctx.ui.hide(self.widget_id),
+            _ => {}
+        }
+
+        while let Some(msg) = ctx.ui.msg(self.widget_id) {
+            ctx.ui.skip(self.widget_id, msg);
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Ctx, dt: f32) {
+        if ctx.ui.is_visible(self.widget_id) {
+            let diagnostics_generation = ctx
+                .lsp
+                .iter_servers()
+                .fold(0usize, |generation, (_, server)| {
+                    generation.wrapping_add(server.diagnostics_generation())
+                });
+
+            if self.diagnostics_generation != Some(diagnostics_generation) {
+                self.diagnostics_generation = Some(diagnostics_generation);
+                self.update_entries(ctx);
+            }
+        }
+
+        self.result_list.update(ctx, dt, |entry| &entry.text);
+    }
+
+    pub fn draw(&self, ctx: &mut Ctx) {
+        self.result_list.draw(ctx, |entry, theme| {
+            let color = if entry.position.is_some() {
+                DecodedDiagnostic::severity_color(entry.severity, theme)
+            } else {
+                theme.normal
+            };
+
+            (entry.text.as_str(), color)
+        });
+    }
+
+    pub fn toggle(&mut self, ctx: &mut Ctx) {
+        if ctx.ui.is_focused(self.widget_id) {
+            ctx.ui.hide(self.widget_id);
+        } else {
+            self.update_entries(ctx);
+            ctx.ui.focus(self.widget_id);
+        }
+    }
+
+    fn submit(&mut self, editor: &mut Editor, ctx: &mut Ctx) {
+        let Some(entry) = self.result_list.get_focused() else {
+            return;
+        };
+
+        let (pane, doc_list) = editor.last_focused_pane_and_doc_list_mut(ctx.ui);
+
+        if pane.open_file(&entry.path, doc_list, ctx).is_err() {
+            return;
+        }
+
+        if let Some((tab, doc)) = pane.get_focused_tab_with_data_mut(doc_list, ctx.ui) {
+            let position = match entry.position {
+                Some(ProblemPosition::Decoded(position)) => Some(position),
+                Some(ProblemPosition::Encoded { encoding, position }) => {
+                    Some(position.decode(encoding, doc))
+                }
+                None => None,
+            };
+
+            if let Some(position) = position {
+                doc.jump_cursors(position, false, ctx.gfx);
+                tab.camera.recenter();
+            }
+        }
+
+        ctx.ui.focus(pane.widget_id());
+    }
+
+    fn update_entries(&mut self, ctx: &mut Ctx) {
+        let focused_index = self.result_list.focused_index();
+
+        self.result_list.drain();
+
+        let mut problems = Vec::new();
+
+        for server in ctx.lsp.iter_servers_mut() {
+            let encoding = server.position_encoding();
+
+            for (path, diagnostics) in server.all_diagnostics_mut() {
+                for diagnostic in diagnostics.encoded() {
+                    problems.push(Problem {
+                        path: path.clone(),
+                        position: ProblemPosition::Encoded {
+                            encoding,
+                            position: diagnostic.range.start,
+                        },
+                        severity: diagnostic.severity,
+                        message: diagnostic.message.clone(),
+                    });
+                }
+
+                for diagnostic in diagnostics.decoded() {
+                    problems.push(Problem {
+                        path: path.clone(),
+                        position: ProblemPosition::Decoded(diagnostic.range.start),
+                        severity: diagnostic.severity,
+                        message: diagnostic.message.clone(),
+                    });
+                }
+            }
+        }
+
+        problems.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then(a.severity.cmp(&b.severity))
+                .then(a.position.line_and_x().cmp(&b.position.line_and_x()))
+        });
+
+        for (i, problem) in problems.iter().enumerate() {
+            if i == 0 || problems[i - 1].path != problem.path {
+                let relative_path = problem
+                    .path
+                    .strip_prefix(ctx.current_dir.as_path())
+                    .unwrap_or(&problem.path);
+
+                self.result_list.push(ProblemsEntry {
+                    path: problem.path.clone(),
+                    position: None,
+                    severity: problem.severity,
+                    text: format_pooled!("{}", relative_path.display()),
+                });
+            }
+
+            let (y, _) = problem.position.line_and_x();
+            let message = problem.message.lines().next().unwrap_or_default();
+
+            self.result_list.push(ProblemsEntry {
+                path: problem.path.clone(),
+                position: Some(problem.position),
+                severity: problem.severity,
+                text: format_pooled!("{}{}: {}", Self::INDENT, y + 1, message),
+            });
+        }
+
+        self.result_list.set_focused_index(focused_index);
+    }
+
+    pub fn widget_id(&self) -> WidgetId {
+        self.widget_id
+    }
+}
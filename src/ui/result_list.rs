@@ -14,7 +14,7 @@ use crate::{
     },
     platform::gfx::Gfx,
     ui::{
-        camera::{Camera, CameraAxis, CameraRecenterRequest},
+        camera::{Camera, CameraAnimation, CameraAxis, CameraRecenterRequest},
         msg::Msg,
     },
 };
@@ -118,8 +118,16 @@ impl<T> ResultList<T> {
                 Msg::Action(action_keybind!(key: Tab, mods: Mods::NONE)) => {
                     input = ResultListInput::Complete
                 }
-                Msg::Action(action_keybind!(key: Up, mods: Mods::NONE)) => self.focus_previous(),
-                Msg::Action(action_keybind!(key: Down, mods: Mods::NONE)) => self.focus_next(),
+                Msg::Action(action_keybind!(key: Up, mods: Mods::NONE)) if !self.is_empty() => {
+                    if !self.try_focus_visible(true, ctx) {
+                        self.focus_previous();
+                    }
+                }
+                Msg::Action(action_keybind!(key: Down, mods: Mods::NONE)) if !self.is_empty() => {
+                    if !self.try_focus_visible(false, ctx) {
+                        self.focus_next();
+                    }
+                }
                 _ => ctx.ui.skip(self.widget_id, msg),
             }
         }
@@ -153,6 +161,20 @@ impl<T> ResultList<T> {
         true
     }
 
+    // If the focused result was scrolled out of view, continue from the visible results instead.
+    fn try_focus_visible(&mut self, is_previous: bool, ctx: &Ctx) -> bool {
+        let min_y = self.min_visible_result_index(ctx.gfx);
+        let max_y = self.max_visible_result_index(ctx.ui, ctx.gfx);
+
+        if min_y >= max_y || (min_y..max_y).contains(&self.focused_index) {
+            return false;
+        }
+
+        self.focused_index = if is_previous { max_y - 1 } else { min_y };
+
+        true
+    }
+
     pub fn update(&mut self, ctx: &mut Ctx, dt: f32, result_text: fn(t: &T) -> &str) {
         let focused_index = self.focused_index();
         let bounds = ctx.ui.bounds(self.widget_id);
@@ -169,9 +191,13 @@ impl<T> ResultList<T> {
 
         self.mark_focused_handled();
 
-        self.camera
-            .vertical
-            .animate(recenter_request, max_y, bounds.height, dt);
+        self.camera.vertical.animate(
+            recenter_request,
+            max_y,
+            bounds.height,
+            CameraAnimation::new(ctx.config),
+            dt,
+        );
 
         let min_y = self.min_visible_result_index(ctx.gfx);
         let max_y = self.max_visible_result_index(ctx.ui, ctx.gfx);
@@ -187,9 +213,13 @@ impl<T> ResultList<T> {
         let max_x = (self.longest_result_length + 1) as f32 * ctx.gfx.glyph_width() - bounds.width;
         let max_x = max_x.max(0.0);
 
-        self.camera
-            .horizontal
-            .animate(CameraRecenterRequest::default(), max_x, bounds.width, dt);
+        self.camera.horizontal.animate(
+            CameraRecenterRequest::default(),
+            max_x,
+            bounds.width,
+            CameraAnimation::new(ctx.config),
+            dt,
+        );
     }
 
     pub fn draw<'a>(
@@ -1,9 +1,18 @@
 use std::{cmp::Ordering, fmt::Write, path::Path};
 
 use crate::{
-    config::Config,
+    config::{language::IndentWidth, Config},
     ctx::Ctx,
-    geometry::sides::{Side, Sides},
+    geometry::{
+        rect::Rect,
+        sides::{Side, Sides},
+        visual_position::VisualPosition,
+    },
+    input::{
+        mouse_button::MouseButton,
+        mousebind::{Mousebind, MousebindKind},
+        vim::Vim,
+    },
     lsp::{types::DecodedDiagnostic, Lsp},
     pool::{format_pooled, Pooled, STRING_POOL},
     text::{cursor_index::CursorIndex, doc::LineEnding},
@@ -16,15 +25,18 @@ use crate::{
 use super::{
     core::{Ui, WidgetId},
     editor::Editor,
+    problems_panel::ProblemsPanel,
 };
 
 pub struct StatusBar {
+    problems_bounds: Option<Rect>,
     widget_id: WidgetId,
 }
 
 impl StatusBar {
     pub fn new(parent_id: WidgetId, ctx: &mut Ctx) -> Self {
         Self {
+            problems_bounds: None,
             widget_id: ctx.ui.new_widget(
                 parent_id,
                 WidgetSettings {
@@ -35,19 +47,30 @@ impl StatusBar {
         }
     }
 
-    pub fn receive_msgs(&self, ctx: &mut Ctx) {
+    pub fn receive_msgs(&self, problems_panel: &mut ProblemsPanel, ctx: &mut Ctx) {
         while let Some(msg) = ctx.ui.msg(self.widget_id) {
-            let Msg::FontChanged = msg else {
-                ctx.ui.skip(self.widget_id, msg);
-                continue;
-            };
-
-            ctx.ui
-                .set_scale(self.widget_id, WidgetScale::Fixed(ctx.gfx.tab_height()))
+            match msg {
+                Msg::FontChanged => ctx
+                    .ui
+                    .set_scale(self.widget_id, WidgetScale::Fixed(ctx.gfx.tab_height())),
+                Msg::Mousebind(Mousebind {
+                    button: Some(MouseButton::Left),
+                    x,
+                    y,
+                    kind: MousebindKind::Press,
+                    ..
+                }) if self
+                    .problems_bounds
+                    .is_some_and(|bounds| bounds.contains_position(VisualPosition::new(x, y))) =>
+                {
+                    problems_panel.toggle(ctx);
+                }
+                _ => ctx.ui.skip(self.widget_id, msg),
+            }
         }
     }
 
-    pub fn draw(&self, editor: &Editor, ctx: &mut Ctx) {
+    pub fn draw(&mut self, editor: &Editor, ctx: &mut Ctx) {
         let gfx = &mut ctx.gfx;
         let theme = &ctx.config.theme;
         let bounds = ctx.ui.bounds(self.widget_id);
@@ -69,7 +92,26 @@ impl StatusBar {
             gfx.add_text(&text, text_x, text_y, theme.subtle);
         }
 
-        if let Some((text, severity)) = Self::get_problems_text(ctx.lsp) {
+        if let Some(is_blocked) = Self::get_read_only_state(editor, ctx.ui, ctx.time) {
+            let text = "Read-only, ";
+            let color = if is_blocked {
+                theme.warning
+            } else {
+                theme.subtle
+            };
+
+            text_x -= gfx.measure_text(text) as f32 * gfx.glyph_width();
+            gfx.add_text(text, text_x, text_y, color);
+        }
+
+        let problems_end_x = text_x;
+
+        let problems_texts = [
+            Self::get_problems_text(ctx.lsp),
+            Self::get_doc_problems_text(editor, ctx.ui, ctx.lsp),
+        ];
+
+        for (text, severity) in problems_texts.into_iter().flatten() {
             let color = DecodedDiagnostic::severity_color(severity, theme);
             let separator = ", ";
 
@@ -80,9 +122,27 @@ impl StatusBar {
             gfx.add_text(&text, text_x, text_y, color);
         }
 
+        // Clicking the problem counts toggles the problems panel.
+        self.problems_bounds = (text_x < problems_end_x).then(|| {
+            Rect::new(
+                bounds.x + text_x,
+                bounds.y,
+                problems_end_x - text_x,
+                bounds.height,
+            )
+        });
+
         gfx.end();
     }
 
+    // Returns whether an edit was recently blocked if the focused doc is read-only.
+    fn get_read_only_state(editor: &Editor, ui: &Ui, time: f64) -> Option<bool> {
+        let (pane, doc_list) = editor.last_focused_pane_and_doc_list(ui);
+        let (_, doc) = pane.get_focused_tab_with_data(doc_list, ui)?;
+
+        doc.is_read_only().then(|| doc.was_edit_blocked(time))
+    }
+
     fn get_problems_text(lsp: &mut Lsp) -> Option<(Pooled<String>, usize)> {
         let mut count = 0;
         let mut severity = usize::MAX;
@@ -118,6 +178,47 @@ impl StatusBar {
         Some((text, severity))
     }
 
+    fn get_doc_problems_text(
+        editor: &Editor,
+        ui: &Ui,
+        lsp: &mut Lsp,
+    ) -> Option<(Pooled<String>, usize)> {
+        let (pane, doc_list) = editor.last_focused_pane_and_doc_list(ui);
+        let (_, doc) = pane.get_focused_tab_with_data(doc_list, ui)?;
+
+        let mut errors = 0;
+        let mut warnings = 0;
+
+        for server in lsp.iter_servers_mut() {
+            for diagnostic in server.diagnostics_mut(doc) {
+                match diagnostic.severity {
+                    1 => errors += 1,
+                    2 => warnings += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        let severity = match (errors, warnings) {
+            (0, 0) => return None,
+            (0, _) => 2,
+            _ => 1,
+        };
+
+        let error_text = if errors == 1 { "Error" } else { "Errors" };
+        let warning_text = if warnings == 1 { "Warning" } else { "Warnings" };
+
+        let text = format_pooled!(
+            "{} {}, {} {} in File",
+            errors,
+            error_text,
+            warnings,
+            warning_text
+        );
+
+        Some((text, severity))
+    }
+
     fn get_doc_text(
         editor: &Editor,
         config: &Config,
@@ -125,11 +226,15 @@ impl StatusBar {
         current_dir: &Path,
     ) -> Option<Pooled<String>> {
         let (pane, doc_list) = editor.last_focused_pane_and_doc_list(ui);
-        let (_, doc) = pane.get_focused_tab_with_data(doc_list, ui)?;
+        let (tab, doc) = pane.get_focused_tab_with_data(doc_list, ui)?;
         let position = doc.cursor(CursorIndex::Main).position;
 
         let mut doc_text = STRING_POOL.new_item();
 
+        if Vim::is_enabled(doc, config) {
+            write!(&mut doc_text, "{}, ", tab.vim_mode().name()).ok()?;
+        }
+
         if let Some(path) = doc
             .path()
             .some()
@@ -142,6 +247,30 @@ impl StatusBar {
             write!(&mut doc_text, "{}, ", language.name).ok()?;
         }
 
+        if doc.is_binary() {
+            write!(&mut doc_text, "Binary, ").ok()?;
+        }
+
+        if let Some(load_progress) = doc.load_progress() {
+            write!(&mut doc_text, "Loading {:.0}%, ", load_progress * 100.0).ok()?;
+        }
+
+        match config.indent_width_for_doc(doc) {
+            IndentWidth::Tab => write!(&mut doc_text, "Tabs, ").ok()?,
+            IndentWidth::Spaces(spaces) => write!(&mut doc_text, "Spaces: {}, ", spaces).ok()?,
+        }
+
+        write!(&mut doc_text, "{}, ", doc.encoding().name()).ok()?;
+
+        if let Some(stats) = tab.selection_stats() {
+            write!(
+                &mut doc_text,
+                "Sel: {} Chars, {} Words, {} Lines, ",
+                stats.chars, stats.words, stats.lines
+            )
+            .ok()?;
+        }
+
         let line_ending_text = match doc.line_ending() {
             LineEnding::Lf => "LF",
             LineEnding::CrLf => "CRLF",
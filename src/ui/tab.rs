@@ -1,33 +1,43 @@
+#[cfg(test)]
+mod tests;
+
 use core::f32;
 use std::{iter::Enumerate, ops::Range};
 
 use crate::{
-    config::language::{DelimiterKind, Language},
+    config::{
+        language::{DelimiterKind, Language, LongLineHighlight},
+        theme::Theme,
+        LineNumberKind,
+    },
     ctx::Ctx,
     geometry::{
         easing::ease_out_quart, position::Position, quad::Quad, rect::Rect,
         visual_position::VisualPosition,
     },
     input::{
-        action::action_name,
-        editing_actions::{handle_action, handle_grapheme, handle_left_click},
+        action::{action_name, Action},
+        editing_actions::{handle_action, handle_grapheme, handle_gutter_click, handle_left_click},
         mods::{Mod, Mods},
         mouse_button::MouseButton,
         mouse_scroll::MouseScroll,
         mousebind::{MouseClickCount, Mousebind, MousebindKind},
+        vim::{Vim, VimMode},
     },
     lsp::types::DecodedRange,
     platform::gfx::Gfx,
-    pool::format_pooled,
+    pool::{format_pooled, Pooled, STRING_POOL},
     text::{
         cursor_index::CursorIndex,
+        diff::DiffHunkKind,
         doc::{Doc, DocFlag},
+        grapheme::{self, GraphemeIterator},
         grapheme_category::GraphemeCategory,
-        selection::Selection,
+        selection::{Selection, SelectionStats},
         syntax_highlighter::HighlightedLine,
     },
     ui::{
-        camera::{CameraAxis, CameraRecenterRequest},
+        camera::{CameraAnimation, CameraAxis, CameraRecenterRequest},
         core::{Ui, WidgetSettings},
         msg::Msg,
     },
@@ -53,6 +63,12 @@ impl VisibleLines {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScrollBar {
+    Vertical,
+    Horizontal,
+}
+
 struct CursorAnimationState {
     last_time: f64,
     last_position: VisualPosition,
@@ -68,10 +84,21 @@ pub struct Tab {
 
     pub camera: Camera,
     handled_cursor_position: Position,
+    handled_line_height: f32,
     longest_line_length: usize,
     mouse_drag: Option<MouseClickCount>,
+    dragged_scroll_bar: Option<ScrollBar>,
+    // Lines marked on the scroll bar for containing the selected text,
+    // found again only when the doc version or the selected text changes.
+    selection_match_ys: Vec<usize>,
+    selection_match_state: Option<(usize, Pooled<String>)>,
+    // Counted again only when the doc version or the selections change.
+    selection_stats: Option<SelectionStats>,
+    selection_stats_state: Option<(usize, Vec<Selection>)>,
     cursor_animation_states: Vec<CursorAnimationState>,
     do_show_completions: bool,
+    vim: Vim,
+    is_pinned: bool,
 
     margin: f32,
 }
@@ -79,6 +106,7 @@ pub struct Tab {
 impl Tab {
     const GUTTER_PADDING_WIDTH: f32 = 1.0;
     const GUTTER_BORDER_WIDTH: f32 = 0.5;
+    const BOOKMARK_MARKER: &str = "*";
 
     const CURSOR_ANIMATION_SPEED: f64 = 8.0;
 
@@ -112,10 +140,18 @@ impl Tab {
 
             camera: Camera::new(),
             handled_cursor_position: Position::ZERO,
+            handled_line_height: 0.0,
             longest_line_length: 0,
             mouse_drag: None,
+            dragged_scroll_bar: None,
+            selection_match_ys: Vec::new(),
+            selection_match_state: None,
+            selection_stats: None,
+            selection_stats_state: None,
             cursor_animation_states: Vec::new(),
             do_show_completions: false,
+            vim: Vim::new(),
+            is_pinned: false,
 
             margin: 0.0,
         }
@@ -136,6 +172,14 @@ impl Tab {
             })
     }
 
+    pub fn is_pinned(&self) -> bool {
+        self.is_pinned
+    }
+
+    pub fn set_pinned(&mut self, is_pinned: bool) {
+        self.is_pinned = is_pinned;
+    }
+
     pub fn set_margin(&mut self, margin: f32) {
         self.margin = margin;
     }
@@ -147,8 +191,18 @@ impl Tab {
     }
 
     pub fn receive_msg(&mut self, msg: Msg, doc: &mut Doc, ctx: &mut Ctx) {
+        let is_vim_enabled = Vim::is_enabled(doc, ctx.config);
+
         match msg {
             Msg::Resize { .. } => self.animate_camera(doc, ctx, 0.0),
+            Msg::Grapheme(grapheme)
+                if is_vim_enabled && self.handle_vim_grapheme(&grapheme, doc, ctx) =>
+            {
+                self.send_lsp_msg(Msg::HideExaminePopup, doc, ctx.ui);
+            }
+            Msg::Action(action) if is_vim_enabled && self.handle_vim_action(action, doc, ctx) => {
+                self.send_lsp_msg(Msg::HideExaminePopup, doc, ctx.ui);
+            }
             Msg::Grapheme(grapheme) => {
                 handle_grapheme(&grapheme, doc, ctx);
 
@@ -158,13 +212,17 @@ impl Tab {
             }
             Msg::LostFocus => {
                 self.mouse_drag = None;
+                self.dragged_scroll_bar = None;
                 self.send_lsp_msg(Msg::HideEditorPopups, doc, ctx.ui);
             }
             Msg::Mousebind(Mousebind {
                 button: Some(MouseButton::Left),
                 kind: MousebindKind::Release,
                 ..
-            }) => self.mouse_drag = None,
+            }) => {
+                self.mouse_drag = None;
+                self.dragged_scroll_bar = None;
+            }
             Msg::Mousebind(Mousebind {
                 button: Some(MouseButton::Left),
                 x,
@@ -176,6 +234,36 @@ impl Tab {
             }) => {
                 self.send_lsp_msg(Msg::HideEditorPopups, doc, ctx.ui);
 
+                let visual_position = VisualPosition::new(x, y);
+
+                if let Some(scroll_bar) = self.scroll_bar_at(visual_position, doc, ctx.ui, ctx.gfx)
+                {
+                    self.dragged_scroll_bar = Some(scroll_bar);
+                    self.scroll_to_scroll_bar_position(visual_position, doc, ctx.ui, ctx.gfx);
+
+                    return;
+                }
+
+                if ctx
+                    .ui
+                    .bounds(self.gutter_widget_id)
+                    .contains_position(visual_position)
+                {
+                    let position = self.mouse_to_position(x, y, doc, ctx.ui, ctx.gfx);
+
+                    // Alt clicking a changed line reverts it instead of selecting it.
+                    if mods.contains(Mod::Alt) && doc.revert_diff_hunk(position.y, ctx) {
+                        return;
+                    }
+
+                    handle_gutter_click(doc, position, mods, ctx.gfx);
+
+                    self.handled_cursor_position = doc.cursor(CursorIndex::Main).position;
+                    self.mouse_drag = Some(MouseClickCount::Triple);
+
+                    return;
+                }
+
                 if mods.contains(Mod::Ctrl) || mods.contains(Mod::Cmd) {
                     let position = self.mouse_to_position(x, y, doc, ctx.ui, ctx.gfx);
 
@@ -231,6 +319,26 @@ impl Tab {
         }
     }
 
+    fn handle_vim_grapheme(&mut self, grapheme: &str, doc: &mut Doc, ctx: &mut Ctx) -> bool {
+        let mut vim = self.vim;
+        let was_handled = vim.handle_grapheme(grapheme, self, doc, ctx);
+        self.vim = vim;
+
+        was_handled
+    }
+
+    fn handle_vim_action(&mut self, action: Action, doc: &mut Doc, ctx: &mut Ctx) -> bool {
+        let mut vim = self.vim;
+        let was_handled = vim.handle_action(action, self, doc, ctx);
+        self.vim = vim;
+
+        was_handled
+    }
+
+    pub fn vim_mode(&self) -> VimMode {
+        self.vim.mode()
+    }
+
     fn send_trigger_signature_help(&mut self, grapheme: &str, doc: &Doc, ctx: &mut Ctx) {
         if !doc.flags().contains(DocFlag::AllowLanguageServer) {
             return;
@@ -293,6 +401,15 @@ impl Tab {
     }
 
     pub fn update(&mut self, doc: &mut Doc, ctx: &mut Ctx, dt: f32) {
+        let language = ctx.config.get_language_for_doc(doc);
+        let is_elastic_tabstops = language.is_some_and(|language| language.elastic_tabstops);
+        let word_chars = language
+            .and_then(|language| language.word_chars.as_ref())
+            .map_or("", |word_chars| word_chars.as_str());
+
+        doc.set_elastic_tabstops(is_elastic_tabstops);
+        doc.set_word_chars(word_chars);
+
         if let Some(count) = self.mouse_drag {
             let visual_position = ctx.window.mouse_position();
             let position =
@@ -303,9 +420,22 @@ impl Tab {
             self.handled_cursor_position = doc.cursor(CursorIndex::Main).position;
         }
 
+        if self.dragged_scroll_bar.is_some() {
+            let mouse_position = ctx.window.mouse_position();
+            self.scroll_to_scroll_bar_position(mouse_position, doc, ctx.ui, ctx.gfx);
+        }
+
         doc.combine_overlapping_cursors();
         doc.update_tokens();
+        doc.update_tab_widths(ctx.gfx);
+        self.update_selection_matches(doc);
+        self.update_selection_stats(doc);
 
+        if doc.take_needs_recenter() {
+            self.camera.recenter();
+        }
+
+        self.rescale_camera(ctx.gfx);
         self.animate_cursors(doc, ctx);
         self.animate_camera(doc, ctx, dt);
 
@@ -322,6 +452,91 @@ impl Tab {
             .set_scale(self.gutter_widget_id, WidgetScale::Fixed(gutter_width));
     }
 
+    fn update_selection_matches(&mut self, doc: &Doc) {
+        let selected_text = doc
+            .cursor(CursorIndex::Main)
+            .get_selection()
+            .filter(|selection| {
+                selection.start.y == selection.end.y && selection.start != selection.end
+            })
+            .map(|selection| &doc.lines()[selection.start.y][selection.start.x..selection.end.x]);
+
+        let Some(selected_text) = selected_text else {
+            self.selection_match_ys.clear();
+            self.selection_match_state = None;
+
+            return;
+        };
+
+        if self
+            .selection_match_state
+            .as_ref()
+            .is_some_and(|(version, text)| {
+                *version == doc.version() && text.as_str() == selected_text
+            })
+        {
+            return;
+        }
+
+        self.selection_match_ys.clear();
+        self.selection_match_ys.extend(
+            doc.lines()
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.contains(selected_text))
+                .map(|(y, _)| y),
+        );
+
+        self.selection_match_state = Some((doc.version(), selected_text.into()));
+    }
+
+    fn update_selection_stats(&mut self, doc: &Doc) {
+        let selections = doc
+            .cursor_indices()
+            .filter_map(|index| doc.cursor(index).get_selection());
+
+        if self
+            .selection_stats_state
+            .as_ref()
+            .is_some_and(|(version, last_selections)| {
+                *version == doc.version() && last_selections.iter().copied().eq(selections.clone())
+            })
+        {
+            return;
+        }
+
+        let mut last_selections = self
+            .selection_stats_state
+            .take()
+            .map(|(_, last_selections)| last_selections)
+            .unwrap_or_default();
+
+        last_selections.clear();
+        last_selections.extend(selections);
+
+        self.selection_stats = doc.selection_stats();
+        self.selection_stats_state = Some((doc.version(), last_selections));
+    }
+
+    pub fn selection_stats(&self) -> Option<SelectionStats> {
+        self.selection_stats
+    }
+
+    // Keeps the same lines in view when the font size changes.
+    fn rescale_camera(&mut self, gfx: &Gfx) {
+        let line_height = gfx.line_height();
+
+        if self.handled_line_height > 0.0 && self.handled_line_height != line_height {
+            let scale = line_height / self.handled_line_height - 1.0;
+
+            for axis in [&mut self.camera.horizontal, &mut self.camera.vertical] {
+                axis.jump_visual_distance(axis.position() * scale);
+            }
+        }
+
+        self.handled_line_height = line_height;
+    }
+
     fn mouse_to_position(&self, x: f32, y: f32, doc: &Doc, ui: &Ui, gfx: &mut Gfx) -> Position {
         // Offset the raw mouse position to make selecting between characters more natural.
         let visual_position = VisualPosition::new(x + 0.25 * gfx.glyph_width(), y);
@@ -403,9 +618,13 @@ impl Tab {
         let max_y = self.camera_max_y(doc, ctx.ui, ctx.gfx);
         let bounds = ctx.ui.bounds(self.doc_widget_id);
 
-        self.camera
-            .vertical
-            .animate(recenter_request, max_y, bounds.height, dt);
+        self.camera.vertical.animate(
+            recenter_request,
+            max_y,
+            bounds.height,
+            CameraAnimation::new(ctx.config),
+            dt,
+        );
     }
 
     fn camera_max_y(&self, doc: &Doc, ui: &Ui, gfx: &Gfx) -> f32 {
@@ -470,7 +689,8 @@ impl Tab {
         CameraRecenterRequest {
             can_start: self.handled_cursor_position != new_cursor_position,
             target_position: new_cursor_visual_position.y + gfx.line_height() / 2.0,
-            scroll_border: gfx.line_height() * CameraAxis::RECENTER_DISTANCE as f32,
+            scroll_border: gfx.line_height()
+                * CameraAxis::scroll_border_lines(ctx.config.scroll_off) as f32,
         }
     }
 
@@ -480,20 +700,30 @@ impl Tab {
         let camera_position = self.camera.position().floor();
         let visible_lines = Self::visible_lines(bounds, camera_position, doc, ctx.gfx);
 
-        let longest_visible_line = doc.lines()[visible_lines.min_y..visible_lines.max_y]
-            .iter()
-            .map(|line| ctx.gfx.measure_text(line))
+        let longest_visible_line = (visible_lines.min_y..visible_lines.max_y)
+            .map(|y| doc.measure_line_range(y, 0, doc.line_len(y), ctx.gfx))
             .max()
             .unwrap_or_default();
 
         self.longest_line_length = self.longest_line_length.max(longest_visible_line);
 
-        let max_x = self.longest_line_length as f32 * ctx.gfx.glyph_width() - bounds.width;
+        let past_end_width = if ctx.config.allow_scrolling_past_right {
+            bounds.width / 2.0
+        } else {
+            0.0
+        };
+
+        let max_x =
+            self.longest_line_length as f32 * ctx.gfx.glyph_width() + past_end_width - bounds.width;
         let max_x = max_x.max(0.0);
 
-        self.camera
-            .horizontal
-            .animate(recenter_request, max_x, bounds.width, dt);
+        self.camera.horizontal.animate(
+            recenter_request,
+            max_x,
+            bounds.width,
+            CameraAnimation::new(ctx.config),
+            dt,
+        );
     }
 
     fn recenter_request_horizontal(&self, doc: &Doc, ctx: &mut Ctx) -> CameraRecenterRequest {
@@ -651,6 +881,7 @@ impl Tab {
         let visible_lines = Self::visible_lines(bounds, camera_position, doc, ctx.gfx);
 
         if doc.flags().contains(DocFlag::ShowGutter) {
+            doc.update_diff_hunks();
             self.draw_gutter(doc, visible_lines, ctx);
         }
 
@@ -661,11 +892,14 @@ impl Tab {
         }
 
         self.draw_indent_guides(doc, camera_position, visible_lines, ctx);
+        self.draw_long_lines(doc, camera_position, visible_lines, ctx);
         self.draw_lines(colors, doc, camera_position, visible_lines, ctx);
+        self.draw_invisible_chars(doc, camera_position, visible_lines, ctx);
         self.draw_diagnostics(doc, camera_position, visible_lines, ctx);
         self.draw_go_to_definition_hint(doc, camera_position, ctx);
         self.draw_cursors(doc, camera_position, visible_lines, ctx);
         self.draw_scroll_bar(doc, camera_position, ctx);
+        self.draw_horizontal_scroll_bar(doc, camera_position, ctx);
 
         ctx.gfx.end();
     }
@@ -681,10 +915,17 @@ impl Tab {
         let cursor_y = doc.cursor(CursorIndex::Main).position.y;
 
         for (i, y) in visible_lines.enumerate() {
-            let line_number = format_pooled!("{}", y + 1);
+            let line_number = match ctx.config.line_numbers {
+                LineNumberKind::Absolute => y + 1,
+                LineNumberKind::Relative => y.abs_diff(cursor_y),
+                LineNumberKind::Hybrid if y == cursor_y => y + 1,
+                LineNumberKind::Hybrid => y.abs_diff(cursor_y),
+            };
+
+            let line_number = format_pooled!("{}", line_number);
             let visual_y = self.line_foreground_visual_y(i, visible_lines.offset, gfx);
 
-            let width = line_number.len() as f32 * gfx.glyph_width();
+            let width = gfx.measure_text(&line_number) as f32 * gfx.glyph_width();
             let visual_x = gutter_bounds.width
                 - width
                 - (Self::GUTTER_PADDING_WIDTH + Self::GUTTER_BORDER_WIDTH) * gfx.glyph_width();
@@ -696,8 +937,14 @@ impl Tab {
             };
 
             gfx.add_text(&line_number, visual_x, visual_y, color);
+
+            if doc.is_bookmarked(y) {
+                gfx.add_text(Self::BOOKMARK_MARKER, 0.0, visual_y, theme.keyword);
+            }
         }
 
+        self.draw_diff_hunks(doc, gutter_bounds, visible_lines, gfx, theme);
+
         gfx.add_rect(
             gutter_bounds
                 .relative_to(gutter_bounds)
@@ -708,6 +955,57 @@ impl Tab {
         ctx.gfx.end();
     }
 
+    fn draw_diff_hunks(
+        &self,
+        doc: &Doc,
+        gutter_bounds: Rect,
+        visible_lines: VisibleLines,
+        gfx: &mut Gfx,
+        theme: &Theme,
+    ) {
+        let marker_width = gfx.border_width() * 2.0;
+        let marker_x = gutter_bounds.width - Self::GUTTER_BORDER_WIDTH * gfx.glyph_width();
+
+        for hunk in doc.diff_hunks() {
+            if hunk.lines.end < visible_lines.min_y || hunk.lines.start > visible_lines.max_y {
+                continue;
+            }
+
+            let start_y = hunk.lines.start.max(visible_lines.min_y);
+            let end_y = hunk.lines.end.min(visible_lines.max_y);
+
+            let visual_y = self.line_background_visual_y(
+                start_y - visible_lines.min_y,
+                visible_lines.offset,
+                gfx,
+            );
+
+            // Removed lines are marked between the lines that surrounded them.
+            let (color, visual_y, height) = match hunk.kind {
+                DiffHunkKind::Added => (
+                    theme.terminal.green,
+                    visual_y,
+                    (end_y - start_y) as f32 * gfx.line_height(),
+                ),
+                DiffHunkKind::Modified => (
+                    theme.terminal.blue,
+                    visual_y,
+                    (end_y - start_y) as f32 * gfx.line_height(),
+                ),
+                DiffHunkKind::Removed => (
+                    theme.terminal.red,
+                    visual_y - marker_width,
+                    marker_width * 2.0,
+                ),
+            };
+
+            gfx.add_rect(
+                Rect::new(marker_x - marker_width, visual_y, marker_width, height),
+                color,
+            );
+        }
+    }
+
     fn update_indent_guide_x(
         &self,
         doc: &Doc,
@@ -717,10 +1015,9 @@ impl Tab {
         ctx: &mut Ctx,
     ) {
         if !doc.is_line_whitespace(y) {
-            let line = doc.get_line(y).unwrap_or_default();
             let line_start = doc.line_start(y);
 
-            *indent_guide_x = ctx.gfx.measure_text(&line[..line_start]);
+            *indent_guide_x = doc.measure_line_range(y, 0, line_start, ctx.gfx);
 
             return;
         }
@@ -740,11 +1037,11 @@ impl Tab {
         visible_lines: VisibleLines,
         ctx: &mut Ctx,
     ) {
-        let Some(language) = ctx.config.get_language_for_doc(doc) else {
+        if ctx.config.get_language_for_doc(doc).is_none() {
             return;
-        };
+        }
 
-        let indent_width = language.indent_width.measure(ctx.gfx);
+        let indent_width = ctx.config.indent_width_for_doc(doc).measure(ctx.gfx);
 
         let theme = &ctx.config.theme;
 
@@ -784,6 +1081,53 @@ impl Tab {
         }
     }
 
+    fn draw_long_lines(
+        &self,
+        doc: &Doc,
+        camera_position: VisualPosition,
+        visible_lines: VisibleLines,
+        ctx: &mut Ctx,
+    ) {
+        let Some((max_line_length, long_line_highlight)) = ctx
+            .config
+            .get_language_for_doc(doc)
+            .and_then(|language| Some((language.max_line_length?, language.long_line_highlight)))
+        else {
+            return;
+        };
+
+        let gfx = &mut ctx.gfx;
+        let theme = &ctx.config.theme;
+
+        for (i, y) in visible_lines.enumerate() {
+            let line_width = doc.measure_line_range(y, 0, doc.lines()[y].len(), gfx);
+
+            if line_width <= max_line_length {
+                continue;
+            }
+
+            let start_x = match long_line_highlight {
+                LongLineHighlight::Overflow => max_line_length,
+                LongLineHighlight::Line => 0,
+            };
+
+            let visual_x = gfx.line_padding_x() + self.margin + gfx.glyph_width() * start_x as f32
+                - camera_position.x;
+
+            let background_visual_y = self.line_background_visual_y(i, visible_lines.offset, gfx);
+
+            gfx.add_rect(
+                Rect::new(
+                    visual_x,
+                    background_visual_y,
+                    (line_width - start_x) as f32 * gfx.glyph_width(),
+                    gfx.line_height(),
+                ),
+                theme.long_line,
+            );
+        }
+    }
+
     fn draw_lines(
         &self,
         (foreground, background): (Option<Color>, Option<Color>),
@@ -800,13 +1144,32 @@ impl Tab {
 
         for (i, y) in visible_lines.enumerate() {
             let line = &lines[y];
+            let tab_widths = doc.tab_widths(y, gfx);
 
             let mut visual_x = gfx.line_padding_x() + self.margin - camera_position.x;
             let foreground_visual_y = self.line_foreground_visual_y(i, visible_lines.offset, gfx);
             let background_visual_y = self.line_background_visual_y(i, visible_lines.offset, gfx);
 
+            if doc.is_line_rtl(y) {
+                Self::draw_rtl_line(
+                    (foreground, background),
+                    y,
+                    (visual_x, foreground_visual_y, background_visual_y),
+                    doc,
+                    gfx,
+                    theme,
+                );
+                continue;
+            }
+
             if let Some(foreground) = foreground {
-                gfx.add_text(line, visual_x, foreground_visual_y, foreground);
+                gfx.add_text_with_tab_widths(
+                    line,
+                    visual_x,
+                    foreground_visual_y,
+                    foreground,
+                    &tab_widths,
+                );
                 continue;
             }
 
@@ -815,7 +1178,13 @@ impl Tab {
                 .map(HighlightedLine::highlights)
                 .filter(|highlights| !highlights.is_empty())
             else {
-                gfx.add_text(line, visual_x, foreground_visual_y, theme.normal);
+                gfx.add_text_with_tab_widths(
+                    line,
+                    visual_x,
+                    foreground_visual_y,
+                    theme.normal,
+                    &tab_widths,
+                );
                 continue;
             };
 
@@ -825,6 +1194,8 @@ impl Tab {
                     .theme
                     .highlight_kind_to_color(highlight.foreground);
                 let highlighted_text = &line[highlight.start..highlight.end];
+                let highlighted_tab_widths =
+                    Doc::tab_widths_from(&tab_widths, line, highlight.start);
 
                 if let Some(highlight_background) = highlight.background {
                     let highlight_background = ctx
@@ -833,17 +1204,176 @@ impl Tab {
                         .highlight_kind_to_color(highlight_background);
 
                     if Some(highlight_background) != background {
-                        gfx.add_background(
+                        gfx.add_background_with_tab_widths(
                             highlighted_text,
                             visual_x,
                             background_visual_y,
                             highlight_background,
+                            highlighted_tab_widths,
                         );
                     }
                 }
 
-                visual_x +=
-                    gfx.add_text(highlighted_text, visual_x, foreground_visual_y, foreground);
+                visual_x += gfx.add_text_with_tab_widths(
+                    highlighted_text,
+                    visual_x,
+                    foreground_visual_y,
+                    foreground,
+                    highlighted_tab_widths,
+                );
+            }
+        }
+    }
+
+    // Right-to-left lines are drawn with their graphemes in reverse order,
+    // matching the mirrored positions from Doc::position_to_visual.
+    fn draw_rtl_line(
+        (foreground, background): (Option<Color>, Option<Color>),
+        y: usize,
+        (visual_x, foreground_visual_y, background_visual_y): (f32, f32, f32),
+        doc: &Doc,
+        gfx: &mut Gfx,
+        theme: &Theme,
+    ) {
+        let line = &doc.lines()[y];
+        let tab_widths = doc.tab_widths(y, gfx);
+        let line_width = gfx.measure_text_with_tab_widths(line, &tab_widths);
+
+        let highlights = doc
+            .highlighted_lines()
+            .get(y)
+            .map(HighlightedLine::highlights)
+            .unwrap_or_default();
+
+        let mut highlights = highlights.iter().peekable();
+        let mut tab_count = 0;
+        let mut leading_width = 0;
+        let mut end = 0;
+
+        for grapheme in GraphemeIterator::new(line) {
+            let start = end;
+            end += grapheme.len();
+
+            while highlights
+                .next_if(|highlight| highlight.end <= start)
+                .is_some()
+            {}
+
+            let highlight = highlights
+                .peek()
+                .filter(|highlight| highlight.start <= start);
+
+            let grapheme_tab_widths = &tab_widths[tab_count.min(tab_widths.len())..];
+
+            if grapheme == "\t" {
+                tab_count += 1;
+            }
+
+            leading_width += gfx.measure_text_with_tab_widths(grapheme, grapheme_tab_widths);
+
+            let grapheme_visual_x =
+                visual_x + (line_width - leading_width) as f32 * gfx.glyph_width();
+
+            let highlight_background = highlight
+                .and_then(|highlight| highlight.background)
+                .map(|highlight_background| theme.highlight_kind_to_color(highlight_background))
+                .filter(|highlight_background| Some(*highlight_background) != background);
+
+            if let (None, Some(highlight_background)) = (foreground, highlight_background) {
+                gfx.add_background_with_tab_widths(
+                    grapheme,
+                    grapheme_visual_x,
+                    background_visual_y,
+                    highlight_background,
+                    grapheme_tab_widths,
+                );
+            }
+
+            let foreground = foreground.unwrap_or_else(|| {
+                highlight
+                    .map(|highlight| theme.highlight_kind_to_color(highlight.foreground))
+                    .unwrap_or(theme.normal)
+            });
+
+            gfx.add_text_with_tab_widths(
+                grapheme,
+                grapheme_visual_x,
+                foreground_visual_y,
+                foreground,
+                grapheme_tab_widths,
+            );
+        }
+    }
+
+    fn draw_invisible_chars(
+        &self,
+        doc: &Doc,
+        camera_position: VisualPosition,
+        visible_lines: VisibleLines,
+        ctx: &mut Ctx,
+    ) {
+        // Terminals pad wide characters with zero-width spaces, so they'd be marked everywhere.
+        if !ctx.config.show_invisible_chars || !doc.flags().contains(DocFlag::ShowInvisibleChars) {
+            return;
+        }
+
+        let gfx = &mut ctx.gfx;
+        let theme = &ctx.config.theme;
+
+        let mut color = theme.warning;
+        color.a /= 2;
+
+        let mut substitute = STRING_POOL.new_item();
+
+        for (i, y) in visible_lines.enumerate() {
+            let line = &doc.lines()[y];
+
+            // Most lines are plain ASCII, so skip them without checking each grapheme.
+            if line
+                .bytes()
+                .all(|b| b == b'\t' || (b' '..0x7F).contains(&b))
+            {
+                continue;
+            }
+
+            let foreground_visual_y = self.line_foreground_visual_y(i, visible_lines.offset, gfx);
+            let background_visual_y = self.line_background_visual_y(i, visible_lines.offset, gfx);
+            let mut end = 0;
+
+            for grapheme in GraphemeIterator::new(line) {
+                let start = end;
+                end += grapheme.len();
+
+                if !grapheme::is_invisible(grapheme) {
+                    continue;
+                }
+
+                substitute.clear();
+                grapheme::push_invisible_substitute(grapheme, &mut substitute);
+
+                let start_x = doc.measure_line_range(y, 0, start, gfx);
+                let width = doc.measure_line_range(y, start, end, gfx);
+
+                let visual_x =
+                    gfx.line_padding_x() + self.margin + start_x as f32 * gfx.glyph_width()
+                        - camera_position.x;
+
+                // Substitutes are drawn over the text instead of being measured as part of it,
+                // so that cursor and click positions stay in sync with the actual characters.
+                // Longer substitutes like "<U+200B>" cover the text that follows them.
+                let visual_width =
+                    (width.max(gfx.measure_text(&substitute)) as f32) * gfx.glyph_width();
+
+                let rect = Rect::new(
+                    visual_x,
+                    background_visual_y,
+                    visual_width,
+                    gfx.line_height(),
+                );
+
+                gfx.add_rect(rect, theme.background);
+                gfx.add_rect(rect, color);
+                gfx.add_text(&substitute, visual_x, foreground_visual_y, theme.warning);
             }
         }
     }
@@ -892,8 +1422,9 @@ impl Tab {
                     let highlight_position =
                         self.position_to_visual(position, camera_position, doc, gfx);
 
-                    let grapheme = doc.grapheme(position);
-                    let grapheme_width = gfx.measure_text(grapheme);
+                    let grapheme_end = position.x + doc.grapheme(position).len();
+                    let grapheme_width =
+                        doc.measure_line_range(position.y, position.x, grapheme_end, gfx);
 
                     gfx.add_zig_zag_underline(
                         highlight_position.x,
@@ -927,7 +1458,7 @@ impl Tab {
         let visual_position = ctx.window.mouse_position();
         let position = self.visual_to_position_unclamped(visual_position, doc, ui, gfx)?;
 
-        if GraphemeCategory::new(doc.grapheme(position)) != GraphemeCategory::Identifier {
+        if doc.grapheme_category(doc.grapheme(position)) != GraphemeCategory::Identifier {
             return None;
         }
 
@@ -946,6 +1477,26 @@ impl Tab {
         Some(())
     }
 
+    pub fn draw_underline(
+        &self,
+        start: Position,
+        end: Position,
+        color: Color,
+        doc: &Doc,
+        ctx: &mut Ctx,
+    ) {
+        let bounds = ctx.ui.bounds(self.doc_widget_id);
+        let camera_position = self.camera.position().floor();
+        let gfx = &mut ctx.gfx;
+
+        let start = self.position_to_visual(start, camera_position, doc, gfx);
+        let end = self.position_to_visual(end, camera_position, doc, gfx);
+
+        gfx.begin(Some(bounds));
+        gfx.add_underline(start.x, start.y + gfx.line_height(), end.x - start.x, color);
+        gfx.end();
+    }
+
     fn draw_cursors(
         &self,
         doc: &Doc,
@@ -1066,13 +1617,19 @@ impl Tab {
             return;
         }
 
-        let highlight_position =
+        let mut highlight_position =
             self.position_to_visual(Position::new(start_x, y), camera_position, doc, gfx);
 
-        let line_width = gfx.measure_text(&lines[y][start_x..end_x]) + newline_width;
+        let line_width = doc.measure_line_range(y, start_x, end_x, gfx) + newline_width;
+        let is_line_rtl = doc.is_line_rtl(y);
+
+        // Selections on right-to-left lines extend leftwards from their start.
+        if is_line_rtl {
+            highlight_position.x -= line_width as f32 * gfx.glyph_width();
+        }
 
         // Make the selection flush with the side of the doc.
-        let padding_x = if start_x == 0 {
+        let padding_x = if start_x == 0 && !is_line_rtl {
             gfx.line_padding_x()
         } else {
             0.0
@@ -1107,12 +1664,104 @@ impl Tab {
         ((time - last_time) * Self::CURSOR_ANIMATION_SPEED) as f32
     }
 
-    fn draw_scroll_bar(&self, doc: &Doc, camera_position: VisualPosition, ctx: &mut Ctx) {
-        let bounds = ctx.ui.bounds(self.doc_widget_id);
+    fn is_scroll_bar_visible(&self, doc: &Doc, ui: &Ui, gfx: &Gfx) -> bool {
+        let bounds = ui.bounds(self.doc_widget_id);
 
-        if !doc.flags().contains(DocFlag::AllowScrollingPastBottom)
-            && doc.lines().len() as f32 * ctx.gfx.line_height() + self.margin * 2.0 <= bounds.height
+        doc.flags().contains(DocFlag::AllowScrollingPastBottom)
+            || doc.lines().len() as f32 * gfx.line_height() + self.margin * 2.0 > bounds.height
+    }
+
+    fn is_horizontal_scroll_bar_visible(&self, doc: &Doc) -> bool {
+        doc.flags().contains(DocFlag::AllowMultipleLines)
+            && self.camera.horizontal.max_position() > 0.0
+    }
+
+    fn scroll_bar_at(
+        &self,
+        visual: VisualPosition,
+        doc: &Doc,
+        ui: &Ui,
+        gfx: &Gfx,
+    ) -> Option<ScrollBar> {
+        let bounds = ui.bounds(self.doc_widget_id);
+
+        if !bounds.contains_position(visual) {
+            return None;
+        }
+
+        // The hit areas are wider than the drawn scroll bars to make them easier to grab.
+        if self.is_scroll_bar_visible(doc, ui, gfx)
+            && visual.x >= bounds.right() - gfx.glyph_width()
         {
+            Some(ScrollBar::Vertical)
+        } else if self.is_horizontal_scroll_bar_visible(doc)
+            && visual.y >= bounds.bottom() - gfx.glyph_width()
+        {
+            Some(ScrollBar::Horizontal)
+        } else {
+            None
+        }
+    }
+
+    fn scroll_to_scroll_bar_position(
+        &mut self,
+        visual: VisualPosition,
+        doc: &Doc,
+        ui: &Ui,
+        gfx: &Gfx,
+    ) {
+        let bounds = ui.bounds(self.doc_widget_id);
+
+        match self.dragged_scroll_bar {
+            Some(ScrollBar::Vertical) => {
+                let doc_height_lines = self.doc_height_lines(ui, gfx);
+
+                let line_y =
+                    (visual.y - bounds.y) / bounds.height * self.scroll_bar_doc_len(doc, ui, gfx);
+                let camera_y = (line_y - doc_height_lines / 2.0) * gfx.line_height();
+
+                self.camera.vertical.jump_to(camera_y);
+            }
+            Some(ScrollBar::Horizontal) => {
+                let content_width = self.camera.horizontal.max_position() + bounds.width;
+                let camera_x = (visual.x - bounds.x) / bounds.width * content_width;
+
+                self.camera
+                    .horizontal
+                    .jump_to(camera_x - bounds.width / 2.0);
+            }
+            None => {}
+        }
+    }
+
+    fn draw_horizontal_scroll_bar(
+        &self,
+        doc: &Doc,
+        camera_position: VisualPosition,
+        ctx: &mut Ctx,
+    ) {
+        if !self.is_horizontal_scroll_bar_visible(doc) {
+            return;
+        }
+
+        let gfx = &mut ctx.gfx;
+        let theme = &ctx.config.theme;
+
+        let bounds = ctx.ui.bounds(self.doc_widget_id);
+        let content_width = self.camera.horizontal.max_position() + bounds.width;
+        let height = gfx.glyph_width() / 2.0;
+
+        let start_x = (camera_position.x / content_width * bounds.width).floor();
+        let end_x = ((camera_position.x + bounds.width) / content_width * bounds.width).floor();
+
+        gfx.add_rect(
+            Rect::new(start_x, bounds.height - height, end_x - start_x, height),
+            theme.emphasized,
+        );
+    }
+
+    fn draw_scroll_bar(&self, doc: &Doc, camera_position: VisualPosition, ctx: &mut Ctx) {
+        if !self.is_scroll_bar_visible(doc, ctx.ui, ctx.gfx) {
             return;
         }
 
@@ -1120,6 +1769,13 @@ impl Tab {
         let gfx = &mut ctx.gfx;
         let theme = &ctx.config.theme;
 
+        for &y in &self.selection_match_ys {
+            gfx.add_rect(
+                self.doc_range_to_scrollbar_rect(y as f32, y as f32, doc, ui, gfx),
+                theme.selection,
+            );
+        }
+
         for language_server in ctx.lsp.iter_servers_mut() {
             // Reversed so that more severe diagnostics are drawn on top.
             for diagnostic in language_server.diagnostics_mut(doc).iter().rev() {
@@ -1169,9 +1825,7 @@ impl Tab {
         ui: &Ui,
         gfx: &Gfx,
     ) -> Rect {
-        let doc_height_lines = self.doc_height_lines(ui, gfx);
-        let doc_len = doc_height_lines.max(doc.lines().len() as f32)
-            + (self.margin * 2.0 / gfx.line_height());
+        let doc_len = self.scroll_bar_doc_len(doc, ui, gfx);
 
         let bounds = ui.bounds(self.doc_widget_id);
         let width = gfx.glyph_width() / 2.0;
@@ -1190,4 +1844,10 @@ impl Tab {
             (end_y - start_y).max(gfx.border_width() * 2.0),
         )
     }
+
+    fn scroll_bar_doc_len(&self, doc: &Doc, ui: &Ui, gfx: &Gfx) -> f32 {
+        let doc_height_lines = self.doc_height_lines(ui, gfx);
+
+        doc_height_lines.max(doc.lines().len() as f32) + (self.margin * 2.0 / gfx.line_height())
+    }
 }
@@ -0,0 +1,71 @@
+use crate::{
+    geometry::position::Position,
+    tests::test_with_doc,
+    text::selection::SelectionStats,
+    ui::{core::WidgetId, slot_list::SlotId},
+};
+
+use super::Tab;
+
+test_with_doc!(
+    selection_matches_update_with_doc_version,
+    "let a = 1;\nlet b = a;\nb",
+    |ctx, doc| {
+        let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+
+        doc.jump_cursors(Position::new(4, 0), false, ctx.gfx);
+        doc.jump_cursors(Position::new(5, 0), true, ctx.gfx);
+        tab.update_selection_matches(doc);
+
+        assert_eq!(tab.selection_match_ys, [0, 1]);
+
+        doc.insert(Position::new(0, 2), "a", ctx);
+        tab.update_selection_matches(doc);
+
+        assert_eq!(tab.selection_match_ys, [0, 1, 2]);
+
+        doc.jump_cursors(Position::ZERO, false, ctx.gfx);
+        tab.update_selection_matches(doc);
+
+        assert!(tab.selection_match_ys.is_empty());
+    }
+);
+
+test_with_doc!(
+    selection_stats_update_with_selections,
+    "hello world\ngoodbye",
+    |ctx, doc| {
+        let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+
+        doc.jump_cursors(Position::ZERO, false, ctx.gfx);
+        tab.update_selection_stats(doc);
+
+        assert_eq!(tab.selection_stats(), None);
+
+        doc.jump_cursors(Position::new(5, 0), true, ctx.gfx);
+        tab.update_selection_stats(doc);
+
+        assert_eq!(
+            tab.selection_stats(),
+            Some(SelectionStats {
+                chars: 5,
+                words: 1,
+                lines: 1,
+            })
+        );
+
+        doc.insert(Position::ZERO, "oh ", ctx);
+        doc.jump_cursors(Position::ZERO, false, ctx.gfx);
+        doc.jump_cursors(Position::new(8, 0), true, ctx.gfx);
+        tab.update_selection_stats(doc);
+
+        assert_eq!(
+            tab.selection_stats(),
+            Some(SelectionStats {
+                chars: 8,
+                words: 2,
+                lines: 1,
+            })
+        );
+    }
+);
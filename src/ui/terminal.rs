@@ -1,21 +1,31 @@
+use std::path::{Path, PathBuf};
+
 use terminal_emulator::TerminalEmulator;
 use terminal_pane::TerminalPane;
 
 use crate::{
     ctx::Ctx,
-    input::action::action_name,
+    geometry::position::Position,
+    input::{action::action_name, mods::Mod},
     platform::process::Process,
+    pool::Pooled,
     text::doc::{Doc, DocFlags},
     ui::{
+        color::Color,
         core::{WidgetScale, WidgetSettings},
         msg::Msg,
     },
 };
 
-use super::{core::WidgetId, pane_list::PaneList, slot_list::SlotList};
+use super::{
+    core::{Ui, WidgetId},
+    pane_list::PaneList,
+    slot_list::SlotList,
+};
 
 mod color_table;
 mod escape_parser;
+mod path_link;
 mod terminal_emulator;
 mod terminal_pane;
 
@@ -45,11 +55,15 @@ type Term = (TerminalDocs, TerminalEmulator);
 pub struct Terminal {
     panes: PaneList<TerminalPane, Term>,
     term_list: SlotList<Term>,
+    // The last directory reported by a shell, along with the workspace it was in.
+    last_current_dir: Option<(Pooled<PathBuf>, Pooled<PathBuf>)>,
 
     widget_id: WidgetId,
 }
 
 impl Terminal {
+    const BELL_FLASH_ALPHA: f32 = 48.0;
+
     pub fn new(parent_id: WidgetId, ctx: &mut Ctx) -> Self {
         let widget_id = ctx.ui.new_widget(
             parent_id,
@@ -62,6 +76,7 @@ impl Terminal {
         let mut terminal = Self {
             panes: PaneList::new(widget_id, ctx.ui),
             term_list: SlotList::new(),
+            last_current_dir: None,
 
             widget_id,
         };
@@ -73,6 +88,10 @@ impl Terminal {
 
     pub fn is_animating(&self, ctx: &Ctx) -> bool {
         self.panes.is_animating(ctx)
+            || self
+                .term_list
+                .iter()
+                .any(|(_, emulator)| emulator.bell_flash_amount(ctx.time).is_some())
     }
 
     pub fn receive_msgs(&mut self, ctx: &mut Ctx) {
@@ -88,6 +107,8 @@ impl Terminal {
     }
 
     pub fn update(&mut self, ctx: &mut Ctx, dt: f32) {
+        self.update_last_current_dir(ctx);
+
         self.panes.update(&mut self.term_list, ctx, dt);
         self.panes.remove_excess(ctx.ui, |pane| !pane.has_tabs());
     }
@@ -98,6 +119,55 @@ impl Terminal {
             &mut self.term_list,
             ctx,
         );
+
+        self.draw_path_link_hint(ctx);
+        self.draw_bell_flashes(ctx);
+    }
+
+    fn draw_bell_flashes(&self, ctx: &mut Ctx) {
+        for pane in self.panes.iter() {
+            let Some((tab, (_, emulator))) =
+                pane.get_focused_tab_with_data(&self.term_list, ctx.ui)
+            else {
+                continue;
+            };
+
+            let Some(flash_amount) = emulator.bell_flash_amount(ctx.time) else {
+                continue;
+            };
+
+            let bounds = tab.doc_bounds(ctx.ui);
+
+            let color = Color {
+                a: (Self::BELL_FLASH_ALPHA * flash_amount) as u8,
+                ..ctx.config.theme.terminal.foreground
+            };
+
+            ctx.gfx.begin(Some(bounds));
+            ctx.gfx.add_rect(bounds.relative_to(bounds), color);
+            ctx.gfx.end();
+        }
+    }
+
+    fn draw_path_link_hint(&mut self, ctx: &mut Ctx) -> Option<()> {
+        if !ctx.window.mods().contains(Mod::Ctrl) && !ctx.window.mods().contains(Mod::Cmd) {
+            return None;
+        }
+
+        let pane = self.panes.get_hovered_mut(ctx.ui)?;
+        let (tab, (docs, emulator)) = pane.get_focused_tab_with_data(&self.term_list, ctx.ui)?;
+        let doc = emulator.doc(docs);
+
+        let visual_position = ctx.window.mouse_position();
+        let position = tab.visual_to_position_unclamped(visual_position, doc, ctx.ui, ctx.gfx)?;
+        let path_link = emulator.path_link_at(position, doc, ctx)?;
+
+        let start = Position::new(path_link.start, position.y);
+        let end = Position::new(path_link.end, position.y);
+
+        tab.draw_underline(start, end, ctx.config.theme.terminal.foreground, doc, ctx);
+
+        Some(())
     }
 
     fn add_pane(&mut self, ctx: &mut Ctx) {
@@ -110,6 +180,10 @@ impl Terminal {
             return;
         }
 
+        if let Some(pane) = self.panes.get_last_focused_mut(ctx.ui) {
+            pane.close_all_tabs(&mut self.term_list, ctx);
+        }
+
         self.panes.remove_focused(ctx.ui);
     }
 
@@ -119,6 +193,48 @@ impl Terminal {
             .filter_map(|(_, emulator)| emulator.pty())
     }
 
+    // New shells start where the last one was, unless the workspace has changed since.
+    fn update_last_current_dir(&mut self, ctx: &mut Ctx) {
+        if let Some(current_dir) = self.current_dir(ctx.ui).filter(|current_dir| {
+            self.last_current_dir
+                .as_ref()
+                .is_none_or(|(_, last_current_dir)| last_current_dir.as_path() != *current_dir)
+        }) {
+            self.last_current_dir = Some((ctx.current_dir.clone(), current_dir.into()));
+        }
+
+        let Some((workspace_dir, last_current_dir)) = &self.last_current_dir else {
+            return;
+        };
+
+        if workspace_dir != ctx.current_dir {
+            self.last_current_dir = None;
+            return;
+        }
+
+        for (_, emulator) in self.term_list.iter_mut() {
+            emulator.set_start_dir(last_current_dir);
+        }
+    }
+
+    pub fn last_current_dir(&self, ctx: &Ctx) -> Option<&Path> {
+        self.last_current_dir
+            .as_ref()
+            .filter(|(workspace_dir, _)| workspace_dir == ctx.current_dir)
+            .map(|(_, last_current_dir)| last_current_dir.as_path())
+    }
+
+    pub fn set_last_current_dir(&mut self, dir: &Path, ctx: &Ctx) {
+        self.last_current_dir = Some((ctx.current_dir.clone(), dir.into()));
+    }
+
+    pub fn current_dir(&self, ui: &Ui) -> Option<&Path> {
+        let pane = self.panes.get_last_focused(ui)?;
+        let (_, (_, emulator)) = pane.get_focused_tab_with_data(&self.term_list, ui)?;
+
+        emulator.current_dir()
+    }
+
     pub fn widget_id(&self) -> WidgetId {
         self.widget_id
     }
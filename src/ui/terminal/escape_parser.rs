@@ -8,6 +8,8 @@ use crate::{
 #[derive(Debug)]
 pub enum EscapeSequence {
     Plain { len: usize },
+    RepeatLastChar(usize),
+    Bell,
     Backspace,
     Tab,
     CarriageReturn,
@@ -15,6 +17,7 @@ pub enum EscapeSequence {
     ReverseNewline,
     HideCursor,
     ShowCursor,
+    SetOriginMode(bool),
     SwitchToNormalBuffer,
     SwitchToAlternateBuffer,
     QueryModifyKeyboard,
@@ -47,11 +50,13 @@ pub enum EscapeSequence {
     ScrollUp(usize),
     ScrollDown(usize),
     ClearCharsAfterCursor(usize),
+    InsertCharsAtCursor(usize),
     DeleteCharsAfterCursor(usize),
     SetScrollRegion { top: usize, bottom: usize },
     QueryDeviceStatus,
     QueryTerminalId,
     SetTitle { len: usize },
+    SetWorkingDir { len: usize },
     ResetTitle,
     QueryForegroundColor,
     QueryBackgroundColor,
@@ -122,7 +127,10 @@ impl EscapeParser {
                     self.flush();
                     self.state = EscapeParserState::Escape;
                 }
-                0x7 => {}
+                0x7 => {
+                    self.flush();
+                    self.pending_sequences.push_back(EscapeSequence::Bell);
+                }
                 0x8 => {
                     self.flush();
                     self.pending_sequences.push_back(EscapeSequence::Backspace);
@@ -272,6 +280,9 @@ impl EscapeParser {
                                     25 => {
                                         self.pending_sequences.push_back(EscapeSequence::HideCursor)
                                     }
+                                    6 => self
+                                        .pending_sequences
+                                        .push_back(EscapeSequence::SetOriginMode(false)),
                                     1047 | 1049 => self
                                         .pending_sequences
                                         .push_back(EscapeSequence::SwitchToNormalBuffer),
@@ -290,6 +301,9 @@ impl EscapeParser {
                                     25 => {
                                         self.pending_sequences.push_back(EscapeSequence::ShowCursor)
                                     }
+                                    6 => self
+                                        .pending_sequences
+                                        .push_back(EscapeSequence::SetOriginMode(true)),
                                     1047 | 1049 => self
                                         .pending_sequences
                                         .push_back(EscapeSequence::SwitchToAlternateBuffer),
@@ -338,7 +352,7 @@ impl EscapeParser {
                                     22 => Some(EscapeSequence::SetColorsBright(false)),
                                     27 => Some(EscapeSequence::SetColorsSwapped(false)),
                                     30 => Some(EscapeSequence::SetForegroundColor(
-                                        TerminalHighlightKind::Background,
+                                        TerminalHighlightKind::Black,
                                     )),
                                     31 => Some(EscapeSequence::SetForegroundColor(
                                         TerminalHighlightKind::Red,
@@ -359,7 +373,7 @@ impl EscapeParser {
                                         TerminalHighlightKind::Cyan,
                                     )),
                                     37 => Some(EscapeSequence::SetForegroundColor(
-                                        TerminalHighlightKind::Foreground,
+                                        TerminalHighlightKind::White,
                                     )),
                                     38 => Some(EscapeSequence::SetForegroundColor(
                                         parse_color_from_parameters(&mut parameters)
@@ -369,7 +383,7 @@ impl EscapeParser {
                                         TerminalHighlightKind::Foreground,
                                     )),
                                     40 => Some(EscapeSequence::SetBackgroundColor(
-                                        TerminalHighlightKind::Background,
+                                        TerminalHighlightKind::Black,
                                     )),
                                     41 => Some(EscapeSequence::SetBackgroundColor(
                                         TerminalHighlightKind::Red,
@@ -390,7 +404,7 @@ impl EscapeParser {
                                         TerminalHighlightKind::Cyan,
                                     )),
                                     47 => Some(EscapeSequence::SetBackgroundColor(
-                                        TerminalHighlightKind::Foreground,
+                                        TerminalHighlightKind::White,
                                     )),
                                     48 => Some(EscapeSequence::SetBackgroundColor(
                                         parse_color_from_parameters(&mut parameters)
@@ -400,7 +414,7 @@ impl EscapeParser {
                                         TerminalHighlightKind::Background,
                                     )),
                                     90 => Some(EscapeSequence::SetForegroundColor(
-                                        TerminalHighlightKind::BrightBackground,
+                                        TerminalHighlightKind::BrightBlack,
                                     )),
                                     91 => Some(EscapeSequence::SetForegroundColor(
                                         TerminalHighlightKind::BrightRed,
@@ -421,10 +435,10 @@ impl EscapeParser {
                                         TerminalHighlightKind::BrightCyan,
                                     )),
                                     97 => Some(EscapeSequence::SetForegroundColor(
-                                        TerminalHighlightKind::BrightForeground,
+                                        TerminalHighlightKind::BrightWhite,
                                     )),
                                     100 => Some(EscapeSequence::SetBackgroundColor(
-                                        TerminalHighlightKind::BrightBackground,
+                                        TerminalHighlightKind::BrightBlack,
                                     )),
                                     101 => Some(EscapeSequence::SetBackgroundColor(
                                         TerminalHighlightKind::BrightRed,
@@ -445,7 +459,7 @@ impl EscapeParser {
                                         TerminalHighlightKind::BrightCyan,
                                     )),
                                     107 => Some(EscapeSequence::SetBackgroundColor(
-                                        TerminalHighlightKind::BrightForeground,
+                                        TerminalHighlightKind::BrightWhite,
                                     )),
                                     _ => {
                                         #[cfg(feature = "terminal_debug")]
@@ -580,6 +594,11 @@ impl EscapeParser {
                             self.pending_sequences
                                 .push_back(EscapeSequence::ClearCharsAfterCursor(distance));
                         }
+                        b'@' => {
+                            let distance = parameter(parameters, 0, 1);
+                            self.pending_sequences
+                                .push_back(EscapeSequence::InsertCharsAtCursor(distance));
+                        }
                         b'P' => {
                             let distance = parameter(parameters, 0, 1);
                             self.pending_sequences
@@ -607,6 +626,15 @@ impl EscapeParser {
                                 }
                             }
                         }
+                        b's' => self.pending_sequences.push_back(EscapeSequence::SaveCursor),
+                        b'u' => self
+                            .pending_sequences
+                            .push_back(EscapeSequence::RestoreCursor),
+                        b'b' => {
+                            let count = parameter(parameters, 0, 1);
+                            self.pending_sequences
+                                .push_back(EscapeSequence::RepeatLastChar(count));
+                        }
                         b'c' => self
                             .pending_sequences
                             .push_back(EscapeSequence::QueryTerminalId),
@@ -664,6 +692,12 @@ impl EscapeParser {
                                 self.pending_sequences.push_back(EscapeSequence::ResetTitle);
                             }
                         }
+                        b"7" if *len > 0 => {
+                            needs_string = true;
+
+                            self.pending_sequences
+                                .push_back(EscapeSequence::SetWorkingDir { len: *len });
+                        }
                         // Setting/requesting foreground/background color.
                         // Only requesting the value is supported.
                         b"10" if parameters.get(1) == Some(&b'?') => {
@@ -746,6 +780,25 @@ fn valid_utf8_range(bytes: &[u8]) -> &str {
     }
 }
 
+const ANSI_COLORS: [TerminalHighlightKind; 16] = [
+    TerminalHighlightKind::Black,
+    TerminalHighlightKind::Red,
+    TerminalHighlightKind::Green,
+    TerminalHighlightKind::Yellow,
+    TerminalHighlightKind::Blue,
+    TerminalHighlightKind::Magenta,
+    TerminalHighlightKind::Cyan,
+    TerminalHighlightKind::White,
+    TerminalHighlightKind::BrightBlack,
+    TerminalHighlightKind::BrightRed,
+    TerminalHighlightKind::BrightGreen,
+    TerminalHighlightKind::BrightYellow,
+    TerminalHighlightKind::BrightBlue,
+    TerminalHighlightKind::BrightMagenta,
+    TerminalHighlightKind::BrightCyan,
+    TerminalHighlightKind::BrightWhite,
+];
+
 fn parse_color_from_parameters<'a>(
     parameters: &mut impl Iterator<Item = &'a usize>,
 ) -> Option<TerminalHighlightKind> {
@@ -763,12 +816,14 @@ fn parse_color_from_parameters<'a>(
             )))
         }
         5 => {
-            // 256 color table:
-            let index = (*parameters.next()?).clamp(0, COLOR_TABLE.len());
+            // 256 color table, where the first 16 colors come from the theme:
+            let index = (*parameters.next()?).clamp(0, COLOR_TABLE.len() - 1);
 
-            Some(TerminalHighlightKind::Custom(Color::from_hex(
-                COLOR_TABLE[index],
-            )))
+            let kind = ANSI_COLORS.get(index).copied().unwrap_or_else(|| {
+                TerminalHighlightKind::Custom(Color::from_hex(COLOR_TABLE[index]))
+            });
+
+            Some(kind)
         }
         _ => None,
     }
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests;
+
+use std::path::{Path, PathBuf};
+
+use crate::pool::{Pooled, PATH_POOL};
+
+// A reference to a file in terminal output, such as "src/main.rs:12:5" in a compiler error.
+pub struct PathLink {
+    pub start: usize,
+    pub end: usize,
+    pub path: Pooled<PathBuf>,
+    // Both are 1-based, and the column counts chars rather than bytes.
+    pub line: usize,
+    pub column: usize,
+}
+
+impl PathLink {
+    const TRAILING_PUNCTUATION: &[char] = &[':', ',', '.', ';'];
+
+    pub fn at(x: usize, line: &str, base_dir: &Path) -> Option<Self> {
+        if x >= line.len() || Self::is_delimiter(line[x..].chars().next()?) {
+            return None;
+        }
+
+        let start = line[..x]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| Self::is_delimiter(*c))
+            .map(|(start, c)| start + c.len_utf8())
+            .unwrap_or(0);
+
+        let end = line[x..]
+            .find(Self::is_delimiter)
+            .map(|end| x + end)
+            .unwrap_or(line.len());
+
+        let token = line[start..end].trim_end_matches(Self::TRAILING_PUNCTUATION);
+        let (path, line_number, column_number) = parse(token)?;
+
+        let path = PATH_POOL.init_item(|full_path| {
+            full_path.push(base_dir);
+            full_path.push(path);
+        });
+
+        if !path.is_file() {
+            return None;
+        }
+
+        Some(Self {
+            start,
+            end: start + token.len(),
+            path,
+            line: line_number.unwrap_or(1),
+            column: column_number.unwrap_or(1),
+        })
+    }
+
+    fn is_delimiter(c: char) -> bool {
+        c.is_whitespace()
+            || matches!(
+                c,
+                '"' | '\'' | '`' | '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | '\u{200B}'
+            )
+    }
+}
+
+// Splits "path:line:column" into its parts, the line and column are optional.
+fn parse(token: &str) -> Option<(&str, Option<usize>, Option<usize>)> {
+    let mut path = token;
+    let mut numbers = [None; 2];
+    let mut number_count = 0;
+
+    while number_count < numbers.len() {
+        let Some((rest, number)) = path
+            .rsplit_once(':')
+            .and_then(|(rest, number)| Some((rest, number.parse::<usize>().ok()?)))
+        else {
+            break;
+        };
+
+        numbers[number_count] = Some(number);
+        number_count += 1;
+        path = rest;
+    }
+
+    if path.is_empty() {
+        return None;
+    }
+
+    match number_count {
+        2 => Some((path, numbers[1], numbers[0])),
+        _ => Some((path, numbers[0], None)),
+    }
+}
@@ -0,0 +1,36 @@
+use std::env::current_dir;
+
+use super::{parse, PathLink};
+
+#[test]
+fn parse_path_with_line_and_column() {
+    assert_eq!(
+        parse("src/main.rs:12:5"),
+        Some(("src/main.rs", Some(12), Some(5)))
+    );
+    assert_eq!(
+        parse("src/main.rs:12"),
+        Some(("src/main.rs", Some(12), None))
+    );
+    assert_eq!(parse("src/main.rs"), Some(("src/main.rs", None, None)));
+    assert_eq!(
+        parse("C:\\src\\main.rs:3:4"),
+        Some(("C:\\src\\main.rs", Some(3), Some(4)))
+    );
+    assert_eq!(parse(":12"), None);
+}
+
+#[test]
+fn path_link_in_compiler_output() {
+    let base_dir = current_dir().unwrap();
+    let line = "  --> src/main.rs:12:5: error";
+
+    let link = PathLink::at(10, line, &base_dir).unwrap();
+
+    assert_eq!(&line[link.start..link.end], "src/main.rs:12:5");
+    assert_eq!((link.line, link.column), (12, 5));
+    assert!(link.path.ends_with("src/main.rs"));
+
+    assert!(PathLink::at(2, line, &base_dir).is_none());
+    assert!(PathLink::at(24, line, &base_dir).is_none());
+}
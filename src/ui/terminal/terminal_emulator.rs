@@ -1,23 +1,32 @@
+#[cfg(test)]
+mod tests;
+
 use std::{
     iter,
     mem::swap,
     ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+    slice,
 };
 
 use crate::{
+    config::BellKind,
     ctx::Ctx,
-    geometry::{position::Position, rect::Rect},
+    geometry::{position::Position, rect::Rect, visual_position::VisualPosition},
     input::{
         action::{action_keybind, action_name, ActionName},
         editing_actions::handle_copy,
         key::Key,
         mods::{Mod, Mods},
+        mouse_button::MouseButton,
+        mousebind::{Mousebind, MousebindKind},
     },
+    lsp::uri::uri_to_path,
     platform::{
         gfx::Gfx,
         process::{Process, ProcessKind},
     },
-    pool::{format_pooled, STRING_POOL},
+    pool::{format_pooled, Pooled, STRING_POOL},
     text::{
         doc::Doc,
         grapheme::{CharCursor, CharIterator},
@@ -27,7 +36,10 @@ use crate::{
         camera::CameraRecenterKind,
         msg::Msg,
         tab::Tab,
-        terminal::escape_parser::{EscapeParser, EscapeSequence},
+        terminal::{
+            escape_parser::{EscapeParser, EscapeSequence},
+            path_link::PathLink,
+        },
     },
 };
 
@@ -59,6 +71,11 @@ impl ColoredGridLine {
         self.is_dirty = true;
     }
 
+    fn truncate(&mut self, len: usize) {
+        self.colors.truncate(len);
+        self.is_dirty = true;
+    }
+
     fn clear(&mut self) {
         self.colors.clear();
         self.is_dirty = true;
@@ -76,12 +93,35 @@ impl ColoredGridLine {
     }
 }
 
+#[derive(Clone, Copy)]
+struct SavedCursor {
+    position: Position,
+    foreground_color: TerminalHighlightKind,
+    background_color: TerminalHighlightKind,
+    are_colors_swapped: bool,
+    are_colors_bright: bool,
+    is_origin_mode: bool,
+}
+
+impl SavedCursor {
+    fn new() -> Self {
+        Self {
+            position: Position::ZERO,
+            foreground_color: TerminalHighlightKind::Foreground,
+            background_color: TerminalHighlightKind::Background,
+            are_colors_swapped: false,
+            are_colors_bright: false,
+            is_origin_mode: false,
+        }
+    }
+}
+
 struct Grid {
     // The position of the terminal's cursor, which follows different rules
     // compared to the document's cursor for compatibility reasons, and may be
     // different from the document's cursor position if the user is selecting text.
     cursor: Position,
-    saved_cursor: Position,
+    saved_cursor: SavedCursor,
     colored_lines: Vec<ColoredGridLine>,
 }
 
@@ -89,7 +129,7 @@ impl Grid {
     pub fn new() -> Self {
         Self {
             cursor: Position::ZERO,
-            saved_cursor: Position::ZERO,
+            saved_cursor: SavedCursor::new(),
             colored_lines: Vec::new(),
         }
     }
@@ -103,6 +143,8 @@ pub struct TerminalEmulator {
     grid_height: usize,
     grid: Grid,
     empty_line_text: String,
+    // The last char that was printed, used to handle repeat sequences.
+    last_char: String,
 
     // Data for either the normal buffer or the alternate buffer,
     // depending on which one isn't currently being used.
@@ -115,18 +157,23 @@ pub struct TerminalEmulator {
     are_colors_bright: bool,
     scroll_top: usize,
     scroll_bottom: usize,
+    is_origin_mode: bool,
     is_in_alternate_buffer: bool,
+    // Reported by the shell, if it supports doing so.
+    current_dir: Option<Pooled<PathBuf>>,
+    bell_time: Option<f64>,
 }
 
 impl TerminalEmulator {
     const MAX_SCROLLBACK_LINES: usize = 100;
     const MIN_GRID_WIDTH: usize = 1;
     const MIN_GRID_HEIGHT: usize = 1;
+    const BELL_FLASH_DURATION: f64 = 0.15;
 
     #[cfg(target_os = "windows")]
     const SHELLS: &[&str] = &["pwsh.exe", "powershell.exe", "cmd.exe"];
 
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     const SHELLS: &[&str] = &["zsh", "bash", "sh"];
 
     pub fn new() -> Self {
@@ -138,6 +185,7 @@ impl TerminalEmulator {
             grid_height: Self::MIN_GRID_HEIGHT,
             grid: Grid::new(),
             empty_line_text: String::new(),
+            last_char: String::new(),
 
             other_grid: Grid::new(),
 
@@ -148,7 +196,10 @@ impl TerminalEmulator {
             are_colors_bright: false,
             scroll_top: 0,
             scroll_bottom: 0,
+            is_origin_mode: false,
             is_in_alternate_buffer: false,
+            current_dir: None,
+            bell_time: None,
         }
     }
 
@@ -229,11 +280,45 @@ impl TerminalEmulator {
                 {
                     handle_copy(doc, ctx);
                 }
-                Msg::Action(action_name!(Paste)) => {
-                    let mut text = STRING_POOL.new_item();
-                    let _ = ctx.window.get_clipboard(&mut text);
-
-                    pty.input().extend(text.bytes());
+                Msg::Mousebind(Mousebind {
+                    button: Some(MouseButton::Left),
+                    x,
+                    y,
+                    mods,
+                    kind: MousebindKind::Press,
+                    ..
+                }) if mods.contains(Mod::Ctrl) || mods.contains(Mod::Cmd) => {
+                    let visual_position = VisualPosition::new(x, y);
+
+                    if let Some(path_link) = tab
+                        .visual_to_position_unclamped(visual_position, doc, ctx.ui, ctx.gfx)
+                        .and_then(|position| self.path_link_at(position, doc, ctx))
+                    {
+                        ctx.ui.skip(
+                            widget_id,
+                            Msg::OpenFileAtLine {
+                                path: path_link.path,
+                                line: path_link.line,
+                                column: path_link.column,
+                            },
+                        );
+                    } else {
+                        tab.receive_msg(msg, doc, ctx);
+                    }
+                }
+                Msg::Action(action_name!(Paste)) => Self::paste(&mut pty, ctx),
+                Msg::Mousebind(Mousebind {
+                    button: Some(MouseButton::Middle),
+                    kind: MousebindKind::Press,
+                    ..
+                }) if ctx.config.terminal.middle_click_paste => Self::paste(&mut pty, ctx),
+                Msg::Mousebind(Mousebind {
+                    button: Some(MouseButton::Left),
+                    kind: MousebindKind::Release,
+                    ..
+                }) if ctx.config.terminal.copy_on_select && doc.has_selection() => {
+                    handle_copy(doc, ctx);
+                    tab.receive_msg(msg, doc, ctx);
                 }
                 Msg::Action(action_keybind!(key, mods: Mods::CTRL)) => {
                     const KEY_A: u8 = Key::A as u8;
@@ -263,6 +348,52 @@ impl TerminalEmulator {
         }
     }
 
+    pub fn path_link_at(&self, position: Position, doc: &Doc, ctx: &Ctx) -> Option<PathLink> {
+        let line = doc.get_line(position.y)?;
+        let base_dir = self.current_dir.as_deref().unwrap_or(ctx.current_dir);
+
+        PathLink::at(position.x, line, base_dir)
+    }
+
+    // Returns how visible the bell's flash should be, fading from 1 to 0.
+    pub fn bell_flash_amount(&self, time: f64) -> Option<f32> {
+        let elapsed = time - self.bell_time?;
+
+        if !(0.0..Self::BELL_FLASH_DURATION).contains(&elapsed) {
+            return None;
+        }
+
+        Some((1.0 - elapsed / Self::BELL_FLASH_DURATION) as f32)
+    }
+
+    // Shells that haven't started yet will start in this directory.
+    pub fn set_start_dir(&mut self, dir: &Path) {
+        if self.pty.is_none() && self.current_dir.is_none() {
+            self.current_dir = Some(dir.into());
+        }
+    }
+
+    pub fn current_dir(&self) -> Option<&Path> {
+        self.current_dir.as_deref().map(PathBuf::as_path)
+    }
+
+    // Shells report their working directory as "file://host/path".
+    fn working_dir_uri_to_path(uri: &str) -> Option<Pooled<PathBuf>> {
+        let host_and_path = uri.strip_prefix("file://")?;
+        let path_start = host_and_path.find('/')?;
+
+        let uri = format_pooled!("file://{}", &host_and_path[path_start..]);
+
+        uri_to_path(&uri)
+    }
+
+    fn paste(pty: &mut Process, ctx: &mut Ctx) {
+        let mut text = STRING_POOL.new_item();
+        let _ = ctx.window.get_clipboard(&mut text);
+
+        pty.input().extend(text.bytes());
+    }
+
     pub fn update(&mut self, docs: &mut TerminalDocs, tab: &mut Tab, ctx: &mut Ctx) {
         if let Some(mut pty) = self.pty.take() {
             {
@@ -313,10 +444,52 @@ impl TerminalEmulator {
     ) {
         let doc = self.doc_mut(docs);
 
+        if !matches!(
+            sequence,
+            EscapeSequence::Plain { .. }
+                | EscapeSequence::RepeatLastChar(..)
+                | EscapeSequence::ResetFormatting
+                | EscapeSequence::SetColorsBright(..)
+                | EscapeSequence::SetColorsSwapped(..)
+                | EscapeSequence::SetForegroundColor(..)
+                | EscapeSequence::SetBackgroundColor(..)
+        ) {
+            self.last_char.clear();
+        }
+
         match sequence {
             EscapeSequence::Plain { len } => {
                 let text = STRING_POOL.init_item(|text| text.push_str(self.parser.next_text(len)));
                 self.insert_at_cursor(&text, doc, tab, ctx);
+
+                if let Some(c) = CharIterator::new(&text).next_back() {
+                    self.last_char.clear();
+                    self.last_char.push_str(c);
+                }
+            }
+            EscapeSequence::RepeatLastChar(count) => {
+                if self.last_char.is_empty() {
+                    return;
+                }
+
+                let text = STRING_POOL.init_item(|text| {
+                    for _ in 0..count.min(self.grid_width * self.grid_height) {
+                        text.push_str(&self.last_char);
+                    }
+                });
+
+                self.insert_at_cursor(&text, doc, tab, ctx);
+            }
+            EscapeSequence::Bell => {
+                let bell = ctx.config.terminal.bell;
+
+                if matches!(bell, BellKind::Visual | BellKind::Both) {
+                    self.bell_time = Some(ctx.time);
+                }
+
+                if matches!(bell, BellKind::Audible | BellKind::Both) {
+                    ctx.window.beep();
+                }
             }
             EscapeSequence::Backspace => self.move_cursor(-1, 0, doc, ctx.gfx),
             EscapeSequence::Tab => {
@@ -336,6 +509,12 @@ impl TerminalEmulator {
                 self.is_cursor_visible = true;
                 self.jump_doc_cursors_to_grid_cursor(doc, ctx.gfx);
             }
+            EscapeSequence::SetOriginMode(flag) => {
+                self.is_origin_mode = flag;
+
+                let y = self.origin_y(0);
+                self.jump_cursor(Position::new(0, y), doc, ctx.gfx);
+            }
             EscapeSequence::SwitchToNormalBuffer => self.switch_to_normal_buffer(docs, tab, ctx),
             EscapeSequence::SwitchToAlternateBuffer => self.switch_to_alternate_buffer(doc, tab),
             EscapeSequence::QueryModifyKeyboard
@@ -376,25 +555,51 @@ impl TerminalEmulator {
                 self.jump_cursor(position, doc, ctx.gfx);
             }
             EscapeSequence::SetCursorY(y) => {
+                let y = self.origin_y(y);
                 let char_x = self.grid_position_byte_to_char(self.grid.cursor, doc);
                 let position = self.grid_position_char_to_byte(Position::new(char_x, y), doc);
 
                 self.jump_cursor(position, doc, ctx.gfx);
             }
             EscapeSequence::SetCursorPosition(x, y) => {
+                let y = self.origin_y(y);
                 let position = self.grid_position_char_to_byte(Position::new(x, y), doc);
 
                 self.jump_cursor(position, doc, ctx.gfx);
             }
             EscapeSequence::MoveCursorX(distance) => self.move_cursor(distance, 0, doc, ctx.gfx),
-            EscapeSequence::MoveCursorY(distance) => self.move_cursor(0, distance, doc, ctx.gfx),
+            EscapeSequence::MoveCursorY(distance) => {
+                let y = self.clamp_y_to_origin(self.grid.cursor.y.saturating_add_signed(distance));
+                let distance = y as isize - self.grid.cursor.y as isize;
+
+                self.move_cursor(0, distance, doc, ctx.gfx);
+            }
             EscapeSequence::MoveCursorYAndResetX(distance) => {
-                let y = self.grid.cursor.y.saturating_add_signed(distance);
+                let y = self.clamp_y_to_origin(self.grid.cursor.y.saturating_add_signed(distance));
 
                 self.jump_cursor(Position::new(0, y), doc, ctx.gfx);
             }
-            EscapeSequence::SaveCursor => self.grid.saved_cursor = self.grid.cursor,
-            EscapeSequence::RestoreCursor => self.jump_cursor(self.grid.saved_cursor, doc, ctx.gfx),
+            EscapeSequence::SaveCursor => {
+                self.grid.saved_cursor = SavedCursor {
+                    position: self.grid.cursor,
+                    foreground_color: self.foreground_color,
+                    background_color: self.background_color,
+                    are_colors_swapped: self.are_colors_swapped,
+                    are_colors_bright: self.are_colors_bright,
+                    is_origin_mode: self.is_origin_mode,
+                };
+            }
+            EscapeSequence::RestoreCursor => {
+                let saved_cursor = self.grid.saved_cursor;
+
+                self.foreground_color = saved_cursor.foreground_color;
+                self.background_color = saved_cursor.background_color;
+                self.are_colors_swapped = saved_cursor.are_colors_swapped;
+                self.are_colors_bright = saved_cursor.are_colors_bright;
+                self.is_origin_mode = saved_cursor.is_origin_mode;
+
+                self.jump_cursor(saved_cursor.position, doc, ctx.gfx);
+            }
             EscapeSequence::ClearToScreenEnd => {
                 let start = self.grid.cursor;
                 let end = self.line_end(self.grid_height - 1, doc);
@@ -472,6 +677,45 @@ impl TerminalEmulator {
 
                 self.delete(start, end, doc, ctx);
             }
+            EscapeSequence::InsertCharsAtCursor(distance) => {
+                let char_x = self.grid_position_byte_to_char(self.grid.cursor, doc);
+                let distance = distance.min(self.grid_width.saturating_sub(char_x));
+
+                if distance == 0 {
+                    return;
+                }
+
+                let start = self.grid_position_to_doc_position(self.grid.cursor, doc);
+                let text = STRING_POOL.init_item(|text| text.extend(iter::repeat_n(' ', distance)));
+
+                doc.insert(start, &text, ctx);
+
+                // Chars pushed past the edge of the grid are lost.
+                let truncate_start = self.grid_position_char_to_byte(
+                    Position::new(self.grid_width, self.grid.cursor.y),
+                    doc,
+                );
+                let truncate_start = self.grid_position_to_doc_position(truncate_start, doc);
+
+                doc.delete(truncate_start, doc.line_end(start.y), ctx);
+
+                let colored_line = &mut self.grid.colored_lines[self.grid.cursor.y];
+                let insert_x = start.x.min(colored_line.len());
+
+                colored_line.splice(
+                    insert_x..insert_x,
+                    iter::repeat_n(
+                        (
+                            TerminalHighlightKind::Foreground,
+                            TerminalHighlightKind::Background,
+                        ),
+                        distance,
+                    ),
+                );
+                colored_line.truncate(doc.line_len(start.y));
+
+                self.jump_doc_cursors_to_grid_cursor(doc, ctx.gfx);
+            }
             EscapeSequence::DeleteCharsAfterCursor(distance) => {
                 let start = self.grid.cursor;
                 let end = self.move_position(start, 1, 0, doc);
@@ -491,11 +735,22 @@ impl TerminalEmulator {
             EscapeSequence::SetScrollRegion { top, bottom } => {
                 self.scroll_bottom = bottom.clamp(0, self.grid_height - 1);
                 self.scroll_top = top.clamp(0, self.scroll_bottom);
+
+                if self.is_origin_mode {
+                    let y = self.origin_y(0);
+                    self.jump_cursor(Position::new(0, y), doc, ctx.gfx);
+                }
             }
             EscapeSequence::QueryDeviceStatus => {
                 let char_x = self.grid_position_byte_to_char(self.grid.cursor, doc);
 
-                let response = format_pooled!("\x1B[{};{}R", self.grid.cursor.y + 1, char_x + 1);
+                let y = if self.is_origin_mode {
+                    self.grid.cursor.y.saturating_sub(self.scroll_top)
+                } else {
+                    self.grid.cursor.y
+                };
+
+                let response = format_pooled!("\x1B[{};{}R", y + 1, char_x + 1);
 
                 input.extend(response.bytes());
             }
@@ -507,6 +762,13 @@ impl TerminalEmulator {
                 let title = self.parser.next_text(len);
                 doc.set_display_name(Some(title.into()));
             }
+            EscapeSequence::SetWorkingDir { len } => {
+                let uri = self.parser.next_text(len);
+
+                if let Some(current_dir) = Self::working_dir_uri_to_path(uri) {
+                    self.current_dir = Some(current_dir);
+                }
+            }
             EscapeSequence::ResetTitle => {
                 doc.set_display_name(Some(TerminalDocs::DISPLAY_NAME.into()));
             }
@@ -672,14 +934,13 @@ impl TerminalEmulator {
         if let Some(pty) = self.pty.as_mut() {
             pty.resize(grid_width, grid_height);
         } else {
-            self.pty = Process::new(
-                Self::SHELLS,
-                ProcessKind::Pty {
-                    width: grid_width,
-                    height: grid_height,
-                },
-            )
-            .ok();
+            let current_dir = self
+                .current_dir
+                .as_deref()
+                .map(PathBuf::as_path)
+                .filter(|current_dir| current_dir.is_dir());
+
+            self.pty = Self::spawn_shell(grid_width, grid_height, current_dir, ctx);
         }
 
         self.grid_width = grid_width;
@@ -689,6 +950,55 @@ impl TerminalEmulator {
         self.scroll_bottom = grid_height - 1;
     }
 
+    fn spawn_shell(
+        grid_width: usize,
+        grid_height: usize,
+        current_dir: Option<&Path>,
+        ctx: &Ctx,
+    ) -> Option<Process> {
+        let terminal_config = &ctx.config.terminal;
+
+        let custom_shell: Option<Vec<&str>> = terminal_config
+            .shell
+            .as_ref()
+            .filter(|shell| !shell.is_empty())
+            .map(|shell| {
+                [shell.as_str()]
+                    .into_iter()
+                    .chain(terminal_config.shell_args.iter().map(|arg| arg.as_str()))
+                    .collect()
+            });
+
+        let shells: Vec<&[&str]> = match &custom_shell {
+            Some(custom_shell) => vec![custom_shell],
+            None => Self::SHELLS.iter().map(slice::from_ref).collect(),
+        };
+
+        let env: Vec<(&str, &str)> = terminal_config
+            .env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+
+        let mut pty = Process::new(
+            &shells,
+            &env,
+            current_dir,
+            ProcessKind::Pty {
+                width: grid_width,
+                height: grid_height,
+            },
+        )
+        .ok()?;
+
+        if let Some(startup_command) = &terminal_config.startup_command {
+            pty.input().extend(startup_command.bytes());
+            pty.input().push(b'\r');
+        }
+
+        Some(pty)
+    }
+
     fn grid_size(ctx: &Ctx, tab: &Tab) -> (usize, usize) {
         let Rect {
             width: doc_width,
@@ -999,6 +1309,23 @@ impl TerminalEmulator {
         doc.jump_cursors(doc_position, false, gfx);
     }
 
+    // In origin mode, cursor positions are relative to the scroll region and can't leave it.
+    fn origin_y(&self, y: usize) -> usize {
+        if self.is_origin_mode {
+            self.clamp_y_to_origin(self.scroll_top.saturating_add(y))
+        } else {
+            y
+        }
+    }
+
+    fn clamp_y_to_origin(&self, y: usize) -> usize {
+        if self.is_origin_mode {
+            y.clamp(self.scroll_top, self.scroll_bottom)
+        } else {
+            y
+        }
+    }
+
     fn move_cursor(&mut self, delta_x: isize, delta_y: isize, doc: &mut Doc, gfx: &mut Gfx) {
         self.grid.cursor = self.move_position(self.grid.cursor, delta_x, delta_y, doc);
         self.jump_doc_cursors_to_grid_cursor(doc, gfx);
@@ -1027,7 +1354,12 @@ impl TerminalEmulator {
 
     fn insert_at_cursor(&mut self, text: &str, doc: &mut Doc, tab: &mut Tab, ctx: &mut Ctx) {
         for c in CharIterator::new(text) {
-            if self.grid_position_byte_to_char(self.grid.cursor, doc) >= self.grid_width {
+            let char_x = self.grid_position_byte_to_char(self.grid.cursor, doc);
+
+            // Wide chars that don't fit at the end of the line wrap instead of being split.
+            if char_x >= self.grid_width
+                || (char_x > 0 && char_x + ctx.gfx.measure_text(c) > self.grid_width)
+            {
                 self.jump_cursor(Position::new(0, self.grid.cursor.y), doc, ctx.gfx);
                 self.newline_cursor(doc, tab, ctx);
             }
@@ -1042,6 +1374,8 @@ impl TerminalEmulator {
         match color {
             TerminalHighlightKind::Foreground => TerminalHighlightKind::BrightForeground,
             TerminalHighlightKind::Background => TerminalHighlightKind::BrightBackground,
+            TerminalHighlightKind::Black => TerminalHighlightKind::BrightBlack,
+            TerminalHighlightKind::White => TerminalHighlightKind::BrightWhite,
             TerminalHighlightKind::Red => TerminalHighlightKind::BrightRed,
             TerminalHighlightKind::Green => TerminalHighlightKind::BrightGreen,
             TerminalHighlightKind::Yellow => TerminalHighlightKind::BrightYellow,
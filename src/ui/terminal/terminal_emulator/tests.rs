@@ -0,0 +1,97 @@
+use crate::{
+    tests::test_with_doc,
+    ui::{core::WidgetId, slot_list::SlotId, tab::Tab, terminal::TerminalDocs},
+};
+
+use super::TerminalEmulator;
+
+fn new_emulator(
+    grid_width: usize,
+    grid_height: usize,
+    docs: &mut TerminalDocs,
+    ctx: &mut crate::ctx::Ctx,
+) -> TerminalEmulator {
+    let mut emulator = TerminalEmulator::new();
+
+    emulator.grid_width = grid_width;
+    emulator.grid_height = grid_height;
+    emulator.scroll_bottom = grid_height - 1;
+
+    emulator.resize_to_grid_size(docs, TerminalEmulator::MIN_GRID_HEIGHT, ctx);
+
+    emulator
+}
+
+test_with_doc!(
+    cursor_position_report_counts_wide_char_cells,
+    "",
+    |ctx, _| {
+        let mut docs = TerminalDocs::new();
+        let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+        let mut emulator = new_emulator(10, 2, &mut docs, ctx);
+        let mut input = Vec::new();
+
+        emulator.handle_escape_sequences(
+            &mut docs,
+            &mut tab,
+            &mut input,
+            "中a\x1B[6n".as_bytes(),
+            ctx,
+        );
+
+        let column = ctx.gfx.measure_text("中") + ctx.gfx.measure_text("a") + 1;
+
+        assert_eq!(input, format!("\x1B[1;{}R", column).into_bytes());
+    }
+);
+
+test_with_doc!(wide_char_wraps_at_line_end, "", |ctx, _| {
+    let mut docs = TerminalDocs::new();
+    let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+    let grid_width = ctx.gfx.measure_text("中") + 1;
+    let mut emulator = new_emulator(grid_width, 2, &mut docs, ctx);
+    let mut input = Vec::new();
+
+    emulator.handle_escape_sequences(
+        &mut docs,
+        &mut tab,
+        &mut input,
+        "ab中\x1B[6n".as_bytes(),
+        ctx,
+    );
+
+    let column = ctx.gfx.measure_text("中") + 1;
+
+    assert_eq!(input, format!("\x1B[2;{}R", column).into_bytes());
+});
+
+test_with_doc!(working_dir_is_set_by_osc_7, "", |ctx, _| {
+    let mut docs = TerminalDocs::new();
+    let mut tab = Tab::new(WidgetId::ROOT, SlotId::ZERO, ctx.ui);
+    let mut emulator = new_emulator(10, 2, &mut docs, ctx);
+    let mut input = Vec::new();
+
+    emulator.handle_escape_sequences(
+        &mut docs,
+        &mut tab,
+        &mut input,
+        b"\x1B]7;file://host/home/some%20dir\x07",
+        ctx,
+    );
+
+    assert!(emulator
+        .current_dir()
+        .is_some_and(|current_dir| current_dir.ends_with("home/some dir")));
+
+    emulator.handle_escape_sequences(
+        &mut docs,
+        &mut tab,
+        &mut input,
+        b"\x1B]7;not a uri\x07",
+        ctx,
+    );
+
+    assert!(emulator
+        .current_dir()
+        .is_some_and(|current_dir| current_dir.ends_with("home/some dir")));
+});
@@ -34,6 +34,28 @@ impl TerminalPane {
     fn new_term() -> Term {
         (TerminalDocs::new(), TerminalEmulator::new())
     }
+
+    // Removing a term drops its emulator, which kills the shell running in it.
+    fn remove_tab(&mut self, term_list: &mut SlotList<Term>, ctx: &mut Ctx) {
+        let Some(tab) = self.get_focused_tab(ctx.ui) else {
+            return;
+        };
+
+        let term_id = tab.data_id();
+
+        self.inner.remove_tab(term_list, ctx.ui);
+
+        if let Some((mut docs, _)) = term_list.remove(term_id) {
+            docs.clear(ctx);
+        }
+    }
+
+    pub fn close_all_tabs(&mut self, term_list: &mut SlotList<Term>, ctx: &mut Ctx) {
+        while self.has_tabs() {
+            self.focus_tab_at_index(0, ctx.ui);
+            self.remove_tab(term_list, ctx);
+        }
+    }
 }
 
 impl PaneWrapper<Term> for TerminalPane {
@@ -56,17 +78,7 @@ impl PaneWrapper<Term> for TerminalPane {
 
                     self.add_tab(term_index, term_list, ctx);
                 }
-                Msg::Action(action_name!(CloseTab)) => {
-                    if let Some(tab) = self.get_focused_tab(ctx.ui) {
-                        let term_id = tab.data_id();
-
-                        self.remove_tab(term_list, ctx.ui);
-
-                        if let Some((mut docs, _)) = term_list.remove(term_id) {
-                            docs.clear(ctx);
-                        }
-                    }
-                }
+                Msg::Action(action_name!(CloseTab)) => self.remove_tab(term_list, ctx),
                 _ => ctx.ui.skip(self.widget_id, msg),
             }
         }